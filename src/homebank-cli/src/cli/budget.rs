@@ -1,11 +1,43 @@
 //! Render the `BudgetSummary` into a visual element in the terminal.
 
-use homebank_db::category::budget_query::BudgetSummary;
+use super::display::DisplaySettings;
+use homebank_db::{category::budget_query::BudgetSummary, HomeBankDb};
 use indicatif::{ProgressBar, ProgressStyle};
 
+/// List the transactions that make up a `BudgetSummary`, one per indented, tab-separated line
+pub fn budget_explanation(summary: &BudgetSummary, db: &HomeBankDb, settings: &DisplaySettings) -> String {
+    summary
+        .transactions()
+        .iter()
+        .map(|tr| {
+            format!(
+                "    {}\t{}\t{}\t{}\n",
+                settings.date(tr.date()),
+                settings.amount(*tr.total(), db),
+                tr.payee_name(db).unwrap_or_default(),
+                tr.memo().as_deref().unwrap_or_default()
+            )
+        })
+        .collect()
+}
+
+/// Describe how much of a budget is used and how much is left, e.g. `120 %, 60 over`
+pub fn budget_status(summary: &BudgetSummary) -> Option<String> {
+    let (pct, remaining, allotment) = (summary.percent_used()?, summary.remaining()?, summary.allotment()?);
+
+    // the remaining amount has the allotment's sign until the budget is overspent
+    let state = if remaining * allotment < 0.0 { "over" } else { "left" };
+
+    Some(format!("{pct:>3.0} %, {:.0} {state}", remaining.abs()))
+}
+
 /// Create a `ProgressBar` out of a `BudgetSummary`
 pub fn budget_pbar(summary: BudgetSummary) -> ProgressBar {
-    if let (Some(val), Some(frac)) = (summary.allotment_rounded(), summary.progress_frac()) {
+    if let (Some(val), Some(frac), Some(status)) = (
+        summary.allotment_rounded(),
+        summary.progress_frac(),
+        budget_status(&summary),
+    ) {
         let pbar = ProgressBar::new(val);
         let bar_colour: &str;
 
@@ -17,10 +49,12 @@ pub fn budget_pbar(summary: BudgetSummary) -> ProgressBar {
             bar_colour = "white";
         }
 
+        // the status is shown instead of `{percent}`, which stops at 100 %
         let template = format!(
-            "{{msg:<30.{bar_colour}}} {{wide_bar:.{bar_colour}}} {{pos:>6.{bar_colour}}}/{{len:>6}} ({{percent:>3.{bar_colour}}} %)"
+            "{{msg:<30.{bar_colour}}} {{wide_bar:.{bar_colour}}} {{pos:>6.{bar_colour}}}/{{len:>6}} ({{prefix:.{bar_colour}}})"
         );
 
+        pbar.set_prefix(status);
         pbar.set_message(format!("{}", summary.name()));
         pbar.set_style(ProgressStyle::default_bar().template(&template));
 
@@ -43,3 +77,30 @@ pub fn budget_pbar(summary: BudgetSummary) -> ProgressBar {
         pbar
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check_status(progress: f32, allotment: Option<f32>, expected: Option<&str>) {
+        let summary = BudgetSummary::new("Food:Groceries", progress, allotment);
+
+        assert_eq!(expected.map(|s| s.to_string()), budget_status(&summary));
+    }
+
+    #[test]
+    fn status_overspent() {
+        check_status(-360.0, Some(-300.0), Some("120 %, 60 over"));
+    }
+
+    #[test]
+    fn status_with_room_left() {
+        check_status(-75.0, Some(-300.0), Some(" 25 %, 225 left"));
+    }
+
+    #[test]
+    fn status_without_allotment() {
+        check_status(-75.0, None, None);
+    }
+}
@@ -0,0 +1,53 @@
+//! Render the hierarchy of categories in the terminal.
+
+use homebank_db::{category::CategoryNode, HomeBankDb};
+use std::collections::HashMap;
+
+/// Indentation for each level of the hierarchy.
+const INDENT: &str = "  ";
+
+/// Marker in front of categories that have subcategories, as wide as [`INDENT`] so that names at the same depth line up.
+const BRANCH: &str = "+ ";
+
+/// List every category under its parent, indented by depth, with the number of transactions in each, e.g. `    Groceries (2)`.
+/// Categories with subcategories are marked with a `+`, e.g. `+ Food (0)`.
+pub fn category_tree(db: &HomeBankDb) -> String {
+    let counts = db.category_counts();
+    let mut lines = String::new();
+
+    for root in db.category_tree() {
+        push_node(&root, 0, &counts, &mut lines);
+    }
+
+    lines
+}
+
+/// Add a line for the category in `node`, followed by the lines for its subcategories.
+fn push_node(node: &CategoryNode, depth: usize, counts: &HashMap<usize, usize>, lines: &mut String) {
+    let cat = node.category();
+    let count = counts.get(&cat.key()).copied().unwrap_or_default();
+    let marker = if node.children().is_empty() { INDENT } else { BRANCH };
+
+    lines.push_str(&format!("{}{marker}{} ({count})\n", INDENT.repeat(depth), cat.name()));
+
+    for child in node.children() {
+        push_node(child, depth + 1, counts, lines);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::tests::example_db;
+
+    #[test]
+    fn tree_with_counts() {
+        let db = example_db();
+        let expected = "  Entertainment (1)\n\
+                        + Food (0)\n    Dining (2)\n    Groceries (2)\n\
+                        + Housing (0)\n    Rent (3)\n  \
+                        Salary (2)\n";
+
+        assert_eq!(expected, category_tree(&db));
+    }
+}
@@ -1,28 +1,64 @@
 //! Top level CLI command
 
-use crate::config::default_cfg_file;
+use super::CurrencyDisplay;
 use clap::Parser;
-use homebank_db::{category::{QueryBudget, QueryReview}, QueryOpts, QueryTransactions};
-use lazy_static::lazy_static;
+use clap_complete::Shell;
+use homebank_db::{category::{QueryBudget, QueryReview}, report::ReconcileStatement, EditOpts, ExportOpts, ImportOpts, QueryOpts, QueryTransactions, QueryType, ReportOpts, WatchOpts};
 use std::path::{Path, PathBuf};
 
-lazy_static! {
-    static ref DEFAULT_CFG: String = default_cfg_file().to_str().unwrap().to_string();
-}
-
-#[derive(Debug, Parser)]
-#[clap(author, about, version)]
+#[derive(Debug, Default, Parser)]
+#[command(author, about, version)]
 pub struct CliOpts {
-    /// Path to `hb` (not HomeBank) configuration file
-    #[clap(
+    /// Path to `hb` (not HomeBank) configuration file [env: HB_CONFIG] [default: `hb/config.toml` in the user's configuration directory]
+    #[arg(
         short = 'c',
         long = "config",
-        default_value = &DEFAULT_CFG
+        value_name = "path"
     )]
-    pub path: PathBuf,
+    pub path: Option<PathBuf>,
+
+    /// Path to a HomeBank file to use directly, bypassing the configuration file, or `-` to read it from stdin [env: HB_FILE, HB_PATH]
+    #[arg(short = 'f', long = "file", value_name = "path.xhb")]
+    pub file: Option<PathBuf>,
+
+    /// Name of the profile in the configuration file to use
+    #[arg(short = 'p', long = "profile", value_name = "name")]
+    pub profile: Option<String>,
+
+    /// Skip elements of the HomeBank file that cannot be parsed, instead of aborting
+    #[arg(long = "lenient")]
+    pub lenient: bool,
+
+    /// Skip transactions in the HomeBank file that cannot be parsed, instead of aborting
+    #[arg(long = "skip-errors", conflicts_with = "lenient")]
+    pub skip_errors: bool,
+
+    /// Read a HomeBank file whose version is not supported, e.g. one saved by a newer HomeBank
+    #[arg(long = "force", conflicts_with_all = ["lenient", "skip_errors"])]
+    pub force: bool,
+
+    /// Write the output to this file instead of stdout. It must not exist yet, unless `--overwrite` or `--append` is given
+    #[arg(short = 'o', long = "output", value_name = "path")]
+    pub output: Option<PathBuf>,
+
+    /// Replace the output file if it already exists
+    #[arg(long = "overwrite", requires = "output", conflicts_with = "append")]
+    pub overwrite: bool,
+
+    /// Add the rows of a `report --format csv` to the end of the output file, instead of replacing it
+    #[arg(long = "append", requires = "output")]
+    pub append: bool,
+
+    /// Format of dates in the output, e.g. `%d.%m.%Y` [default: `date_format` in the configuration file, or `%Y-%m-%d`]
+    #[arg(long = "date-format", value_name = "strftime")]
+    pub date_format: Option<String>,
+
+    /// How the currency of amounts, including those in tables, is shown [default: `currency_display` in the configuration file, or `none`]
+    #[arg(long = "currency-display", value_name = "display", value_enum)]
+    pub currency_display: Option<CurrencyDisplay>,
 
     /// Optional subcommand
-    #[clap(subcommand)]
+    #[command(subcommand)]
     pub subcmd: Option<SubCommand>,
 }
 
@@ -30,14 +66,74 @@ impl CliOpts {
     /// Create a new `CliOpts`
     pub fn new(path: &Path, subcmd: Option<SubCommand>) -> Self {
         Self {
-            path: path.to_path_buf(),
+            path: Some(path.to_path_buf()),
+            file: None,
+            profile: None,
+            lenient: false,
+            skip_errors: false,
+            force: false,
+            output: None,
+            overwrite: false,
+            append: false,
+            date_format: None,
+            currency_display: None,
             subcmd,
         }
     }
 
-    /// Retrieve the CLI config path
-    pub fn path(&self) -> &Path {
-        &self.path
+    /// Retrieve the CLI config path, if one was given
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Retrieve the path to the HomeBank file given directly, if any
+    pub fn file(&self) -> Option<&Path> {
+        self.file.as_deref()
+    }
+
+    /// Retrieve the name of the profile to use, if any
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Check whether the HomeBank file should be parsed leniently
+    pub fn lenient(&self) -> bool {
+        self.lenient
+    }
+
+    /// Check whether transactions that cannot be parsed should be skipped
+    pub fn skip_errors(&self) -> bool {
+        self.skip_errors
+    }
+
+    /// Check whether a HomeBank file with an unsupported version should be read anyway
+    pub fn force(&self) -> bool {
+        self.force
+    }
+
+    /// Check whether an existing output file should be replaced
+    pub fn overwrite(&self) -> bool {
+        self.overwrite
+    }
+
+    /// Retrieve the path of the file to write the output to, if any
+    pub fn output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
+
+    /// Check whether the output should be added to the end of the output file
+    pub fn append(&self) -> bool {
+        self.append
+    }
+
+    /// Retrieve the format of dates given, if any
+    pub fn date_format(&self) -> Option<&str> {
+        self.date_format.as_deref()
+    }
+
+    /// Retrieve how the currency of amounts should be shown, if given
+    pub fn currency_display(&self) -> Option<CurrencyDisplay> {
+        self.currency_display
     }
 
     /// Retrieve the subcommand given, if any
@@ -49,30 +145,74 @@ impl CliOpts {
     }
 }
 
-impl Default for CliOpts {
-    fn default() -> Self {
-        CliOpts {
-            path: default_cfg_file(),
-            subcmd: None,
-        }
-    }
-}
-
 #[derive(Debug, Parser)]
 pub enum SubCommand {
     /// Perform a query on the HomeBank database.
-    #[clap(visible_alias = "q")]
+    #[command(visible_alias = "q")]
     Query(QueryOpts),
 
     /// Calculate a sum of transactions in a query.
-    #[clap(visible_alias = "s")]
+    #[command(visible_alias = "s")]
     Sum(QueryTransactions),
 
     /// Print a tab-separated table of each category and the sum of its transactions.
-    #[clap(visible_alias = "r")]
+    #[command(visible_alias = "r")]
     Review(QueryReview),
 
     /// Look at your category budgets.
-    #[clap(visible_alias = "b")]
+    #[command(visible_alias = "b")]
     Budget(QueryBudget),
+
+    /// Print summary reports of your transactions.
+    Report(ReportOpts),
+
+    /// Compare the cleared balance of an account to a bank statement.
+    Reconcile(ReconcileStatement),
+
+    /// Export your transactions to other formats.
+    Export(ExportOpts),
+
+    /// Import transactions from other formats into the HomeBank file.
+    Import(ImportOpts),
+
+    /// Rename or merge categories and payees, or rename and remove tags, in the HomeBank file.
+    Edit(EditOpts),
+
+    /// Check the HomeBank file for missing or inconsistent references.
+    Validate {
+        /// Also list the elements and attributes that were ignored when reading the file, e.g. ones added by a newer HomeBank.
+        #[arg(long = "unknown")]
+        unknown: bool,
+    },
+
+    /// Print an overview of the HomeBank file.
+    Stats {
+        /// Total the income and expenses of this calendar year. Defaults to the current year.
+        #[arg(short = 'y', long = "year", value_name = "YYYY")]
+        year: Option<i32>,
+    },
+
+    /// Print a shell completion script to stdout.
+    Completions {
+        /// Shell to generate the completion script for.
+        #[arg(value_enum, value_name = "shell")]
+        shell: Shell,
+    },
+}
+
+impl SubCommand {
+    /// Retrieve the options to watch the HomeBank file, if the subcommand should be re-run whenever it changes
+    pub fn watch(&self) -> Option<&WatchOpts> {
+        let opts = match self {
+            SubCommand::Query(q_opts) => match q_opts.qtype() {
+                QueryType::Transactions(query) => query.watch(),
+                _ => return None,
+            },
+            SubCommand::Sum(query) => query.watch(),
+            SubCommand::Budget(query) => query.watch(),
+            _ => return None,
+        };
+
+        opts.watch().then_some(opts)
+    }
 }
@@ -0,0 +1,68 @@
+//! Generate shell completion scripts for the `hb` command.
+
+use super::CliOpts;
+use clap::CommandFactory;
+use clap_complete::{generate, Shell};
+use std::io::Write;
+
+/// Name of the binary that the completion scripts complete.
+const BIN_NAME: &str = "hb";
+
+/// Write the completion script for `shell` to `buf`, covering all subcommands and options.
+pub fn print_completions(shell: Shell, buf: &mut dyn Write) {
+    let mut cmd = CliOpts::command();
+
+    generate(shell, &mut cmd, BIN_NAME, buf);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn completions(shell: Shell) -> String {
+        let mut buf = vec![];
+        print_completions(shell, &mut buf);
+
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn cli_definition_is_valid() {
+        CliOpts::command().debug_assert();
+    }
+
+    #[test]
+    fn bash_completions() {
+        let observed = completions(Shell::Bash);
+
+        assert!(!observed.is_empty());
+        for subcmd in ["query", "sum", "budget", "report", "validate", "completions"] {
+            assert!(observed.contains(subcmd), "missing subcommand `{subcmd}`");
+        }
+    }
+
+    #[test]
+    fn zsh_completions_include_values() {
+        let observed = completions(Shell::Zsh);
+
+        // payment methods, transaction statuses, and report formats
+        for value in ["etransfer", "reconciled", "csv"] {
+            assert!(observed.contains(value), "missing value `{value}`");
+        }
+    }
+
+    #[test]
+    fn powershell_completions() {
+        let observed = completions(Shell::PowerShell);
+
+        assert!(observed.contains("'hb;report'"));
+    }
+
+    #[test]
+    fn fish_completions() {
+        let observed = completions(Shell::Fish);
+
+        assert!(observed.contains("-l lenient"));
+    }
+}
@@ -0,0 +1,202 @@
+//! Settings for how dates and amounts are displayed in the terminal.
+
+use super::{CliOpts, SubCommand};
+use crate::config::{check_date_format, Config, ConfigError};
+use chrono::NaiveDate;
+use clap::{builder::PossibleValue, ValueEnum};
+use super::table::{Cell, Table};
+use homebank_db::{Currency, HomeBankDb, ReportFormat};
+use std::str::FromStr;
+
+/// Date format used when none is configured, e.g. `2022-01-31`.
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// How the currency of an amount is shown.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CurrencyDisplay {
+    /// Only the number, e.g. `-12.50`
+    #[default]
+    None,
+
+    /// The number formatted like HomeBank does, with the symbol of the base currency, e.g. `$ -12.50`
+    Symbol,
+
+    /// The number followed by the ISO code of the base currency, e.g. `-12.50 CAD`
+    Code,
+}
+
+impl FromStr for CurrencyDisplay {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "symbol" => Ok(Self::Symbol),
+            "code" => Ok(Self::Code),
+            _ => Err(format!("unknown currency display `{s}`")),
+        }
+    }
+}
+
+impl ValueEnum for CurrencyDisplay {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[CurrencyDisplay::None, CurrencyDisplay::Symbol, CurrencyDisplay::Code]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            CurrencyDisplay::None => PossibleValue::new("none"),
+            CurrencyDisplay::Symbol => PossibleValue::new("symbol"),
+            CurrencyDisplay::Code => PossibleValue::new("code"),
+        })
+    }
+}
+
+/// How reports, dates, and amounts are rendered, from the configuration file and the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisplaySettings {
+    /// Output format of reports.
+    format: ReportFormat,
+
+    /// `strftime` format of dates.
+    date_format: String,
+
+    /// How the currency of amounts is shown.
+    currency: CurrencyDisplay,
+}
+
+impl DisplaySettings {
+    /// Create new `DisplaySettings`, checking that `date_format` is a valid `strftime` format
+    pub fn new(format: ReportFormat, date_format: &str, currency: CurrencyDisplay) -> Result<Self, ConfigError> {
+        check_date_format(date_format)?;
+
+        Ok(Self {
+            format,
+            date_format: date_format.to_string(),
+            currency,
+        })
+    }
+
+    /// Combine the settings given on the command line with the defaults from the configuration
+    pub fn resolve(cfg: &Config, opts: &CliOpts) -> Result<Self, ConfigError> {
+        let format = match opts.subcommand() {
+            Some(SubCommand::Report(r_opts)) => r_opts.format(),
+            _ => None,
+        };
+
+        Self::new(
+            format.or(cfg.output_format()).unwrap_or_default(),
+            opts.date_format().or(cfg.date_format()).unwrap_or(DEFAULT_DATE_FORMAT),
+            opts.currency_display().or(cfg.currency_display()).unwrap_or_default(),
+        )
+    }
+
+    /// Retrieve the output format of reports
+    pub fn format(&self) -> ReportFormat {
+        self.format
+    }
+
+    /// Retrieve how the currency of amounts is shown
+    pub fn currency(&self) -> CurrencyDisplay {
+        self.currency
+    }
+
+    /// Format a date
+    pub fn date(&self, date: &NaiveDate) -> String {
+        date.format(&self.date_format).to_string()
+    }
+
+    /// Format an amount in the base currency of the database
+    pub fn amount(&self, amount: f32, db: &HomeBankDb) -> String {
+        db.base_currency()
+            .and_then(|curr| self.amount_in(amount, curr))
+            .unwrap_or_else(|| format!("{amount:.2}"))
+    }
+
+    /// Create a table cell for an amount in `currency`, which is a plain number unless the currency is shown
+    pub fn money(&self, amount: f32, currency: Option<&Currency>) -> Cell {
+        match currency.and_then(|curr| self.amount_in(amount, curr)) {
+            Some(text) => Cell::Money(amount, text),
+            None => Cell::Amount(amount),
+        }
+    }
+
+    /// Format the amounts of a table in the base currency of the database, unless the currency isn't shown
+    pub fn format_table(&self, table: &mut Table, db: &HomeBankDb) {
+        if let Some(curr) = db.base_currency().filter(|_| self.currency != CurrencyDisplay::None) {
+            table.format_amounts(|val| self.amount_in(val, curr).unwrap_or_default());
+        }
+    }
+
+    /// Format an amount in `currency`, or `None` if the currency isn't shown
+    fn amount_in(&self, amount: f32, currency: &Currency) -> Option<String> {
+        match self.currency {
+            CurrencyDisplay::None => None,
+            CurrencyDisplay::Symbol => Some(currency.format_amount(amount)),
+            CurrencyDisplay::Code => Some(format!("{amount:.2} {}", currency.iso())),
+        }
+    }
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            format: ReportFormat::Table,
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            currency: CurrencyDisplay::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::tests::example_db;
+
+    #[track_caller]
+    fn check_amount(currency: CurrencyDisplay, amount: f32, expected: &str) {
+        let settings = DisplaySettings::new(ReportFormat::Table, DEFAULT_DATE_FORMAT, currency).unwrap();
+
+        assert_eq!(expected, settings.amount(amount, &example_db()));
+    }
+
+    #[test]
+    fn date_formats() {
+        let date = NaiveDate::from_ymd_opt(2022, 1, 31).unwrap();
+        let settings = DisplaySettings::new(ReportFormat::Table, "%d.%m.%Y", CurrencyDisplay::None).unwrap();
+
+        assert_eq!("2022-01-31", DisplaySettings::default().date(&date));
+        assert_eq!("31.01.2022", settings.date(&date));
+    }
+
+    #[test]
+    fn invalid_date_format() {
+        let observed = DisplaySettings::new(ReportFormat::Table, "%d.%m.%Q", CurrencyDisplay::None).unwrap_err();
+
+        assert_eq!("Invalid date format `%d.%m.%Q`. It must be a `strftime` format, e.g. `%Y-%m-%d`.", observed.to_string());
+    }
+
+    #[test]
+    fn amounts_with_currency() {
+        check_amount(CurrencyDisplay::None, -1234.5, "-1234.50");
+        check_amount(CurrencyDisplay::Symbol, -1234.5, "$ -1,234.50");
+        check_amount(CurrencyDisplay::Code, -1234.5, "-1234.50 CAD");
+    }
+
+    #[test]
+    fn money_cells() {
+        let db = example_db();
+        let settings = |currency| DisplaySettings::new(ReportFormat::Table, DEFAULT_DATE_FORMAT, currency).unwrap();
+
+        assert_eq!(Cell::Amount(-1234.5), DisplaySettings::default().money(-1234.5, db.base_currency()));
+        assert_eq!(Cell::Money(-1234.5, "$ -1,234.50".to_string()), settings(CurrencyDisplay::Symbol).money(-1234.5, db.base_currency()));
+        assert_eq!(Cell::Money(-1234.5, "-1234.50 CAD".to_string()), settings(CurrencyDisplay::Code).money(-1234.5, db.base_currency()));
+        assert_eq!(Cell::Amount(-1234.5), settings(CurrencyDisplay::Symbol).money(-1234.5, None));
+    }
+
+    #[test]
+    fn parse_currency_display() {
+        assert_eq!(Ok(CurrencyDisplay::Code), "Code".parse());
+        assert!("iso".parse::<CurrencyDisplay>().is_err());
+    }
+}
@@ -0,0 +1,52 @@
+//! Describe edits to the HomeBank file in the terminal.
+
+use homebank_db::{Edit, HomeBankDb};
+use std::path::Path;
+
+/// Describe the edits made to the HomeBank file at `path`, or that would be made if there is no `path`, as the lines leading up to their summary
+pub fn edit_description(plan: &[Edit], db: &HomeBankDb, path: Option<&Path>) -> String {
+    let category = |key: usize| db.category_full_name(key).unwrap_or_default().to_string();
+    let payee = |key: usize| db.payee_name(key).unwrap_or_default().to_string();
+    let location = path.map(|path| format!(" in `{}`", path.display())).unwrap_or_default();
+
+    if let Some((from, into)) = plan.iter().find_map(|edit| match edit {
+        Edit::MergePayees { from, into } => Some((from, *into)),
+        _ => None,
+    }) {
+        // a payee that didn't exist yet is created by renaming the first one merged into it
+        let renamed = plan.iter().find_map(|edit| match edit {
+            Edit::RenamePayee { key, name } if *key == into => Some(name),
+            _ => None,
+        });
+        let (target, sources) = match renamed {
+            Some(name) => (format!("new payee `{name}`"), [&[into], from.as_slice()].concat()),
+            None => (format!("payee `{}`", payee(into)), from.clone()),
+        };
+
+        let mut lines = vec![match path {
+            Some(_) => format!("Merged into {target}{location}:"),
+            None => format!("Would merge into {target}:"),
+        }];
+        for key in sources {
+            let count = db.transactions().iter().filter(|tr| *tr.payee() == Some(key)).count();
+            lines.push(format!("  `{}`: {count} transaction(s)", payee(key)));
+        }
+        lines.push("affecting:".to_string());
+
+        return lines.join("\n");
+    }
+
+    let (would, did, change) = match plan.first() {
+        Some(Edit::RenameCategory { key, name }) => ("rename", "Renamed", format!("category `{}` to `{name}`", category(*key))),
+        Some(Edit::MergeCategories { from, into }) => ("merge", "Merged", format!("category `{}` into `{}`", category(*from), category(*into))),
+        Some(Edit::RenamePayee { key, name }) => ("rename", "Renamed", format!("payee `{}` to `{name}`", payee(*key))),
+        Some(Edit::RenameTag { from, into, .. }) => ("rename", "Renamed", format!("tag `{from}` to `{into}`")),
+        Some(Edit::RemoveTag { tag, .. }) => ("remove", "Removed", format!("tag `{tag}`")),
+        Some(Edit::MergePayees { .. }) | None => ("change", "Changed", "nothing".to_string()),
+    };
+
+    match path {
+        Some(_) => format!("{did} {change}{location}, affecting:"),
+        None => format!("Would {would} {change}, affecting:"),
+    }
+}
@@ -0,0 +1,100 @@
+//! Load the HomeBank file given on the command line or in the configuration.
+
+use super::CliOpts;
+use crate::config::Config;
+use anyhow::Context;
+use homebank_db::{db::HomeBankDbError, HomeBankDb};
+use std::io::Read;
+
+/// Load the HomeBank database from the file in `cfg`, or from `stdin` if the file is `-`.
+///
+/// Warnings about skipped elements or transactions are printed to stderr, so that piped output stays clean.
+pub fn load_db(cfg: &Config, opts: &CliOpts, stdin: &mut dyn Read) -> Result<HomeBankDb, anyhow::Error> {
+    if cfg.is_stdin() {
+        if opts.lenient() || opts.skip_errors() || opts.force() {
+            anyhow::bail!("`--lenient`, `--skip-errors`, and `--force` cannot be used when reading the HomeBank file from stdin.");
+        }
+
+        return HomeBankDb::from_reader(stdin).context("Error parsing HomeBank file from stdin.");
+    }
+
+    let context = || format!("Error parsing HomeBank file `{}`.", cfg.path().display());
+
+    if opts.lenient() {
+        let (db, warnings) = HomeBankDb::try_from_path_lenient(cfg.path()).with_context(context)?;
+
+        for warning in warnings {
+            eprintln!("Warning: {warning}");
+        }
+
+        Ok(db)
+    } else if opts.skip_errors() {
+        let (db, skipped) = HomeBankDb::try_from_path_skip_errors(cfg.path()).with_context(context)?;
+
+        for (line, e) in &skipped {
+            eprintln!("Warning: skipped transaction on line {line}: {e}");
+        }
+        if !skipped.is_empty() {
+            eprintln!("Skipped {} transaction(s) that could not be parsed.", skipped.len());
+        }
+
+        Ok(db)
+    } else if opts.force() {
+        HomeBankDb::try_from_path_any_version(cfg.path()).with_context(context)
+    } else {
+        let db = HomeBankDb::try_from(cfg.path());
+
+        if let Err(HomeBankDbError::UnsupportedVersion { .. }) = db {
+            eprintln!("Use `--force` to read the HomeBank file anyway.");
+        }
+
+        db.with_context(context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::Path};
+
+    /// Path to a HomeBank file with a few months of transactions.
+    const XHB_FIXTURE: &str = "../homebank-db/tests/transactions.xhb";
+
+    fn stdin_cfg() -> Config {
+        Config::new(Path::new("-"))
+    }
+
+    #[test]
+    fn load_from_stdin() {
+        let mut stdin = fs::File::open(XHB_FIXTURE).unwrap();
+        let db = load_db(&stdin_cfg(), &CliOpts::default(), &mut stdin).unwrap();
+
+        assert_eq!(11, db.transactions().len());
+    }
+
+    #[test]
+    fn load_from_truncated_stdin() {
+        let observed = load_db(&stdin_cfg(), &CliOpts::default(), &mut "<homebank><account key=".as_bytes());
+
+        assert!(observed.unwrap_err().to_string().contains("from stdin"));
+    }
+
+    #[test]
+    fn load_from_stdin_not_lenient() {
+        let opts = CliOpts {
+            lenient: true,
+            ..Default::default()
+        };
+        let observed = load_db(&stdin_cfg(), &opts, &mut fs::File::open(XHB_FIXTURE).unwrap());
+
+        assert!(observed.unwrap_err().to_string().contains("`--lenient`"));
+    }
+
+    #[test]
+    fn load_from_file_ignores_stdin() {
+        let cfg = Config::new(Path::new(XHB_FIXTURE));
+        let db = load_db(&cfg, &CliOpts::default(), &mut "not a HomeBank file".as_bytes()).unwrap();
+
+        assert_eq!(11, db.transactions().len());
+    }
+}
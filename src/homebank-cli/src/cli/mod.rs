@@ -1,7 +1,42 @@
 //! CLI argument parsing and configuration
 
 pub mod budget;
+pub mod category;
 pub mod command;
+pub mod completions;
+pub mod display;
+pub mod edit;
+pub mod load;
+pub mod output;
+pub mod reconcile;
+pub mod report;
+pub mod stats;
+pub mod table;
+pub mod transaction;
+pub mod watch;
 
 pub use budget::budget_pbar;
+pub use category::category_tree;
 pub use command::{CliOpts, SubCommand};
+pub use completions::print_completions;
+pub use display::{CurrencyDisplay, DisplaySettings};
+pub use edit::edit_description;
+pub use load::load_db;
+pub use output::{has_content, open_output, same_file, Output};
+pub use reconcile::statement_summary;
+pub use report::{cashflow_table, category_stats_table, duplicates_table, monthly_table, net_worth_table, reconcile_table, tag_stats_table, tags_table, top_table, yearly_table};
+pub use stats::stats_summary;
+pub use table::{Align, Cell, CsvStyle, Table, TableError};
+pub use transaction::grouped_table;
+pub use watch::watch_file;
+
+#[cfg(test)]
+mod tests {
+    use homebank_db::HomeBankDb;
+    use std::path::Path;
+
+    /// Load the example HomeBank file of `homebank_db`, with a few months of transactions in three accounts, that most tests use.
+    pub(crate) fn example_db() -> HomeBankDb {
+        HomeBankDb::try_from(Path::new("../homebank-db/tests/transactions.xhb")).unwrap()
+    }
+}
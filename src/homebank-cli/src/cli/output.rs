@@ -0,0 +1,150 @@
+//! Choose where the output of a subcommand is written.
+
+use anyhow::Context;
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, StdoutLock, Write},
+    path::{Path, PathBuf},
+};
+
+/// Where the output of a subcommand is written: stdout, or a temporary file that replaces the output file once the subcommand succeeds.
+///
+/// If the subcommand fails, the output is dropped without being [finished][Output::finish], and the output file is left as it was.
+pub enum Output {
+    Stdout(StdoutLock<'static>),
+    File {
+        /// Temporary file next to the output file, holding everything written so far.
+        writer: BufWriter<File>,
+
+        /// Path of the temporary file.
+        tmp_path: PathBuf,
+
+        /// Path of the output file that the temporary file replaces.
+        path: PathBuf,
+    },
+}
+
+impl Output {
+    /// Flush the output, and move the temporary file into place if writing to a file
+    pub fn finish(mut self) -> Result<(), anyhow::Error> {
+        self.flush()?;
+
+        if let Output::File { tmp_path, path, .. } = &self {
+            fs::rename(tmp_path, path).with_context(|| format!("Cannot write output file `{}`.", path.display()))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Stdout(out) => out.write(buf),
+            Output::File { writer, .. } => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Stdout(out) => out.flush(),
+            Output::File { writer, .. } => writer.flush(),
+        }
+    }
+}
+
+impl Drop for Output {
+    fn drop(&mut self) {
+        // the temporary file is already gone if it was moved into place
+        if let Output::File { tmp_path, .. } = self {
+            let _ = fs::remove_file(tmp_path);
+        }
+    }
+}
+
+/// Open a temporary file to write the output of a subcommand to, replacing the file at `path` once it is [finished][Output::finish],
+/// or write to stdout if no path is given.
+///
+/// With `append`, the temporary file starts with the contents of the output file, if there is one.
+pub fn open_output(path: Option<&Path>, append: bool) -> Result<Output, anyhow::Error> {
+    let Some(path) = path else {
+        return Ok(Output::Stdout(io::stdout().lock()));
+    };
+
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    let tmp_path = path.with_file_name(format!(".{name}.tmp"));
+    let context = || format!("Cannot open output file `{}`.", path.display());
+
+    if append && path.exists() {
+        fs::copy(path, &tmp_path).with_context(context)?;
+    }
+
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(&tmp_path)
+        .with_context(context)?;
+
+    Ok(Output::File {
+        writer: BufWriter::new(file),
+        tmp_path,
+        path: path.to_path_buf(),
+    })
+}
+
+/// Check whether the file at `path` already has something in it, e.g. the header of a CSV file being appended to.
+pub fn has_content(path: &Path) -> bool {
+    path.metadata().map(|meta| meta.len() > 0).unwrap_or(false)
+}
+
+/// Check whether `a` and `b` are paths to the same existing file.
+pub fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_or_append() {
+        let path = std::env::temp_dir().join(format!("hb-output-{}.csv", std::process::id()));
+        let write = |text: &str, append: bool| {
+            let mut out = open_output(Some(&path), append).unwrap();
+            write!(out, "{text}").unwrap();
+            out.finish().unwrap();
+        };
+
+        write("first\n", false);
+        write("second\n", true);
+        assert_eq!("first\nsecond\n", fs::read_to_string(&path).unwrap());
+        assert!(has_content(&path));
+
+        write("third\n", false);
+        assert_eq!("third\n", fs::read_to_string(&path).unwrap());
+
+        fs::remove_file(&path).unwrap();
+        assert!(!has_content(&path));
+    }
+
+    #[test]
+    fn unfinished_output_leaves_file_unchanged() {
+        let path = std::env::temp_dir().join(format!("hb-output-unfinished-{}.txt", std::process::id()));
+        fs::write(&path, "kept\n").unwrap();
+
+        let mut out = open_output(Some(&path), false).unwrap();
+        writeln!(out, "lost").unwrap();
+        drop(out);
+
+        assert_eq!("kept\n", fs::read_to_string(&path).unwrap());
+        assert!(!path.with_file_name(format!(".hb-output-unfinished-{}.txt.tmp", std::process::id())).exists());
+        assert!(same_file(&path, &path.parent().unwrap().join(".").join(path.file_name().unwrap())));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,29 @@
+//! Render the comparison of an account to a bank statement in the terminal.
+
+use super::{display::DisplaySettings, report::transaction_table};
+use homebank_db::{report::StatementComparison, HomeBankDb, Transaction};
+
+/// Describe how the cleared balance of an account compares to a statement, followed by the uncleared transactions that could explain any difference
+pub fn statement_summary(comparison: &StatementComparison, db: &HomeBankDb, settings: &DisplaySettings) -> String {
+    let balance = comparison.balance();
+    let mut lines = vec![
+        balance.account().to_string(),
+        format!("Cleared balance:    {:>12}", settings.amount(balance.cleared(), db)),
+        format!("Statement balance:  {:>12}", settings.amount(comparison.statement_balance(), db)),
+        format!("Difference:         {:>12}", settings.amount(comparison.difference(), db)),
+    ];
+
+    if comparison.is_balanced() {
+        lines.push("The cleared balance matches the statement.".to_string());
+    } else if comparison.uncleared().is_empty() {
+        lines.push("No uncleared transactions could explain the difference.".to_string());
+    } else {
+        let uncleared: Vec<&Transaction> = comparison.uncleared().iter().collect();
+
+        lines.push(String::new());
+        lines.push("Uncleared transactions:".to_string());
+        lines.push(transaction_table(&uncleared, db, settings).render_table());
+    }
+
+    lines.join("\n")
+}
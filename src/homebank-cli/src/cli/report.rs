@@ -0,0 +1,285 @@
+//! Render reports as tables in the terminal.
+
+use super::{
+    display::DisplaySettings,
+    table::{Align, Cell, Table},
+};
+use homebank_db::{
+    report::{CashflowPeriod, CategoryStats, ReconcileBalance, ReportDuplicates, MonthlySummary, NetWorthPoint, ReportTop, TopType, YearlyReport},
+    HomeBankDb, TagStats, Transaction,
+};
+
+/// Create a `Table` out of a list of `MonthlySummary`s
+pub fn monthly_table(summaries: &[MonthlySummary]) -> Table {
+    let mut table = Table::new(&[
+        ("Month", Align::Left),
+        ("Income", Align::Right),
+        ("Expenses", Align::Right),
+        ("Net", Align::Right),
+        ("Top categories", Align::Left),
+    ]);
+
+    for summary in summaries {
+        table.push_row(vec![
+            format!("{} {}", summary.month_name(), summary.year()).into(),
+            summary.income().into(),
+            summary.expenses().into(),
+            summary.net().into(),
+            category_breakdown(summary.top_categories()).into(),
+        ]);
+    }
+
+    table
+}
+
+/// Create a `Table` out of a list of `YearlyReport`s
+pub fn yearly_table(reports: &[YearlyReport]) -> Table {
+    let mut table = Table::new(&[
+        ("Year", Align::Left),
+        ("Income", Align::Right),
+        ("Expenses", Align::Right),
+        ("Net", Align::Right),
+        ("Transactions", Align::Right),
+        ("Top categories", Align::Left),
+    ]);
+
+    for report in reports {
+        table.push_row(vec![
+            report.year().to_string().into(),
+            report.income().into(),
+            report.expenses().into(),
+            report.net().into(),
+            report.num_transactions().to_string().into(),
+            category_breakdown(report.top_categories()).into(),
+        ]);
+    }
+
+    table
+}
+
+/// Create a `Table` out of a list of `CashflowPeriod`s, ending with the average of all periods
+pub fn cashflow_table(periods: &[CashflowPeriod]) -> Table {
+    let mut table = Table::new(&[
+        ("Period", Align::Left),
+        ("Income", Align::Right),
+        ("Expenses", Align::Right),
+        ("Net", Align::Right),
+    ]);
+
+    for period in periods {
+        table.push_row(vec![
+            period.label().into(),
+            period.income().into(),
+            period.expenses().into(),
+            period.net().into(),
+        ]);
+    }
+
+    if !periods.is_empty() {
+        let n = periods.len() as f32;
+        let income = periods.iter().map(|period| period.income()).sum::<f32>() / n;
+        let expenses = periods.iter().map(|period| period.expenses()).sum::<f32>() / n;
+
+        table.push_row(vec!["Average".into(), income.into(), expenses.into(), (income + expenses).into()]);
+    }
+
+    table
+}
+
+/// Create a `Table` out of the `ReconcileBalance` of each account
+pub fn reconcile_table(balances: &[ReconcileBalance]) -> Table {
+    let mut table = Table::new(&[
+        ("Account", Align::Left),
+        ("Reconciled", Align::Right),
+        ("Cleared", Align::Right),
+        ("Uncleared", Align::Right),
+        ("Balance", Align::Right),
+    ]);
+
+    for balance in balances {
+        table.push_row(vec![
+            balance.account().into(),
+            balance.reconciled().into(),
+            balance.cleared().into(),
+            balance.uncleared().into(),
+            balance.balance().into(),
+        ]);
+    }
+
+    table
+}
+
+/// Create a `Table` out of a list of `NetWorthPoint`s, with a column for each account group
+pub fn net_worth_table(points: &[NetWorthPoint], settings: &DisplaySettings) -> Table {
+    // every point has the same groups, so the first one names the columns
+    let group_names: Vec<&str> = match points.first() {
+        Some(point) => point.groups().iter().map(|(name, _)| name.as_str()).collect(),
+        None => vec![],
+    };
+
+    let mut header = vec![("Date", Align::Left)];
+    header.extend(group_names.iter().map(|name| (*name, Align::Right)));
+    header.push(("Total", Align::Right));
+    let mut table = Table::new(&header);
+
+    for point in points {
+        let mut row = vec![settings.date(point.date()).into()];
+        row.extend(point.groups().iter().map(|(_, balance)| (*balance).into()));
+        row.push(point.total().into());
+        table.push_row(row);
+    }
+
+    table
+}
+
+/// Create a `Table` ranking the payees, categories, or transactions requested by a `ReportTop`
+pub fn top_table(report: &ReportTop, db: &HomeBankDb, settings: &DisplaySettings) -> Table {
+    match report.top_type() {
+        TopType::Payees => {
+            let mut table = Table::new(&[("Payee", Align::Left), ("Transactions", Align::Right)]);
+
+            for (payee, count) in db.most_frequent_payees(report.n()) {
+                table.push_row(vec![payee.name().into(), count.to_string().into()]);
+            }
+
+            table
+        }
+        TopType::Categories => {
+            let mut table = Table::new(&[("Category", Align::Left), ("Transactions", Align::Right)]);
+
+            for (cat, count) in db.most_frequent_categories(report.n()) {
+                table.push_row(vec![cat.full_name(db).into(), count.to_string().into()]);
+            }
+
+            table
+        }
+        TopType::Transactions => {
+            let transactions = if report.smallest() {
+                db.smallest_transactions(report.n())
+            } else {
+                db.largest_transactions(report.n())
+            };
+
+            transaction_table(&transactions, db, settings)
+        }
+    }
+}
+
+/// Create a `Table` out of the total spending for each tag, e.g. from `HomeBankDb::tag_spending_report`
+pub fn tags_table(totals: &[(String, f64)]) -> Table {
+    let mut table = Table::new(&[("Tag", Align::Left), ("Spending", Align::Right)]);
+
+    for (tag, total) in totals {
+        table.push_row(vec![tag.as_str().into(), (*total as f32).into()]);
+    }
+
+    table
+}
+
+/// Create a `Table` with the statistics of the transactions in each category
+pub fn category_stats_table(stats: &[CategoryStats]) -> Table {
+    let mut table = Table::new(&[
+        ("Category", Align::Left),
+        ("Transactions", Align::Right),
+        ("Total", Align::Right),
+        ("Average", Align::Right),
+        ("Median", Align::Right),
+        ("Std. dev.", Align::Right),
+    ]);
+
+    for cat in stats {
+        table.push_row(vec![
+            cat.name().into(),
+            cat.count().to_string().into(),
+            (cat.total() as f32).into(),
+            (cat.average() as f32).into(),
+            (cat.median() as f32).into(),
+            (cat.std_dev() as f32).into(),
+        ]);
+    }
+
+    table
+}
+
+/// Create a `Table` with the number of transactions and total amount for each tag, and every way it is written if case is ignored
+pub fn tag_stats_table(tags: &[(String, TagStats)], case_insensitive: bool) -> Table {
+    let mut columns = vec![("Tag", Align::Left), ("Transactions", Align::Right), ("Total", Align::Right)];
+    if case_insensitive {
+        columns.push(("Written as", Align::Left));
+    }
+    let mut table = Table::new(&columns);
+
+    for (name, stats) in tags {
+        let mut row: Vec<Cell> = vec![name.as_str().into(), stats.count().to_string().into(), (stats.total() as f32).into()];
+        if case_insensitive {
+            row.push(stats.spellings().iter().map(String::as_str).collect::<Vec<_>>().join(", ").into());
+        }
+        table.push_row(row);
+    }
+
+    table
+}
+
+/// Create a `Table` listing each group of duplicate `Transaction`s found by a `ReportDuplicates`, numbered from 1
+pub fn duplicates_table(report: &ReportDuplicates, db: &HomeBankDb, settings: &DisplaySettings) -> Table {
+    let mut table = Table::new(&[
+        ("Group", Align::Right),
+        ("Date", Align::Left),
+        ("Account", Align::Left),
+        ("Payee", Align::Left),
+        ("Category", Align::Left),
+        ("Memo", Align::Left),
+        ("Amount", Align::Right),
+    ]);
+
+    for (i, group) in report.groups(db).iter().enumerate() {
+        for tr in group {
+            let mut row = vec![(i + 1).to_string().into()];
+            row.extend(transaction_row(tr, db, settings));
+            table.push_row(row);
+        }
+    }
+
+    table
+}
+
+/// Create a `Table` listing `Transaction`s
+pub fn transaction_table(transactions: &[&Transaction], db: &HomeBankDb, settings: &DisplaySettings) -> Table {
+    let mut table = Table::new(&[
+        ("Date", Align::Left),
+        ("Account", Align::Left),
+        ("Payee", Align::Left),
+        ("Category", Align::Left),
+        ("Memo", Align::Left),
+        ("Amount", Align::Right),
+    ]);
+
+    for tr in transactions {
+        table.push_row(transaction_row(tr, db, settings));
+    }
+
+    table
+}
+
+/// Describe a `Transaction` in the columns of a `transaction_table`
+fn transaction_row(tr: &Transaction, db: &HomeBankDb, settings: &DisplaySettings) -> Vec<Cell> {
+    let categories: Vec<String> = tr.category_names(db).into_iter().flatten().collect();
+
+    vec![
+        settings.date(tr.date()).into(),
+        tr.account_name(db).unwrap_or_default().into(),
+        tr.payee_name(db).unwrap_or_default().into(),
+        categories.join(", ").into(),
+        tr.memo().clone().unwrap_or_default().into(),
+        settings.money(*tr.total(), db.account(tr.account()).and_then(|acc| db.currency(acc.currency()))),
+    ]
+}
+
+/// Combine category names and their sums into a single value
+fn category_breakdown(categories: &[(String, f32)]) -> String {
+    categories
+        .iter()
+        .map(|(name, sum)| format!("{name} ({sum:.2})"))
+        .collect::<Vec<String>>()
+        .join("; ")
+}
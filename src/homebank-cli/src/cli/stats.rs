@@ -0,0 +1,74 @@
+//! Render an overview of the HomeBank database in the terminal.
+
+use super::display::DisplaySettings;
+use homebank_db::{DbStats, HomeBankDb};
+
+/// Width of the labels, so that the values line up.
+const LABEL_WIDTH: usize = 28;
+
+/// Describe the contents of the database on one line per statistic, e.g. `Transactions:               11`
+pub fn stats_summary(stats: &DbStats, db: &HomeBankDb, settings: &DisplaySettings) -> String {
+    let date_or_none = |date: &Option<_>| match date {
+        Some(d) => settings.date(d),
+        None => "none".to_string(),
+    };
+    let largest_expense = match stats.largest_expense() {
+        Some(tr) => {
+            let description = tr.payee_name(db).or_else(|| tr.memo().clone()).unwrap_or_default();
+
+            format!("{} on {} {description}", settings.amount(*tr.total(), db), settings.date(tr.date()))
+                .trim_end()
+                .to_string()
+        }
+        None => "none".to_string(),
+    };
+
+    let rows = [
+        ("HomeBank file version", stats.version().to_string()),
+        ("Accounts", stats.num_accounts().to_string()),
+        ("Payees", stats.num_payees().to_string()),
+        ("Categories", stats.num_categories().to_string()),
+        ("Currencies", stats.num_currencies().to_string()),
+        ("Transactions", stats.num_transactions().to_string()),
+        ("Favourites", stats.num_favourites().to_string()),
+        ("First transaction", date_or_none(stats.first_date())),
+        ("Last transaction", date_or_none(stats.last_date())),
+        (&format!("Income in {}", stats.year()), settings.amount(stats.income() as f32, db)),
+        (&format!("Expenses in {}", stats.year()), settings.amount(stats.expenses() as f32, db)),
+        ("Uncategorised transactions", stats.num_uncategorised().to_string()),
+        ("Largest expense", largest_expense),
+    ];
+
+    rows.iter()
+        .map(|(label, value)| format!("{:<LABEL_WIDTH$}{value}", format!("{label}:")))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::tests::example_db;
+    use crate::cli::CurrencyDisplay;
+    use homebank_db::ReportFormat;
+
+    #[test]
+    fn summary_of_example() {
+        let db = example_db();
+        let summary = stats_summary(&db.stats(Some(2022)), &db, &DisplaySettings::default());
+
+        assert!(summary.starts_with("HomeBank file version:      1.4.0\nAccounts:                   3\n"), "{summary}");
+        assert!(summary.contains("\nIncome in 2022:             4000.00\nExpenses in 2022:           -2755.75\n"), "{summary}");
+        assert!(summary.ends_with("\nLargest expense:            -1200.00 on 2021-12-10 Landlord"), "{summary}");
+    }
+
+    #[test]
+    fn summary_with_display_settings() {
+        let db = example_db();
+        let settings = DisplaySettings::new(ReportFormat::Table, "%d.%m.%Y", CurrencyDisplay::Code).unwrap();
+        let summary = stats_summary(&db.stats(Some(2022)), &db, &settings);
+
+        assert!(summary.contains("\nIncome in 2022:             4000.00 CAD\n"), "{summary}");
+        assert!(summary.ends_with("\nLargest expense:            -1200.00 CAD on 10.12.2021 Landlord"), "{summary}");
+    }
+}
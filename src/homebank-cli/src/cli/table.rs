@@ -0,0 +1,384 @@
+//! Render rows of values as an aligned table or as CSV.
+
+use homebank_db::ReportFormat;
+use thiserror::Error;
+
+/// Horizontal alignment of a column in a table.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// A single value in a table.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Cell {
+    /// Free-form text.
+    Text(String),
+
+    /// A monetary amount, formatted with two decimal places.
+    Amount(f32),
+
+    /// A monetary amount along with how it is shown in the terminal, e.g. `$ -1,234.50`. CSV output has the plain amount.
+    Money(f32, String),
+}
+
+impl From<String> for Cell {
+    fn from(s: String) -> Self {
+        Cell::Text(s)
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(s: &str) -> Self {
+        Cell::Text(s.to_string())
+    }
+}
+
+impl From<f32> for Cell {
+    fn from(val: f32) -> Self {
+        Cell::Amount(val)
+    }
+}
+
+/// Errors when setting up how a table is rendered.
+#[derive(Debug, Error, PartialEq)]
+pub enum TableError {
+    #[error("The decimal separator and thousands separator must be different, but both are `{0}`.")]
+    SameSeparators(char),
+}
+
+/// How values are written in CSV output.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CsvStyle {
+    /// Character between fields.
+    delimiter: char,
+
+    /// Character between the whole and fractional parts of an amount.
+    decimal_sep: char,
+
+    /// Character between each group of thousands in an amount, if any.
+    thousands_sep: Option<char>,
+}
+
+impl CsvStyle {
+    /// Create a new CSV style from the number separators.
+    /// The field delimiter is `,`, unless a separator already uses it, in which case it is `;`.
+    pub fn new(decimal_sep: char, thousands_sep: Option<char>) -> Result<Self, TableError> {
+        if Some(decimal_sep) == thousands_sep {
+            return Err(TableError::SameSeparators(decimal_sep));
+        }
+
+        let delimiter = if decimal_sep == ',' || thousands_sep == Some(',') {
+            ';'
+        } else {
+            ','
+        };
+
+        Ok(Self {
+            delimiter,
+            decimal_sep,
+            thousands_sep,
+        })
+    }
+
+    /// Retrieve the field delimiter
+    pub fn delimiter(&self) -> char {
+        self.delimiter
+    }
+
+    /// Format an amount with this style's separators
+    pub fn format_amount(&self, val: f32) -> String {
+        let plain = format!("{:.2}", val.abs());
+        let (whole, frac) = plain.split_once('.').unwrap_or((&plain, "00"));
+
+        let mut grouped = String::new();
+        for (i, digit) in whole.chars().enumerate() {
+            // add the separator before each group of 3 digits, counting from the right
+            if let Some(sep) = self.thousands_sep {
+                if i > 0 && (whole.len() - i) % 3 == 0 {
+                    grouped.push(sep);
+                }
+            }
+            grouped.push(digit);
+        }
+
+        // don't print `-0.00` for small negative values that round to zero
+        let sign = if val < 0.0 && plain != "0.00" { "-" } else { "" };
+
+        format!("{sign}{grouped}{}{frac}", self.decimal_sep)
+    }
+}
+
+impl Default for CsvStyle {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            decimal_sep: '.',
+            thousands_sep: None,
+        }
+    }
+}
+
+/// Rows of values with a header, ready to be printed.
+#[derive(Debug, PartialEq)]
+pub struct Table {
+    /// Name and alignment of each column.
+    header: Vec<(String, Align)>,
+
+    /// Values in each row.
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    /// Create a new table with the given column names and alignments
+    pub fn new(header: &[(&str, Align)]) -> Self {
+        Self {
+            header: header
+                .iter()
+                .map(|(name, align)| (name.to_string(), *align))
+                .collect(),
+            rows: vec![],
+        }
+    }
+
+    /// Add a row of values to the table
+    pub fn push_row(&mut self, row: Vec<Cell>) {
+        self.rows.push(row);
+    }
+
+    /// Show the plain amounts in the table with `format` in the terminal, e.g. with the symbol of their currency
+    pub fn format_amounts<F: Fn(f32) -> String>(&mut self, format: F) {
+        for cell in self.rows.iter_mut().flatten() {
+            if let Cell::Amount(val) = cell {
+                *cell = Cell::Money(*val, format(*val));
+            }
+        }
+    }
+
+    /// Render the table in the requested format
+    pub fn render(&self, format: &ReportFormat, csv_style: &CsvStyle) -> String {
+        match format {
+            ReportFormat::Table => self.render_table(),
+            ReportFormat::Csv => self.render_csv(csv_style),
+        }
+    }
+
+    /// Render the table with aligned columns
+    pub fn render_table(&self) -> String {
+        let rows: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| match cell {
+                        Cell::Text(s) => s.clone(),
+                        Cell::Amount(val) => format!("{val:.2}"),
+                        Cell::Money(_, text) => text.clone(),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // width of each column is the width of its widest value
+        let mut widths: Vec<usize> = self.header.iter().map(|(name, _)| name.chars().count()).collect();
+        for row in &rows {
+            for (width, val) in widths.iter_mut().zip(row) {
+                *width = (*width).max(val.chars().count());
+            }
+        }
+
+        let header: Vec<String> = self.header.iter().map(|(name, _)| name.clone()).collect();
+        let mut lines = vec![self.render_row(&header, &widths)];
+
+        let separator: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+        lines.push(separator.join("  "));
+
+        for row in &rows {
+            lines.push(self.render_row(row, &widths));
+        }
+
+        lines.join("\n")
+    }
+
+    /// Render a single row of the table with aligned columns
+    fn render_row(&self, row: &[String], widths: &[usize]) -> String {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .zip(&self.header)
+            .map(|((val, width), (_, align))| match align {
+                Align::Left => format!("{val:<width$}"),
+                Align::Right => format!("{val:>width$}"),
+            })
+            .collect();
+
+        // don't leave trailing whitespace from the last column
+        cells.join("  ").trim_end().to_string()
+    }
+
+    /// Render the table as delimiter-separated values
+    pub fn render_csv(&self, style: &CsvStyle) -> String {
+        let header: Vec<String> = self.header.iter().map(|(name, _)| name.clone()).collect();
+        let header = csv_row(&header, style.delimiter());
+
+        if self.rows.is_empty() {
+            header
+        } else {
+            format!("{header}\n{}", self.render_csv_rows(style))
+        }
+    }
+
+    /// Render the rows of the table as delimiter-separated values, without the header, e.g. to add them to an existing file
+    pub fn render_csv_rows(&self, style: &CsvStyle) -> String {
+        let mut lines = vec![];
+
+        for row in &self.rows {
+            let row: Vec<String> = row
+                .iter()
+                .map(|cell| match cell {
+                    Cell::Text(s) => s.clone(),
+                    Cell::Amount(val) | Cell::Money(val, _) => style.format_amount(*val),
+                })
+                .collect();
+
+            lines.push(csv_row(&row, style.delimiter()));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Join values into a single CSV line, quoting any values that need it.
+fn csv_row(row: &[String], delimiter: char) -> String {
+    row.iter()
+        .map(|val| {
+            if val.contains(delimiter) || val.contains('"') || val.contains('\n') {
+                format!("\"{}\"", val.replace('"', "\"\""))
+            } else {
+                val.to_string()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(&delimiter.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_table() -> Table {
+        let mut table = Table::new(&[("Name", Align::Left), ("Amount", Align::Right)]);
+        table.push_row(vec!["Rent".into(), (-1200.0).into()]);
+        table.push_row(vec!["Food, dining".into(), (-20.0).into()]);
+
+        table
+    }
+
+    #[track_caller]
+    fn check_render(format: ReportFormat, style: CsvStyle, expected: &str) {
+        let observed = example_table().render(&format, &style);
+
+        assert_eq!(expected, observed);
+    }
+
+    #[track_caller]
+    fn check_format_amount(decimal_sep: char, thousands_sep: Option<char>, val: f32, expected: &str) {
+        let observed = CsvStyle::new(decimal_sep, thousands_sep).unwrap().format_amount(val);
+
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    fn render_table() {
+        let expected = "Name            Amount
+------------  --------
+Rent          -1200.00
+Food, dining    -20.00";
+
+        check_render(ReportFormat::Table, CsvStyle::default(), expected);
+    }
+
+    #[test]
+    fn render_csv() {
+        let expected = "Name,Amount
+Rent,-1200.00
+\"Food, dining\",-20.00";
+
+        check_render(ReportFormat::Csv, CsvStyle::default(), expected);
+    }
+
+    #[test]
+    fn render_csv_eu_style() {
+        let style = CsvStyle::new(',', Some('.')).unwrap();
+        let expected = "Name;Amount
+Rent;-1.200,00
+Food, dining;-20,00";
+
+        check_render(ReportFormat::Csv, style, expected);
+    }
+
+    #[test]
+    fn formatted_amounts_only_in_terminal() {
+        let mut table = example_table();
+        table.format_amounts(|val| format!("{val:.0} €"));
+
+        assert_eq!("Name           Amount\n------------  -------\nRent          -1200 €\nFood, dining    -20 €", table.render_table());
+        assert_eq!("Rent,-1200.00\n\"Food, dining\",-20.00", table.render_csv_rows(&CsvStyle::default()));
+    }
+
+    #[test]
+    fn render_csv_rows_without_header() {
+        let observed = example_table().render_csv_rows(&CsvStyle::default());
+
+        assert_eq!("Rent,-1200.00\n\"Food, dining\",-20.00", observed);
+    }
+
+    #[test]
+    fn decimal_comma_forces_semicolon_delimiter() {
+        let style = CsvStyle::new(',', None).unwrap();
+
+        assert_eq!(';', style.delimiter());
+    }
+
+    #[test]
+    fn thousands_comma_forces_semicolon_delimiter() {
+        let style = CsvStyle::new('.', Some(',')).unwrap();
+
+        assert_eq!(';', style.delimiter());
+    }
+
+    #[test]
+    fn same_separators_are_invalid() {
+        let observed = CsvStyle::new(',', Some(','));
+
+        assert_eq!(Err(TableError::SameSeparators(',')), observed);
+    }
+
+    #[test]
+    fn format_amount_default() {
+        check_format_amount('.', None, 1234567.5, "1234567.50");
+    }
+
+    #[test]
+    fn format_amount_thousands() {
+        check_format_amount('.', Some(' '), -1234567.5, "-1 234 567.50");
+    }
+
+    #[test]
+    fn format_amount_small() {
+        check_format_amount(',', Some('.'), 999.99, "999,99");
+    }
+
+    #[test]
+    fn format_amount_negative_zero() {
+        check_format_amount('.', None, -0.001, "0.00");
+    }
+
+    #[test]
+    fn csv_quotes_are_escaped() {
+        let observed = csv_row(&["say \"hi\"".to_string()], ',');
+
+        assert_eq!("\"say \"\"hi\"\"\"", observed);
+    }
+}
@@ -0,0 +1,54 @@
+//! Render grouped transactions in the terminal.
+
+use super::table::{Align, Table};
+use homebank_db::transaction::{GroupBy, TransactionGroup};
+
+/// Create a `Table` with a column for each key in `group_by`, followed by the totals of each group
+pub fn grouped_table(groups: &[TransactionGroup], group_by: &[GroupBy]) -> Table {
+    let mut header: Vec<(&str, Align)> = group_by.iter().map(|g| (g.name(), Align::Left)).collect();
+    header.extend([
+        ("Count", Align::Right),
+        ("Sum", Align::Right),
+        ("Average", Align::Right),
+        ("Min", Align::Right),
+        ("Max", Align::Right),
+    ]);
+    let mut table = Table::new(&header);
+
+    for group in groups {
+        let mut row: Vec<_> = group.keys().iter().map(|key| key.as_str().into()).collect();
+        row.extend([
+            group.count().to_string().into(),
+            group.sum().into(),
+            group.average().into(),
+            group.min().into(),
+            group.max().into(),
+        ]);
+        table.push_row(row);
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::tests::example_db;
+    use clap::Parser;
+    use homebank_db::{transaction::group_transactions, Query, QueryTransactions};
+
+    #[test]
+    fn group_by_year() {
+        let db = example_db();
+        let query = QueryTransactions::try_parse_from(["transactions", "--group-by", "year", "--exclude-transfers"]).unwrap();
+        let groups = group_transactions(&query.exec(&db), query.group_by(), &db);
+
+        let expected = "\
+Year  Count       Sum   Average       Min       Max
+----  -----  --------  --------  --------  --------
+2021      1  -1200.00  -1200.00  -1200.00  -1200.00
+2022      8   1244.25    155.53  -1200.00   2000.00";
+
+        assert_eq!(expected, grouped_table(&groups, query.group_by()).render_table());
+    }
+}
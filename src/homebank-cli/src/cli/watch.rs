@@ -0,0 +1,130 @@
+//! Re-run a command whenever the HomeBank file changes.
+
+use anyhow::Context;
+use homebank_db::WatchOpts;
+use notify::{Config, Event, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    io::Write,
+    path::Path,
+    sync::mpsc::{channel, Receiver},
+    time::Duration,
+};
+
+/// How long to wait for more events after a change, since saving a file usually takes several writes.
+const SETTLE_TIME: Duration = Duration::from_millis(200);
+
+/// Clear the terminal and move the cursor to the top left corner.
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Clear the terminal and call `run`, then do it again every time the file at `path` changes.
+///
+/// The directory of the file is watched rather than the file itself, because HomeBank replaces the file when saving it.
+/// Errors from `run` are printed instead of returned, since the file may be read while it is only partly written.
+/// This only returns if the file can no longer be watched, or once `run` has been called `max_runs` times, if given.
+pub fn watch_file<F>(path: &Path, opts: &WatchOpts, max_runs: Option<usize>, mut run: F) -> Result<(), anyhow::Error>
+where
+    F: FnMut() -> Result<(), anyhow::Error>,
+{
+    let path = path
+        .canonicalize()
+        .with_context(|| format!("Cannot watch HomeBank file `{}`.", path.display()))?;
+    let dir = path.parent().unwrap_or(&path);
+
+    let (tx, rx) = channel();
+    let mut watcher: Box<dyn Watcher> = match opts.poll_interval() {
+        Some(interval) => Box::new(PollWatcher::new(tx, Config::default().with_poll_interval(interval))?),
+        None => Box::new(RecommendedWatcher::new(tx, Config::default())?),
+    };
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .with_context(|| format!("Cannot watch directory `{}`.", dir.display()))?;
+
+    for runs in 1.. {
+        print!("{CLEAR_SCREEN}");
+        std::io::stdout().flush()?;
+
+        if let Err(e) = run() {
+            eprintln!("Error: {e:#}");
+        }
+
+        if max_runs.is_some_and(|max| runs >= max) {
+            break;
+        }
+        wait_for_change(&rx, &path)?;
+    }
+
+    Ok(())
+}
+
+/// Block until an event changes the file at `path`, then skip the events that follow it closely.
+fn wait_for_change(rx: &Receiver<notify::Result<Event>>, path: &Path) -> Result<(), anyhow::Error> {
+    loop {
+        let event = rx.recv().context("Stopped watching the HomeBank file.")??;
+
+        if (event.kind.is_create() || event.kind.is_modify()) && event.paths.iter().any(|p| p == path) {
+            break;
+        }
+    }
+
+    std::thread::sleep(SETTLE_TIME);
+    while rx.try_recv().is_ok() {}
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        fs,
+        sync::atomic::{AtomicBool, Ordering},
+    };
+    use tempfile::TempDir;
+
+    #[test]
+    fn runs_before_waiting() {
+        let dir = TempDir::new().unwrap();
+        let xhb = dir.path().join("transactions.xhb");
+        fs::write(&xhb, "<homebank/>").unwrap();
+        let mut runs = 0;
+
+        watch_file(&xhb, &WatchOpts::new(true, Some(50)), Some(1), || {
+            runs += 1;
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(1, runs);
+    }
+
+    #[test]
+    fn runs_again_after_change() {
+        let dir = TempDir::new().unwrap();
+        let xhb = dir.path().join("transactions.xhb");
+        fs::write(&xhb, "<homebank/>").unwrap();
+        let watching = AtomicBool::new(true);
+        let mut runs = 0;
+
+        std::thread::scope(|s| {
+            // keep saving the file, since the watcher may only start noticing changes after the first run
+            s.spawn(|| {
+                for n in 0.. {
+                    if !watching.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    fs::write(&xhb, format!("<homebank v=\"{n}\"/>")).unwrap();
+                    std::thread::sleep(Duration::from_millis(50));
+                }
+            });
+
+            watch_file(&xhb, &WatchOpts::new(true, Some(20)), Some(2), || {
+                runs += 1;
+                Ok(())
+            })
+            .unwrap();
+            watching.store(false, Ordering::Relaxed);
+        });
+
+        assert_eq!(2, runs);
+    }
+}
@@ -2,19 +2,55 @@
 
 use super::{
     parse::{expand_tilde, file_to_string},
-    ConfigError,
+    ConfigError, PathSource,
 };
-use crate::cli::CliOpts;
+use crate::cli::{CliOpts, CurrencyDisplay};
+use chrono::format::{Item, StrftimeItems};
 use clap::crate_name;
 use dirs_next::config_dir;
+use homebank_db::{PayMode, ReportFormat, Rule};
+use regex::Regex;
 use serde::Deserialize;
-use std::path::{Path, PathBuf};
+use std::{
+    collections::BTreeMap,
+    env,
+    ffi::OsString,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Name of the implicit profile used when the configuration only has a top-level `path`.
+pub const IMPLICIT_PROFILE: &str = "default";
+
+/// Environment variable with the path to a HomeBank file, used instead of a configuration file.
+pub const HB_FILE_VAR: &str = "HB_FILE";
+
+/// Environment variable with the path to a HomeBank file, used if `HB_FILE` is not set.
+pub const HB_PATH_VAR: &str = "HB_PATH";
+
+/// Path given in place of a HomeBank file to read it from stdin instead.
+pub const STDIN_PATH: &str = "-";
+
+/// Environment variable with the path to an alternative `hb` configuration file.
+pub const HB_CONFIG_VAR: &str = "HB_CONFIG";
 
 /// The `hb` configuration.
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, PartialEq)]
 pub struct Config {
     // path to the HomeBank transactions file
     path: PathBuf,
+
+    // rules applied to imported transactions
+    rules: Vec<Rule>,
+
+    // output format of reports, unless one is given on the command line
+    output_format: Option<ReportFormat>,
+
+    // `strftime` format of dates, unless one is given on the command line
+    date_format: Option<String>,
+
+    // how the currency of amounts is shown, unless given on the command line
+    currency_display: Option<CurrencyDisplay>,
 }
 
 impl Config {
@@ -22,6 +58,10 @@ impl Config {
     pub fn new(path: &Path) -> Self {
         Config {
             path: path.to_path_buf(),
+            rules: vec![],
+            output_format: None,
+            date_format: None,
+            currency_display: None,
         }
     }
 
@@ -29,40 +69,64 @@ impl Config {
     pub fn path(&self) -> &Path {
         &self.path
     }
-}
 
-impl TryFrom<&CliOpts> for Config {
-    type Error = ConfigError;
+    /// Retrieve the rules applied to imported transactions
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
 
-    fn try_from(opts: &CliOpts) -> Result<Self, Self::Error> {
-        // check that the config file exists
-        if !opts.path.exists() {
-            return Err(ConfigError::DoesNotExist(opts.path().to_path_buf()));
-        } else if !opts.path.is_file() {
-            // check that the config is a file
-            return Err(ConfigError::NotAFile(opts.path().to_path_buf()));
-        } else {
-            // read the file and parse its contents
-            let file_contents = match file_to_string(&opts.path) {
-                Ok(s) => s,
-                Err(_) => return Err(ConfigError::ParseError(opts.path().to_path_buf())),
-            };
-
-            // try to deserialize from its contents via toml
-            Config::try_from(file_contents.as_str())
-        }
+    /// Retrieve the default output format of reports, if one is configured
+    pub fn output_format(&self) -> Option<ReportFormat> {
+        self.output_format
     }
-}
 
-impl TryFrom<&str> for Config {
-    type Error = ConfigError;
+    /// Retrieve the default `strftime` format of dates, if one is configured
+    pub fn date_format(&self) -> Option<&str> {
+        self.date_format.as_deref()
+    }
 
-    fn try_from(s: &str) -> Result<Self, Self::Error> {
-        let mut cfg: Config = match toml::from_str(s) {
+    /// Retrieve the default way of showing the currency of amounts, if one is configured
+    pub fn currency_display(&self) -> Option<CurrencyDisplay> {
+        self.currency_display
+    }
+
+    /// Check whether the HomeBank file is read from stdin
+    pub fn is_stdin(&self) -> bool {
+        self.path == Path::new(STDIN_PATH)
+    }
+
+    /// Parse the configuration from the contents of a TOML file, selecting a profile by name.
+    /// If no profile is given, the `default_profile` or the top-level `path` is used.
+    pub fn from_toml(s: &str, profile: Option<&str>) -> Result<Self, ConfigError> {
+        let cfg_file: ConfigFile = match toml::from_str(s) {
             Ok(cfg) => cfg,
             Err(_) => return Err(ConfigError::MissingHomeBankPath),
         };
 
+        let path = cfg_file.profile_path(profile)?;
+        let mut cfg = Config::new(path);
+        cfg.rules = cfg_file
+            .rules
+            .iter()
+            .map(RuleEntry::to_rule)
+            .collect::<Result<Vec<Rule>, ConfigError>>()?;
+        cfg.output_format = match &cfg_file.output_format {
+            Some(s) => Some(
+                <ReportFormat as FromStr>::from_str(s).map_err(|_| ConfigError::InvalidOutputFormat(s.clone()))?,
+            ),
+            None => None,
+        };
+        if let Some(s) = &cfg_file.date_format {
+            check_date_format(s)?;
+        }
+        cfg.date_format = cfg_file.date_format.clone();
+        cfg.currency_display = match &cfg_file.currency_display {
+            Some(s) => Some(
+                <CurrencyDisplay as FromStr>::from_str(s).map_err(|_| ConfigError::InvalidCurrencyDisplay(s.clone()))?,
+            ),
+            None => None,
+        };
+
         // if the path is tilded, fix it
         if let Some(d) = expand_tilde(cfg.path()) {
             cfg.path = d;
@@ -70,7 +134,10 @@ impl TryFrom<&str> for Config {
 
         // check that the HomeBank XHB file is a file
         if !cfg.path().is_file() {
-            return Err(ConfigError::HomeBankFileNotAFile(cfg.path().to_path_buf()));
+            return Err(ConfigError::HomeBankFileNotAFile(
+                cfg.path().to_path_buf(),
+                PathSource::ConfigFile,
+            ));
         }
 
         // check that the HomeBank XHB file is absolute
@@ -82,6 +149,217 @@ impl TryFrom<&str> for Config {
 
         Ok(cfg)
     }
+
+    /// Find the HomeBank file from the command line options and the environment.
+    ///
+    /// The first of these that is given is used:
+    /// 1. `--file` or `--config` on the command line
+    /// 2. the `HB_FILE`, `HB_PATH`, or `HB_CONFIG` environment variables
+    /// 3. the configuration file in the default location
+    pub fn resolve(opts: &CliOpts) -> Result<Self, ConfigError> {
+        Config::resolve_with_env(
+            opts,
+            env::var_os(HB_FILE_VAR),
+            env::var_os(HB_PATH_VAR),
+            env::var_os(HB_CONFIG_VAR),
+        )
+    }
+
+    /// Find the HomeBank file from the command line options and the values of the environment variables.
+    fn resolve_with_env(
+        opts: &CliOpts,
+        hb_file: Option<OsString>,
+        hb_path: Option<OsString>,
+        hb_config: Option<OsString>,
+    ) -> Result<Self, ConfigError> {
+        // treat empty variables as unset, so that `HB_FILE= hb ...` can be used to ignore an exported one
+        let hb_file = hb_file
+            .filter(|s| !s.is_empty())
+            .map(|s| (HB_FILE_VAR, PathBuf::from(s)))
+            .or_else(|| hb_path.filter(|s| !s.is_empty()).map(|s| (HB_PATH_VAR, PathBuf::from(s))));
+        let hb_config = hb_config.filter(|s| !s.is_empty()).map(PathBuf::from);
+
+        match (opts.file(), opts.path(), hb_file, hb_config) {
+            (Some(file), _, _, _) => Config::from_xhb(file, PathSource::CommandLine("--file")),
+            (None, Some(cfg), _, _) => {
+                Config::from_cfg_file(cfg, PathSource::CommandLine("--config"), opts.profile())
+            }
+            (None, None, Some((var, file)), _) => {
+                if file != Path::new(STDIN_PATH) && !file.exists() {
+                    return Err(ConfigError::EnvPathDoesNotExist(var, file));
+                }
+
+                Config::from_xhb(&file, PathSource::Environment(var))
+            }
+            (None, None, None, Some(cfg)) => {
+                if !cfg.exists() {
+                    return Err(ConfigError::EnvVarInvalid(HB_CONFIG_VAR, cfg));
+                }
+
+                Config::from_cfg_file(&cfg, PathSource::Environment(HB_CONFIG_VAR), opts.profile())
+            }
+            (None, None, None, None) => {
+                Config::from_cfg_file(&default_cfg_file(), PathSource::Default, opts.profile())
+            }
+        }
+    }
+
+    /// Use a HomeBank file directly, bypassing the configuration file entirely.
+    fn from_xhb(file: &Path, source: PathSource) -> Result<Self, ConfigError> {
+        if file != Path::new(STDIN_PATH) && !file.is_file() {
+            return Err(ConfigError::HomeBankFileNotAFile(file.to_path_buf(), source));
+        }
+
+        Ok(Config::new(file))
+    }
+
+    /// Read and parse a configuration file.
+    fn from_cfg_file(
+        path: &Path,
+        source: PathSource,
+        profile: Option<&str>,
+    ) -> Result<Self, ConfigError> {
+        // check that the config file exists
+        if !path.exists() {
+            return Err(ConfigError::DoesNotExist(path.to_path_buf(), source));
+        } else if !path.is_file() {
+            // check that the config is a file
+            return Err(ConfigError::NotAFile(path.to_path_buf(), source));
+        }
+
+        // read the file and parse its contents
+        let file_contents = match file_to_string(path) {
+            Ok(s) => s,
+            Err(_) => return Err(ConfigError::ParseError(path.to_path_buf())),
+        };
+
+        // try to deserialize from its contents via toml
+        Config::from_toml(file_contents.as_str(), profile)
+    }
+}
+
+/// The contents of the `hb` configuration file, before a profile is selected.
+#[derive(Debug, Deserialize, PartialEq)]
+struct ConfigFile {
+    /// Path to the HomeBank file when there is only a single one.
+    path: Option<PathBuf>,
+
+    /// Profile to use when none is given on the command line.
+    default_profile: Option<String>,
+
+    /// Named profiles, each with their own HomeBank file.
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+
+    /// Rules applied to imported transactions, in order.
+    #[serde(default)]
+    rules: Vec<RuleEntry>,
+
+    /// Output format of reports, `table` or `csv`.
+    output_format: Option<String>,
+
+    /// `strftime` format of dates, e.g. `%d.%m.%Y`.
+    date_format: Option<String>,
+
+    /// How the currency of amounts is shown, `none`, `symbol`, or `code`.
+    currency_display: Option<String>,
+}
+
+/// A named HomeBank file in the configuration.
+#[derive(Debug, Deserialize, PartialEq)]
+struct Profile {
+    /// Path to the HomeBank file.
+    path: PathBuf,
+}
+
+/// A rule in the configuration that fills in the details of imported transactions.
+#[derive(Debug, Deserialize, PartialEq)]
+struct RuleEntry {
+    /// Regular expression that the payee's name must match.
+    payee_regex: String,
+
+    /// Full name of the category to set, e.g. `Food:Groceries`.
+    category: Option<String>,
+
+    /// Payment method to set.
+    paymode: Option<String>,
+
+    /// Tags to add.
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl RuleEntry {
+    /// Check the regular expression and payment method of the rule.
+    /// Categories are checked once the HomeBank file is loaded.
+    fn to_rule(&self) -> Result<Rule, ConfigError> {
+        let invalid = |reason: String| ConfigError::InvalidRule(self.payee_regex.clone(), reason);
+
+        let payee = Regex::new(&self.payee_regex).map_err(|e| invalid(e.to_string()))?;
+        let pay_mode = match &self.paymode {
+            Some(s) => Some(
+                <PayMode as FromStr>::from_str(s).map_err(|_| invalid(format!("unknown paymode `{s}`")))?,
+            ),
+            None => None,
+        };
+
+        Ok(Rule::new(payee, self.category.as_deref(), pay_mode, &self.tags))
+    }
+}
+
+impl ConfigFile {
+    /// Names of all the profiles, including the implicit one from a top-level `path`.
+    fn profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.profiles.keys().cloned().collect();
+
+        if self.path.is_some() && !self.profiles.contains_key(IMPLICIT_PROFILE) {
+            names.push(IMPLICIT_PROFILE.to_string());
+            names.sort();
+        }
+
+        names
+    }
+
+    /// Retrieve the path for the named profile.
+    fn named_profile_path(&self, name: &str) -> Result<&Path, ConfigError> {
+        match (self.profiles.get(name), &self.path) {
+            (Some(profile), _) => Ok(&profile.path),
+            (None, Some(path)) if name == IMPLICIT_PROFILE => Ok(path),
+            _ => Err(ConfigError::ProfileNotFound(
+                name.to_string(),
+                self.profile_names(),
+            )),
+        }
+    }
+
+    /// Retrieve the path for the requested profile, falling back on the default one.
+    fn profile_path(&self, profile: Option<&str>) -> Result<&Path, ConfigError> {
+        match (profile, &self.default_profile, &self.path) {
+            (Some(name), _, _) => self.named_profile_path(name),
+            (None, Some(name), _) => self.named_profile_path(name),
+            (None, None, Some(path)) => Ok(path),
+            (None, None, None) if !self.profiles.is_empty() => {
+                Err(ConfigError::NoDefaultProfile(self.profile_names()))
+            }
+            (None, None, None) => Err(ConfigError::MissingHomeBankPath),
+        }
+    }
+}
+
+impl TryFrom<&CliOpts> for Config {
+    type Error = ConfigError;
+
+    fn try_from(opts: &CliOpts) -> Result<Self, Self::Error> {
+        Config::resolve(opts)
+    }
+}
+
+impl TryFrom<&str> for Config {
+    type Error = ConfigError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        Config::from_toml(s, None)
+    }
 }
 
 /// The default folder for the application's configuration
@@ -99,6 +377,15 @@ pub fn default_cfg_file() -> PathBuf {
     default_cfg_dir().join("config.toml")
 }
 
+/// Check that a `strftime` format only contains specifiers that `chrono` understands.
+pub fn check_date_format(date_format: &str) -> Result<(), ConfigError> {
+    if StrftimeItems::new(date_format).any(|item| item == Item::Error) {
+        return Err(ConfigError::InvalidDateFormat(date_format.to_string()));
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -177,6 +464,10 @@ mod tests {
         let input = Path::new("/etc/passwd");
         let expected = Config {
             path: PathBuf::from("/etc/passwd"),
+            rules: vec![],
+            output_format: None,
+            date_format: None,
+            currency_display: None,
         };
 
         check_new(input, expected);
@@ -187,6 +478,10 @@ mod tests {
         let input = Path::new("Cargo.toml");
         let expected = Config {
             path: PathBuf::from("Cargo.toml"),
+            rules: vec![],
+            output_format: None,
+            date_format: None,
+            currency_display: None,
         };
 
         check_new(input, expected);
@@ -203,7 +498,17 @@ mod tests {
     #[should_panic]
     fn try_from_directory_config() {
         let cli_opts = CliOpts {
-            path: PathBuf::from("./src"),
+            path: Some(PathBuf::from("./src")),
+            file: None,
+            lenient: false,
+            skip_errors: false,
+            force: false,
+            overwrite: false,
+            profile: None,
+            output: None,
+            append: false,
+            date_format: None,
+            currency_display: None,
             subcmd: None,
         };
         let expected = Config::new(Path::new("path"));
@@ -215,7 +520,17 @@ mod tests {
     #[should_panic]
     fn try_from_nonexistent_config() {
         let cli_opts = CliOpts {
-            path: PathBuf::from("path/to/nonexistent/directory/file.toml"),
+            path: Some(PathBuf::from("path/to/nonexistent/directory/file.toml")),
+            file: None,
+            lenient: false,
+            skip_errors: false,
+            force: false,
+            overwrite: false,
+            profile: None,
+            output: None,
+            append: false,
+            date_format: None,
+            currency_display: None,
             subcmd: None,
         };
         let expected = Config::new(Path::new(""));
@@ -229,6 +544,10 @@ mod tests {
         let input = CliOpts::new(Path::new("tests/absolute_existing_linux.toml"), None);
         let expected = Config {
             path: PathBuf::from("/etc/passwd"),
+            rules: vec![],
+            output_format: None,
+            date_format: None,
+            currency_display: None,
         };
 
         check_try_from_cli(input, expected);
@@ -241,6 +560,10 @@ mod tests {
         let input = CliOpts::new(Path::new("tests/relative_existing_linux.toml"), None);
         let expected = Config {
             path: PathBuf::from("/etc/passwd"),
+            rules: vec![],
+            output_format: None,
+            date_format: None,
+            currency_display: None,
         };
 
         check_try_from_cli(input, expected);
@@ -253,11 +576,241 @@ mod tests {
         let input = CliOpts::new(Path::new("tests/absolute_missing_linux.toml"), None);
         let expected = Config {
             path: PathBuf::from("/etc/passwd"),
+            rules: vec![],
+            output_format: None,
+            date_format: None,
+            currency_display: None,
         };
 
         check_try_from_cli(input, expected);
     }
 
+    /// Options with neither `--file` nor `--config` given.
+    fn opts_without_paths() -> CliOpts {
+        CliOpts::default()
+    }
+
+    #[track_caller]
+    fn check_resolve(opts: &CliOpts, hb_file: Option<&str>, hb_config: Option<&str>, expected: Config) {
+        let observed =
+            Config::resolve_with_env(opts, hb_file.map(OsString::from), None, hb_config.map(OsString::from))
+                .unwrap();
+
+        assert_eq!(expected, observed);
+    }
+
+    #[track_caller]
+    fn check_resolve_err(opts: &CliOpts, hb_file: Option<&str>, hb_config: Option<&str>, expected_msg: &str) {
+        let observed =
+            Config::resolve_with_env(opts, hb_file.map(OsString::from), None, hb_config.map(OsString::from))
+                .unwrap_err();
+
+        assert_eq!(expected_msg, observed.to_string());
+    }
+
+    #[test]
+    fn resolve_hb_file() {
+        let expected = Config::new(Path::new("Cargo.toml"));
+
+        check_resolve(&opts_without_paths(), Some("Cargo.toml"), None, expected);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn resolve_hb_config() {
+        let expected = Config::new(Path::new("/etc/passwd"));
+
+        check_resolve(
+            &opts_without_paths(),
+            None,
+            Some("tests/absolute_existing_linux.toml"),
+            expected,
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn resolve_hb_file_before_hb_config() {
+        let expected = Config::new(Path::new("Cargo.toml"));
+
+        check_resolve(
+            &opts_without_paths(),
+            Some("Cargo.toml"),
+            Some("tests/absolute_existing_linux.toml"),
+            expected,
+        );
+    }
+
+    #[test]
+    fn resolve_cli_file_before_env() {
+        let opts = CliOpts {
+            file: Some(PathBuf::from("Cargo.toml")),
+            ..Default::default()
+        };
+        let expected = Config::new(Path::new("Cargo.toml"));
+
+        check_resolve(&opts, Some("path/to/stale.xhb"), Some("path/to/stale.toml"), expected);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn resolve_cli_config_before_env() {
+        let opts = CliOpts::new(Path::new("tests/absolute_existing_linux.toml"), None);
+        let expected = Config::new(Path::new("/etc/passwd"));
+
+        check_resolve(&opts, Some("path/to/stale.xhb"), None, expected);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn resolve_empty_hb_file_is_unset() {
+        let expected = Config::new(Path::new("/etc/passwd"));
+
+        check_resolve(
+            &opts_without_paths(),
+            Some(""),
+            Some("tests/absolute_existing_linux.toml"),
+            expected,
+        );
+    }
+
+    #[test]
+    fn resolve_stdin_file() {
+        let opts = CliOpts {
+            file: Some(PathBuf::from("-")),
+            ..Default::default()
+        };
+        let observed = Config::resolve_with_env(&opts, None, None, None).unwrap();
+
+        assert!(observed.is_stdin());
+    }
+
+    #[test]
+    fn resolve_missing_hb_file_names_variable() {
+        check_resolve_err(
+            &opts_without_paths(),
+            Some("path/to/stale.xhb"),
+            None,
+            "HomeBank file `path/to/stale.xhb` from the `HB_FILE` environment variable does not exist. Unset it or point it to an existing HomeBank file.",
+        );
+    }
+
+    #[test]
+    fn resolve_hb_file_directory_is_not_a_file() {
+        check_resolve_err(
+            &opts_without_paths(),
+            Some("src"),
+            None,
+            "HomeBank file `src` (from the `HB_FILE` environment variable) is not a file.",
+        );
+    }
+
+    #[test]
+    fn resolve_hb_path() {
+        let observed = Config::resolve_with_env(&opts_without_paths(), None, Some(OsString::from("Cargo.toml")), None);
+
+        assert_eq!(Config::new(Path::new("Cargo.toml")), observed.unwrap());
+    }
+
+    #[test]
+    fn resolve_hb_file_before_hb_path() {
+        let observed = Config::resolve_with_env(
+            &opts_without_paths(),
+            Some(OsString::from("Cargo.toml")),
+            Some(OsString::from("path/to/stale.xhb")),
+            None,
+        );
+
+        assert_eq!(Config::new(Path::new("Cargo.toml")), observed.unwrap());
+    }
+
+    #[test]
+    fn resolve_missing_hb_path_names_variable() {
+        let observed =
+            Config::resolve_with_env(&opts_without_paths(), None, Some(OsString::from("path/to/stale.xhb")), None);
+
+        assert!(
+            matches!(observed, Err(ConfigError::EnvPathDoesNotExist(HB_PATH_VAR, _))),
+            "{:?}",
+            observed
+        );
+    }
+
+    #[test]
+    fn resolve_missing_hb_config_names_variable() {
+        check_resolve_err(
+            &opts_without_paths(),
+            None,
+            Some("path/to/stale.toml"),
+            "Environment variable `HB_CONFIG` is set to `path/to/stale.toml`, which does not exist. Unset it or point it to an existing configuration file.",
+        );
+    }
+
+    #[test]
+    fn resolve_missing_cli_config_names_flag() {
+        let opts = CliOpts::new(Path::new("path/to/missing.toml"), None);
+
+        check_resolve_err(
+            &opts,
+            None,
+            None,
+            "Configuration file `path/to/missing.toml` (from `--config`) does not exist.",
+        );
+    }
+
+    // the environment is shared by all tests in the process, so tests that modify it take turns
+    static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    #[test]
+    fn resolve_reads_environment() {
+        let _guard = ENV_LOCK.lock().unwrap();
+
+        env::set_var(HB_FILE_VAR, "Cargo.toml");
+        let with_var = Config::resolve(&opts_without_paths());
+
+        env::remove_var(HB_FILE_VAR);
+        let without_var = Config::resolve(&CliOpts {
+            file: Some(PathBuf::from("src/main.rs")),
+            ..Default::default()
+        });
+
+        assert_eq!(Config::new(Path::new("Cargo.toml")), with_var.unwrap());
+        assert_eq!(Config::new(Path::new("src/main.rs")), without_var.unwrap());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn resolve_reads_hb_config() {
+        let _guard = ENV_LOCK.lock().unwrap();
+
+        env::remove_var(HB_FILE_VAR);
+        env::set_var(HB_CONFIG_VAR, "tests/absolute_existing_linux.toml");
+        let from_var = Config::resolve(&opts_without_paths());
+
+        // `--config` takes precedence over the variable
+        let from_flag = Config::resolve(&CliOpts::new(Path::new("tests/empty_file.toml"), None));
+        env::remove_var(HB_CONFIG_VAR);
+
+        assert_eq!(Config::new(Path::new("/etc/passwd")), from_var.unwrap());
+        assert!(matches!(from_flag, Err(ConfigError::MissingHomeBankPath)), "{:?}", from_flag);
+    }
+
+    #[test]
+    fn resolve_cleared_environment_uses_default() {
+        let _guard = ENV_LOCK.lock().unwrap();
+
+        env::set_var(HB_FILE_VAR, "path/to/stale.xhb");
+        env::remove_var(HB_FILE_VAR);
+        env::remove_var(HB_PATH_VAR);
+        env::remove_var(HB_CONFIG_VAR);
+        let observed = Config::resolve(&opts_without_paths());
+
+        // the default configuration may or may not exist, but the stale variable must not be used
+        if let Err(e) = observed {
+            assert!(!e.to_string().contains(HB_FILE_VAR), "{}", e);
+        }
+    }
+
     #[track_caller]
     fn check_try_from_toml(input: &str, expected: Config) {
         let observed = Config::try_from(input).unwrap();
@@ -282,4 +835,209 @@ mod tests {
 
         check_try_from_toml(&input, expected);
     }
+
+    #[track_caller]
+    fn check_from_toml_profile(input: &str, profile: Option<&str>, expected: Config) {
+        let observed = Config::from_toml(input, profile).unwrap();
+
+        assert_eq!(expected, observed);
+    }
+
+    #[track_caller]
+    fn check_from_toml_profile_err(input: &str, profile: Option<&str>, expected_msg: &str) {
+        let observed = Config::from_toml(input, profile).unwrap_err();
+
+        assert_eq!(expected_msg, observed.to_string());
+    }
+
+    const PROFILES_TOML: &str = r#"
+        default_profile = "personal"
+
+        [profiles.personal]
+        path = "/etc/passwd"
+
+        [profiles.household]
+        path = "/etc/group"
+    "#;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn from_toml_named_profile() {
+        let expected = Config::new(Path::new("/etc/group"));
+
+        check_from_toml_profile(PROFILES_TOML, Some("household"), expected);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn from_toml_default_profile() {
+        let expected = Config::new(Path::new("/etc/passwd"));
+
+        check_from_toml_profile(PROFILES_TOML, None, expected);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn from_toml_legacy_path_is_implicit_profile() {
+        let input = "path = '/etc/passwd'";
+        let expected = Config::new(Path::new("/etc/passwd"));
+
+        check_from_toml_profile(input, Some(IMPLICIT_PROFILE), expected);
+    }
+
+    #[test]
+    fn from_toml_missing_profile() {
+        check_from_toml_profile_err(
+            PROFILES_TOML,
+            Some("business"),
+            "Profile `business` not found in the configuration file. Available profiles: household, personal.",
+        );
+    }
+
+    #[test]
+    fn from_toml_missing_profile_lists_implicit_profile() {
+        let input = r#"
+            path = "/etc/passwd"
+
+            [profiles.household]
+            path = "/etc/group"
+        "#;
+
+        check_from_toml_profile_err(
+            input,
+            Some("business"),
+            "Profile `business` not found in the configuration file. Available profiles: default, household.",
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn from_toml_rules() {
+        let input = r#"
+            path = "/etc/passwd"
+
+            [[rules]]
+            payee_regex = "ALBERT HEIJN"
+            category = "Food:Groceries"
+            paymode = "DebitCard"
+            tags = ["weekly"]
+
+            [[rules]]
+            payee_regex = "^Landlord$"
+            category = "Housing:Rent"
+        "#;
+        let observed = Config::from_toml(input, None).unwrap();
+
+        let expected = vec![
+            Rule::new(
+                Regex::new("ALBERT HEIJN").unwrap(),
+                Some("Food:Groceries"),
+                Some(PayMode::DebitCard),
+                &["weekly".to_string()],
+            ),
+            Rule::new(Regex::new("^Landlord$").unwrap(), Some("Housing:Rent"), None, &[]),
+        ];
+        assert_eq!(expected, observed.rules());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn from_toml_display_settings() {
+        let input = r#"
+            path = "/etc/passwd"
+            output_format = "csv"
+            date_format = "%d.%m.%Y"
+            currency_display = "code"
+        "#;
+        let observed = Config::from_toml(input, None).unwrap();
+
+        assert_eq!(Some(ReportFormat::Csv), observed.output_format());
+        assert_eq!(Some("%d.%m.%Y"), observed.date_format());
+        assert_eq!(Some(CurrencyDisplay::Code), observed.currency_display());
+    }
+
+    #[test]
+    fn from_toml_invalid_display_settings() {
+        check_from_toml_profile_err(
+            "path = '/etc/passwd'\ndate_format = '%d.%m.%Q'",
+            None,
+            "Invalid date format `%d.%m.%Q`. It must be a `strftime` format, e.g. `%Y-%m-%d`.",
+        );
+        check_from_toml_profile_err(
+            "path = '/etc/passwd'\noutput_format = 'json'",
+            None,
+            "Invalid output format `json`. It must be `table` or `csv`.",
+        );
+        check_from_toml_profile_err(
+            "path = '/etc/passwd'\ncurrency_display = 'iso'",
+            None,
+            "Invalid currency display `iso`. It must be `none`, `symbol`, or `code`.",
+        );
+    }
+
+    #[test]
+    fn from_toml_invalid_rule() {
+        let input = r#"
+            path = "/etc/passwd"
+
+            [[rules]]
+            payee_regex = "Grocer"
+            paymode = "Barter"
+        "#;
+
+        check_from_toml_profile_err(input, None, "Invalid rule for payees matching `Grocer`: unknown paymode `Barter`.");
+    }
+
+    #[test]
+    fn from_toml_no_default_profile() {
+        let input = r#"
+            [profiles.personal]
+            path = "/etc/passwd"
+        "#;
+
+        check_from_toml_profile_err(
+            input,
+            None,
+            "No profile given and no `default_profile` set in the configuration file. Available profiles: personal.",
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn try_from_cli_with_profile() {
+        let mut input = CliOpts::new(Path::new("tests/profiles_linux.toml"), None);
+        input.profile = Some("household".to_string());
+        let expected = Config::new(Path::new("/etc/group"));
+
+        check_try_from_cli(input, expected);
+    }
+
+    #[test]
+    fn try_from_cli_file_without_config() {
+        let mut input = CliOpts::new(Path::new("path/to/nonexistent/directory/file.toml"), None);
+        input.file = Some(PathBuf::from("Cargo.toml"));
+        let expected = Config::new(Path::new("Cargo.toml"));
+
+        check_try_from_cli(input, expected);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn try_from_cli_file_overrides_config() {
+        let mut input = CliOpts::new(Path::new("tests/absolute_existing_linux.toml"), None);
+        input.file = Some(PathBuf::from("Cargo.toml"));
+        let expected = Config::new(Path::new("Cargo.toml"));
+
+        check_try_from_cli(input, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn try_from_cli_missing_file() {
+        let mut input = CliOpts::new(Path::new("tests/absolute_existing_linux.toml"), None);
+        input.file = Some(PathBuf::from("path/to/nonexistent/file.xhb"));
+        let expected = Config::new(Path::new(""));
+
+        check_try_from_cli(input, expected);
+    }
 }
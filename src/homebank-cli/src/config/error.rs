@@ -1,20 +1,37 @@
 //! Errors when parsing the configuration file
 
+use super::PathSource;
 use std::path::PathBuf;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum ConfigError {
-    #[error("Configuration file `{0}` does not exist.")]
-    DoesNotExist(PathBuf),
-    #[error("Configuration file `{0}` is not a file.")]
-    NotAFile(PathBuf),
+    #[error("Configuration file `{0}` ({1}) does not exist.")]
+    DoesNotExist(PathBuf, PathSource),
+    #[error("Environment variable `{0}` is set to `{1}`, which does not exist. Unset it or point it to an existing configuration file.")]
+    EnvVarInvalid(&'static str, PathBuf),
+    #[error("HomeBank file `{1}` from the `{0}` environment variable does not exist. Unset it or point it to an existing HomeBank file.")]
+    EnvPathDoesNotExist(&'static str, PathBuf),
+    #[error("Configuration file `{0}` ({1}) is not a file.")]
+    NotAFile(PathBuf, PathSource),
     #[error("Configuration file is missing a `path` variable.")]
     MissingHomeBankPath,
     #[error("Error parsing configuration file `{0}`.")]
     ParseError(PathBuf),
-    #[error("HomeBank file `{0}` is not a file.")]
-    HomeBankFileNotAFile(PathBuf),
+    #[error("HomeBank file `{0}` ({1}) is not a file.")]
+    HomeBankFileNotAFile(PathBuf, PathSource),
     #[error("HomeBank file `{0}` is given as a relative path. Please specify it absolutely.")]
     HomeBankFileIsRelative(PathBuf),
+    #[error("Profile `{0}` not found in the configuration file. Available profiles: {}.", .1.join(", "))]
+    ProfileNotFound(String, Vec<String>),
+    #[error("No profile given and no `default_profile` set in the configuration file. Available profiles: {}.", .0.join(", "))]
+    NoDefaultProfile(Vec<String>),
+    #[error("Invalid rule for payees matching `{0}`: {1}.")]
+    InvalidRule(String, String),
+    #[error("Invalid date format `{0}`. It must be a `strftime` format, e.g. `%Y-%m-%d`.")]
+    InvalidDateFormat(String),
+    #[error("Invalid output format `{0}`. It must be `table` or `csv`.")]
+    InvalidOutputFormat(String),
+    #[error("Invalid currency display `{0}`. It must be `none`, `symbol`, or `code`.")]
+    InvalidCurrencyDisplay(String),
 }
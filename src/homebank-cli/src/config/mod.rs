@@ -3,6 +3,8 @@
 pub mod cfg;
 pub mod error;
 pub mod parse;
+pub mod source;
 
-pub use cfg::{default_cfg_file, Config};
+pub use cfg::{check_date_format, default_cfg_file, Config};
 pub use error::ConfigError;
+pub use source::PathSource;
@@ -0,0 +1,31 @@
+//! Where a configuration or HomeBank file path came from
+
+use std::fmt;
+
+/// Where a configuration or HomeBank file path came from.
+/// This is included in error messages, so that a stale environment variable is easy to spot.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PathSource {
+    /// Given by a command line flag (e.g. `--config`).
+    CommandLine(&'static str),
+
+    /// Set by an environment variable (e.g. `HB_CONFIG`).
+    Environment(&'static str),
+
+    /// Set in the `hb` configuration file.
+    ConfigFile,
+
+    /// The default location of the `hb` configuration file.
+    Default,
+}
+
+impl fmt::Display for PathSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathSource::CommandLine(flag) => write!(f, "from `{flag}`"),
+            PathSource::Environment(var) => write!(f, "from the `{var}` environment variable"),
+            PathSource::ConfigFile => write!(f, "from the configuration file"),
+            PathSource::Default => write!(f, "from the default location"),
+        }
+    }
+}
@@ -5,9 +5,14 @@
 
 use anyhow::Context;
 use clap::Parser;
-use cli::{budget::budget_pbar, CliOpts, SubCommand};
+use cli::{
+    budget::{budget_explanation, budget_pbar}, cashflow_table, category_stats_table, category_tree, duplicates_table, edit_description, DisplaySettings, grouped_table, has_content, load_db, monthly_table, open_output, net_worth_table, reconcile_table, print_completions, same_file, statement_summary, stats_summary, tag_stats_table, tags_table, top_table, watch_file, yearly_table, CliOpts, CsvStyle, SubCommand,
+};
 use config::Config;
-use homebank_db::{transaction::sum_transactions, HomeBankDb, Query, QueryType};
+use homebank_db::{
+    transaction::{sum_transactions, GroupedResult}, edit_xhb, EditType, ExportType, ImportType, Query, QueryType, ReportFormat, ReportType, Rules,
+};
+use std::io::Write;
 
 pub mod cli;
 pub mod config;
@@ -16,56 +21,116 @@ pub mod config;
 fn main() -> Result<(), anyhow::Error> {
     let cli_opts = CliOpts::parse();
 
+    // checked once, since the file is replaced on every refresh with `--watch`
+    if let Some(output) = cli_opts.output() {
+        if output.exists() && !cli_opts.overwrite() && !cli_opts.append() {
+            anyhow::bail!("Output file `{}` already exists. Use `--overwrite` to replace it.", output.display());
+        }
+    }
+
+    // completions don't need a HomeBank file, so they're printed before one is loaded
+    if let Some(SubCommand::Completions { shell }) = cli_opts.subcommand() {
+        let mut out = open_output(cli_opts.output(), false)?;
+        print_completions(*shell, &mut out);
+        out.finish()?;
+
+        return Ok(());
+    }
+
+    if cli_opts.file().is_some() && cli_opts.path().is_some() {
+        eprintln!("Warning: `--file` was given, ignoring the configuration file from `--config`.");
+    }
+
     let cfg = Config::try_from(&cli_opts)?;
-    let db = match HomeBankDb::try_from(cfg.path()) {
-        Ok(db) => db,
-        Err(e) => return Err(e).with_context(|| "Error parsing HomeBank file."),
-    };
+
+    // the output would replace the HomeBank file before it is read
+    if cli_opts.output().is_some_and(|output| same_file(output, cfg.path())) {
+        anyhow::bail!("The output file cannot be the HomeBank file `{}`.", cfg.path().display());
+    }
+    let settings = DisplaySettings::resolve(&cfg, &cli_opts)?;
+
+    // only the rows of a CSV report can be added to the end of a file
+    let csv_report = matches!(cli_opts.subcommand(), Some(SubCommand::Report(_))) && settings.format() == ReportFormat::Csv;
+    if cli_opts.append() && !csv_report {
+        anyhow::bail!("`--append` can only be used with `report --format csv`.");
+    }
+
+    match cli_opts.subcommand().and_then(|subcmd| subcmd.watch()) {
+        Some(_) if cfg.is_stdin() => anyhow::bail!("Cannot watch a HomeBank file read from stdin."),
+        Some(watch_opts) => watch_file(cfg.path(), watch_opts, None, || run(&cfg, &cli_opts, &settings)),
+        None => run(&cfg, &cli_opts, &settings),
+    }
+}
+
+/// Load the HomeBank file and run the subcommand on it.
+fn run(cfg: &Config, cli_opts: &CliOpts, settings: &DisplaySettings) -> Result<(), anyhow::Error> {
+    let db = load_db(cfg, cli_opts, &mut std::io::stdin().lock())?;
+
+    // checked before the file is opened, since that creates it
+    let skip_header = cli_opts.append() && cli_opts.output().is_some_and(has_content);
+    let mut out = open_output(cli_opts.output(), cli_opts.append())?;
 
     match &cli_opts.subcommand() {
         Some(SubCommand::Query(q_opts)) => match q_opts.qtype() {
-            QueryType::Transactions(query) => {
-                let filt_transactions = query.exec(&db);
+            QueryType::Transactions(query) => match query.grouped(&db) {
+                GroupedResult::Transactions(filt_transactions) => {
+                    writeln!(out, "{:#?}", filt_transactions)?;
+                }
+                GroupedResult::Groups(groups) => {
+                    let mut table = grouped_table(&groups, query.group_by());
 
-                println!("{:#?}", filt_transactions);
-            }
+                    settings.format_table(&mut table, &db);
+
+                    writeln!(out, "{}", table.render_table())?;
+                }
+            },
             QueryType::Payees(query) => {
                 let filt_payees = query.exec(&db);
 
-                println!("{:#?}", filt_payees);
+                writeln!(out, "{:#?}", filt_payees)?;
             }
             QueryType::Currencies(query) => {
                 let filt_currencies = query.exec(&db);
 
-                println!("{:#?}", filt_currencies);
+                writeln!(out, "{:#?}", filt_currencies)?;
+            }
+            QueryType::Categories(query) if query.tree() => {
+                write!(out, "{}", category_tree(&db))?;
             }
             QueryType::Categories(query) => {
                 let filt_categories = query.exec(&db);
 
                 for cat in filt_categories {
-                    println!("{}", cat.full_name(&db));
+                    writeln!(out, "{}", cat.full_name(&db))?;
                 }
             }
             QueryType::Accounts(query) => {
                 let filt_accounts = query.exec(&db);
 
-                println!("{:#?}", filt_accounts);
+                writeln!(out, "{:#?}", filt_accounts)?;
             }
             QueryType::Groups(query) => {
                 let filt_groups = query.exec(&db);
 
-                println!("{:#?}", filt_groups);
+                writeln!(out, "{:#?}", filt_groups)?;
             }
-        },
-        // QueryType::Templates(query) => {
-        //     let filt_templates = query.exec(&db);
+            QueryType::Tags(query) => {
+                let mut table = tag_stats_table(&query.exec(&db), query.case_insensitive());
 
-        //     println!("{:#?}", filt_templates);
-        // }
+                settings.format_table(&mut table, &db);
+
+                writeln!(out, "{}", table.render_table())?;
+            }
+            QueryType::Templates(query) => {
+                let filt_templates = query.exec(&db);
+
+                writeln!(out, "{:#?}", filt_templates)?;
+            }
+        },
         Some(SubCommand::Sum(query)) => {
             let filt_transactions = query.exec(&db);
-            let sum = sum_transactions(&filt_transactions);
-            println!("{sum:.2}");
+            let sum = sum_transactions(&filt_transactions, None);
+            writeln!(out, "{}", settings.amount(sum, &db))?;
         }
         Some(SubCommand::Budget(query)) => {
             let filt_budget = query.exec(&db);
@@ -75,8 +140,14 @@ fn main() -> Result<(), anyhow::Error> {
             }
 
             for summary in filt_budget {
+                let explanation = query.explain().then(|| budget_explanation(&summary, &db, settings));
+
                 let pbar = budget_pbar(summary);
                 pbar.abandon();
+
+                if let Some(lines) = explanation {
+                    write!(out, "{lines}")?;
+                }
             }
         }
         Some(SubCommand::Review(query)) => {
@@ -85,14 +156,162 @@ fn main() -> Result<(), anyhow::Error> {
             // print the values in a tab-separated format
             for (cat, subcat, sum) in review {
                 if let Some(subcat_name) = subcat {
-                    println!("{cat}\t{subcat_name}\t{sum:.2}");
+                    writeln!(out, "{cat}\t{subcat_name}\t{sum:.2}")?;
                 } else {
-                    println!("{cat}\t\t{sum:.2}");
+                    writeln!(out, "{cat}\t\t{sum:.2}")?;
                 }
             }
         }
+        Some(SubCommand::Report(r_opts)) => {
+            let csv_style = CsvStyle::new(r_opts.decimal_sep(), r_opts.thousands_sep())?;
+
+            let mut table = match r_opts.rtype() {
+                ReportType::Monthly(report) => monthly_table(&report.exec(&db)),
+                ReportType::Yearly(report) => yearly_table(&report.exec(&db)),
+                ReportType::Top(report) => top_table(report, &db, settings),
+                ReportType::Cashflow(report) => cashflow_table(&report.exec(&db)),
+                ReportType::Duplicates(report) => duplicates_table(report, &db, settings),
+                ReportType::Reconcile(report) => reconcile_table(&report.exec(&db)),
+                ReportType::NetWorth(report) => net_worth_table(&report.exec(&db), settings),
+                ReportType::Tags(report) => tags_table(&report.exec(&db)),
+                ReportType::Stats(report) => category_stats_table(&report.exec(&db)),
+            };
+
+            // totals are in the base currency, while transactions already have the currency of their account
+            settings.format_table(&mut table, &db);
+
+            // the header is already at the top of a file that is being appended to
+            if skip_header {
+                let rows = table.render_csv_rows(&csv_style);
+
+                if !rows.is_empty() {
+                    writeln!(out, "{rows}")?;
+                }
+            } else {
+                writeln!(out, "{}", table.render(&settings.format(), &csv_style))?;
+            }
+        }
+        Some(SubCommand::Reconcile(statement)) => {
+            let comparison = statement.compare(&db)?;
+
+            writeln!(out, "{}", statement_summary(&comparison, &db, settings))?;
+        }
+        Some(SubCommand::Export(e_opts)) => match e_opts.etype() {
+            ExportType::Beancount(export) => write!(out, "{}", export.ledger(&db))?,
+            ExportType::Ledger(export) => write!(out, "{}", export.journal(&db))?,
+            ExportType::Qif(export) => write!(out, "{}", export.qif(&db))?,
+        },
+        Some(SubCommand::Import(i_opts)) => {
+            if cfg.is_stdin() {
+                anyhow::bail!("Cannot import into a HomeBank file read from stdin.");
+            }
+
+            // check the rules before reading anything, so that a mistake in them is reported first
+            let rules = Rules::new(cfg.rules(), &db).context("Error in the rules of the configuration file.")?;
+
+            let (mut result, dry_run) = match i_opts.itype() {
+                ImportType::Csv(import) => (import.read(&db)?, import.dry_run()),
+                ImportType::Ofx(import) => (import.read(&db)?, import.dry_run()),
+            };
+            rules.apply_to_import(&mut result, &db);
+
+            for (line, e) in result.errors() {
+                eprintln!("Error on line {line}: {e}");
+            }
+            for (line, tr) in result.duplicates() {
+                eprintln!("Skipped duplicate on line {line}: {}\t{:.2}", settings.date(tr.date()), tr.total());
+            }
+
+            // write all of the rows or none of them, so that fixing the file and importing it again is safe
+            if !result.errors().is_empty() {
+                anyhow::bail!(
+                    "{} row(s) could not be imported, so `{}` was not changed.",
+                    result.errors().len(),
+                    cfg.path().display()
+                );
+            }
+
+            if dry_run {
+                for payee in result.payees() {
+                    writeln!(out, "New payee: {}", payee.name())?;
+                }
+                for cat in result.categories() {
+                    writeln!(out, "New category: {}", result.category_full_name(cat, &db))?;
+                }
+                for (_, tr) in result.transactions() {
+                    writeln!(
+                        out,
+                        "{}\t{:.2}\t{}\t{}\t{}",
+                        settings.date(tr.date()),
+                        tr.total(),
+                        result.payee_name(tr, &db).unwrap_or_default(),
+                        tr.memo().as_deref().unwrap_or_default(),
+                        result.category_name(tr, &db).unwrap_or_default()
+                    )?;
+                }
+            } else {
+                result
+                    .write(cfg.path())
+                    .with_context(|| format!("Error writing HomeBank file `{}`.", cfg.path().display()))?;
+
+                writeln!(
+                    out,
+                    "Imported {} transaction(s) into `{}`.",
+                    result.transactions().len(),
+                    cfg.path().display()
+                )?;
+            }
+        }
+        Some(SubCommand::Edit(e_opts)) => {
+            if cfg.is_stdin() {
+                anyhow::bail!("Cannot edit a HomeBank file read from stdin.");
+            }
+
+            let (plan, dry_run) = match e_opts.etype() {
+                EditType::Category(edit) => (vec![edit.plan(&db)?], edit.dry_run()),
+                EditType::Payee(edit) => (edit.plan(&db)?, edit.dry_run()),
+                EditType::Tag(edit) => (vec![edit.plan(&db)?], edit.dry_run()),
+            };
+            let summary = edit_xhb(cfg.path(), &plan, dry_run)
+                .with_context(|| format!("Error writing HomeBank file `{}`.", cfg.path().display()))?;
+
+            let path = (!dry_run).then(|| cfg.path());
+            writeln!(out, "{}", edit_description(&plan, &db, path))?;
+            write!(out, "{summary}")?;
+        }
+        Some(SubCommand::Validate { unknown }) => {
+            let issues = db.validate();
+            let num_errors = issues.iter().filter(|issue| issue.is_error()).count();
+
+            for issue in &issues {
+                writeln!(out, "{issue}")?;
+            }
+
+            // ignored data is lost if the file is written, but it isn't inconsistent
+            if *unknown {
+                let report = db.parse_report();
+
+                for (element, count) in report.unknown_elements() {
+                    writeln!(out, "note [unknown-element]: Ignored {count} <{element}> element(s).")?;
+                }
+                for ((element, attribute), count) in report.unknown_attributes() {
+                    writeln!(out, "note [unknown-attribute]: Ignored the `{attribute}` attribute of {count} <{element}> element(s).")?;
+                }
+            }
+
+            // exit with an error so that scripts can detect an inconsistent file, keeping the issues that were found
+            if num_errors > 0 {
+                out.finish()?;
+                anyhow::bail!("Found {num_errors} error(s) in the HomeBank file.");
+            }
+        }
+        Some(SubCommand::Stats { year }) => writeln!(out, "{}", stats_summary(&db.stats(*year), &db, settings))?,
+        // handled before the HomeBank file is loaded
+        Some(SubCommand::Completions { .. }) => {}
         None => {}
     }
 
+    out.finish()?;
+
     Ok(())
 }
@@ -0,0 +1,1055 @@
+//! Command line tests for the `hb` binary.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tempfile::TempDir;
+
+/// Path to a HomeBank file with a few months of transactions.
+fn xhb_fixture() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../homebank-db/tests/transactions.xhb")
+}
+
+/// Write an `hb` configuration file pointing to `xhb` into `dir`, e.g. a [`TempDir`] that is removed after the test.
+fn write_config(dir: &Path, xhb: &str) -> PathBuf {
+    let cfg = dir.join("config.toml");
+    fs::write(&cfg, format!("path = {xhb:?}\n")).unwrap();
+
+    cfg
+}
+
+/// Run `hb` with a configuration directory that has no configuration file in it.
+fn hb() -> Command {
+    let missing_dir = std::env::temp_dir().join("hb-cli-tests-nonexistent");
+
+    let mut cmd = Command::cargo_bin("hb").unwrap();
+    cmd.env("HOME", &missing_dir)
+        .env("XDG_CONFIG_HOME", &missing_dir)
+        .env("APPDATA", &missing_dir)
+        .env_remove("HB_FILE")
+        .env_remove("HB_PATH")
+        .env_remove("HB_CONFIG");
+
+    cmd
+}
+
+#[test]
+fn file_without_default_config() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .arg("sum")
+        .assert()
+        .success()
+        .stdout("44.25\n")
+        .stderr("");
+}
+
+#[test]
+fn file_from_stdin() {
+    hb().args(["--file", "-", "sum"])
+        .write_stdin(fs::read(xhb_fixture()).unwrap())
+        .assert()
+        .success()
+        .stdout("44.25\n")
+        .stderr("");
+}
+
+#[test]
+fn file_overrides_config_with_warning() {
+    let empty_xhb = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../homebank-db/tests/empty.xhb");
+    let dir = TempDir::new().unwrap();
+    let cfg = write_config(dir.path(), empty_xhb.canonicalize().unwrap().to_str().unwrap());
+
+    hb().arg("--config")
+        .arg(cfg)
+        .arg("--file")
+        .arg(xhb_fixture())
+        .arg("sum")
+        .assert()
+        .success()
+        .stdout("44.25\n")
+        .stderr(predicate::str::contains("`--file` was given"));
+}
+
+#[test]
+fn config_without_file() {
+    let xhb = xhb_fixture().canonicalize().unwrap();
+    let dir = TempDir::new().unwrap();
+    let cfg = write_config(dir.path(), xhb.to_str().unwrap());
+
+    hb().arg("--config")
+        .arg(cfg)
+        .arg("sum")
+        .assert()
+        .success()
+        .stdout("44.25\n")
+        .stderr("");
+}
+
+#[test]
+fn missing_default_config_without_file() {
+    hb().arg("sum")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("does not exist"));
+}
+
+#[test]
+fn missing_file() {
+    hb().arg("--file")
+        .arg("path/to/nonexistent/file.xhb")
+        .arg("sum")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is not a file"));
+}
+
+#[test]
+fn file_from_environment() {
+    hb().env("HB_FILE", xhb_fixture())
+        .arg("sum")
+        .assert()
+        .success()
+        .stdout("44.25\n")
+        .stderr("");
+}
+
+#[test]
+fn file_from_hb_path() {
+    hb().env("HB_PATH", xhb_fixture())
+        .arg("sum")
+        .assert()
+        .success()
+        .stdout("44.25\n")
+        .stderr("");
+}
+
+#[test]
+fn stale_environment_file() {
+    hb().env("HB_FILE", "path/to/stale.xhb")
+        .arg("sum")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("`HB_FILE` environment variable"));
+}
+
+#[test]
+fn query_transactions_group_by() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["--currency-display", "symbol", "query", "transactions", "--payee", "Landlord", "--group-by", "payee"])
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("Landlord      3  $ -3,600.00  $ -1,200.00  $ -1,200.00  $ -1,200.00\n"));
+}
+
+#[test]
+fn query_transactions_group_by_plain_amounts() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["query", "transactions", "--payee", "Landlord", "--group-by", "payee"])
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("Landlord      3  -3600.00  -1200.00  -1200.00  -1200.00\n"));
+}
+
+#[test]
+fn query_payees_in_key_order() {
+    let run = || hb().arg("--file").arg(xhb_fixture()).args(["query", "payees"]).output().unwrap().stdout;
+    let first = String::from_utf8(run()).unwrap();
+
+    // the same file always gives the same output, listed by key
+    assert_eq!(first, String::from_utf8(run()).unwrap());
+    let positions: Vec<usize> = ["\"Employer\"", "\"Landlord\"", "\"Grocer\"", "\"Amazon.com (Prime)\""]
+        .iter()
+        .map(|name| first.find(name).unwrap())
+        .collect();
+    assert!(positions.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn query_categories_tree() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["query", "categories", "--tree"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+ Housing (0)\n    Rent (3)\n"));
+}
+
+#[test]
+fn report_top_payees() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["report", "--format", "csv", "top", "--type", "payees", "--n", "2"])
+        .assert()
+        .success()
+        .stdout("Payee,Transactions\nGrocer,3\nLandlord,3\n");
+}
+
+#[test]
+fn report_tags() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["report", "--format", "csv", "tags"])
+        .assert()
+        .success()
+        .stdout("Tag,Spending\nfood,210.25\nrestaurant,60.00\n");
+}
+
+#[test]
+fn report_category_stats() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["report", "--format", "csv", "stats", "--category", "^Food:"])
+        .assert()
+        .success()
+        .stdout(
+            "Category,Transactions,Total,Average,Median,Std. dev.\n\
+             Food:Dining,2,-80.00,-40.00,-40.00,20.00\n\
+             Food:Groceries,2,-230.25,-115.12,-115.12,35.12\n",
+        );
+}
+
+#[test]
+fn output_replaces_or_appends_to_file() {
+    let dir = TempDir::new().unwrap();
+    let output = dir.path().join("top.csv");
+    fs::write(&output, "previous\n").unwrap();
+    let top_payees = |append: bool| {
+        let mut cmd = hb();
+        cmd.arg("--file").arg(xhb_fixture()).arg("--output").arg(&output);
+        if append {
+            cmd.arg("--append");
+        } else {
+            cmd.arg("--overwrite");
+        }
+
+        cmd.args(["report", "--format", "csv", "top", "--type", "payees", "--n", "1"])
+            .assert()
+            .success()
+            .stdout("");
+    };
+
+    top_payees(false);
+    assert_eq!("Payee,Transactions\nGrocer,3\n", fs::read_to_string(&output).unwrap());
+
+    // the header is only written once
+    top_payees(true);
+    assert_eq!("Payee,Transactions\nGrocer,3\nGrocer,3\n", fs::read_to_string(&output).unwrap());
+
+    top_payees(false);
+    assert_eq!("Payee,Transactions\nGrocer,3\n", fs::read_to_string(&output).unwrap());
+}
+
+#[test]
+fn output_to_new_file() {
+    let output = std::env::temp_dir().join(format!("hb-cli-output-{}.txt", std::process::id()));
+    let _ = fs::remove_file(&output);
+
+    hb().arg("--file").arg(xhb_fixture()).arg("--output").arg(&output).arg("sum").assert().success().stdout("");
+    assert_eq!("44.25\n", fs::read_to_string(&output).unwrap());
+
+    // an existing file is only replaced with `--overwrite`
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .arg("--output")
+        .arg(&output)
+        .args(["sum", "--date-from", "2022-01-01"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already exists. Use `--overwrite` to replace it."));
+    // `--force` only reads unsupported versions
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["--force", "--output"])
+        .arg(&output)
+        .arg("sum")
+        .assert()
+        .failure();
+    assert_eq!("44.25\n", fs::read_to_string(&output).unwrap());
+
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["--overwrite", "--output"])
+        .arg(&output)
+        .args(["sum", "--date-from", "2022-01-01"])
+        .assert()
+        .success();
+    assert_eq!("1244.25\n", fs::read_to_string(&output).unwrap());
+
+    fs::remove_file(&output).unwrap();
+}
+
+#[test]
+fn output_kept_when_command_fails() {
+    let output = std::env::temp_dir().join(format!("hb-cli-output-failed-{}.txt", std::process::id()));
+    fs::write(&output, "previous\n").unwrap();
+
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["--overwrite", "--output"])
+        .arg(&output)
+        .args(["reconcile", "--account", "^Nonexistent$", "--statement-date", "2022-02-28", "--statement-balance", "0"])
+        .assert()
+        .failure();
+    assert_eq!("previous\n", fs::read_to_string(&output).unwrap());
+
+    fs::remove_file(&output).unwrap();
+}
+
+#[test]
+fn output_cannot_be_homebank_file() {
+    let dir = TempDir::new().unwrap();
+    let xhb = xhb_copy(dir.path());
+    let original = fs::read_to_string(&xhb).unwrap();
+
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(["--overwrite", "--output"])
+        .arg(&xhb)
+        .arg("sum")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("The output file cannot be the HomeBank file"));
+    assert_eq!(original, fs::read_to_string(&xhb).unwrap());
+}
+
+#[test]
+fn append_needs_csv_report() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["--output", "unused.txt", "--append", "sum"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("`--append` can only be used with `report --format csv`."));
+    hb().arg("--file").arg(xhb_fixture()).args(["--append", "sum"]).assert().failure();
+}
+
+#[test]
+fn report_net_worth() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["report", "-f", "csv", "net-worth", "--interval", "quarter"])
+        .args(["--date-from", "2021-11-01", "--date-to", "2022-02-28"])
+        .assert()
+        .success()
+        .stdout("Date,Banking,Total\n2021-12-31,4550.00,4550.00\n2022-02-28,5794.25,5794.25\n");
+}
+
+#[test]
+fn report_cashflow() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["report", "-f", "csv", "cashflow", "--interval", "year"])
+        .assert()
+        .success()
+        .stdout(
+            "Period,Income,Expenses,Net\n\
+             2021,0.00,-1200.00,-1200.00\n\
+             2022,4000.00,-2755.75,1244.25\n\
+             Average,2000.00,-1977.88,22.12\n",
+        );
+}
+
+#[test]
+fn report_reconcile() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["report", "-f", "csv", "reconcile", "--account", "Chequing", "--as-of", "2022-01-31"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Account,Reconciled,Cleared,Uncleared,Balance\nChequing,"));
+}
+
+#[test]
+fn reconcile_matching_statement() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["reconcile", "--account", "Chequing", "--statement-date", "2022-02-28", "--statement-balance", "1849.75"])
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("The cleared balance matches the statement.\n"));
+}
+
+#[test]
+fn reconcile_lists_uncleared_transactions() {
+    // highest amount first
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["reconcile", "-a", "Chequing", "-d", "2022-02-28", "-b", "1804.25"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Difference:               -45.50\n"))
+        .stdout(predicate::str::is_match("(?s)Streaming.*February rent").unwrap());
+}
+
+#[test]
+fn reconcile_several_accounts() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["reconcile", "-a", "a", "-d", "2022-02-28", "-b", "0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("`a` matches more than one account: Savings, Credit Card."));
+}
+
+#[test]
+fn report_duplicates() {
+    // the rent on 2022-01-10 and 2022-02-10 only matches once the date is left out
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["report", "-f", "csv", "duplicates", "--key", "amount,payee,category"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "Group,Date,Account,Payee,Category,Memo,Amount\n1,2021-12-10,Chequing,Landlord,Housing:Rent,December rent,-1200.00\n",
+        ));
+}
+
+#[test]
+fn report_duplicates_within_tolerance() {
+    // the rent is paid on the 10th of every month, at most 31 days apart
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["report", "-f", "csv", "duplicates", "--tolerance-days", "31"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "1,2021-12-10,Chequing,Landlord,Housing:Rent,December rent,-1200.00\n1,2022-01-10,",
+        ));
+}
+
+#[test]
+fn report_duplicates_none_by_default() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["report", "-f", "csv", "duplicates"])
+        .assert()
+        .success()
+        .stdout("Group,Date,Account,Payee,Category,Memo,Amount\n");
+}
+
+#[test]
+fn report_top_smallest_transactions() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["report", "-f", "csv", "top", "-t", "transactions", "-n", "1", "--smallest"])
+        .assert()
+        .success()
+        .stdout("Date,Account,Payee,Category,Memo,Amount\n2021-12-10,Chequing,Landlord,Housing:Rent,December rent,-1200.00\n");
+}
+
+#[test]
+fn report_with_display_settings_from_config() {
+    let xhb = xhb_fixture().canonicalize().unwrap();
+    let dir = TempDir::new().unwrap();
+    let cfg = write_config(dir.path(), xhb.to_str().unwrap());
+    let mut contents = fs::read_to_string(&cfg).unwrap();
+    contents.push_str("output_format = \"csv\"\ndate_format = \"%d.%m.%Y\"\ncurrency_display = \"code\"\n");
+    fs::write(&cfg, contents).unwrap();
+
+    let top = ["report", "top", "-t", "transactions", "-n", "1", "--smallest"];
+
+    hb().arg("--config")
+        .arg(&cfg)
+        .args(top)
+        .assert()
+        .success()
+        .stdout("Date,Account,Payee,Category,Memo,Amount\n10.12.2021,Chequing,Landlord,Housing:Rent,December rent,-1200.00\n");
+
+    // flags on the command line take precedence over the configuration file
+    hb().arg("--config")
+        .arg(&cfg)
+        .args(["--date-format", "%Y/%m/%d"])
+        .args(top)
+        .args(["--format", "table"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2021/12/10  Chequing"));
+
+    hb().arg("--config")
+        .arg(&cfg)
+        .args(["--currency-display", "symbol", "sum"])
+        .assert()
+        .success()
+        .stdout("$ 44.25\n");
+}
+
+#[test]
+fn invalid_date_format() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["--date-format", "%Q", "sum"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid date format `%Q`"));
+}
+
+#[test]
+fn validate_consistent_file() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .arg("validate")
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn validate_inconsistent_file() {
+    let xhb = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../homebank-db/tests/inconsistent.xhb");
+
+    hb().arg("--file")
+        .arg(xhb)
+        .arg("validate")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("error [missing-account]: Transaction #2 (2022-01-06) is in account 5, which does not exist."))
+        .stdout(predicate::str::contains("warning [missing-payee]"))
+        .stderr(predicate::str::contains("Found 6 error(s)"));
+}
+
+#[test]
+fn validate_unknown_elements_and_attributes() {
+    let dir = TempDir::new().unwrap();
+    let xhb = xhb_copy(dir.path());
+    let content = fs::read_to_string(&xhb)
+        .unwrap()
+        .replacen("<ope ", "<tag key=\"1\" name=\"food\"/>\n<ope color=\"2\" ", 1);
+    fs::write(&xhb, content).unwrap();
+
+    hb().arg("--file").arg(&xhb).arg("validate").assert().success().stdout("");
+    hb().arg("--file")
+        .arg(&xhb)
+        .arg("validate")
+        .arg("--unknown")
+        .assert()
+        .success()
+        .stdout("note [unknown-element]: Ignored 1 <tag> element(s).\nnote [unknown-attribute]: Ignored the `color` attribute of 1 <ope> element(s).\n");
+}
+
+#[test]
+fn force_reads_unsupported_version() {
+    let xhb = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../homebank-db/tests/future_version.xhb");
+
+    hb().arg("--file")
+        .arg(&xhb)
+        .arg("sum")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unsupported XHB file version 2.0.0"))
+        .stderr(predicate::str::contains("Use `--force`"));
+    hb().arg("--file").arg(&xhb).args(["--force", "sum"]).assert().success().stdout("1000.00\n");
+}
+
+#[test]
+fn skip_errors_reports_skipped_transactions() {
+    let bad_xhb = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../homebank-db/tests/missing_amount.xhb");
+
+    hb().arg("--file").arg(&bad_xhb).arg("sum").assert().failure();
+
+    hb().arg("--file")
+        .arg(&bad_xhb)
+        .args(["--skip-errors", "sum"])
+        .assert()
+        .success()
+        .stdout("724.50\n")
+        .stderr(predicate::str::contains("Skipped 1 transaction(s)"));
+}
+
+#[test]
+fn sum_convert_to_base() {
+    let xhb = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../homebank-db/tests/currencies.xhb");
+
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(["sum", "--convert-to-base"])
+        .assert()
+        .success()
+        .stdout("-40.00\n");
+}
+
+#[test]
+fn sum_exclude_void() {
+    let void_xhb = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../homebank-db/tests/void.xhb");
+
+    hb().arg("--file")
+        .arg(&void_xhb)
+        .args(["sum", "--exclude-void"])
+        .assert()
+        .success()
+        .stdout("800.00\n");
+
+    hb().arg("--file")
+        .arg(&void_xhb)
+        .arg("sum")
+        .assert()
+        .success()
+        .stdout("750.00\n");
+}
+
+#[test]
+fn completions_without_config() {
+    hb().args(["completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("validate"))
+        .stderr("");
+}
+
+#[test]
+fn export_ledger() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["export", "ledger", "--date-to", "2021-12-11"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "2021-12-10 * Landlord\n    ; December rent\n    Assets:Chequing  $-1200.00\n    Expenses:Housing:Rent\n",
+        ))
+        .stderr("");
+}
+
+#[test]
+fn report_monthly_fill_empty() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["report", "monthly", "--year", "2022", "--fill-empty"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("March 2022         0.00      0.00    0.00\n"))
+        .stdout(predicate::str::contains("December 2022"));
+}
+
+#[test]
+fn stats() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["stats", "--year", "2022"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Transactions:               11\n"))
+        .stdout(predicate::str::contains("First transaction:          2021-12-10\nLast transaction:           2022-02-14\n"))
+        .stdout(predicate::str::contains("Uncategorised transactions: 0\n"));
+}
+
+#[test]
+fn export_beancount() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["export", "beancount", "--date-to", "2021-12-11"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("2021-12-10 open Assets:Chequing CAD\n"))
+        .stdout(predicate::str::contains(
+            "2021-12-10 * \"Landlord\" \"December rent\"\n  Assets:Chequing  -1200.00 CAD\n  Expenses:Housing:Rent\n",
+        ))
+        .stderr("");
+}
+
+#[test]
+fn budget_explain() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["budget", "Groceries", "--date-from", "2022-01-01", "--date-to", "2022-02-01", "--explain"])
+        .assert()
+        .success()
+        .stdout("    2022-01-15\t-150.25\tGrocer\tWeekly groceries\n    2022-01-20\t-80.00\tGrocer\t\n");
+}
+
+#[test]
+fn export_qif() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["export", "qif", "--account", "^Savings$", "--date-format", "%Y-%m-%d"])
+        .assert()
+        .success()
+        .stdout("!Account\nNSavings\nTBank\n^\n!Type:Bank\nD2022-01-25\nT500.00\nMSavings\nL[Chequing]\n^\n")
+        .stderr("");
+}
+
+/// Path to a CSV file downloaded from a bank, with one row already in `transactions.xhb`.
+fn csv_fixture() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../homebank-db/tests/bank.csv")
+}
+
+/// Copy the HomeBank fixture into `dir`, e.g. a [`TempDir`] that is removed after the test, so that it can be changed.
+fn xhb_copy(dir: &Path) -> PathBuf {
+    let xhb = dir.join("transactions.xhb");
+    fs::copy(xhb_fixture(), &xhb).unwrap();
+
+    xhb
+}
+
+/// Arguments to import `bank.csv` into the chequing account.
+fn import_csv_args() -> Vec<String> {
+    vec![
+        "import".to_string(),
+        "csv".to_string(),
+        csv_fixture().to_str().unwrap().to_string(),
+        "--account".to_string(),
+        "Chequing".to_string(),
+        "--map".to_string(),
+        "date=0,amount=3,payee=1,memo=2".to_string(),
+        "--date-format".to_string(),
+        "%d.%m.%Y".to_string(),
+    ]
+}
+
+#[test]
+fn import_csv() {
+    let dir = TempDir::new().unwrap();
+    let xhb = xhb_copy(dir.path());
+
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(import_csv_args())
+        .arg("--create-payees")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Imported 2 transaction(s)"))
+        .stderr("Skipped duplicate on line 2: 2022-01-15\t-150.25\n");
+
+    hb().arg("--file")
+        .arg(&xhb)
+        .arg("sum")
+        .assert()
+        .success()
+        .stdout("-22.35\n");
+
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(["query", "payees", "^Bakery$"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Bakery"));
+}
+
+#[test]
+fn import_csv_dry_run() {
+    let dir = TempDir::new().unwrap();
+    let xhb = xhb_copy(dir.path());
+
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(import_csv_args())
+        .args(["--create-payees", "--allow-duplicates", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(
+            "New payee: Bakery\n\
+             2022-01-15\t-150.25\tGrocer\tWeekly groceries\t\n\
+             2022-03-01\t-62.10\tGrocer\tGroceries\t\n\
+             2022-03-02\t-4.50\tBakery\tBread\t\n",
+        )
+        .stderr("");
+
+    assert_eq!(fs::read(xhb_fixture()).unwrap(), fs::read(&xhb).unwrap());
+}
+
+#[test]
+fn import_csv_creates_categories() {
+    let dir = TempDir::new().unwrap();
+    let xhb = xhb_copy(dir.path());
+    let csv = xhb.with_file_name("categories.csv");
+    fs::write(&csv, "Date,Amount,Category\n2022-03-01,-6.5,Food:Snacks\n2022-03-02,-3,food:snacks\n").unwrap();
+
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(["import", "csv"])
+        .arg(&csv)
+        .args(["--account", "Chequing", "--map", "date=0,amount=1,category=2", "--create-categories"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Imported 2 transaction(s)"));
+
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(["sum", "--category", "^Food:Snacks$"])
+        .assert()
+        .success()
+        .stdout("-9.50\n");
+}
+
+#[test]
+fn import_csv_applies_rules() {
+    let dir = TempDir::new().unwrap();
+    let xhb = xhb_copy(dir.path());
+    let cfg = xhb.with_file_name("import_rules.toml");
+    fs::write(
+        &cfg,
+        format!(
+            "path = {xhb:?}\n\n\
+             [[rules]]\n\
+             payee_regex = \"(?i)^grocer$\"\n\
+             category = \"Food:Groceries\"\n\
+             paymode = \"DebitCard\"\n\n\
+             [[rules]]\n\
+             payee_regex = \"Bakery\"\n\
+             category = \"Food:Dining\"\n"
+        ),
+    )
+    .unwrap();
+
+    hb().arg("--config")
+        .arg(&cfg)
+        .args(import_csv_args())
+        .args(["--create-payees", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(
+            "New payee: Bakery\n\
+             2022-03-01\t-62.10\tGrocer\tGroceries\tFood:Groceries\n\
+             2022-03-02\t-4.50\tBakery\tBread\tFood:Dining\n",
+        );
+}
+
+#[test]
+fn import_rules_with_unknown_category() {
+    let dir = TempDir::new().unwrap();
+    let xhb = xhb_copy(dir.path());
+    let cfg = xhb.with_file_name("import_unknown_rule.toml");
+    fs::write(
+        &cfg,
+        format!("path = {xhb:?}\n\n[[rules]]\npayee_regex = \"Grocer\"\ncategory = \"Food:Snacks\"\n"),
+    )
+    .unwrap();
+
+    hb().arg("--config")
+        .arg(&cfg)
+        .args(import_csv_args())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No category named `Food:Snacks`."));
+
+    assert_eq!(fs::read(xhb_fixture()).unwrap(), fs::read(&xhb).unwrap());
+}
+
+#[test]
+fn import_csv_reports_every_error() {
+    let dir = TempDir::new().unwrap();
+    let xhb = xhb_copy(dir.path());
+
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(import_csv_args())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Error on line 4: No payee named `Bakery`."))
+        .stderr(predicate::str::contains("1 row(s) could not be imported"));
+
+    assert_eq!(fs::read(xhb_fixture()).unwrap(), fs::read(&xhb).unwrap());
+}
+
+#[test]
+fn import_ofx_twice() {
+    let dir = TempDir::new().unwrap();
+    let xhb = xhb_copy(dir.path());
+    let ofx = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../homebank-db/tests/statement_v1.ofx");
+
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(["import", "ofx"])
+        .arg(&ofx)
+        .args(["--account", "Credit Card", "--create-payees"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Imported 3 transaction(s)"))
+        .stderr("");
+
+    // every `FITID` is already in the account the second time
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(["import", "ofx"])
+        .arg(&ofx)
+        .args(["--account", "Credit Card"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Imported 0 transaction(s)"))
+        .stderr(predicate::str::contains("Skipped duplicate on line 52: 2022-02-15\t50.00"));
+
+    hb().arg("--file")
+        .arg(&xhb)
+        .arg("sum")
+        .assert()
+        .success()
+        .stdout("60.81\n");
+}
+
+#[test]
+fn watch_stdin_fails() {
+    hb().args(["--file", "-", "query", "transactions", "--watch"])
+        .write_stdin(fs::read(xhb_fixture()).unwrap())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cannot watch a HomeBank file read from stdin."));
+}
+
+#[test]
+fn edit_category_dry_run_then_merge() {
+    let dir = TempDir::new().unwrap();
+    let xhb = xhb_copy(dir.path());
+    let original = fs::read_to_string(&xhb).unwrap();
+    let merge = ["edit", "category", "--merge", "Food:Dining", "--into", "Food:Groceries"];
+
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(merge)
+        .arg("--dry-run")
+        .assert()
+        .success()
+        .stdout("Would merge category `Food:Dining` into `Food:Groceries`, affecting:\n  2 transaction(s)\n");
+    assert_eq!(original, fs::read_to_string(&xhb).unwrap());
+
+    hb().arg("--file").arg(&xhb).args(merge).assert().success();
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(["query", "categories", "Din"])
+        .assert()
+        .success()
+        .stdout("");
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(["sum", "--category", "Groceries"])
+        .assert()
+        .success()
+        .stdout("-310.25\n");
+}
+
+#[test]
+fn edit_category_rename() {
+    let dir = TempDir::new().unwrap();
+    let xhb = xhb_copy(dir.path());
+
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(["edit", "category", "--rename", "Food:Dining", "Restaurants"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Renamed category `Food:Dining` to `Restaurants` in `"));
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(["sum", "--category", "Restaurants$"])
+        .assert()
+        .success()
+        .stdout("-80.00\n");
+}
+
+#[test]
+fn edit_category_income_into_expense() {
+    let dir = TempDir::new().unwrap();
+    let xhb = xhb_copy(dir.path());
+
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(["edit", "category", "--merge", "Salary", "--into", "Entertainment"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Use `--allow-type-change` to merge them anyway."));
+}
+
+#[test]
+fn edit_payee_dry_run_then_merge() {
+    let dir = TempDir::new().unwrap();
+    let xhb = xhb_copy(dir.path());
+    let original = fs::read_to_string(&xhb).unwrap();
+    let merge = ["edit", "payee", "--merge", "^(Grocer|Landlord)$", "--into", "Bills"];
+
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(merge)
+        .arg("--dry-run")
+        .assert()
+        .success()
+        .stdout("Would merge into new payee `Bills`:\n  `Landlord`: 3 transaction(s)\n  `Grocer`: 3 transaction(s)\naffecting:\n  6 transaction(s)\n");
+    assert_eq!(original, fs::read_to_string(&xhb).unwrap());
+
+    hb().arg("--file").arg(&xhb).args(merge).assert().success();
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(["query", "payees"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Bills").and(predicate::str::contains("Grocer").not()));
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(["sum", "--payee", "^Bills$"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("-3910.25\n"));
+}
+
+#[test]
+fn edit_payee_unknown() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["edit", "payee", "--rename", "Baker", "Bakery"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No payee named `Baker`."));
+}
+
+#[test]
+fn sum_ignore_case() {
+    hb().arg("--file").arg(xhb_fixture()).args(["sum", "--payee", "grocer"]).assert().success().stdout("0.00\n");
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["sum", "--payee", "grocer", "--ignore-case"])
+        .assert()
+        .success()
+        .stdout("-310.25\n");
+}
+
+#[test]
+fn sum_exclude_category() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["sum", "--category", "^Food", "--exclude-category", "Dining"])
+        .assert()
+        .success()
+        .stdout("-230.25\n");
+}
+
+#[test]
+fn query_tags() {
+    hb().arg("--file")
+        .arg(xhb_fixture())
+        .args(["query", "tags"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("food                   2  -210.25"));
+}
+
+#[test]
+fn edit_tag_rename_then_remove() {
+    let dir = TempDir::new().unwrap();
+    let xhb = xhb_copy(dir.path());
+    let original = fs::read_to_string(&xhb).unwrap();
+
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(["edit", "tag", "--rename", "FOOD", "groceries", "--case-insensitive", "--dry-run"])
+        .assert()
+        .success()
+        .stdout("Would rename tag `FOOD` to `groceries`, affecting:\n  2 transaction(s)\n");
+    assert_eq!(original, fs::read_to_string(&xhb).unwrap());
+
+    hb().arg("--file").arg(&xhb).args(["edit", "tag", "--rename", "food", "groceries"]).assert().success();
+    hb().arg("--file").arg(&xhb).args(["edit", "tag", "--remove", "restaurant"]).assert().success();
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(["sum", "--tag", "^groceries$"])
+        .assert()
+        .success()
+        .stdout("-210.25\n");
+    hb().arg("--file")
+        .arg(&xhb)
+        .args(["edit", "tag", "--remove", "restaurant"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No transaction has the tag `restaurant`."));
+}
@@ -52,4 +52,8 @@ pub enum AccountError {
     /// When the recondiled date provided cannot be properly parsed into a `NaiveDate`.
     #[error("Invalid account date.")]
     InvalidReconcileDate,
+
+    /// When the order for sorting accounts is not recognized.
+    #[error("Invalid account sort order `{0}`. Must be 'position', 'name', or 'balance'.")]
+    InvalidSortKey(String),
 }
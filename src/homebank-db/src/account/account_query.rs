@@ -1,24 +1,39 @@
 //! Options for filtering [`Account`s][crate::account::account_struct::Account] from the [`HomeBankDb`].
 
-use crate::{db::HomeBankDb, query::Query, Account, AccountType};
+use crate::{db::HomeBankDb, query::Query, Account, AccountSort, AccountType};
 use clap::Parser;
 use regex::Regex;
+use std::collections::HashMap;
 
 /// Options for filtering the [`Account`s][crate::account::account_struct::Account]
 #[derive(Debug, Parser)]
-#[clap(name = "accounts", visible_alias = "a", about = "Query accounts")]
+#[command(name = "accounts", visible_alias = "a", about = "Query accounts")]
 pub struct QueryAccounts {
     /// Include accounts of a certain type. Options are 'None', 'Bank', 'Cash', 'Asset', 'CreditCard', 'Liability', 'Chequing', or 'Savings'.
-    #[clap(short = 'T', long = "type", value_name = "type")]
+    #[arg(short = 'T', long = "type", value_name = "type")]
     acct_type: Option<Vec<AccountType>>,
 
     /// Include accounts in group(s) that match the regular expression.
-    #[clap(short = 'g', long = "group", value_name = "regex")]
+    #[arg(short = 'g', long = "group", value_name = "regex")]
     group: Option<Regex>,
 
     /// Include accounts whose institutions match the regular expression.
-    #[clap(short = 'i', long = "institution", value_name = "regex")]
+    #[arg(short = 'i', long = "institution", value_name = "regex")]
     institution: Option<Regex>,
+
+    /// Sort accounts by 'position', 'name', or 'balance'.
+    #[arg(
+        short = 's',
+        long = "sort",
+        visible_alias = "sort-accounts",
+        value_name = "order",
+        default_value = "position"
+    )]
+    sort: AccountSort,
+
+    /// Reverse the sorting order (e.g. to list the largest balances first).
+    #[arg(short = 'r', long = "reverse")]
+    reverse: bool,
 }
 
 impl QueryAccounts {
@@ -36,13 +51,44 @@ impl QueryAccounts {
     fn institution(&self) -> &Option<Regex> {
         &self.institution
     }
+
+    /// Retrieve the order for sorting [`Account`s][crate::account::account_struct::Account].
+    fn sort(&self) -> &AccountSort {
+        &self.sort
+    }
+
+    /// Retrieve whether the sorting order is reversed.
+    fn reverse(&self) -> bool {
+        self.reverse
+    }
+
+    /// Sort [`Account`s][crate::account::account_struct::Account] in place.
+    /// Accounts that are tied keep their display position order.
+    fn sort_accounts(&self, accounts: &mut [Account], db: &HomeBankDb) {
+        // start from the display order, so that ties keep it
+        accounts.sort_by_key(|acct| (acct.position(), acct.key()));
+
+        match self.sort() {
+            AccountSort::Position => {}
+            AccountSort::Name => accounts.sort_by(|a, b| a.name().cmp(b.name())),
+            AccountSort::Balance => {
+                // each balance scans every transaction, so only work it out once per account
+                let balances: HashMap<usize, f32> = accounts.iter().map(|acct| (acct.key(), acct.balance(db))).collect();
+                accounts.sort_by(|a, b| balances[&a.key()].total_cmp(&balances[&b.key()]))
+            }
+        }
+
+        if self.reverse() {
+            accounts.reverse();
+        }
+    }
 }
 
 impl Query for QueryAccounts {
     type T = Account;
 
     fn exec(&self, db: &HomeBankDb) -> Vec<Self::T> {
-        let filt_accounts = db
+        let mut filt_accounts: Vec<Account> = db
             .accounts()
             .values()
             // filter the account types
@@ -70,6 +116,78 @@ impl Query for QueryAccounts {
             .cloned()
             .collect();
 
+        self.sort_accounts(&mut filt_accounts, db);
+
         filt_accounts
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    #[track_caller]
+    fn check_sorted_names(args: &[&str], expected: &[&str]) {
+        let db = example_db();
+        let query = QueryAccounts::try_parse_from(args).unwrap();
+        let observed: Vec<String> = query
+            .exec(&db)
+            .iter()
+            .map(|acct| acct.name().to_string())
+            .collect();
+
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    fn sort_by_position() {
+        check_sorted_names(&["accounts"], &["Chequing", "Savings", "Credit Card"]);
+    }
+
+    #[test]
+    fn sort_by_balance() {
+        check_sorted_names(
+            &["accounts", "--sort", "balance"],
+            &["Credit Card", "Chequing", "Savings"],
+        );
+    }
+
+    #[test]
+    fn sort_by_balance_reversed() {
+        check_sorted_names(
+            &["accounts", "--sort-accounts", "balance", "--reverse"],
+            &["Savings", "Chequing", "Credit Card"],
+        );
+    }
+
+    #[test]
+    fn sort_by_name() {
+        check_sorted_names(
+            &["accounts", "--sort", "name"],
+            &["Chequing", "Credit Card", "Savings"],
+        );
+    }
+
+    #[test]
+    fn sort_by_balance_ties_keep_position() {
+        let db = HomeBankDb::empty();
+        let query = QueryAccounts::try_parse_from(["accounts", "--sort", "balance"]).unwrap();
+        let mut accounts: Vec<Account> = [(3, "Wallet"), (1, "Petty cash"), (2, "Jar")]
+            .iter()
+            .map(|&(pos, name)| Account::new(pos, pos, name, 0.0))
+            .collect();
+
+        query.sort_accounts(&mut accounts, &db);
+        let observed: Vec<&str> = accounts.iter().map(|acct| acct.name()).collect();
+
+        assert_eq!(vec!["Petty cash", "Jar", "Wallet"], observed);
+    }
+
+    #[test]
+    fn parse_bad_sort_order() {
+        let observed = QueryAccounts::try_parse_from(["accounts", "--sort", "size"]);
+
+        assert!(observed.is_err());
+    }
+}
@@ -0,0 +1,31 @@
+//! Orders for listing [`Account`s][crate::account::account_struct::Account].
+
+use super::AccountError;
+use std::str::FromStr;
+
+/// Orders for listing [`Account`s][crate::account::account_struct::Account].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum AccountSort {
+    /// The display position set in HomeBank.
+    #[default]
+    Position,
+
+    /// The account name, alphabetically.
+    Name,
+
+    /// The current balance, from lowest to highest.
+    Balance,
+}
+
+impl FromStr for AccountSort {
+    type Err = AccountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Position" | "position" | "pos" => Ok(AccountSort::Position),
+            "Name" | "name" => Ok(AccountSort::Name),
+            "Balance" | "balance" => Ok(AccountSort::Balance),
+            _ => Err(AccountError::InvalidSortKey(s.to_string())),
+        }
+    }
+}
@@ -1,13 +1,17 @@
 //! Chequing accounts, credits cards, and details for all kinds of accounts.
 
 use super::{AccountError, AccountType};
-use crate::transaction::julian_date_from_u32;
+use crate::{transaction::julian_date_from_u32, HomeBankDb};
 use chrono::NaiveDate;
 use std::str::FromStr;
 use xml::attribute::OwnedAttribute;
 
+/// Account flag set when the account has been closed.
+const CLOSED_FLAG: usize = 1 << 1;
+
 /// Chequing accounts, credits cards, and details for all kinds of accounts.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Account {
     /// Unique key for this account.
     key: usize,
@@ -68,6 +72,17 @@ impl Account {
         }
     }
 
+    /// Create a new `Account`
+    pub fn new(key: usize, pos: usize, name: &str, initial_amount: f32) -> Self {
+        Self {
+            key,
+            pos,
+            name: name.to_string(),
+            initial_amount,
+            ..Self::empty()
+        }
+    }
+
     /// Retrieve the `Account` key
     pub(crate) fn key(&self) -> usize {
         self.key
@@ -78,6 +93,62 @@ impl Account {
         &self.name
     }
 
+    /// Retrieve the account's display position
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Retrieve the account's flags
+    pub fn flags(&self) -> usize {
+        self.flags
+    }
+
+    /// Check whether the account has been closed
+    pub fn is_closed(&self) -> bool {
+        self.flags & CLOSED_FLAG != 0
+    }
+
+    /// Retrieve the account's initial amount
+    pub fn initial_amount(&self) -> f32 {
+        self.initial_amount
+    }
+
+    /// Retrieve the account's overdraft amount
+    pub fn minimum_amount(&self) -> f32 {
+        self.minimum_amount
+    }
+
+    /// Retrieve the account's maximum total amount
+    pub fn maximum_amount(&self) -> f32 {
+        self.maximum_amount
+    }
+
+    /// Retrieve the account's notes, if there are any
+    pub fn notes(&self) -> Option<&str> {
+        match self.notes.as_str() {
+            "" => None,
+            s => Some(s),
+        }
+    }
+
+    /// Retrieve the date the account was last reconciled
+    pub fn reconciled_date(&self) -> &NaiveDate {
+        &self.reconciled_date
+    }
+
+    /// Calculate the account's current balance, from its initial amount and all of its transactions that aren't voided
+    pub fn balance(&self, db: &HomeBankDb) -> f32 {
+        db.transactions()
+            .iter()
+            .filter(|tr| tr.account() == self.key() && !tr.is_void())
+            .fold(self.initial_amount(), |acc, tr| acc + tr.total())
+    }
+
+    /// Retrieve the index of the account's currency
+    pub fn currency(&self) -> usize {
+        self.currency_idx
+    }
+
     /// Retrieve the account type
     pub fn atype(&self) -> &AccountType {
         &self.atype
@@ -189,9 +260,139 @@ impl TryFrom<Vec<OwnedAttribute>> for Account {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::tests::example_db;
+    use xml::{reader::XmlEvent, EventReader};
+
     #[test]
     fn it_works() {
         let result = 4;
         assert_eq!(2 + 2, result);
     }
+
+    #[track_caller]
+    fn check_try_from_single_str(input: &str, expected: Result<Account, AccountError>) {
+        // set up the reader from the input string
+        let mut reader = EventReader::from_str(input);
+
+        // skip the XML starting header and parse the first event
+        let (_start, first) = (reader.next(), reader.next());
+
+        // get the first event
+        if let Ok(XmlEvent::StartElement {
+            name, attributes, ..
+        }) = first
+        {
+            if "account" == name.local_name.as_str() {
+                let observed = Account::try_from(attributes);
+                assert_eq!(expected, observed);
+            } else {
+                panic!(
+                    "Incorrect account string passed into check. Expected `account`, found `{:#?}`",
+                    name.local_name.as_str()
+                );
+            }
+        } else {
+            panic!("Incorrect string passed into check. `{:#?}`", first);
+        }
+    }
+
+    #[test]
+    fn parse_all_fields() {
+        let input = r#"<account key="3" flags="2" pos="4" type="4" curr="1" name="Visa" bankname="Big Bank" initial="-250.5" minimum="-5000" maximum="100" notes="Cancel before renewal" grp="2" rdate="738191">"#;
+        let expected = Ok(Account {
+            key: 3,
+            flags: 2,
+            pos: 4,
+            atype: AccountType::CreditCard,
+            currency_idx: 1,
+            name: "Visa".to_string(),
+            bank_name: "Big Bank".to_string(),
+            initial_amount: -250.5,
+            minimum_amount: -5000.0,
+            maximum_amount: 100.0,
+            notes: "Cancel before renewal".to_string(),
+            group_idx: Some(2),
+            reconciled_date: NaiveDate::from_ymd_opt(2022, 2, 5).unwrap(),
+        });
+
+        check_try_from_single_str(input, expected);
+    }
+
+    #[test]
+    fn initial_amount_and_type_accessors() {
+        let input = r#"<account key="1" pos="1" type="7" curr="1" name="Rainy day" initial="1234.56">"#;
+        let mut reader = EventReader::from_str(input);
+        let _start = reader.next();
+
+        let acct = match reader.next() {
+            Ok(XmlEvent::StartElement { attributes, .. }) => Account::try_from(attributes).unwrap(),
+            other => panic!("Unexpected XML event `{other:?}`"),
+        };
+
+        assert_eq!((1234.56, &AccountType::Savings), (acct.initial_amount(), acct.atype()));
+    }
+
+    #[test]
+    fn parse_bad_type() {
+        check_try_from_single_str(r#"<account type="8">"#, Err(AccountError::InvalidType));
+    }
+
+    #[test]
+    fn parse_bad_initial_amount() {
+        check_try_from_single_str(r#"<account initial="lots">"#, Err(AccountError::InvalidInitialAmount));
+    }
+
+    #[test]
+    fn closed_flag() {
+        let mut acct = Account::new(1, 1, "Chequing", 0.0);
+        assert!(!acct.is_closed());
+
+        acct.flags = CLOSED_FLAG | 1;
+        assert!(acct.is_closed());
+    }
+
+    #[test]
+    fn notes_accessor() {
+        let mut acct = Account::new(1, 1, "Chequing", 0.0);
+        assert_eq!(None, acct.notes());
+
+        acct.notes = "Joint account".to_string();
+        assert_eq!(Some("Joint account"), acct.notes());
+    }
+
+    #[track_caller]
+    fn check_balance(name: &str, expected: f32) {
+        let db = example_db();
+        let acct = db.accounts().values().find(|acct| acct.name() == name).unwrap();
+
+        assert_eq!(expected, acct.balance(&db));
+    }
+
+    #[test]
+    fn balance_with_transactions() {
+        check_balance("Chequing", 544.25);
+    }
+
+    #[test]
+    fn balance_with_incoming_transfer() {
+        check_balance("Savings", 5500.0);
+    }
+
+    #[test]
+    fn balance_without_transactions() {
+        check_balance("Credit Card", -250.0);
+    }
+
+    #[test]
+    fn balance_without_voided() {
+        let xml = r#"<homebank v="1.4" d="050504">
+<account key="1" flags="0" pos="1" type="1" curr="1" name="Chequing" initial="100"/>
+<ope date="738134" amount="-30" account="1" paymode="1"/>
+<ope date="738135" amount="-40" account="1" paymode="1" st="4"/>
+</homebank>"#;
+        let db = HomeBankDb::from_reader(xml.as_bytes()).unwrap();
+
+        assert_eq!(70.0, db.accounts()[&1].balance(&db));
+    }
 }
\ No newline at end of file
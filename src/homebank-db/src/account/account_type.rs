@@ -5,6 +5,7 @@ use std::str::FromStr;
 
 /// Chequing, savings, and other types of financial accounts.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AccountType {
     None,
     Bank,
@@ -16,6 +17,13 @@ pub enum AccountType {
     Savings,
 }
 
+impl AccountType {
+    /// Check if the account holds a debt, such as a credit card or a loan, rather than money.
+    pub fn is_liability(&self) -> bool {
+        matches!(self, AccountType::CreditCard | AccountType::Liability)
+    }
+}
+
 impl TryFrom<usize> for AccountType {
     type Error = AccountError;
 
@@ -53,3 +61,39 @@ impl FromStr for AccountType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_codes() {
+        let expected = [
+            AccountType::None,
+            AccountType::Bank,
+            AccountType::Cash,
+            AccountType::Asset,
+            AccountType::CreditCard,
+            AccountType::Liability,
+            AccountType::Chequing,
+            AccountType::Savings,
+        ];
+
+        for (code, atype) in expected.iter().enumerate() {
+            assert_eq!(Ok(*atype), AccountType::try_from(code), "code {code}");
+        }
+    }
+
+    #[test]
+    fn unknown_code() {
+        assert_eq!(Err(AccountError::InvalidType), AccountType::try_from(8));
+    }
+
+    #[test]
+    fn liabilities() {
+        assert!(AccountType::CreditCard.is_liability());
+        assert!(AccountType::Liability.is_liability());
+        assert!(!AccountType::Asset.is_liability());
+        assert!(!AccountType::Chequing.is_liability());
+    }
+}
@@ -1,6 +1,6 @@
 //! Query the budget in your HomeBank database.
 
-use crate::{transaction::sum_transactions, Category, HomeBankDb, Query, QueryTransactions};
+use crate::{transaction::sum_transactions, Category, HomeBankDb, Query, QueryTransactions, Transaction, WatchOpts};
 use super::{TODAY_FIRST_OF_MONTH_STR, FIRST_OF_NEXT_MONTH_STR};
 
 use chrono::NaiveDate;
@@ -12,28 +12,40 @@ use std::str::FromStr;
 #[derive(Debug, Parser)]
 pub struct QueryBudget {
     /// Name of the category.
-    #[clap(value_name = "regex")]
+    #[arg(value_name = "regex")]
     name: Option<Regex>,
 
     /// Consider the budget from the month including this date.
-    #[clap(
+    #[arg(
         short = 'd',
         long = "date-from",
-        default_value = &TODAY_FIRST_OF_MONTH_STR,
-        parse(try_from_str = NaiveDate::from_str),
+        default_value = TODAY_FIRST_OF_MONTH_STR.as_str(),
+        value_parser = NaiveDate::from_str,
         value_name = "date"
     )]
     date_from: NaiveDate,
 
     /// Consider the budget from the month up to and excluding this date.
-    #[clap(
+    #[arg(
         short = 'D',
         long = "date-to",
-        default_value = &FIRST_OF_NEXT_MONTH_STR,
-        parse(try_from_str = NaiveDate::from_str),
+        default_value = FIRST_OF_NEXT_MONTH_STR.as_str(),
+        value_parser = NaiveDate::from_str,
         value_name = "date"
     )]
     date_to: NaiveDate,
+
+    /// List the transactions that contribute to each budget under its summary.
+    #[arg(long = "explain")]
+    explain: bool,
+
+    /// Count the transactions of each category's subcategories towards its budget.
+    #[arg(long = "rollup")]
+    rollup: bool,
+
+    /// Re-run the query whenever the HomeBank file changes.
+    #[command(flatten)]
+    watch: WatchOpts,
 }
 
 impl QueryBudget {
@@ -43,9 +55,33 @@ impl QueryBudget {
             name,
             date_from,
             date_to,
+            explain: false,
+            rollup: false,
+            watch: WatchOpts::default(),
         }
     }
 
+    /// Count the transactions of subcategories towards the budgets of their parents
+    pub fn with_rollup(mut self, rollup: bool) -> Self {
+        self.rollup = rollup;
+        self
+    }
+
+    /// Check whether the contributing transactions should be listed
+    pub fn explain(&self) -> bool {
+        self.explain
+    }
+
+    /// Check whether the transactions of subcategories count towards their parents' budgets
+    pub fn rollup(&self) -> bool {
+        self.rollup
+    }
+
+    /// Retrieve whether and how the HomeBank file is watched to re-run the query
+    pub fn watch(&self) -> &WatchOpts {
+        &self.watch
+    }
+
     /// Retrieve the regular expression for the `Category` name
     fn name(&self) -> &Option<Regex> {
         &self.name
@@ -75,6 +111,9 @@ pub struct BudgetSummary {
 
     /// The fraction of the spending over the allotted amount.
     progress_frac: Option<f32>,
+
+    /// The [`Transaction`s][crate::transaction::transaction_struct::Transaction] that make up the progress, with split transactions reduced to the parts in this [`Category`].
+    transactions: Vec<Transaction>,
 }
 
 impl BudgetSummary {
@@ -84,10 +123,22 @@ impl BudgetSummary {
             name: name.to_string(),
             progress,
             allotment,
-            progress_frac: allotment.map(|val| progress / val),
+            progress_frac: allotment.filter(|val| *val != 0.0).map(|val| progress / val),
+            transactions: vec![],
         }
     }
 
+    /// Set the transactions that make up the progress of the budget
+    pub fn with_transactions(mut self, transactions: Vec<Transaction>) -> Self {
+        self.transactions = transactions;
+        self
+    }
+
+    /// Retrieve the transactions that make up the progress of the budget
+    pub fn transactions(&self) -> &Vec<Transaction> {
+        &self.transactions
+    }
+
     /// Retrieve the name of the [`Category`] to which the budget applies
     pub fn name(&self) -> &str {
         &self.name
@@ -118,10 +169,80 @@ impl BudgetSummary {
         self.allotment.map(|val| val.abs() as u64)
     }
 
+    /// Retrieve how much of the allotment is left (`allotment - progress`), if there is one.
+    ///
+    /// This has the same sign as the allotment while there is room left, and the opposite sign once it is overspent.
+    pub fn remaining(&self) -> Option<f32> {
+        self.allotment.map(|val| val - self.progress)
+    }
+
+    /// Retrieve the percentage of the allotment that has been used, if there is a non-zero one
+    pub fn percent_used(&self) -> Option<f32> {
+        self.allotment.filter(|val| *val != 0.0).map(|val| self.progress * 100.0 / val)
+    }
+
     /// Helper function to determine if there is a budget or not
     pub fn has_allotment(&self) -> bool {
         self.allotment.is_some()
     }
+
+    /// Summarize the budget as a single row of a report, without its transactions
+    pub fn report_row(&self) -> BudgetReportRow {
+        BudgetReportRow {
+            name: self.name.clone(),
+            spent: self.progress,
+            allotment: self.allotment,
+            remaining: self.remaining(),
+            pct_used: self.percent_used(),
+        }
+    }
+}
+
+/// A single row of a budget report, e.g. for writing it as JSON.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BudgetReportRow {
+    /// The [`Category`] name
+    name: String,
+
+    /// The total sum of the [`Transaction`s][crate::transaction::transaction_struct::Transaction] in the [`Category`].
+    spent: f32,
+
+    /// How much room is allotted for the [`Category`], if it has a budget.
+    allotment: Option<f32>,
+
+    /// How much of the allotment is left, if there is one.
+    remaining: Option<f32>,
+
+    /// The percentage of the allotment that has been used, if there is a non-zero one.
+    pct_used: Option<f32>,
+}
+
+impl BudgetReportRow {
+    /// Retrieve the name of the [`Category`] to which the budget applies
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Retrieve the total spent in the [`Category`]
+    pub fn spent(&self) -> f32 {
+        self.spent
+    }
+
+    /// Retrieve the allotment for the budget
+    pub fn allotment(&self) -> Option<f32> {
+        self.allotment
+    }
+
+    /// Retrieve how much of the allotment is left
+    pub fn remaining(&self) -> Option<f32> {
+        self.remaining
+    }
+
+    /// Retrieve the percentage of the allotment that has been used
+    pub fn pct_used(&self) -> Option<f32> {
+        self.pct_used
+    }
 }
 
 impl Query for QueryBudget {
@@ -133,7 +254,7 @@ impl Query for QueryBudget {
             .values()
             // filter out categories that don't match the regex
             .filter(|&cat| match self.name() {
-                Some(re) => re.is_match(&cat.full_name(db)),
+                Some(re) => db.category_full_name(cat.key()).is_some_and(|name| re.is_match(name)),
                 None => true,
             })
             // filter out categories that don't have a budget
@@ -141,19 +262,19 @@ impl Query for QueryBudget {
             .cloned()
             .collect();
 
-        filt_categories.sort_by_key(|a| a.full_name(db));
+        filt_categories.sort_by_key(|a| db.category_full_name(a.key()));
 
         let budget_spent: Vec<BudgetSummary> = filt_categories
             .iter()
             .map(|cat| {
-                let cat_name_re = Regex::new(&cat.full_name(db)).unwrap();
+                let keys = budget_category_keys(cat, db, self.rollup());
                 let transaction_query = QueryTransactions::new(
                     &Some(*self.date_from()),
                     &Some(*self.date_to()),
                     &None,
                     &None,
                     &None,
-                    &Some(cat_name_re),
+                    &None,
                     &None,
                     &None,
                     &None,
@@ -163,14 +284,182 @@ impl Query for QueryBudget {
                     &None,
                 );
 
-                let filt_transactions = transaction_query.exec(db);
-                let sum = sum_transactions(&filt_transactions);
+                // split transactions are reduced to the parts in the budget's categories
+                let filt_transactions: Vec<Transaction> = transaction_query
+                    .exec(db)
+                    .iter()
+                    .filter_map(|tr| {
+                        let matching_idx: Vec<usize> = tr
+                            .categories()
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, cat)| cat.is_some_and(|key| keys.contains(&key)))
+                            .map(|(i, _)| i)
+                            .collect();
+
+                        tr.subset(&matching_idx)
+                    })
+                    .collect();
+                let sum = sum_transactions(&filt_transactions, None);
                 let allotment = cat.budget_amount_over_interval(*self.date_from(), *self.date_to());
 
-                BudgetSummary::new(&cat.full_name(db), sum, allotment)
+                BudgetSummary::new(db.category_full_name(cat.key()).unwrap_or_default(), sum, allotment).with_transactions(filt_transactions)
             })
             .collect();
 
         budget_spent
     }
 }
+
+/// Find the keys of the categories whose transactions count towards a budget.
+///
+/// This is the category itself, and all of its descendants if `rollup` is set.
+fn budget_category_keys(cat: &Category, db: &HomeBankDb, rollup: bool) -> Vec<usize> {
+    let mut keys = vec![cat.key()];
+
+    let mut i = 0;
+    while rollup && i < keys.len() {
+        for child in db.child_categories(keys[i]) {
+            // guard against cycles in a malformed file
+            if !keys.contains(&child.key()) {
+                keys.push(child.key());
+            }
+        }
+        i += 1;
+    }
+
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    fn example_summaries(name: &str) -> Vec<BudgetSummary> {
+        let db = example_db();
+        let query = QueryBudget::new(
+            Some(Regex::new(name).unwrap()),
+            NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2022, 2, 1).unwrap(),
+        );
+
+        query.exec(&db)
+    }
+
+    #[test]
+    fn explained_transactions_sum_to_progress() {
+        let summaries = example_summaries(".*");
+        assert_eq!(2, summaries.len());
+
+        for summary in summaries {
+            let listed = sum_transactions(summary.transactions(), None);
+
+            assert!((summary.progress() - listed).abs() < 0.005, "{}", summary.name());
+        }
+    }
+
+    /// A budget on `Food`, with spending only recorded on its subcategories.
+    const PARENT_BUDGET_XHB: &str = r#"<homebank v="1.4" d="050504">
+<account key="1" flags="0" pos="1" type="1" curr="1" name="Chequing" initial="0"/>
+<cat key="1" flags="1" name="Food" b0="-250"/>
+<cat key="2" parent="1" flags="0" name="Groceries"/>
+<cat key="3" parent="1" flags="0" name="Dining"/>
+<cat key="4" flags="0" name="Seafood"/>
+<ope date="738170" amount="-150" account="1" paymode="1" category="2"/>
+<ope date="738175" amount="-100" account="1" paymode="1" scat="3||4" samt="-40||-60" smem="||"/>
+</homebank>"#;
+
+    #[track_caller]
+    fn check_parent_budget(rollup: bool, expected_progress: f32, expected_amounts: Vec<f32>) {
+        let db = HomeBankDb::from_reader(PARENT_BUDGET_XHB.as_bytes()).unwrap();
+        let query = QueryBudget::new(
+            Some(Regex::new("^Food$").unwrap()),
+            NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2022, 2, 1).unwrap(),
+        )
+        .with_rollup(rollup);
+        let summaries = query.exec(&db);
+
+        assert_eq!(1, summaries.len());
+        assert_eq!(expected_progress, summaries[0].progress());
+        assert_eq!(Some(-250.0), summaries[0].allotment());
+
+        let amounts: Vec<f32> = summaries[0].transactions().iter().map(|tr| *tr.total()).collect();
+        assert_eq!(expected_amounts, amounts);
+    }
+
+    #[test]
+    fn parent_budget_without_rollup() {
+        check_parent_budget(false, 0.0, vec![]);
+    }
+
+    #[test]
+    fn parent_budget_with_rollup() {
+        // `Seafood` isn't a subcategory of `Food`, even though its name contains it
+        check_parent_budget(true, -190.0, vec![-150.0, -40.0]);
+    }
+
+    #[test]
+    fn overspent_summary() {
+        let summary = BudgetSummary::new("Food:Groceries", -360.0, Some(-300.0));
+
+        assert_eq!(Some(60.0), summary.remaining());
+        assert_eq!(Some(120.0), summary.percent_used());
+    }
+
+    #[test]
+    fn summary_with_room_left() {
+        let summary = BudgetSummary::new("Food:Groceries", -75.0, Some(-300.0));
+
+        assert_eq!(Some(-225.0), summary.remaining());
+        assert_eq!(Some(25.0), summary.percent_used());
+    }
+
+    #[test]
+    fn summary_without_allotment() {
+        let summary = BudgetSummary::new("Food:Dining", -75.0, None);
+
+        assert_eq!(None, summary.remaining());
+        assert_eq!(None, summary.percent_used());
+    }
+
+    #[test]
+    fn summary_with_zero_allotment() {
+        let summary = BudgetSummary::new("Food:Dining", -75.0, Some(0.0));
+
+        assert_eq!(Some(75.0), summary.remaining());
+        assert_eq!(None, summary.percent_used());
+        assert_eq!(&None, summary.progress_frac());
+    }
+
+    #[test]
+    fn overspent_report_row() {
+        let row = BudgetSummary::new("Food:Groceries", -360.0, Some(-300.0)).report_row();
+
+        assert_eq!("Food:Groceries", row.name());
+        assert_eq!(-360.0, row.spent());
+        assert_eq!(Some(-300.0), row.allotment());
+        assert_eq!(Some(60.0), row.remaining());
+        assert_eq!(Some(120.0), row.pct_used());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn report_row_to_json() {
+        let row = BudgetSummary::new("Food:Dining", -75.0, None).report_row();
+        let json = serde_json::to_string(&row).unwrap();
+
+        assert_eq!(r#"{"name":"Food:Dining","spent":-75.0,"allotment":null,"remaining":null,"pct_used":null}"#, json);
+    }
+
+    #[test]
+    fn explained_split_is_reduced_to_category() {
+        let summaries = example_summaries("Groceries");
+        let amounts: Vec<f32> = summaries[0].transactions().iter().map(|tr| *tr.total()).collect();
+
+        // the weekly groceries, and the groceries part of the split with dining
+        assert_eq!(vec![-150.25, -80.0], amounts);
+        assert_eq!(-230.25, summaries[0].progress());
+    }
+}
@@ -0,0 +1,163 @@
+//! Follow a [`Category`]'s budget month by month.
+
+use crate::{transaction::sum_transactions, Category, HomeBankDb, Query, QueryTransactions};
+use chrono::{Datelike, NaiveDate};
+use regex::Regex;
+
+/// The budget and spending of a [`Category`] within a single month.
+#[derive(Debug, PartialEq, Clone)]
+pub struct BudgetMonth {
+    /// The first day of the month.
+    month: NaiveDate,
+
+    /// How much room is allotted for the month, including any amount carried forward from the previous month.
+    allotment: f32,
+
+    /// The total sum of [`Transaction`s][crate::transaction::transaction_struct::Transaction] within the month.
+    spent: f32,
+}
+
+impl BudgetMonth {
+    /// Create a new month of a budget timeline
+    pub fn new(month: NaiveDate, allotment: f32, spent: f32) -> Self {
+        Self {
+            month,
+            allotment,
+            spent,
+        }
+    }
+
+    /// Retrieve the first day of the month
+    pub fn month(&self) -> &NaiveDate {
+        &self.month
+    }
+
+    /// Retrieve the allotment for the month
+    pub fn allotment(&self) -> f32 {
+        self.allotment
+    }
+
+    /// Retrieve the sum of the transactions within the month
+    pub fn spent(&self) -> f32 {
+        self.spent
+    }
+
+    /// Retrieve how much of the allotment is left over at the end of the month
+    pub fn remaining(&self) -> f32 {
+        self.allotment - self.spent
+    }
+}
+
+/// Retrieve the first day of the month after the one containing `d`.
+fn first_of_next_month(d: NaiveDate) -> NaiveDate {
+    if d.month() == 12 {
+        NaiveDate::from_ymd_opt(d.year() + 1, 1, 1).unwrap()
+    } else {
+        NaiveDate::from_ymd_opt(d.year(), d.month() + 1, 1).unwrap()
+    }
+}
+
+/// Follow the budget of a [`Category`] for each month from the one including `from` up to the one including the day before `to`.
+///
+/// If the [`Category`]'s budget is cumulative, the unused part of each month's allotment is carried forward to the next month.
+/// Overspending is not carried forward.
+pub fn budget_timeline(cat: &Category, db: &HomeBankDb, from: NaiveDate, to: NaiveDate) -> Vec<BudgetMonth> {
+    let cat_name_re = Regex::new(&regex::escape(&cat.full_name(db))).unwrap();
+
+    let mut timeline = vec![];
+    let mut carried = 0.0;
+    let mut month = NaiveDate::from_ymd_opt(from.year(), from.month(), 1).unwrap();
+
+    while month < to {
+        let next_month = first_of_next_month(month);
+        let transaction_query = QueryTransactions::new(
+            &Some(month),
+            &Some(next_month),
+            &None,
+            &None,
+            &None,
+            &Some(cat_name_re.clone()),
+            &None,
+            &None,
+            &None,
+            &None,
+            &None,
+            &None,
+            &None,
+        );
+        let spent = sum_transactions(&transaction_query.exec(db), None);
+        let budget = cat.budget_amount(month.month() as usize).unwrap_or(0.0);
+
+        let summary = BudgetMonth::new(month, budget + carried, spent);
+
+        // budgets share the sign of the transactions they cover, so the remainder is unused only if it keeps that sign
+        carried = if cat.budget_is_cumulative() && summary.remaining() * summary.allotment() > 0.0 {
+            summary.remaining()
+        } else {
+            0.0
+        };
+
+        timeline.push(summary);
+        month = next_month;
+    }
+
+    timeline
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    #[track_caller]
+    fn check_remaining(flags: usize, expected: Vec<f32>) {
+        let db = example_db();
+
+        // `Food:Groceries`, with a budget of 300 each month
+        let mut cat = Category::new(4, flags, "Groceries", Some(3));
+        cat.set_budget(0, -300.0).unwrap();
+
+        let observed: Vec<f32> = budget_timeline(
+            &cat,
+            &db,
+            NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2022, 3, 1).unwrap(),
+        )
+        .iter()
+        .map(|m| m.remaining())
+        .collect();
+
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    fn non_cumulative_remaining() {
+        check_remaining(1, vec![-69.75, -300.0]);
+    }
+
+    #[test]
+    fn cumulative_remaining() {
+        check_remaining(1 | 64, vec![-69.75, -369.75]);
+    }
+
+    #[test]
+    fn overspending_not_carried() {
+        let db = example_db();
+
+        // `Housing:Rent`, with a budget smaller than the rent
+        let mut cat = Category::new(2, 1 | 64, "Rent", Some(1));
+        cat.set_budget(0, -1000.0).unwrap();
+
+        let observed: Vec<f32> = budget_timeline(
+            &cat,
+            &db,
+            NaiveDate::from_ymd_opt(2022, 1, 15).unwrap(),
+            NaiveDate::from_ymd_opt(2022, 2, 2).unwrap(),
+        )
+        .iter()
+        .map(|m| m.allotment())
+        .collect();
+
+        assert_eq!(vec![-1000.0, -1000.0], observed);
+    }
+}
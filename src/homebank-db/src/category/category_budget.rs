@@ -1,15 +1,19 @@
 //! A budget for a given [`Category`][crate::category::category_struct::Category].
 
-use crate::CategoryError;
+use crate::{report::Interval, CategoryError};
 use chrono::{Datelike, Duration, NaiveDate};
-use kronos::{Grain, Grains, NthOf, TimeSequence};
 
 /// A budget for a given [`Category`][crate::category::category_struct::Category].
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CategoryBudget {
     /// A uniform budget for each month.
     pub each_month: Option<f32>,
-    
+
+    /// Whether each month has its own amount, rather than the uniform `each_month` amount.
+    /// HomeBank keeps both sets of amounts, and chooses between them with the category's `GF_CUSTOM` flag.
+    pub per_month: bool,
+
     pub january: Option<f32>,
     pub february: Option<f32>,
     pub march: Option<f32>,
@@ -34,6 +38,7 @@ impl CategoryBudget {
     pub fn empty() -> Self {
         Self {
             each_month: None,
+            per_month: false,
             january: None,
             february: None,
             march: None,
@@ -51,7 +56,10 @@ impl CategoryBudget {
 
     /// Check if there is a budget in the first place
     pub fn is_empty(&self) -> bool {
-        let non_budget = Self::empty();
+        let non_budget = Self {
+            per_month: self.per_month,
+            ..Self::empty()
+        };
         *self == non_budget
     }
 
@@ -85,8 +93,8 @@ impl CategoryBudget {
             return None;
         }
 
-        // if there is a global budget per month, return that
-        if let Some(val) = self.each_month {
+        // if there is a global budget per month, return that, unless each month has its own amount
+        if let (Some(val), false) = (self.each_month, self.per_month) {
             return Some(val);
         }
 
@@ -110,47 +118,49 @@ impl CategoryBudget {
     /// Get the budget amount over a period of time.
     ///
     /// The dates form a half-open interval [`from`, `to`) including the first date and excluding the second.
-    /// Only the months for the `from` and `to` dates are considered, since budgets are set per month.
+    /// Budgets are set per month, so every month that the interval touches counts in full.
     pub fn budget_over_interval(&self, from: NaiveDate, to: NaiveDate) -> Option<f32> {
         if self.is_empty() {
             return None;
         }
 
-        // create an iterator for the first of each month
-        let first_of_month = NthOf(1, Grains(Grain::Day), Grains(Grain::Month));
-
-        // create an off-by-1 date so the next `first_of_month` date will always be the correct first day of the month
-        let day_before_from = from - Duration::days(1);
-
-        // create an off-by-1 date so the next `first_of_month` date will always be the correct first day of the month
-        let day_before_to = to - Duration::days(1);
-        // calculate the last date that should be used as the upper bound of the time interval
-        let last_date = first_of_month
-            .future(&day_before_to.and_hms_opt(0, 0, 0).unwrap())
-            .next()
-            .unwrap()
-            .start
-            .date();
-
-        // create a date iterator to step over the first day of each month between the `first_date` and `last_date`
-        let mut time_step = first_of_month.future(
-            &day_before_from
-            .and_hms_opt(0, 0, 0)
-            .unwrap()
-        );
-        let mut date_iter = time_step.next().unwrap().start.date();
-
-        let mut sum = 0.0;
-
-        // iterate over all the first days of each month
-        while date_iter < last_date {
-            date_iter = time_step.next().unwrap().start.date();
-
-            sum += self.budget(date_iter.month() as usize).unwrap_or(0.0);
-        }
+        let sum = Interval::Month
+            .starts(&from, &(to - Duration::days(1)))
+            .iter()
+            .map(|first_of_month| self.budget(first_of_month.month() as usize).unwrap_or(0.0))
+            .sum();
 
         Some(sum)
     }
+
+    /// Get the amount stored for `index` as it is numbered in the HomeBank file: 0 for each month, or 1 - 12 for a single month.
+    pub(crate) fn stored_amount(&self, index: usize) -> Option<f32> {
+        match index {
+            0 => self.each_month,
+            1 => self.january,
+            2 => self.february,
+            3 => self.march,
+            4 => self.april,
+            5 => self.may,
+            6 => self.june,
+            7 => self.july,
+            8 => self.august,
+            9 => self.september,
+            10 => self.october,
+            11 => self.november,
+            12 => self.december,
+            _ => None,
+        }
+    }
+
+    /// Get the budget amount over a whole year, the sum of the amounts for each month.
+    pub fn annual(&self) -> Option<f32> {
+        if self.is_empty() {
+            return None;
+        }
+
+        Some((1..=12).map(|month| self.budget(month).unwrap_or(0.0)).sum())
+    }
 }
 
 impl Default for CategoryBudget {
@@ -207,6 +217,7 @@ mod tests {
     fn each_month_overrides_any_month() {
         let budget = CategoryBudget {
             each_month: Some(100.0),
+            per_month: false,
             january: Some(1.0),
             february: Some(2.0),
             march: Some(3.0),
@@ -232,6 +243,7 @@ mod tests {
     fn single_month() {
         let budget = CategoryBudget {
             each_month: None,
+            per_month: false,
             january: Some(1.0),
             february: Some(2.0),
             march: Some(3.0),
@@ -281,4 +293,53 @@ mod tests {
 
         assert!(!observed);
     }
+
+    /// A budget with different amounts for the first three months, and a uniform amount that HomeBank ignores.
+    fn first_quarter_budget() -> CategoryBudget {
+        CategoryBudget {
+            each_month: Some(-1000.0),
+            per_month: true,
+            january: Some(-100.0),
+            february: Some(-200.0),
+            march: Some(-300.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn per_month_overrides_each_month() {
+        let budget = first_quarter_budget();
+
+        check_budget_amount((&budget, 2), Some(-200.0));
+        check_budget_amount((&budget, 4), None);
+    }
+
+    #[track_caller]
+    fn check_budget_over_interval(from: (u32, u32), to: (i32, u32, u32), expected: Option<f32>) {
+        let from = NaiveDate::from_ymd_opt(2022, from.0, from.1).unwrap();
+        let to = NaiveDate::from_ymd_opt(to.0, to.1, to.2).unwrap();
+
+        assert_eq!(expected, first_quarter_budget().budget_over_interval(from, to));
+    }
+
+    #[test]
+    fn budget_over_quarter() {
+        check_budget_over_interval((1, 1), (2022, 4, 1), Some(-600.0));
+    }
+
+    #[test]
+    fn budget_over_single_month() {
+        check_budget_over_interval((2, 1), (2022, 3, 1), Some(-200.0));
+    }
+
+    #[test]
+    fn budget_over_partial_months() {
+        check_budget_over_interval((2, 15), (2022, 3, 10), Some(-500.0));
+    }
+
+    #[test]
+    fn budget_over_year() {
+        check_budget_over_interval((3, 1), (2023, 2, 1), Some(-400.0));
+        assert_eq!(Some(-600.0), first_quarter_budget().annual());
+    }
 }
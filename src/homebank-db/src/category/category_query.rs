@@ -7,15 +7,19 @@ use regex::Regex;
 
 /// Options for filtering [`Categories`][crate::category::category_struct::Category] from the [`HomeBankDb`].
 #[derive(Debug, Parser)]
-#[clap(
+#[command(
     name = "categories",
     visible_alias = "c",
     about = "Query transaction categories"
 )]
 pub struct QueryCategories {
     /// Name of the [`Category`][crate::category::category_struct::Category]
-    #[clap(value_name = "regex")]
+    #[arg(value_name = "regex")]
     name: Option<Regex>,
+
+    /// Show the hierarchy of categories, indented under their parents, with the number of transactions in each.
+    #[arg(long = "tree", conflicts_with = "name")]
+    tree: bool,
 }
 
 impl QueryCategories {
@@ -23,6 +27,11 @@ impl QueryCategories {
     fn name(&self) -> &Option<Regex> {
         &self.name
     }
+
+    /// Select whether the categories are shown as a tree
+    pub fn tree(&self) -> bool {
+        self.tree
+    }
 }
 
 impl Query for QueryCategories {
@@ -6,8 +6,21 @@ use chrono::NaiveDate;
 use std::str::FromStr;
 use xml::attribute::OwnedAttribute;
 
+/// Bit set on subcategories.
+pub(crate) const GF_SUB: usize = 1;
+
+/// Bit set on categories for income, rather than expenses.
+pub(crate) const GF_INCOME: usize = 1 << 1;
+
+/// Bit set on categories whose budget has a different amount for each month.
+pub(crate) const GF_CUSTOM: usize = 1 << 2;
+
+/// Bit set on categories whose unused budget rolls over from one month to the next.
+pub(crate) const GF_CUMULATIVE: usize = 1 << 6;
+
 /// Categories for each [`Transaction`][crate::transaction::transaction_struct::Transaction].
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Category {
     /// The unique primary key for the category in the database.
     key: usize,
@@ -125,16 +138,24 @@ impl Category {
             key,
             flags,
             name: name.to_string(),
-            budget: CategoryBudget::empty(),
+            budget: CategoryBudget {
+                per_month: flags & GF_CUSTOM != 0,
+                ..CategoryBudget::empty()
+            },
             parent_key,
         }
     }
 
     /// Retrieve the `Category`'s key
-    pub(crate) fn key(&self) -> usize {
+    pub fn key(&self) -> usize {
         self.key
     }
 
+    /// Retrieve the `Category`'s parent key, if it has a parent
+    pub fn parent_key(&self) -> Option<usize> {
+        self.parent_key
+    }
+
     /// Retrieve the `Category`'s name
     pub fn name(&self) -> &str {
         &self.name
@@ -145,10 +166,25 @@ impl Category {
         self.parent_key.is_some()
     }
 
+    /// Check if the `Category` has a parent category.
+    pub fn has_parent(&self) -> bool {
+        self.is_child()
+    }
+
+    /// Check if the `Category` is at the top of the hierarchy, without a parent category.
+    pub fn is_root(&self) -> bool {
+        !self.has_parent()
+    }
+
+    /// Check if the `Category` has no subcategories.
+    pub fn is_leaf(&self, db: &HomeBankDb) -> bool {
+        db.children_of(self.key).is_empty()
+    }
+
     /// Retrieve the `Category`'s parent category name, if one exists.
     pub fn parent_name<'db>(&self, db: &'db HomeBankDb) -> Option<&'db str> {
         if let Some(idx) = self.parent_key {
-            if let Some(parent_cat) = db.categories().get(&idx) {
+            if let Some(parent_cat) = db.category(idx) {
                 Some(parent_cat.name())
             } else {
                 None
@@ -158,10 +194,30 @@ impl Category {
         }
     }
 
+    /// Determine if the `Category` is beneath another one.
+    pub fn is_subcategory(&self) -> bool {
+        self.parent_key.is_some()
+    }
+
+    /// Retrieve the categories above this one, starting with its parent.
+    /// Parents missing from the database end the list.
+    pub fn ancestors<'db>(&self, db: &'db HomeBankDb) -> Vec<&'db Category> {
+        let mut ancestors: Vec<&Category> = vec![];
+        let mut parent = self.parent_key.and_then(|idx| db.category(idx));
+
+        // guard against cycles in a malformed file
+        while let Some(cat) = parent.filter(|cat| cat.key != self.key && !ancestors.iter().any(|a| a.key == cat.key)) {
+            ancestors.push(cat);
+            parent = cat.parent_key.and_then(|idx| db.category(idx));
+        }
+
+        ancestors
+    }
+
     /// Retrieve the `Category`'s name, including the parent category, if one exists.
     pub fn full_name(&self, db: &HomeBankDb) -> String {
         if let Some(idx) = self.parent_key {
-            if let Some(parent_cat) = db.categories().get(&idx) {
+            if let Some(parent_cat) = db.category(idx) {
                 format!("{}:{}", parent_cat.name(), self.name())
             } else {
                 self.name().to_string()
@@ -196,6 +252,16 @@ impl Category {
         self.budget.budget(month)
     }
 
+    /// Determine if the `Category` is for income, rather than expenses.
+    pub fn is_income(&self) -> bool {
+        self.flags & GF_INCOME != 0
+    }
+
+    /// Determine if unused budget amounts carry forward to the following month.
+    pub fn budget_is_cumulative(&self) -> bool {
+        self.flags & GF_CUMULATIVE != 0
+    }
+
     /// Retrieve the total budget amount of an interval of time.
     pub fn budget_amount_over_interval(&self, from: NaiveDate, to: NaiveDate) -> Option<f32> {
         self.budget.budget_over_interval(from, to)
@@ -257,6 +323,10 @@ impl TryFrom<Vec<OwnedAttribute>> for Category {
                 _ => {}
             }
         }
+
+        // the flags may come after the budget amounts
+        cat.budget.per_month = cat.flags & GF_CUSTOM != 0;
+
         Ok(cat)
     }
 }
@@ -376,6 +446,70 @@ mod tests {
         check_try_from_single_str(input, expected);
     }
 
+    #[test]
+    fn parse_budget_per_month() {
+        // the `GF_CUSTOM` flag comes after the amounts, but still applies to them
+        let input = r#"<cat key="1" b0="-400" b2="-200" flags="12" name="Name">"#;
+        let expected = Ok(Category {
+            key: 1,
+            name: "Name".to_string(),
+            parent_key: None,
+            flags: 12,
+            budget: CategoryBudget {
+                each_month: Some(-400.0),
+                per_month: true,
+                february: Some(-200.0),
+                ..Default::default()
+            },
+        });
+
+        check_try_from_single_str(input, expected);
+    }
+
+    #[test]
+    fn parse_cumulative_budget() {
+        let input = r#"<cat key="1" flags="72" name="Name" b0="-400">"#;
+        let expected = Ok(Category {
+            key: 1,
+            name: "Name".to_string(),
+            parent_key: None,
+            flags: 72,
+            budget: CategoryBudget {
+                each_month: Some(-400.0),
+                ..Default::default()
+            },
+        });
+
+        check_try_from_single_str(input, expected);
+    }
+
+    #[test]
+    fn subcategory_ancestors() {
+        let input = r#"<homebank v="1.4" d="050504">
+<cat key="1" flags="0" name="Vehicle"/>
+<cat key="3" parent="1" flags="1" name="Fuel"/>
+<cat key="4" parent="3" flags="1" name="Diesel"/>
+<cat key="7" parent="8" flags="1" name="Loop"/>
+<cat key="8" parent="7" flags="1" name="Back"/>
+</homebank>"#;
+        let db = HomeBankDb::from_reader(input.as_bytes()).unwrap();
+        let names = |key: usize| -> Vec<&str> {
+            db.category(key).unwrap().ancestors(&db).iter().map(|cat| cat.name()).collect()
+        };
+
+        assert_eq!(vec!["Fuel", "Vehicle"], names(4));
+        assert_eq!(Vec::<&str>::new(), names(1));
+        assert_eq!(vec!["Back"], names(7));
+        assert!(db.category(4).unwrap().is_subcategory());
+        assert!(!db.category(1).unwrap().is_subcategory());
+    }
+
+    #[test]
+    fn cumulative_budget_flag() {
+        assert!(Category::new(1, 72, "Name", None).budget_is_cumulative());
+        assert!(!Category::new(1, 8, "Name", None).budget_is_cumulative());
+    }
+
     #[test]
     fn parse_non_budget() {
         let cat = Category {
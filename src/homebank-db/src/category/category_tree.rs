@@ -0,0 +1,99 @@
+//! The hierarchy of [`Categories`][crate::category::category_struct::Category] in the [`HomeBankDb`].
+
+use super::Category;
+use crate::HomeBankDb;
+use std::collections::HashSet;
+
+/// A [`Category`][crate::category::category_struct::Category] with its subcategories nested underneath.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CategoryNode<'db> {
+    /// The category at this point in the hierarchy.
+    category: &'db Category,
+
+    /// Categories directly under this one, sorted by name.
+    children: Vec<CategoryNode<'db>>,
+}
+
+impl<'db> CategoryNode<'db> {
+    /// Retrieve the category
+    pub fn category(&self) -> &'db Category {
+        self.category
+    }
+
+    /// Retrieve the categories directly under this one
+    pub fn children(&self) -> &[CategoryNode<'db>] {
+        &self.children
+    }
+
+    /// Build the node for `category`, with every category beneath it.
+    /// `seen` guards against cycles in a malformed file.
+    fn build(category: &'db Category, db: &'db HomeBankDb, seen: &mut HashSet<usize>) -> Self {
+        seen.insert(category.key());
+
+        let mut children = vec![];
+        for child in db.child_categories(category.key()) {
+            if !seen.contains(&child.key()) {
+                children.push(Self::build(child, db, seen));
+            }
+        }
+
+        Self { category, children }
+    }
+}
+
+impl HomeBankDb {
+    /// Retrieve the top-level categories with their subcategories nested underneath, all sorted by name.
+    ///
+    /// Categories whose parent is missing from the database are placed at the top, like [`root_categories`][Self::root_categories].
+    pub fn category_tree(&self) -> Vec<CategoryNode<'_>> {
+        let mut seen: HashSet<usize> = HashSet::new();
+
+        self.root_categories()
+            .into_iter()
+            .map(|root| CategoryNode::build(root, self, &mut seen))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Flatten the tree into each category's name, indented by its depth.
+    fn outline(nodes: &[CategoryNode], depth: usize) -> Vec<String> {
+        nodes
+            .iter()
+            .flat_map(|node| {
+                let mut lines = vec![format!("{}{}", "  ".repeat(depth), node.category().name())];
+                lines.extend(outline(node.children(), depth + 1));
+                lines
+            })
+            .collect()
+    }
+
+    #[track_caller]
+    fn check_tree(input: &str, expected: &[&str]) {
+        let db = HomeBankDb::from_reader(input.as_bytes()).unwrap();
+
+        assert_eq!(expected.to_vec(), outline(&db.category_tree(), 0));
+    }
+
+    #[test]
+    fn nested_tree() {
+        let input = r#"<homebank v="1.4" d="050504">
+<cat key="1" flags="0" name="Vehicle"/>
+<cat key="2" parent="1" flags="1" name="Insurance"/>
+<cat key="3" parent="1" flags="1" name="Fuel"/>
+<cat key="4" parent="3" flags="1" name="Diesel"/>
+<cat key="5" flags="0" name="Food"/>
+<cat key="6" parent="9" flags="1" name="Orphan"/>
+</homebank>"#;
+
+        check_tree(input, &["Food", "Orphan", "Vehicle", "  Fuel", "    Diesel", "  Insurance"]);
+    }
+
+    #[test]
+    fn empty_tree() {
+        check_tree(r#"<homebank v="1.4" d="050504"></homebank>"#, &[]);
+    }
+}
@@ -1,14 +1,18 @@
 //! Categories for each [`Transaction`][crate::transaction::transaction_struct::Transaction].
 
 pub mod budget_query;
+pub mod budget_timeline;
 pub mod category_struct;
+pub mod category_tree;
 pub mod category_budget;
 pub mod category_error;
 pub mod category_query;
 pub mod review_query;
 
 pub use budget_query::QueryBudget;
+pub use budget_timeline::{budget_timeline, BudgetMonth};
 pub use category_struct::Category;
+pub use category_tree::CategoryNode;
 pub use category_budget::CategoryBudget;
 pub use category_error::CategoryError;
 pub use category_query::QueryCategories;
@@ -13,27 +13,27 @@ use std::str::FromStr;
 #[derive(Debug, Parser)]
 pub struct QueryReview {
     /// Consider the budget from the month including this date.
-    #[clap(
+    #[arg(
         short = 'd',
         long = "date-from",
-        default_value = &TODAY_FIRST_OF_MONTH_STR,
-        parse(try_from_str = NaiveDate::from_str),
+        default_value = TODAY_FIRST_OF_MONTH_STR.as_str(),
+        value_parser = NaiveDate::from_str,
         value_name = "date"
     )]
     date_from: NaiveDate,
 
     /// Consider the budget from the month up to and excluding this date.
-    #[clap(
+    #[arg(
         short = 'D',
         long = "date-to",
-        default_value = &FIRST_OF_NEXT_MONTH_STR,
-        parse(try_from_str = NaiveDate::from_str),
+        default_value = FIRST_OF_NEXT_MONTH_STR.as_str(),
+        value_parser = NaiveDate::from_str,
         value_name = "date"
     )]
     date_to: NaiveDate,
 
     /// Exclude any (sub)categories that have no transactions.
-    #[clap(short = 'x')]
+    #[arg(short = 'x')]
     exclude_none: bool
 }
 
@@ -92,7 +92,7 @@ impl Query for QueryReview {
                 );
 
                 let filt_transactions = transaction_query.exec(db);
-                let sum = sum_transactions(&filt_transactions);
+                let sum = sum_transactions(&filt_transactions, None);
                 let cat_name = cat.name().to_string();
 
                 let val = match cat.parent_name(db){
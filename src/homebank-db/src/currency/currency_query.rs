@@ -6,14 +6,14 @@ use regex::Regex;
 
 /// Options for filtering [`Currencies`][crate::currency::currency_struct::Currency] from the [`HomeBankDb`].
 #[derive(Debug, Parser)]
-#[clap(
+#[command(
     name = "currencies",
     visible_alias = "C",
     about = "Query currencies used"
 )]
 pub struct QueryCurrencies {
     /// Name of the currency.
-    #[clap(value_name = "regex")]
+    #[arg(value_name = "regex")]
     name: Option<Regex>,
 }
 
@@ -8,6 +8,7 @@ use xml::attribute::OwnedAttribute;
 
 /// Currencies used within a HomeBank database.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Currency {
     /// The unique key for a currency in the database.
     key: usize,
@@ -21,8 +22,8 @@ pub struct Currency {
     /// The common name for this currency.
     name: String,
 
-    /// The monetary symbol used for this currency, like `$` for the dollar or `€` for the Euro.
-    symbol: char,
+    /// The monetary symbol used for this currency, like `$` for the dollar, `€` for the Euro, or `CHF` for the Swiss franc.
+    symbol: String,
 
     /// Does the currency symbol prefix the amount?
     syprf: bool,
@@ -32,8 +33,8 @@ pub struct Currency {
     decimal_separator: char,
 
     /// What character provides a visual break between thousands digits?
-    /// This is typically `,` or `.`, but may vary by locale.
-    thousands_separator: char,
+    /// This is typically `,` or `.`, but may vary by locale, and some currencies don't group digits at all.
+    thousands_separator: Option<char>,
 
     /// How many digits should be displayed after the `decimal_separator`?
     decimal_len: usize,
@@ -54,10 +55,10 @@ impl Currency {
             flags: 0,
             iso: "".to_string(),
             name: "".to_string(),
-            symbol: '$',
+            symbol: "$".to_string(),
             syprf: false,
             decimal_separator: '.',
-            thousands_separator: ' ',
+            thousands_separator: Some(' '),
             decimal_len: 2,
             conversion_rate: 1.0,
             mdate: NaiveDate::from_ymd_opt(2000, 1, 1).unwrap(),
@@ -69,9 +70,89 @@ impl Currency {
         self.key
     }
 
+    /// Retrieve the name of the `Currency`
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// Retrieve the ISO 4217 code of the `Currency`
+    pub fn iso(&self) -> &str {
+        &self.iso
+    }
+
+    /// Retrieve the monetary symbol of the `Currency`
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Check if the symbol is written before the amount
+    pub fn symbol_is_prefix(&self) -> bool {
+        self.syprf
+    }
+
+    /// Retrieve the character separating the whole amount from its fraction
+    pub fn decimal_char(&self) -> char {
+        self.decimal_separator
+    }
+
+    /// Retrieve the character between groups of thousands, if digits are grouped
+    pub fn group_char(&self) -> Option<char> {
+        self.thousands_separator
+    }
+
+    /// Retrieve the number of digits displayed after the decimal separator
+    pub fn decimal_len(&self) -> usize {
+        self.decimal_len
+    }
+
+    /// Retrieve the conversion rate from this `Currency` to the base currency
+    pub fn conversion_rate(&self) -> f32 {
+        self.conversion_rate
+    }
+
+    /// Retrieve the flags of the `Currency`
+    pub(crate) fn flags(&self) -> usize {
+        self.flags
+    }
+
+    /// Retrieve the date the conversion rate was last modified
+    pub(crate) fn modified_date(&self) -> &NaiveDate {
+        &self.mdate
+    }
+
+    /// Format an amount the way HomeBank displays it in this `Currency`, e.g. `€ 1.234,56`.
+    pub fn format_amount(&self, amount: f32) -> String {
+        let digits = format!("{:.*}", self.decimal_len, amount.abs());
+        let (whole, fraction) = match digits.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (digits.as_str(), None),
+        };
+
+        let mut number = String::new();
+        for (i, c) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                if let Some(sep) = self.thousands_separator {
+                    number.push(sep);
+                }
+            }
+            number.push(c);
+        }
+        if let Some(fraction) = fraction {
+            number.push(self.decimal_separator);
+            number.push_str(fraction);
+        }
+
+        // amounts that round to zero are written without a sign
+        if amount < 0.0 && digits.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            number.insert(0, '-');
+        }
+
+        if self.syprf {
+            format!("{} {number}", self.symbol)
+        } else {
+            format!("{number} {}", self.symbol)
+        }
+    }
 }
 
 impl Default for Currency {
@@ -127,10 +208,10 @@ impl TryFrom<Vec<OwnedAttribute>> for Currency {
                     curr.iso = i.value.to_string();
                 }
                 "symb" => {
-                    curr.symbol = match i.value.chars().next() {
-                        Some(c) => c,
-                        None => return Err(CurrencyError::InvalidSymbol),
-                    };
+                    if i.value.is_empty() {
+                        return Err(CurrencyError::InvalidSymbol);
+                    }
+                    curr.symbol = i.value.to_string();
                 }
                 "dchar" => {
                     curr.decimal_separator = match i.value.chars().next() {
@@ -139,10 +220,8 @@ impl TryFrom<Vec<OwnedAttribute>> for Currency {
                     };
                 }
                 "gchar" => {
-                    curr.thousands_separator = match i.value.chars().next() {
-                        Some(c) => c,
-                        None => return Err(CurrencyError::InvalidThousandsSeparator),
-                    };
+                    // an empty separator means that digits aren't grouped
+                    curr.thousands_separator = i.value.chars().next();
                 }
                 "rate" => {
                     curr.conversion_rate = match f32::from_str(&i.value) {
@@ -156,3 +235,99 @@ impl TryFrom<Vec<OwnedAttribute>> for Currency {
         Ok(curr)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xml::{reader::XmlEvent, EventReader};
+
+    #[track_caller]
+    fn check_try_from_single_str(input: &str) -> Result<Currency, CurrencyError> {
+        // set up the reader from the input string
+        let mut reader = EventReader::from_str(input);
+
+        // skip the XML starting header and parse the first event
+        let (_start, first) = (reader.next(), reader.next());
+
+        match first {
+            Ok(XmlEvent::StartElement { name, attributes, .. }) if name.local_name == "cur" => {
+                Currency::try_from(attributes)
+            }
+            _ => panic!("Incorrect string passed into check. `{:#?}`", first),
+        }
+    }
+
+    #[track_caller]
+    fn check_format_amount(input: &str, amount: f32, expected: &str) {
+        let curr = check_try_from_single_str(input).unwrap();
+
+        assert_eq!(expected, curr.format_amount(amount));
+    }
+
+    const EURO: &str = r#"<cur key="2" flags="0" iso="EUR" name="Euro" symb="€" syprf="1" dchar="," gchar="." frac="2" rate="1.4" mdate="738204"/>"#;
+
+    #[test]
+    fn parse_all_fields() {
+        let curr = check_try_from_single_str(EURO).unwrap();
+
+        assert_eq!(2, curr.key());
+        assert_eq!("EUR", curr.iso());
+        assert_eq!("Euro", curr.name());
+        assert_eq!("€", curr.symbol());
+        assert!(curr.symbol_is_prefix());
+        assert_eq!(',', curr.decimal_char());
+        assert_eq!(Some('.'), curr.group_char());
+        assert_eq!(2, curr.decimal_len());
+        assert_eq!(1.4, curr.conversion_rate());
+    }
+
+    #[test]
+    fn parse_multi_character_symbol() {
+        let input = r#"<cur key="3" iso="CHF" name="Swiss Franc" symb="CHF" syprf="0" dchar="." gchar="" frac="2"/>"#;
+        let curr = check_try_from_single_str(input).unwrap();
+
+        assert_eq!("CHF", curr.symbol());
+        assert_eq!(None, curr.group_char());
+    }
+
+    #[test]
+    fn parse_empty_symbol() {
+        let observed = check_try_from_single_str(r#"<cur key="3" symb=""/>"#);
+
+        assert!(matches!(observed, Err(CurrencyError::InvalidSymbol)));
+    }
+
+    #[test]
+    fn format_grouped_prefix() {
+        check_format_amount(EURO, 1234.56, "€ 1.234,56");
+        check_format_amount(EURO, -1234567.5, "€ -1.234.567,50");
+        check_format_amount(EURO, 999.0, "€ 999,00");
+    }
+
+    #[test]
+    fn format_ungrouped_suffix() {
+        let input = r#"<cur key="3" symb="kr" syprf="0" dchar="," gchar="" frac="0"/>"#;
+
+        check_format_amount(input, 1234.56, "1235 kr");
+    }
+
+    #[test]
+    fn format_without_minor_units() {
+        let input = r#"<cur key="4" iso="JPY" name="Yen" symb="¥" syprf="1" dchar="." gchar="," frac="0"/>"#;
+
+        check_format_amount(input, -123456.0, "¥ -123,456");
+        check_format_amount(input, 0.4, "¥ 0");
+    }
+
+    #[test]
+    fn format_negative_suffix() {
+        let input = r#"<cur key="5" iso="EUR" name="Euro" symb="€" syprf="0" dchar="," gchar=" " frac="2"/>"#;
+
+        check_format_amount(input, -1234.5, "-1 234,50 €");
+    }
+
+    #[test]
+    fn format_rounded_to_zero() {
+        check_format_amount(EURO, -0.001, "€ 0,00");
+    }
+}
@@ -0,0 +1,372 @@
+//! Rank the payees, categories, and transactions in the HomeBank database, total its cash flow, and describe the amounts in each category.
+//! Voided transactions are not included in any of the rankings or totals.
+
+use super::HomeBankDb;
+use crate::{Category, Payee, Transaction};
+use chrono::Datelike;
+use std::collections::{BTreeMap, HashMap};
+
+impl HomeBankDb {
+    /// Iterate over the transactions that count towards a ranking, skipping voided ones.
+    fn ranked_transactions(&self) -> impl Iterator<Item = &Transaction> {
+        self.transactions().iter().filter(|tr| !tr.is_void())
+    }
+
+    /// Retrieve the `n` payees involved in the most transactions, along with their number of transactions.
+    /// Payees with the same number of transactions are sorted by name.
+    pub fn most_frequent_payees(&self, n: usize) -> Vec<(&Payee, usize)> {
+        let mut counts: HashMap<usize, usize> = HashMap::new();
+
+        for idx in self.ranked_transactions().filter_map(|tr| *tr.payee()) {
+            *counts.entry(idx).or_default() += 1;
+        }
+
+        let mut ranked: Vec<(&Payee, usize)> = counts
+            .into_iter()
+            .filter_map(|(idx, count)| self.payee(idx).map(|payee| (payee, count)))
+            .collect();
+        ranked.sort_by(|(a, a_count), (b, b_count)| {
+            b_count.cmp(a_count).then_with(|| a.name().cmp(b.name()))
+        });
+        ranked.truncate(n);
+
+        ranked
+    }
+
+    /// Count the transactions in each category, by category key.
+    /// Each split of a transaction counts towards its own category.
+    pub fn category_counts(&self) -> HashMap<usize, usize> {
+        let mut counts: HashMap<usize, usize> = HashMap::new();
+
+        for tr in self.ranked_transactions() {
+            for idx in tr.categories().into_iter().flatten() {
+                *counts.entry(*idx).or_default() += 1;
+            }
+        }
+
+        counts
+    }
+
+    /// Retrieve the `n` categories used in the most transactions, along with their number of transactions.
+    /// Each split of a transaction counts towards its own category.
+    /// Categories with the same number of transactions are sorted by their full name.
+    pub fn most_frequent_categories(&self, n: usize) -> Vec<(&Category, usize)> {
+        let mut ranked: Vec<(&Category, usize, String)> = self
+            .category_counts()
+            .into_iter()
+            .filter_map(|(idx, count)| self.category(idx).map(|cat| (cat, count, cat.full_name(self))))
+            .collect();
+        ranked.sort_by(|(_, a_count, a_name), (_, b_count, b_name)| {
+            b_count.cmp(a_count).then_with(|| a_name.cmp(b_name))
+        });
+
+        ranked
+            .into_iter()
+            .take(n)
+            .map(|(cat, count, _)| (cat, count))
+            .collect()
+    }
+
+    /// Retrieve the `n` transactions with the highest amounts (i.e. the largest incomes).
+    /// Transactions with the same amount stay in the order they are stored.
+    pub fn largest_transactions(&self, n: usize) -> Vec<&Transaction> {
+        let mut ranked: Vec<&Transaction> = self.ranked_transactions().collect();
+        ranked.sort_by(|a, b| b.total().total_cmp(a.total()));
+        ranked.truncate(n);
+
+        ranked
+    }
+
+    /// Retrieve the `n` transactions with the lowest amounts (i.e. the largest expenses).
+    /// Transactions with the same amount stay in the order they are stored.
+    pub fn smallest_transactions(&self, n: usize) -> Vec<&Transaction> {
+        let mut ranked: Vec<&Transaction> = self.ranked_transactions().collect();
+        ranked.sort_by(|a, b| a.total().total_cmp(b.total()));
+        ranked.truncate(n);
+
+        ranked
+    }
+
+    /// Total the income and expenses of each month in `year`, as `(month, income, expenses)` with months starting from 1.
+    ///
+    /// Income is the sum of the positive amounts, and expenses the sum of the negative amounts,
+    /// with each split of a transaction counting as its own amount.
+    /// Transfers are left out, since they only move money between accounts.
+    /// Months without any transactions are left out.
+    pub fn monthly_cash_flow(&self, year: i32) -> Vec<(u32, f64, f64)> {
+        let mut months: BTreeMap<u32, (f64, f64)> = BTreeMap::new();
+
+        for tr in self.ranked_transactions().filter(|tr| !tr.is_transfer() && tr.date().year() == year) {
+            let (income, expenses) = months.entry(tr.date().month()).or_default();
+
+            for amount in tr.amounts() {
+                if *amount > 0.0 {
+                    *income += f64::from(*amount);
+                } else {
+                    *expenses += f64::from(*amount);
+                }
+            }
+        }
+
+        months
+            .into_iter()
+            .map(|(month, (income, expenses))| (month, income, expenses))
+            .collect()
+    }
+
+    /// Total the expenses with each tag, largest first, as positive amounts.
+    ///
+    /// A transaction with several tags counts in full towards each of them, and tags that differ only by case are combined.
+    /// Income and transfers are left out. Tags with the same total are sorted by name.
+    pub fn tag_spending_report(&self) -> Vec<(String, f64)> {
+        let mut totals: BTreeMap<String, f64> = BTreeMap::new();
+
+        for tr in self.ranked_transactions().filter(|tr| !tr.is_transfer() && *tr.total() < 0.0) {
+            for tag in tr.normalized_tags() {
+                *totals.entry(tag).or_default() += f64::from(tr.total().abs());
+            }
+        }
+
+        // the map is ordered by name, and a stable sort keeps that order for equal totals
+        let mut ranked: Vec<(String, f64)> = totals.into_iter().collect();
+        ranked.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        ranked
+    }
+
+    /// Retrieve the amount of every transaction in a category, with each split of a transaction counting as its own amount.
+    fn category_amounts(&self, category_idx: usize) -> Vec<f64> {
+        self.ranked_transactions()
+            .flat_map(|tr| tr.categories().into_iter().zip(tr.amounts()))
+            .filter(|(cat, _)| **cat == Some(category_idx))
+            .map(|(_, amount)| f64::from(*amount))
+            .collect()
+    }
+
+    /// Retrieve the amount of every transaction in each category, by category key, in a single pass over the transactions.
+    /// Each split of a transaction counts as its own amount.
+    pub(crate) fn amounts_by_category(&self) -> HashMap<usize, Vec<f64>> {
+        let mut amounts: HashMap<usize, Vec<f64>> = HashMap::new();
+
+        for tr in self.ranked_transactions() {
+            for (cat, amount) in tr.categories().into_iter().zip(tr.amounts()) {
+                if let Some(idx) = cat {
+                    amounts.entry(*idx).or_default().push(f64::from(*amount));
+                }
+            }
+        }
+
+        amounts
+    }
+
+    /// Calculate the mean amount of the transactions in a category.
+    /// Each split of a transaction counts as its own amount. Returns `None` if the category has no transactions.
+    pub fn average_transaction_amount_for_category(&self, category_idx: usize) -> Option<f64> {
+        mean(&self.category_amounts(category_idx))
+    }
+
+    /// Calculate the median amount of the transactions in a category, the mean of the middle two if there is an even number of them.
+    /// Each split of a transaction counts as its own amount. Returns `None` if the category has no transactions.
+    pub fn median_transaction_amount_for_category(&self, category_idx: usize) -> Option<f64> {
+        median(&mut self.category_amounts(category_idx))
+    }
+
+    /// Calculate the (population) standard deviation of the amounts of the transactions in a category.
+    /// Each split of a transaction counts as its own amount. Returns `None` if the category has no transactions.
+    pub fn standard_deviation_for_category(&self, category_idx: usize) -> Option<f64> {
+        std_dev(&self.category_amounts(category_idx))
+    }
+}
+
+/// Calculate the mean of the amounts, or `None` if there aren't any.
+pub(crate) fn mean(amounts: &[f64]) -> Option<f64> {
+    match amounts.is_empty() {
+        true => None,
+        false => Some(amounts.iter().sum::<f64>() / amounts.len() as f64),
+    }
+}
+
+/// Calculate the median of the amounts, the mean of the middle two if there is an even number of them, or `None` if there aren't any.
+/// The amounts are sorted in place.
+pub(crate) fn median(amounts: &mut [f64]) -> Option<f64> {
+    amounts.sort_by(|a, b| a.total_cmp(b));
+
+    let mid = amounts.len() / 2;
+    match amounts.len() {
+        0 => None,
+        len if len % 2 == 0 => Some((amounts[mid - 1] + amounts[mid]) / 2.0),
+        _ => Some(amounts[mid]),
+    }
+}
+
+/// Calculate the (population) standard deviation of the amounts, or `None` if there aren't any.
+pub(crate) fn std_dev(amounts: &[f64]) -> Option<f64> {
+    let avg = mean(amounts)?;
+    let variance = amounts.iter().map(|amount| (amount - avg).powi(2)).sum::<f64>() / amounts.len() as f64;
+
+    Some(variance.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+    use std::path::Path;
+
+    #[test]
+    fn most_frequent_payees() {
+        let db = example_db();
+        let observed: Vec<(&str, usize)> = db
+            .most_frequent_payees(3)
+            .iter()
+            .map(|(payee, count)| (payee.name(), *count))
+            .collect();
+
+        // ties are broken by name
+        assert_eq!(vec![("Grocer", 3), ("Landlord", 3), ("Employer", 2)], observed);
+    }
+
+    #[test]
+    fn most_frequent_categories() {
+        let db = example_db();
+        let observed: Vec<(String, usize)> = db
+            .most_frequent_categories(3)
+            .iter()
+            .map(|(cat, count)| (cat.full_name(&db), *count))
+            .collect();
+        let expected = vec![
+            ("Housing:Rent".to_string(), 3),
+            ("Food:Dining".to_string(), 2),
+            ("Food:Groceries".to_string(), 2),
+        ];
+
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    fn largest_transactions() {
+        let db = example_db();
+        let observed: Vec<f32> = db.largest_transactions(3).iter().map(|tr| *tr.total()).collect();
+
+        assert_eq!(vec![2000.0, 2000.0, 500.0], observed);
+    }
+
+    #[test]
+    fn smallest_transactions() {
+        let db = example_db();
+        let observed: Vec<Option<String>> = db
+            .smallest_transactions(2)
+            .iter()
+            .map(|tr| tr.memo().clone())
+            .collect();
+        let expected = vec![
+            Some("December rent".to_string()),
+            Some("January rent".to_string()),
+        ];
+
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    fn more_than_available() {
+        let db = example_db();
+
+        assert_eq!(4, db.most_frequent_payees(10).len());
+        assert_eq!(11, db.largest_transactions(100).len());
+    }
+
+    #[test]
+    fn void_not_ranked() {
+        let db = HomeBankDb::try_from(Path::new("tests/void.xhb")).unwrap();
+        let observed: Vec<f32> = db.smallest_transactions(5).iter().map(|tr| *tr.total()).collect();
+
+        assert_eq!(vec![-200.0, 1000.0], observed);
+        assert_eq!(1, db.most_frequent_payees(1)[0].1);
+    }
+
+    #[test]
+    fn empty_db() {
+        let db = HomeBankDb::empty();
+
+        assert!(db.most_frequent_payees(5).is_empty());
+        assert!(db.most_frequent_categories(5).is_empty());
+        assert!(db.smallest_transactions(5).is_empty());
+    }
+
+    #[test]
+    fn monthly_cash_flow() {
+        let db = example_db();
+
+        // the transfer to savings in January is neither income nor an expense
+        assert_eq!(vec![(1, 2000.0, -1450.25), (2, 2000.0, -1305.5)], db.monthly_cash_flow(2022));
+        assert_eq!(vec![(12, 0.0, -1200.0)], db.monthly_cash_flow(2021));
+        assert!(db.monthly_cash_flow(1999).is_empty());
+    }
+
+    #[test]
+    fn monthly_cash_flow_of_mixed_split() {
+        // a refund split from a purchase is income, even though the transaction is an expense overall
+        let xml = r#"<homebank v="1.4" d="050504">
+<ope date="738170" amount="-80" account="1" paymode="1" st="0" scat="1||2" samt="-100||20" smem="||"/>
+</homebank>"#;
+        let db = HomeBankDb::from_reader(xml.as_bytes()).unwrap();
+
+        assert_eq!(vec![(1, 20.0, -100.0)], db.monthly_cash_flow(2022));
+    }
+
+    #[test]
+    fn tag_spending_report() {
+        assert_eq!(vec![("food".to_string(), 210.25), ("restaurant".to_string(), 60.0)], example_db().tag_spending_report());
+        assert!(HomeBankDb::empty().tag_spending_report().is_empty());
+    }
+
+    #[test]
+    fn category_statistics() {
+        let db = example_db();
+
+        // groceries are a whole transaction of -150.25 and a split of -80
+        assert_eq!(Some(-115.125), db.average_transaction_amount_for_category(4));
+        assert_eq!(Some(-115.125), db.median_transaction_amount_for_category(4));
+        assert_eq!(Some(35.125), db.standard_deviation_for_category(4));
+
+        assert_eq!(Some(-1200.0), db.median_transaction_amount_for_category(2));
+        assert_eq!(Some(0.0), db.standard_deviation_for_category(2));
+
+        // the housing category itself has no transactions, only its subcategory
+        assert_eq!(None, db.average_transaction_amount_for_category(1));
+        assert_eq!(None, db.median_transaction_amount_for_category(1));
+        assert_eq!(None, db.standard_deviation_for_category(1));
+    }
+
+    #[test]
+    fn odd_median() {
+        let xml = r#"<homebank v="1.4" d="050504">
+<cat key="1" flags="0" name="Fuel"/>
+<ope date="738170" amount="-20" account="1" paymode="1" st="0" category="1"/>
+<ope date="738171" amount="-90" account="1" paymode="1" st="0" category="1"/>
+<ope date="738172" amount="-40" account="1" paymode="1" st="0" category="1"/>
+<ope date="738173" amount="-1000" account="1" paymode="1" st="4" category="1"/>
+</homebank>"#;
+        let db = HomeBankDb::from_reader(xml.as_bytes()).unwrap();
+
+        assert_eq!(Some(-40.0), db.median_transaction_amount_for_category(1));
+        assert_eq!(Some(-50.0), db.average_transaction_amount_for_category(1));
+    }
+
+    #[test]
+    fn tag_spending_leaves_out_income_transfers_and_void() {
+        let xml = r#"<homebank v="1.4" d="050504">
+<account key="1" flags="0" pos="1" type="1" curr="1" name="Chequing" initial="0" minimum="0"/>
+<account key="2" flags="0" pos="2" type="1" curr="1" name="Savings" initial="0" minimum="0"/>
+<ope date="738170" amount="-20" account="1" paymode="1" st="0" tags="Trip"/>
+<ope date="738171" amount="-30" account="1" paymode="1" st="0" tags="trip hotel"/>
+<ope date="738172" amount="-40" account="1" paymode="1" st="4" tags="trip"/>
+<ope date="738173" amount="500" account="1" paymode="1" st="0" tags="trip"/>
+<ope date="738174" amount="-100" account="1" dst_account="2" paymode="5" st="0" tags="trip" kxfer="1"/>
+<ope date="738174" amount="100" account="2" dst_account="1" paymode="5" st="0" tags="trip" kxfer="1"/>
+</homebank>"#;
+        let db = HomeBankDb::from_reader(xml.as_bytes()).unwrap();
+
+        assert_eq!(vec![("trip".to_string(), 50.0), ("hotel".to_string(), 30.0)], db.tag_spending_report());
+    }
+}
@@ -0,0 +1,210 @@
+//! Find transactions in the HomeBank database that are likely duplicates of each other.
+
+use super::HomeBankDb;
+use crate::Transaction;
+use std::collections::HashMap;
+
+/// A field that likely duplicate transactions must share.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DuplicateKey {
+    /// Date of the transaction, within a number of days.
+    Date,
+
+    /// Total amount of the transaction, within a small difference.
+    Amount,
+
+    /// Account the transaction applies to.
+    Account,
+
+    /// Payee of the transaction.
+    Payee,
+
+    /// Memo of the transaction.
+    Memo,
+
+    /// Info field of the transaction, such as a reference number.
+    Info,
+
+    /// Categories of the transaction (or of each of its splits).
+    Category,
+}
+
+impl DuplicateKey {
+    /// Describe the value of this field in `tr`, so that equal values can be grouped together
+    pub(crate) fn value_of(&self, tr: &Transaction) -> String {
+        match self {
+            Self::Date => tr.date().to_string(),
+            Self::Amount => format!("{:.2}", tr.total()),
+            Self::Account => tr.account().to_string(),
+            Self::Payee => format!("{:?}", tr.payee()),
+            Self::Memo => format!("{:?}", tr.memo()),
+            Self::Info => format!("{:?}", tr.info()),
+            Self::Category => format!("{:?}", tr.categories()),
+        }
+    }
+}
+
+/// Fields that likely duplicates share by default: the same account, amount, and payee, around the same date.
+pub const DEFAULT_DUPLICATE_KEYS: [DuplicateKey; 4] = [
+    DuplicateKey::Date,
+    DuplicateKey::Amount,
+    DuplicateKey::Account,
+    DuplicateKey::Payee,
+];
+
+impl HomeBankDb {
+    /// Find groups of transaction indices that are likely duplicates of each other.
+    ///
+    /// Transactions in a group are in the same account, for the same payee, with amounts within `amount_epsilon` of each other,
+    /// and dates within `tolerance_days` of each other.
+    pub fn find_duplicates(&self, tolerance_days: u32, amount_epsilon: f64) -> Vec<Vec<usize>> {
+        self.find_duplicates_by(&DEFAULT_DUPLICATE_KEYS, tolerance_days, amount_epsilon)
+    }
+
+    /// Find groups of transaction indices that share every field in `keys`.
+    ///
+    /// Dates only need to be within `tolerance_days` and amounts within `amount_epsilon` of each other.
+    /// Transactions that are close to a member of a group join it, so a group can span more than `tolerance_days` in total.
+    /// Groups are ordered by their first transaction, and the indices within each group are in increasing order.
+    pub fn find_duplicates_by(&self, keys: &[DuplicateKey], tolerance_days: u32, amount_epsilon: f64) -> Vec<Vec<usize>> {
+        let transactions = self.transactions();
+
+        // the fields that must match exactly narrow down which transactions need to be compared
+        let mut buckets: HashMap<Vec<String>, Vec<usize>> = HashMap::new();
+        for (i, tr) in transactions.iter().enumerate() {
+            let key = keys
+                .iter()
+                .filter(|k| !matches!(k, DuplicateKey::Date | DuplicateKey::Amount))
+                .map(|k| k.value_of(tr))
+                .collect();
+            buckets.entry(key).or_default().push(i);
+        }
+
+        let close_in = |k: &DuplicateKey, i: usize, j: usize| match k {
+            DuplicateKey::Date => (*transactions[i].date() - *transactions[j].date()).num_days().unsigned_abs() <= tolerance_days.into(),
+            DuplicateKey::Amount => f64::from((transactions[i].total() - transactions[j].total()).abs()) <= amount_epsilon,
+            _ => true,
+        };
+
+        // sorted by a field that only needs to be close, each transaction is only compared to the ones after it within the tolerance
+        let sweep = keys.iter().find(|k| matches!(k, DuplicateKey::Date | DuplicateKey::Amount));
+
+        // link every pair of close transactions, and keep each set of linked transactions as a group
+        let mut parent: Vec<usize> = (0..transactions.len()).collect();
+        for bucket in buckets.values_mut() {
+            let Some(sweep) = sweep else {
+                // every field must match exactly, so the whole bucket is one group
+                for &j in &bucket[1..] {
+                    link(&mut parent, bucket[0], j);
+                }
+                continue;
+            };
+
+            match sweep {
+                DuplicateKey::Date => bucket.sort_by_key(|&i| *transactions[i].date()),
+                _ => bucket.sort_by(|&i, &j| transactions[i].total().total_cmp(transactions[j].total())),
+            }
+
+            for (n, &i) in bucket.iter().enumerate() {
+                for &j in &bucket[n + 1..] {
+                    if !close_in(sweep, i, j) {
+                        break;
+                    }
+
+                    if keys.iter().all(|k| close_in(k, i, j)) {
+                        link(&mut parent, i, j);
+                    }
+                }
+            }
+        }
+
+        let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+        for i in 0..transactions.len() {
+            groups.entry(root(&mut parent, i)).or_default().push(i);
+        }
+
+        let mut groups: Vec<Vec<usize>> = groups.into_values().filter(|group| group.len() > 1).collect();
+        groups.sort();
+
+        groups
+    }
+}
+
+/// Find the first index of the group that index `i` has been linked to.
+fn root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+
+    i
+}
+
+/// Link the groups of indices `i` and `j` into one, under the first index of either.
+fn link(parent: &mut [usize], i: usize, j: usize) {
+    let (ri, rj) = (root(parent, i), root(parent, j));
+    parent[ri.max(rj)] = ri.min(rj);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two identical transactions, a copy a day later, a copy a cent off, and an unrelated transaction.
+    const INPUT: &str = r#"<homebank v="1.4" d="050504">
+<account key="1" flags="0" pos="1" type="1" curr="1" name="Chequing" initial="0"/>
+<pay key="1" name="Grocer"/>
+<ope date="738170" amount="-42.1" account="1" paymode="1" payee="1"/>
+<ope date="738170" amount="-42.1" account="1" paymode="1" payee="1"/>
+<ope date="738171" amount="-42.1" account="1" paymode="1" payee="1"/>
+<ope date="738175" amount="-42.11" account="1" paymode="1" payee="1"/>
+<ope date="738176" amount="-8" account="1" paymode="1"/>
+</homebank>"#;
+
+    #[track_caller]
+    fn check_duplicates(tolerance_days: u32, amount_epsilon: f64, expected: Vec<Vec<usize>>) {
+        let db = HomeBankDb::from_reader(INPUT.as_bytes()).unwrap();
+
+        assert_eq!(expected, db.find_duplicates(tolerance_days, amount_epsilon));
+    }
+
+    #[test]
+    fn exact_duplicates() {
+        check_duplicates(0, 0.001, vec![vec![0, 1]]);
+    }
+
+    #[test]
+    fn duplicates_within_a_day() {
+        check_duplicates(1, 0.001, vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn duplicates_within_a_cent() {
+        // the transaction 4 days after the last copy is only close enough with a wider tolerance
+        check_duplicates(1, 0.011, vec![vec![0, 1, 2]]);
+        check_duplicates(4, 0.011, vec![vec![0, 1, 2, 3]]);
+    }
+
+    #[track_caller]
+    fn check_duplicates_by(keys: &[DuplicateKey], tolerance_days: u32, expected: Vec<Vec<usize>>) {
+        let db = HomeBankDb::from_reader(INPUT.as_bytes()).unwrap();
+
+        assert_eq!(expected, db.find_duplicates_by(keys, tolerance_days, 0.001));
+    }
+
+    #[test]
+    fn duplicates_swept_by_amount() {
+        check_duplicates_by(&[DuplicateKey::Amount, DuplicateKey::Payee], 0, vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn duplicates_swept_by_date_then_amount() {
+        // the dates are close, but the amount of the last copy is too far off
+        check_duplicates_by(&[DuplicateKey::Date, DuplicateKey::Amount], 6, vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn duplicates_by_exact_fields() {
+        check_duplicates_by(&[DuplicateKey::Account, DuplicateKey::Payee], 0, vec![vec![0, 1, 2, 3]]);
+    }
+}
@@ -1,5 +1,6 @@
 //! Errors when parsing or loading a HomeBank database XML file.
 
+use semver::{Version, VersionReq};
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -18,6 +19,26 @@ pub enum HomeBankDbError {
     #[error("Error reading XHB file `{0}`.")]
     CouldNotRead(PathBuf),
 
+    /// The database is being read from a stream (e.g. stdin), but there is an error when reading from it.
+    #[error("Error reading XHB contents.")]
+    CouldNotReadInput,
+
+    /// The database looks compressed, but cannot be decompressed.
+    #[error("Error decompressing XHB contents.")]
+    CouldNotDecompress,
+
+    /// The database file declares an encoding that is not supported.
+    #[error("Unsupported encoding `{0}` in XHB file.")]
+    UnsupportedEncoding(String),
+
+    /// The database is being written to a stream, but there is an error when writing to it.
+    #[error("Error writing XHB contents.")]
+    CouldNotWriteOutput,
+
+    /// The database file cannot be written to.
+    #[error("Error writing XHB file `{0}`.")]
+    CouldNotWrite(PathBuf),
+
     /// The database file is found, opened, and read from, but its contents cannot be parsed.
     #[error("Error parsing XHB file `{0}`.")]
     CouldNotParse(PathBuf),
@@ -29,4 +50,22 @@ pub enum HomeBankDbError {
     /// The database schema version cannot be properly parsed.
     #[error("Invalid database version.")]
     InvalidVersion,
+
+    /// The database declares a version of HomeBank's file format that cannot be parsed, e.g. one from a newer HomeBank.
+    #[error("Unsupported XHB file version {found}, only versions {supported} can be read.")]
+    UnsupportedVersion { found: Version, supported: VersionReq },
+
+    /// An element of the database cannot be parsed, and the database is being parsed strictly.
+    #[error("Error parsing `<{0}>` element on line {1}. {2}")]
+    InvalidElement(String, u64, String),
+
+    /// The XML itself is malformed (e.g. a truncated file), so parsing cannot continue.
+    /// `element` is the innermost element that was open when the problem was found.
+    #[error("Malformed XML in `<{element}>` element at line {line}, column {position}. {message}")]
+    MalformedElement {
+        element: String,
+        line: u64,
+        position: u64,
+        message: String,
+    },
 }
@@ -0,0 +1,87 @@
+//! Names of the payees and categories in the HomeBank database, resolved once instead of for every transaction.
+
+use super::HomeBankDb;
+use std::collections::HashMap;
+
+/// The names of the payees, and the full names of the categories, in the HomeBank database by key.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct NameIndex {
+    /// Full name of each category, e.g. `Food:Groceries`.
+    categories: HashMap<usize, String>,
+
+    /// Name of each payee.
+    payees: HashMap<usize, String>,
+}
+
+impl NameIndex {
+    /// Resolve the names of every payee and category in `db`.
+    pub(crate) fn build(db: &HomeBankDb) -> Self {
+        Self {
+            categories: db
+                .categories()
+                .iter()
+                .map(|(key, cat)| (*key, cat.full_name(db)))
+                .collect(),
+            payees: db
+                .payees()
+                .iter()
+                .map(|(key, payee)| (*key, payee.name().to_string()))
+                .collect(),
+        }
+    }
+
+    /// Retrieve the full name of the category with key `key`
+    pub fn category(&self, key: usize) -> Option<&str> {
+        self.categories.get(&key).map(String::as_str)
+    }
+
+    /// Retrieve the name of the payee with key `key`
+    pub fn payee(&self, key: usize) -> Option<&str> {
+        self.payees.get(&key).map(String::as_str)
+    }
+}
+
+impl HomeBankDb {
+    /// Retrieve the full name of the category with key `key`, e.g. `Food:Groceries`, without walking its parents again.
+    pub fn category_full_name(&self, key: usize) -> Option<&str> {
+        self.names().category(key)
+    }
+
+    /// Retrieve the name of the payee with key `key`.
+    pub fn payee_name(&self, key: usize) -> Option<&str> {
+        self.names().payee(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    #[test]
+    fn consistent_with_full_name() {
+        let db = example_db();
+
+        assert!(!db.categories().is_empty());
+        for (key, cat) in db.categories() {
+            assert_eq!(Some(cat.full_name(&db).as_str()), db.category_full_name(*key));
+        }
+        for (key, payee) in db.payees() {
+            assert_eq!(Some(payee.name()), db.payee_name(*key));
+        }
+        assert_eq!(None, db.category_full_name(999));
+        assert_eq!(None, db.payee_name(999));
+    }
+
+    #[test]
+    fn children_before_parents() {
+        // children can come before their parents in the file, so names are resolved once everything is parsed
+        let xml = r#"<homebank v="1.4" d="050504">
+<cat key="2" parent="1" flags="1" name="Groceries"/>
+<cat key="1" flags="0" name="Food"/>
+</homebank>"#;
+        let db = HomeBankDb::from_reader(xml.as_bytes()).unwrap();
+
+        assert_eq!(Some("Food:Groceries"), db.category_full_name(2));
+    }
+}
@@ -0,0 +1,202 @@
+//! Open a HomeBank database file, whether or not it is compressed or UTF-8 encoded.
+
+use super::{db_struct::ParseMode, HomeBankDb, HomeBankDbError};
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+use flate2::read::GzDecoder;
+use std::{
+    fs::{self, File},
+    io::{BufReader, Read},
+    path::Path,
+};
+
+/// First bytes of any gzip stream.
+pub(crate) const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Open and parse a HomeBank database file.
+///
+/// Gzip compression is detected from the file's contents, not its extension, and the file is decompressed first.
+/// The text is then decoded, so that files saved in a legacy encoding (e.g. Windows-1252) parse the same as UTF-8 ones.
+pub fn open_homebank(path: &Path) -> Result<HomeBankDb, HomeBankDbError> {
+    if !path.exists() {
+        return Err(HomeBankDbError::DoesNotExist(path.to_path_buf()));
+    }
+
+    let xhb_file = match File::open(path) {
+        Ok(f) => f,
+        Err(_) => return Err(HomeBankDbError::CouldNotOpen(path.to_path_buf())),
+    };
+
+    match HomeBankDb::from_reader(BufReader::new(xhb_file)) {
+        Err(HomeBankDbError::CouldNotReadInput) => Err(HomeBankDbError::CouldNotRead(path.to_path_buf())),
+        parsed => parsed,
+    }
+}
+
+impl HomeBankDb {
+    /// Parse a HomeBank database from any source, such as an in-memory string or stdin.
+    ///
+    /// Like [`open_homebank`], the contents may be gzip-compressed or in a legacy encoding.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, HomeBankDbError> {
+        Self::read_with_mode(reader, ParseMode::Strict)
+    }
+
+    /// Parse a HomeBank database from any source, even if it declares a version of the file format that isn't supported.
+    pub fn from_reader_any_version<R: Read>(reader: R) -> Result<Self, HomeBankDbError> {
+        Self::read_with_mode(reader, ParseMode::AnyVersion)
+    }
+
+    /// Read all of `reader`, then parse it.
+    fn read_with_mode<R: Read>(mut reader: R, mode: ParseMode) -> Result<Self, HomeBankDbError> {
+        let mut raw = vec![];
+        if reader.read_to_end(&mut raw).is_err() {
+            return Err(HomeBankDbError::CouldNotReadInput);
+        }
+
+        let (db, _, _) = HomeBankDb::parse_reader(xml_from_bytes(raw)?.as_bytes(), mode)?;
+
+        Ok(db)
+    }
+}
+
+/// Read the XML text of a HomeBank database file.
+pub(crate) fn read_xml(path: &Path) -> Result<String, HomeBankDbError> {
+    if !path.exists() {
+        return Err(HomeBankDbError::DoesNotExist(path.to_path_buf()));
+    }
+
+    match fs::read(path) {
+        Ok(raw) => xml_from_bytes(raw),
+        Err(_) => Err(HomeBankDbError::CouldNotOpen(path.to_path_buf())),
+    }
+}
+
+/// Check whether the raw contents of a HomeBank database are gzip-compressed.
+pub(crate) fn is_gzipped(raw: &[u8]) -> bool {
+    raw.starts_with(&GZIP_MAGIC)
+}
+
+/// Decompress, if needed, and decode the raw contents of a HomeBank database.
+pub(crate) fn xml_from_bytes(raw: Vec<u8>) -> Result<String, HomeBankDbError> {
+    let bytes = if is_gzipped(&raw) {
+        let mut decompressed = vec![];
+        match GzDecoder::new(raw.as_slice()).read_to_end(&mut decompressed) {
+            Ok(_) => decompressed,
+            Err(_) => return Err(HomeBankDbError::CouldNotDecompress),
+        }
+    } else {
+        raw
+    };
+
+    decode(&bytes)
+}
+
+/// Decode the bytes of an XML document into text.
+///
+/// The encoding is taken from a byte order mark, then from the XML declaration.
+/// Without either, the text is UTF-8 if it is valid, and Windows-1252 otherwise, since that is what older versions of HomeBank on Windows wrote.
+fn decode(bytes: &[u8]) -> Result<String, HomeBankDbError> {
+    let encoding = match Encoding::for_bom(bytes) {
+        Some((enc, _)) => enc,
+        None => match declared_encoding(bytes) {
+            Some(label) => match Encoding::for_label(label.as_bytes()) {
+                Some(enc) => enc,
+                None => return Err(HomeBankDbError::UnsupportedEncoding(label)),
+            },
+            None if std::str::from_utf8(bytes).is_ok() => UTF_8,
+            None => WINDOWS_1252,
+        },
+    };
+
+    // the byte order mark, if any, is removed while decoding
+    let (text, _) = encoding.decode_with_bom_removal(bytes);
+
+    Ok(text.into_owned())
+}
+
+/// Find the `encoding` given in the XML declaration, if there is one.
+pub(crate) fn declared_encoding(bytes: &[u8]) -> Option<String> {
+    // the declaration is always ASCII, so it can be found before knowing the encoding
+    let end = bytes.windows(2).position(|w| w == b"?>")?;
+    let decl = String::from_utf8_lossy(&bytes[..end]);
+    if !decl.trim_start().starts_with("<?xml") {
+        return None;
+    }
+
+    let (_, after) = decl.split_once("encoding")?;
+    let value = after.trim_start().strip_prefix('=')?.trim_start();
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let (label, _) = value[1..].split_once(quote)?;
+
+    Some(label.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check_open(path: &str) {
+        let db = open_homebank(Path::new(path)).unwrap();
+
+        assert_eq!(3, db.transactions().len());
+        assert!(db.payees().values().any(|p| p.name() == "Café Olé"));
+    }
+
+    #[test]
+    fn open_plain() {
+        check_open("tests/encoding_utf8.xhb");
+    }
+
+    #[test]
+    fn open_gzipped() {
+        check_open("tests/encoding_utf8.xhb.gz");
+    }
+
+    #[test]
+    fn open_windows_1252() {
+        check_open("tests/encoding_windows1252.xhb");
+    }
+
+    #[test]
+    fn try_from_decompresses() {
+        let db = HomeBankDb::try_from(Path::new("tests/encoding_utf8.xhb.gz")).unwrap();
+
+        assert_eq!(3, db.transactions().len());
+    }
+
+    #[test]
+    fn from_reader_fixture() {
+        let input = fs::read_to_string("tests/transactions.xhb").unwrap();
+        let from_reader = HomeBankDb::from_reader(input.as_bytes()).unwrap();
+        let from_path = open_homebank(Path::new("tests/transactions.xhb")).unwrap();
+
+        assert_eq!(from_path, from_reader);
+        assert_eq!(11, from_reader.transactions().len());
+    }
+
+    #[test]
+    fn from_reader_truncated_gzip() {
+        let gzipped = fs::read("tests/encoding_utf8.xhb.gz").unwrap();
+
+        assert_eq!(
+            Err(HomeBankDbError::CouldNotDecompress),
+            HomeBankDb::from_reader(&gzipped[..gzipped.len() / 2])
+        );
+    }
+
+    #[test]
+    fn declared_encoding_label() {
+        let input = br#"<?xml version="1.0" encoding='ISO-8859-1'?><homebank/>"#;
+
+        assert_eq!(Some("ISO-8859-1".to_string()), declared_encoding(input));
+        assert_eq!(None, declared_encoding(br#"<?xml version="1.0"?>"#));
+    }
+
+    #[test]
+    fn unsupported_encoding() {
+        let input = br#"<?xml version="1.0" encoding="klingon"?><homebank/>"#;
+        let expected = Err(HomeBankDbError::UnsupportedEncoding("klingon".to_string()));
+
+        assert_eq!(expected, decode(input));
+    }
+}
@@ -0,0 +1,130 @@
+//! Elements and attributes of the HomeBank file that were seen, but not handled, when parsing it.
+
+use std::collections::BTreeMap;
+use xml::attribute::OwnedAttribute;
+
+/// Attributes handled for each element of the HomeBank file. Elements not listed here are ignored entirely.
+const HANDLED: [(&str, &[&str]); 9] = [
+    ("homebank", &["v", "d"]),
+    (
+        "properties",
+        &["title", "notes", "curr", "car_category", "auto_smode", "auto_weekday", "auto_nbdays"],
+    ),
+    (
+        "cur",
+        &["key", "flags", "iso", "name", "symb", "syprf", "dchar", "gchar", "frac", "rate", "mdate"],
+    ),
+    ("grp", &["key", "name"]),
+    (
+        "account",
+        &[
+            "key", "flags", "pos", "type", "curr", "name", "bankname", "initial", "minimum", "maximum", "notes", "grp",
+            "rdate",
+        ],
+    ),
+    ("pay", &["key", "name", "category", "paymode"]),
+    (
+        "cat",
+        &[
+            "key", "parent", "flags", "name", "b0", "b1", "b2", "b3", "b4", "b5", "b6", "b7", "b8", "b9", "b10", "b11",
+            "b12",
+        ],
+    ),
+    (
+        "fav",
+        &[
+            "key", "amount", "account", "dst_account", "paymode", "flags", "payee", "category", "wording", "nextdate",
+            "unit", "every", "limit", "weekend",
+        ],
+    ),
+    (
+        "ope",
+        &[
+            "date", "amount", "account", "dst_account", "paymode", "st", "flags", "payee", "category", "wording", "info",
+            "tags", "kxfer", "scat", "samt", "smem",
+        ],
+    ),
+];
+
+/// The elements and attributes of the HomeBank file that were ignored when parsing it, e.g. ones added by a newer version of HomeBank.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ParseReport {
+    /// Number of times each unknown element was seen.
+    unknown_elements: BTreeMap<String, usize>,
+
+    /// Number of times each unknown attribute was seen, by the element it was on and its name.
+    unknown_attributes: BTreeMap<(String, String), usize>,
+}
+
+impl ParseReport {
+    /// Retrieve the number of times each unknown element was seen
+    pub fn unknown_elements(&self) -> &BTreeMap<String, usize> {
+        &self.unknown_elements
+    }
+
+    /// Retrieve the number of times each unknown attribute was seen, by the element it was on and its name
+    pub fn unknown_attributes(&self) -> &BTreeMap<(String, String), usize> {
+        &self.unknown_attributes
+    }
+
+    /// Check whether every element and attribute was handled
+    pub fn is_empty(&self) -> bool {
+        self.unknown_elements.is_empty() && self.unknown_attributes.is_empty()
+    }
+
+    /// Count the element, or any of its attributes, if they aren't handled when parsing.
+    pub(crate) fn record(&mut self, element: &str, attributes: &[OwnedAttribute]) {
+        let handled = match HANDLED.iter().find(|(name, _)| *name == element) {
+            Some((_, handled)) => handled,
+            None => {
+                *self.unknown_elements.entry(element.to_string()).or_default() += 1;
+                return;
+            }
+        };
+
+        for attr in attributes {
+            let name = attr.name.local_name.as_str();
+
+            if !handled.contains(&name) {
+                *self
+                    .unknown_attributes
+                    .entry((element.to_string(), name.to_string()))
+                    .or_default() += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::HomeBankDb;
+    use crate::tests::example_db;
+
+    #[test]
+    fn example_is_fully_handled() {
+        let db = example_db();
+
+        assert!(db.parse_report().is_empty(), "{:?}", db.parse_report());
+    }
+
+    #[test]
+    fn newer_elements_and_attributes() {
+        let xml = r#"<homebank v="1.6" d="050805">
+<properties title="Newer" curr="1" auto_smode="1" auto_weekday="1" auto_nbdays="0" dark="1"/>
+<tag key="1" name="food"/>
+<tag key="2" name="restaurant"/>
+<ope date="738134" amount="-20" account="1" paymode="1" st="0" color="3"/>
+<ope date="738135" amount="-30" account="1" paymode="1" st="0" color="4"/>
+</homebank>"#;
+        let report = HomeBankDb::from_reader(xml.as_bytes()).unwrap().parse_report().clone();
+
+        assert_eq!(vec![(&"tag".to_string(), &2)], report.unknown_elements().iter().collect::<Vec<_>>());
+        assert_eq!(
+            vec![
+                (&("ope".to_string(), "color".to_string()), &2),
+                (&("properties".to_string(), "dark".to_string()), &1),
+            ],
+            report.unknown_attributes().iter().collect::<Vec<_>>()
+        );
+    }
+}
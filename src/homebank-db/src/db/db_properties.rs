@@ -6,6 +6,7 @@ use xml::attribute::OwnedAttribute;
 
 /// Properties for the entire HomeBank database.
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HomeBankDbProperties {
     /// Title for the database.
     title: String,
@@ -18,6 +19,9 @@ pub struct HomeBankDbProperties {
 
     /// Mode for how automatically scheduled transactions should be added.
     sched_mode: ScheduleMode,
+
+    /// Notes about the database.
+    notes: Option<String>,
 }
 
 impl HomeBankDbProperties {
@@ -28,6 +32,7 @@ impl HomeBankDbProperties {
             currency_key: 1,
             car_category_key: 1,
             sched_mode: ScheduleMode::NotCurrentlySet(None, None),
+            notes: None,
         }
     }
 
@@ -43,8 +48,50 @@ impl HomeBankDbProperties {
             currency_key: currency,
             car_category_key: car_category,
             sched_mode,
+            notes: None,
         }
     }
+
+    /// Add notes about the database
+    pub fn with_notes(mut self, notes: &str) -> Self {
+        self.notes = Some(notes.to_string());
+        self
+    }
+
+    /// Retrieve the title of the database, if it has a non-empty one
+    pub fn title(&self) -> Option<&str> {
+        match self.title.as_str() {
+            "" => None,
+            title => Some(title),
+        }
+    }
+
+    /// Retrieve the key of the base [`Currency`][crate::currency::currency_struct::Currency]
+    pub fn currency(&self) -> usize {
+        self.currency_key
+    }
+
+    /// Retrieve the key of the base [`Currency`][crate::currency::currency_struct::Currency].
+    /// This is the same as [`currency`][Self::currency], named after what the key is used for.
+    pub fn base_currency_idx(&self) -> usize {
+        self.currency_key
+    }
+
+    /// Retrieve the key of the vehicle [`Category`][crate::category::category_struct::Category]
+    pub fn car_category_idx(&self) -> usize {
+        self.car_category_key
+    }
+
+    /// Retrieve the default mode for adding scheduled transactions
+    pub fn sched_mode(&self) -> &ScheduleMode {
+        &self.sched_mode
+    }
+
+    /// Retrieve the notes about the database, if there are any.
+    /// HomeBank doesn't write notes for every database, so this is usually `None`.
+    pub fn notes(&self) -> Option<&str> {
+        self.notes.as_deref().filter(|notes| !notes.is_empty())
+    }
 }
 
 impl Default for HomeBankDbProperties {
@@ -92,6 +139,9 @@ impl TryFrom<Vec<OwnedAttribute>> for HomeBankDbProperties {
                 "title" => {
                     props.title = i.value.to_string();
                 }
+                "notes" => {
+                    props.notes = Some(i.value.to_string());
+                }
                 "curr" => {
                     props.currency_key = match usize::from_str(&i.value) {
                         Ok(idx) => idx,
@@ -169,6 +219,7 @@ impl TryFrom<Vec<OwnedAttribute>> for HomeBankDbProperties {
 
 /// Default setting for how scheduled [`Transaction`][crate::transaction::transaction_struct::Transaction] dates should be calculated.
 #[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ScheduleMode {
     /// Not currently set.
     /// This is used when creating a new HomeBank database, or when parsing the XML file for the first time.
@@ -232,6 +283,23 @@ mod tests {
         check_try_from_single_str(input, &expected);
     }
     
+    #[test]
+    fn check_empty_title() {
+        let props = HomeBankDbProperties::default();
+
+        assert_eq!(None, props.title());
+        assert_eq!(None, props.notes());
+    }
+
+    #[test]
+    fn check_notes() {
+        let input = r#"<properties title="DbTitle" notes="Shared household budget">"#;
+        let expected = Ok(HomeBankDbProperties::new("DbTitle", 1, 1, ScheduleMode::NotCurrentlySet(None, None))
+            .with_notes("Shared household budget"));
+
+        check_try_from_single_str(input, &expected);
+    }
+
     #[test]
     fn check_currency() {
         let input = r#"<properties curr="2">"#;
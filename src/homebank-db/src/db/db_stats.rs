@@ -0,0 +1,294 @@
+//! Summarize the contents of the HomeBank database at a glance.
+
+use super::HomeBankDb;
+use crate::{category::TODAY, Transaction};
+use chrono::{Datelike, NaiveDate};
+use semver::Version;
+
+/// An overview of the HomeBank database: how much is in it, the dates it covers, and the totals for a year.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DbStats {
+    /// Version of HomeBank's file format.
+    version: Version,
+
+    /// Number of accounts.
+    num_accounts: usize,
+
+    /// Number of payees.
+    num_payees: usize,
+
+    /// Number of categories, including subcategories.
+    num_categories: usize,
+
+    /// Number of currencies.
+    num_currencies: usize,
+
+    /// Number of transactions, including voided ones.
+    num_transactions: usize,
+
+    /// Number of favourite (template or scheduled) transactions.
+    num_favourites: usize,
+
+    /// Date of the earliest transaction that isn't voided.
+    first_date: Option<NaiveDate>,
+
+    /// Date of the latest transaction that isn't voided.
+    last_date: Option<NaiveDate>,
+
+    /// Year that the income and expenses are totalled for.
+    year: i32,
+
+    /// Sum of the income in `year`.
+    income: f64,
+
+    /// Sum of the expenses in `year` (negative).
+    expenses: f64,
+
+    /// Number of transactions (other than transfers) with at least one part that has no category, or one missing from the database.
+    num_uncategorised: usize,
+
+    /// The transaction (other than a transfer) with the lowest amount, if any are expenses.
+    largest_expense: Option<Transaction>,
+}
+
+impl DbStats {
+    /// Retrieve the version of HomeBank's file format
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
+    /// Retrieve the number of accounts
+    pub fn num_accounts(&self) -> usize {
+        self.num_accounts
+    }
+
+    /// Retrieve the number of payees
+    pub fn num_payees(&self) -> usize {
+        self.num_payees
+    }
+
+    /// Retrieve the number of categories, including subcategories
+    pub fn num_categories(&self) -> usize {
+        self.num_categories
+    }
+
+    /// Retrieve the number of currencies
+    pub fn num_currencies(&self) -> usize {
+        self.num_currencies
+    }
+
+    /// Retrieve the number of transactions, including voided ones
+    pub fn num_transactions(&self) -> usize {
+        self.num_transactions
+    }
+
+    /// Retrieve the number of favourite transactions
+    pub fn num_favourites(&self) -> usize {
+        self.num_favourites
+    }
+
+    /// Retrieve the date of the earliest transaction that isn't voided
+    pub fn first_date(&self) -> &Option<NaiveDate> {
+        &self.first_date
+    }
+
+    /// Retrieve the date of the latest transaction that isn't voided
+    pub fn last_date(&self) -> &Option<NaiveDate> {
+        &self.last_date
+    }
+
+    /// Retrieve the year that the income and expenses are totalled for
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    /// Retrieve the sum of the income in the year
+    pub fn income(&self) -> f64 {
+        self.income
+    }
+
+    /// Retrieve the sum of the expenses in the year
+    pub fn expenses(&self) -> f64 {
+        self.expenses
+    }
+
+    /// Retrieve the number of transactions with at least one part that has no category
+    pub fn num_uncategorised(&self) -> usize {
+        self.num_uncategorised
+    }
+
+    /// Retrieve the transaction with the lowest amount, if any are expenses
+    pub fn largest_expense(&self) -> &Option<Transaction> {
+        &self.largest_expense
+    }
+}
+
+impl HomeBankDb {
+    /// Count the transactions in the database, including voided ones.
+    pub fn transaction_count(&self) -> usize {
+        self.transactions().len()
+    }
+
+    /// Retrieve the date of the earliest transaction that isn't voided, or `None` if there are no such transactions.
+    pub fn earliest_date(&self) -> Option<NaiveDate> {
+        self.transactions().iter().filter(|tr| !tr.is_void()).map(|tr| *tr.date()).min()
+    }
+
+    /// Retrieve the date of the latest transaction that isn't voided, or `None` if there are no such transactions.
+    pub fn latest_date(&self) -> Option<NaiveDate> {
+        self.transactions().iter().filter(|tr| !tr.is_void()).map(|tr| *tr.date()).max()
+    }
+
+    /// Summarize the database, with the income and expenses totalled for `year`, or the current year if none is given.
+    ///
+    /// Transfers only move money between accounts, so they are left out of the totals, the uncategorised transactions, and the largest expense.
+    /// Voided transactions are counted, but left out of everything else.
+    pub fn stats(&self, year: Option<i32>) -> DbStats {
+        let year = year.unwrap_or(TODAY.year());
+        let counted = || self.transactions().iter().filter(|tr| !tr.is_void() && !tr.is_transfer());
+        let (income, expenses) = self
+            .monthly_cash_flow(year)
+            .iter()
+            .fold((0.0, 0.0), |(income, expenses), (_, i, e)| (income + i, expenses + e));
+
+        DbStats {
+            version: self.version().version().clone(),
+            num_accounts: self.accounts().len(),
+            num_payees: self.payees().len(),
+            num_categories: self.categories().len(),
+            num_currencies: self.currencies().len(),
+            num_transactions: self.transaction_count(),
+            num_favourites: self.favourites().len(),
+            first_date: self.earliest_date(),
+            last_date: self.latest_date(),
+            year,
+            income,
+            expenses,
+            num_uncategorised: counted()
+                .filter(|tr| tr.categories().iter().any(|cat| cat.and_then(|key| self.category(key)).is_none()))
+                .count(),
+            // the first of several equally large expenses is kept
+            largest_expense: counted()
+                .filter(|tr| *tr.total() < 0.0)
+                .reduce(|lowest, tr| if tr.total() < lowest.total() { tr } else { lowest })
+                .cloned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    #[test]
+    fn stats_of_example() {
+        let db = example_db();
+        let stats = db.stats(Some(2022));
+
+        assert_eq!(&Version::new(1, 4, 0), stats.version());
+        assert_eq!(
+            (3, 4, 7, 1, 11, 0),
+            (
+                stats.num_accounts(),
+                stats.num_payees(),
+                stats.num_categories(),
+                stats.num_currencies(),
+                stats.num_transactions(),
+                stats.num_favourites()
+            )
+        );
+        assert_eq!(
+            (NaiveDate::from_ymd_opt(2021, 12, 10), NaiveDate::from_ymd_opt(2022, 2, 14)),
+            (*stats.first_date(), *stats.last_date())
+        );
+        assert_eq!((4000.0, -2755.75), (stats.income(), stats.expenses()));
+        assert_eq!(0, stats.num_uncategorised());
+
+        let largest = stats.largest_expense().as_ref().unwrap();
+        assert_eq!((-1200.0, Some("December rent")), (*largest.total(), largest.memo().as_deref()));
+    }
+
+    #[test]
+    fn transaction_count_and_dates() {
+        let db = example_db();
+
+        assert_eq!(11, db.transaction_count());
+        assert_eq!(NaiveDate::from_ymd_opt(2021, 12, 10), db.earliest_date());
+        assert_eq!(NaiveDate::from_ymd_opt(2022, 2, 14), db.latest_date());
+    }
+
+    #[test]
+    fn dates_of_unordered_transactions() {
+        let xml = r#"<homebank v="1.4" d="050504">
+<account key="1" flags="0" pos="1" type="1" curr="1" name="Chequing" initial="0"/>
+<ope date="738136" amount="-20" account="1" paymode="1"/>
+<ope date="738134" amount="-30" account="1" paymode="1"/>
+<ope date="738135" amount="-40" account="1" paymode="1"/>
+</homebank>"#;
+        let db = HomeBankDb::from_reader(xml.as_bytes()).unwrap();
+
+        assert_eq!(3, db.transaction_count());
+        assert_eq!(NaiveDate::from_ymd_opt(2021, 12, 10), db.earliest_date());
+        assert_eq!(NaiveDate::from_ymd_opt(2021, 12, 12), db.latest_date());
+    }
+
+    #[test]
+    fn dates_without_voided() {
+        let xml = r#"<homebank v="1.4" d="050504">
+<account key="1" flags="0" pos="1" type="1" curr="1" name="Chequing" initial="0"/>
+<ope date="738134" amount="-30" account="1" paymode="1" st="4"/>
+<ope date="738135" amount="-40" account="1" paymode="1"/>
+<ope date="738136" amount="-20" account="1" paymode="1"/>
+<ope date="738137" amount="-10" account="1" paymode="1" st="4"/>
+</homebank>"#;
+        let db = HomeBankDb::from_reader(xml.as_bytes()).unwrap();
+        let stats = db.stats(Some(2021));
+
+        assert_eq!(4, db.transaction_count());
+        assert_eq!(
+            (NaiveDate::from_ymd_opt(2021, 12, 11), NaiveDate::from_ymd_opt(2021, 12, 12)),
+            (db.earliest_date(), db.latest_date())
+        );
+        assert_eq!((db.earliest_date(), db.latest_date()), (*stats.first_date(), *stats.last_date()));
+    }
+
+    #[test]
+    fn dates_of_only_voided() {
+        let xml = r#"<homebank v="1.4" d="050504">
+<account key="1" flags="0" pos="1" type="1" curr="1" name="Chequing" initial="0"/>
+<ope date="738134" amount="-30" account="1" paymode="1" st="4"/>
+</homebank>"#;
+        let db = HomeBankDb::from_reader(xml.as_bytes()).unwrap();
+
+        assert_eq!((1, None, None), (db.transaction_count(), db.earliest_date(), db.latest_date()));
+    }
+
+    #[test]
+    fn stats_of_uncategorised() {
+        let xml = r#"<homebank v="1.4" d="050504">
+<account key="1" flags="0" pos="1" type="1" curr="1" name="Chequing" initial="0"/>
+<cat key="1" flags="0" name="Food"/>
+<ope date="738134" amount="-20" account="1" paymode="1"/>
+<ope date="738135" amount="-30" account="1" paymode="1" scat="1||0" samt="-10||-20"/>
+<ope date="738136" amount="-40" account="1" paymode="1" category="1"/>
+<ope date="738137" amount="-99" account="1" paymode="1" st="4"/>
+</homebank>"#;
+        let stats = HomeBankDb::from_reader(xml.as_bytes()).unwrap().stats(Some(1999));
+
+        assert_eq!(2, stats.num_uncategorised());
+        assert_eq!(Some(-40.0), stats.largest_expense().as_ref().map(|tr| *tr.total()));
+        assert_eq!((0.0, 0.0), (stats.income(), stats.expenses()));
+    }
+
+    #[test]
+    fn stats_of_empty() {
+        let db = HomeBankDb::empty();
+        let stats = db.stats(None);
+
+        assert_eq!((0, None), (stats.num_transactions(), *stats.first_date()));
+        assert_eq!((0, None, None), (db.transaction_count(), db.earliest_date(), db.latest_date()));
+        assert_eq!(TODAY.year(), stats.year());
+        assert!(stats.largest_expense().is_none());
+    }
+}
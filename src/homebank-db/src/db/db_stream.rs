@@ -0,0 +1,281 @@
+//! Read the transactions of a HomeBank database file one at a time, without parsing the rest of it into memory.
+//!
+//! [`open_homebank`][crate::db::db_open::open_homebank] keeps the whole text of the file and every element parsed from it.
+//! Queries that only need the transactions can stream them instead, keeping a single `<ope>` element in memory at a time.
+
+use super::{
+    db_open::{declared_encoding, is_gzipped, read_xml, GZIP_MAGIC},
+    db_struct::{malformed_error, parse_transaction},
+    HomeBankDbError, SUPPORTED_VERSIONS,
+};
+use crate::{HomeBankDbSchema, Transaction};
+use encoding_rs::{Encoding, UTF_8};
+use flate2::read::GzDecoder;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Cursor, Read},
+    path::Path,
+};
+use xml::{attribute::OwnedAttribute, common::Position, reader::XmlEvent, EventReader};
+
+/// Number of bytes at the start of the file searched for a byte order mark or an encoding declaration.
+const HEAD_LEN: usize = 512;
+
+/// An iterator over the transactions in a HomeBank database, parsed as the XML is read.
+///
+/// Like [`HomeBankDb::from_reader`][crate::HomeBankDb::from_reader], the first transaction or element that can't be parsed is an error,
+/// after which the iterator ends.
+/// So is a file declaring a version outside of [`SUPPORTED_VERSIONS`][crate::db::db_version::SUPPORTED_VERSIONS].
+pub struct TransactionStream<R: Read> {
+    /// Parser reading the XML events.
+    parser: EventReader<R>,
+
+    /// Whether the parser is inside the `<homebank>` element.
+    in_info: bool,
+
+    /// The elements that are currently open, to report where malformed XML was found.
+    open_elements: Vec<String>,
+
+    /// Whether the end of the document, or an error, was reached.
+    done: bool,
+}
+
+impl<R: Read> TransactionStream<R> {
+    /// Stream the transactions in the UTF-8 XML text read from `reader`
+    pub fn new(reader: R) -> Self {
+        Self {
+            parser: EventReader::new(reader),
+            in_info: false,
+            open_elements: vec![],
+            done: false,
+        }
+    }
+}
+
+impl<R: Read> Iterator for TransactionStream<R> {
+    type Item = Result<Transaction, HomeBankDbError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let event = self.parser.next();
+            // rows are counted from 0 by the XML parser
+            let line = self.parser.position().row + 1;
+
+            match event {
+                Ok(XmlEvent::StartElement { name, attributes, .. }) => {
+                    let is_transaction = self.in_info && name.local_name == "ope";
+                    let is_root = name.local_name == "homebank";
+                    self.open_elements.push(name.local_name);
+
+                    if is_root {
+                        self.in_info = true;
+
+                        if let Err(e) = check_version(attributes, line) {
+                            self.done = true;
+                            return Some(Err(e));
+                        }
+                        continue;
+                    }
+
+                    if is_transaction {
+                        let parsed = parse_transaction(attributes);
+                        self.done = parsed.is_err();
+
+                        return Some(parsed.map_err(|e| HomeBankDbError::InvalidElement("ope".to_string(), line, e.to_string())));
+                    }
+                }
+                Ok(XmlEvent::EndElement { name }) => {
+                    self.open_elements.pop();
+
+                    if name.local_name == "homebank" {
+                        self.in_info = false;
+                    }
+                }
+                Ok(XmlEvent::EndDocument) => self.done = true,
+                Ok(_) => {}
+                Err(e) => {
+                    self.done = true;
+
+                    // like the full parser, a document without any element has no transactions
+                    if let Some(err) = malformed_error(&self.open_elements, line, self.parser.position().column, &e) {
+                        return Some(Err(err));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Check that the attributes of the `<homebank>` element on `line` declare a supported version of the file format.
+///
+/// Like when parsing the whole file, files that don't declare a version are assumed to be supported.
+fn check_version(attributes: Vec<OwnedAttribute>, line: u64) -> Result<(), HomeBankDbError> {
+    if !attributes.iter().any(|a| a.name.local_name == "v") {
+        return Ok(());
+    }
+
+    match HomeBankDbSchema::try_from(attributes) {
+        Ok(ver) if ver.is_supported() => Ok(()),
+        Ok(ver) => Err(HomeBankDbError::UnsupportedVersion {
+            found: ver.version().clone(),
+            supported: SUPPORTED_VERSIONS.clone(),
+        }),
+        Err(e) => Err(HomeBankDbError::InvalidElement("homebank".to_string(), line, e.to_string())),
+    }
+}
+
+/// Stream the transactions in the HomeBank database file at `path`.
+///
+/// Like [`open_homebank`][crate::db::db_open::open_homebank], gzip compression and legacy encodings are detected from the contents.
+/// UTF-8 files are streamed straight from the file.
+/// Files in a legacy encoding, which are only written by old versions of HomeBank, or starting with a byte order mark, are decoded in full first.
+/// Files that don't declare their encoding are read twice: once to check that all of them is valid UTF-8, then again to stream them.
+/// Gzipped files are decompressed on each read.
+pub fn stream_transactions(path: &Path) -> Result<TransactionStream<Box<dyn Read>>, HomeBankDbError> {
+    let mut head = vec![];
+    if open_decompressed(path)?.take(HEAD_LEN as u64).read_to_end(&mut head).is_err() {
+        return Err(HomeBankDbError::CouldNotRead(path.to_path_buf()));
+    }
+
+    // a byte order mark is only removed when decoding the whole text
+    let utf8 = Encoding::for_bom(&head).is_none()
+        && match declared_encoding(&head) {
+            Some(label) => Encoding::for_label(label.as_bytes()) == Some(UTF_8),
+            // without a declared encoding, the file is only UTF-8 if all of it is valid
+            None => match is_utf8(open_decompressed(path)?) {
+                Some(valid) => valid,
+                None => return Err(HomeBankDbError::CouldNotRead(path.to_path_buf())),
+            },
+        };
+
+    let reader: Box<dyn Read> = match utf8 {
+        true => open_decompressed(path)?,
+        false => Box::new(Cursor::new(read_xml(path)?.into_bytes())),
+    };
+
+    Ok(TransactionStream::new(reader))
+}
+
+/// Open the file at `path` for reading, decompressing it as it is read if it is gzipped.
+fn open_decompressed(path: &Path) -> Result<Box<dyn Read>, HomeBankDbError> {
+    if !path.exists() {
+        return Err(HomeBankDbError::DoesNotExist(path.to_path_buf()));
+    }
+
+    let mut reader = match File::open(path) {
+        Ok(f) => BufReader::new(f),
+        Err(_) => return Err(HomeBankDbError::CouldNotOpen(path.to_path_buf())),
+    };
+
+    let gzipped = match reader.fill_buf() {
+        Ok(buf) => is_gzipped(&buf[..buf.len().min(GZIP_MAGIC.len())]),
+        Err(_) => return Err(HomeBankDbError::CouldNotRead(path.to_path_buf())),
+    };
+
+    match gzipped {
+        true => Ok(Box::new(BufReader::new(GzDecoder::new(reader)))),
+        false => Ok(Box::new(reader)),
+    }
+}
+
+/// Check whether everything read from `reader` is valid UTF-8, a chunk at a time.
+/// Returns `None` if it cannot be read.
+fn is_utf8<R: Read>(mut reader: R) -> Option<bool> {
+    let mut buf = [0u8; 8192];
+    // bytes at the end of the last chunk that start a character continuing in the next one
+    let mut carried = 0;
+
+    loop {
+        let read = reader.read(&mut buf[carried..]).ok()?;
+        if read == 0 {
+            return Some(carried == 0);
+        }
+
+        let filled = carried + read;
+        match std::str::from_utf8(&buf[..filled]) {
+            Ok(_) => carried = 0,
+            Err(e) if e.error_len().is_none() => {
+                buf.copy_within(e.valid_up_to()..filled, 0);
+                carried = filled - e.valid_up_to();
+            }
+            Err(_) => return Some(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HomeBankDb;
+
+    #[track_caller]
+    fn check_same_as_eager(path: &str) {
+        let streamed: Vec<Transaction> = stream_transactions(Path::new(path)).unwrap().map(|tr| tr.unwrap()).collect();
+        let db = HomeBankDb::try_from(Path::new(path)).unwrap();
+
+        assert_eq!(db.transactions(), &streamed);
+    }
+
+    #[test]
+    fn same_transactions_as_eager_parse() {
+        check_same_as_eager("tests/transactions.xhb");
+    }
+
+    #[test]
+    fn gzipped_and_legacy_encodings() {
+        check_same_as_eager("tests/encoding_utf8.xhb.gz");
+        check_same_as_eager("tests/encoding_windows1252.xhb");
+    }
+
+    #[test]
+    fn stops_at_invalid_transaction() {
+        let xml = r#"<homebank v="1.4" d="050504">
+<ope date="738134" amount="-20" account="1" paymode="1" st="0"/>
+<ope date="738135" account="1" paymode="1" st="0"/>
+<ope date="738136" amount="-30" account="1" paymode="1" st="0"/>
+</homebank>"#;
+        let mut stream = TransactionStream::new(xml.as_bytes());
+
+        assert!(stream.next().unwrap().is_ok());
+        assert!(matches!(stream.next(), Some(Err(HomeBankDbError::InvalidElement(_, 3, _)))));
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn malformed_document() {
+        let xml = r#"<homebank v="1.4" d="050504">
+<ope date="738134" amount="-20" account="1" paymode="1" st="0"/>
+<ope date="738135" amount="-30" account="1"#;
+        let observed: Vec<_> = TransactionStream::new(xml.as_bytes()).collect();
+
+        assert_eq!(2, observed.len());
+        assert!(matches!(observed[1], Err(HomeBankDbError::MalformedElement { .. })));
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let mut stream = stream_transactions(Path::new("tests/future_version.xhb")).unwrap();
+
+        assert!(matches!(stream.next(), Some(Err(HomeBankDbError::UnsupportedVersion { .. }))));
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn malformed_before_root() {
+        let observed: Vec<_> = TransactionStream::new(&b"<?xml version=\"1.0\"?>\n</homebank>\n"[..]).collect();
+
+        assert_eq!(1, observed.len());
+        assert!(matches!(observed[0], Err(HomeBankDbError::MalformedElement { .. })));
+        assert_eq!(0, TransactionStream::new(&b"<?xml version=\"1.0\"?>\n"[..]).count());
+    }
+
+    #[test]
+    fn utf8_split_across_chunks() {
+        let text = "é".repeat(10_000);
+
+        assert_eq!(Some(true), is_utf8(text.as_bytes()));
+        assert_eq!(Some(false), is_utf8(&b"caf\xe9 ol\xe9"[..]));
+    }
+}
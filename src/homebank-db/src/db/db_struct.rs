@@ -1,9 +1,15 @@
 //! Data structure for the HomeBank database.
 
-use super::{HomeBankDbError, HomeBankDbProperties};
-use crate::{Account, Category, Currency, Group, HomeBankDbSchema, Payee, Transaction};
-use std::{collections::HashMap, fs::File, io::BufReader, path::Path};
-use xml::{reader::XmlEvent, EventReader};
+use super::{db_open::read_xml, open_homebank, HomeBankDbError, HomeBankDbProperties, NameIndex, ParseReport, ParseWarning, SUPPORTED_VERSIONS};
+use crate::{template::Favourite, Account, Category, Currency, Group, HomeBankDbSchema, Payee, Transaction, TransactionError};
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    io::Read,
+    path::Path,
+    str::FromStr,
+};
+use xml::{attribute::OwnedAttribute, common::Position, reader::XmlEvent, EventReader};
 
 /// Data structure for the HomeBank database.
 #[derive(Debug, PartialEq)]
@@ -15,23 +21,31 @@ pub struct HomeBankDb {
     properties: HomeBankDbProperties,
 
     /// Every [`Currency`][crate::currency::currency_struct::Currency] used in this database.
-    currencies: HashMap<usize, Currency>,
+    currencies: BTreeMap<usize, Currency>,
 
     /// Every [`Group`][crate::group::group_struct::Group] of accounts in this database.
-    groups: HashMap<usize, Group>,
+    groups: BTreeMap<usize, Group>,
 
     /// Every [`Account`][crate::account::account_struct::Account] in this database.
-    accounts: HashMap<usize, Account>,
+    accounts: BTreeMap<usize, Account>,
 
     /// Every [`Payee`][crate::payee::payee_struct::Payee] in this database.
-    payees: HashMap<usize, Payee>,
+    payees: BTreeMap<usize, Payee>,
 
     /// Every [`Category`][crate::category::category_struct::Category] in this database.
-    categories: HashMap<usize, Category>,
+    categories: BTreeMap<usize, Category>,
+
+    /// Every [`Favourite`][crate::template::template_struct::Favourite] transaction in this database.
+    favourites: BTreeMap<usize, Favourite>,
 
-    // pub favourites: Vec<Favourite>,
     /// Every [`Transaction`][crate::transaction::transaction_struct::Transaction] in this database.
     transactions: Vec<Transaction>,
+
+    /// Elements and attributes of the file that were ignored when parsing it.
+    parse_report: ParseReport,
+
+    /// Names of the payees and categories, resolved once the file is parsed.
+    names: NameIndex,
 }
 
 impl HomeBankDb {
@@ -40,16 +54,34 @@ impl HomeBankDb {
         Self {
             homebank_version: HomeBankDbSchema::empty(),
             properties: HomeBankDbProperties::empty(),
-            currencies: HashMap::new(),
-            groups: HashMap::new(),
-            accounts: HashMap::new(),
-            payees: HashMap::new(),
-            categories: HashMap::new(),
-            // favourites: vec![],
+            currencies: BTreeMap::new(),
+            groups: BTreeMap::new(),
+            accounts: BTreeMap::new(),
+            payees: BTreeMap::new(),
+            categories: BTreeMap::new(),
+            favourites: BTreeMap::new(),
             transactions: vec![],
+            parse_report: ParseReport::default(),
+            names: NameIndex::default(),
         }
     }
 
+    /// Retrieve the elements and attributes of the HomeBank file that were seen, but ignored, when parsing it.
+    /// These are usually added by a newer version of HomeBank, and are lost when the file is read.
+    pub fn parse_report(&self) -> &ParseReport {
+        &self.parse_report
+    }
+
+    /// Retrieve the names of the payees and categories, by key
+    pub fn names(&self) -> &NameIndex {
+        &self.names
+    }
+
+    /// Resolve the names of the payees and categories again, after either of them changed.
+    pub(crate) fn rebuild_names(&mut self) {
+        self.names = NameIndex::build(self);
+    }
+
     /// Retrieve the version of the database
     pub fn version(&self) -> &HomeBankDbSchema {
         &self.homebank_version
@@ -65,167 +97,505 @@ impl HomeBankDb {
         &self.properties
     }
 
+    /// Retrieve the title of the database, if it has a non-empty one
+    pub fn title(&self) -> Option<&str> {
+        self.properties.title()
+    }
+
     /// Retrieve the mutable transactions
     fn mut_properties(&mut self) -> &mut HomeBankDbProperties {
         &mut self.properties
     }
 
     /// Retrieve the database accounts
-    pub fn accounts(&self) -> &HashMap<usize, Account> {
+    pub fn accounts(&self) -> &BTreeMap<usize, Account> {
         &self.accounts
     }
 
+    /// Retrieve the account with this key
+    pub fn account(&self, idx: usize) -> Option<&Account> {
+        self.accounts.get(&idx)
+    }
+
+    /// Find the account with this name, ignoring case
+    pub fn account_by_name(&self, name: &str) -> Option<&Account> {
+        self.accounts.values().find(|acct| same_name(acct.name(), name))
+    }
+
     /// Retrieve the mutable accounts
-    fn mut_accounts(&mut self) -> &mut HashMap<usize, Account> {
+    fn mut_accounts(&mut self) -> &mut BTreeMap<usize, Account> {
         &mut self.accounts
     }
 
     /// Retrieve the database properties
-    pub fn currencies(&self) -> &HashMap<usize, Currency> {
+    pub fn currencies(&self) -> &BTreeMap<usize, Currency> {
         &self.currencies
     }
 
+    /// Retrieve the currency with this key
+    pub fn currency(&self, idx: usize) -> Option<&Currency> {
+        self.currencies.get(&idx)
+    }
+
+    /// Find the currency with this name (e.g. `Canadian Dollar`), ignoring case
+    pub fn currency_by_name(&self, name: &str) -> Option<&Currency> {
+        self.currencies.values().find(|curr| same_name(curr.name(), name))
+    }
+
     /// Retrieve the mutable transactions
-    fn mut_currencies(&mut self) -> &mut HashMap<usize, Currency> {
+    fn mut_currencies(&mut self) -> &mut BTreeMap<usize, Currency> {
         &mut self.currencies
     }
 
+    /// Retrieve the base currency given in the database properties
+    pub fn base_currency(&self) -> Option<&Currency> {
+        self.currencies.get(&self.properties.currency())
+    }
+
+    /// Convert an amount in the currency with key `currency_idx` into the base currency.
+    ///
+    /// Returns `None` if the currency doesn't exist or has no exchange rate.
+    pub fn convert_to_base(&self, amount: f32, currency_idx: usize) -> Option<f32> {
+        if currency_idx == self.properties.currency() {
+            return Some(amount);
+        }
+
+        // HomeBank stores a rate of 0 for currencies whose rate was never set
+        self.currencies
+            .get(&currency_idx)
+            .map(|curr| curr.conversion_rate())
+            .filter(|rate| *rate > 0.0)
+            .map(|rate| amount * rate)
+    }
+
     /// Retrieve the groups in the database
-    pub fn groups(&self) -> &HashMap<usize, Group> {
+    pub fn groups(&self) -> &BTreeMap<usize, Group> {
         &self.groups
     }
 
     /// Retrieve the mutable currencies
-    fn mut_groups(&mut self) -> &mut HashMap<usize, Group> {
+    fn mut_groups(&mut self) -> &mut BTreeMap<usize, Group> {
         &mut self.groups
     }
 
     /// Retrieve the payees in the database
-    pub fn payees(&self) -> &HashMap<usize, Payee> {
+    pub fn payees(&self) -> &BTreeMap<usize, Payee> {
         &self.payees
     }
 
+    /// Retrieve the payee with this key
+    pub fn payee(&self, idx: usize) -> Option<&Payee> {
+        self.payees.get(&idx)
+    }
+
+    /// Find the payee with this name, ignoring case
+    pub fn payee_by_name(&self, name: &str) -> Option<&Payee> {
+        self.payees.values().find(|payee| same_name(payee.name(), name))
+    }
+
     /// Retrieve the mutable map of payees
-    fn mut_payees(&mut self) -> &mut HashMap<usize, Payee> {
+    fn mut_payees(&mut self) -> &mut BTreeMap<usize, Payee> {
         &mut self.payees
     }
 
     /// Retrieve the payees in the database
-    pub fn categories(&self) -> &HashMap<usize, Category> {
+    pub fn categories(&self) -> &BTreeMap<usize, Category> {
         &self.categories
     }
 
+    /// Retrieve the category with this key
+    pub fn category(&self, idx: usize) -> Option<&Category> {
+        self.categories.get(&idx)
+    }
+
+    /// Find the category with this full name (e.g. `Food:Groceries`), ignoring case
+    pub fn category_by_name(&self, name: &str) -> Option<&Category> {
+        self.categories.values().find(|cat| same_name(&cat.full_name(self), name))
+    }
+
+    /// Retrieve the categories directly under a parent category, sorted by name
+    pub fn child_categories(&self, parent_idx: usize) -> Vec<&Category> {
+        let mut children: Vec<&Category> = self
+            .categories
+            .values()
+            .filter(|cat| cat.parent_key() == Some(parent_idx))
+            .collect();
+        children.sort_by(|a, b| a.name().cmp(b.name()).then(a.key().cmp(&b.key())));
+
+        children
+    }
+
+    /// Retrieve the categories directly under `category_idx`, sorted by name, like [`child_categories`][Self::child_categories].
+    pub fn children_of(&self, category_idx: usize) -> Vec<&Category> {
+        self.child_categories(category_idx)
+    }
+
+    /// Retrieve the categories at the top of the hierarchy, sorted by name.
+    ///
+    /// Categories whose parent is missing from the database are included, so that every category can be reached from a root.
+    pub fn root_categories(&self) -> Vec<&Category> {
+        let mut roots: Vec<&Category> = self
+            .categories
+            .values()
+            .filter(|cat| match cat.parent_key() {
+                Some(idx) => !self.categories.contains_key(&idx),
+                None => true,
+            })
+            .collect();
+        roots.sort_by(|a, b| a.name().cmp(b.name()).then(a.key().cmp(&b.key())));
+
+        roots
+    }
+
     /// Retrieve the mutable map of payees
-    fn mut_categories(&mut self) -> &mut HashMap<usize, Category> {
+    fn mut_categories(&mut self) -> &mut BTreeMap<usize, Category> {
         &mut self.categories
     }
 
+    /// Retrieve the favourite (i.e. template or scheduled) transactions in the database
+    pub fn favourites(&self) -> &BTreeMap<usize, Favourite> {
+        &self.favourites
+    }
+
+    /// Retrieve the favourite with this key
+    pub fn favourite(&self, idx: usize) -> Option<&Favourite> {
+        self.favourites.get(&idx)
+    }
+
+    /// Retrieve the mutable map of favourites
+    fn mut_favourites(&mut self) -> &mut BTreeMap<usize, Favourite> {
+        &mut self.favourites
+    }
+
     /// Retrieve the list of transactions
     pub fn transactions(&self) -> &Vec<Transaction> {
         &self.transactions
     }
 
+    /// Remove the transaction at index `i` from the database, e.g. once it has been found to be a duplicate.
+    ///
+    /// The indices of the transactions after it shift down by one.
+    /// This only changes the database in memory, not the HomeBank file it was read from.
+    pub fn remove_transaction(&mut self, i: usize) -> Option<Transaction> {
+        (i < self.transactions.len()).then(|| self.transactions.remove(i))
+    }
+
+    /// Merge the payees with the keys in `sources` into the payee `target`: transactions and favourites are re-pointed to `target`, then the sources are removed.
+    ///
+    /// Returns the number of transactions that were re-pointed, or `None` if `target` isn't a payee.
+    /// Like [`remove_transaction`][HomeBankDb::remove_transaction], this only changes the database in memory.
+    /// Use [`edit_xhb`][crate::edit::edit_references::edit_xhb] with [`Edit::MergePayees`][crate::edit::edit_references::Edit::MergePayees] to change the HomeBank file.
+    /// That edit also re-points the elements that aren't parsed into the database, like assignment rules,
+    /// and [`EditPayee::plan`][crate::edit::edit_payee::EditPayee::plan] creates a missing target by renaming the first source,
+    /// whereas here the target must already be a payee.
+    pub fn merge_payees(&mut self, sources: &[usize], target: usize) -> Option<usize> {
+        if !self.payees.contains_key(&target) {
+            return None;
+        }
+
+        let sources: Vec<usize> = sources.iter().copied().filter(|key| *key != target).collect();
+        let is_source = |payee: Option<usize>| payee.is_some_and(|key| sources.contains(&key));
+        let mut merged = 0;
+
+        for tr in self.transactions.iter_mut().filter(|tr| is_source(*tr.payee())) {
+            tr.set_payee(target);
+            merged += 1;
+        }
+        for fav in self.favourites.values_mut().filter(|fav| is_source(fav.payee())) {
+            fav.set_payee(target);
+        }
+        self.payees.retain(|key, _| !sources.contains(key));
+        self.rebuild_names();
+
+        Some(merged)
+    }
+
     /// Retrieve the mutable transactions
     fn mut_transactions(&mut self) -> &mut Vec<Transaction> {
         &mut self.transactions
     }
 }
 
-impl TryFrom<&Path> for HomeBankDb {
-    type Error = HomeBankDbError;
+/// Check if two names are the same, ignoring case.
+pub(crate) fn same_name(a: &str, b: &str) -> bool {
+    a.to_lowercase() == b.to_lowercase()
+}
 
-    fn try_from(path: &Path) -> Result<Self, Self::Error> {
-        if !path.exists() {
-            return Err(HomeBankDbError::DoesNotExist(path.to_path_buf()));
-        }
+/// Transactions that were skipped while parsing, along with the line they are found on and why they were skipped.
+pub type SkippedTransactions = Vec<(u64, TransactionError)>;
 
-        let xhb_file = match File::open(path) {
-            Ok(f) => f,
-            Err(_) => return Err(HomeBankDbError::CouldNotOpen(path.to_path_buf())),
-        };
+/// How to handle elements of the HomeBank file that cannot be parsed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum ParseMode {
+    /// Abort at the first element that cannot be parsed.
+    Strict,
+
+    /// Skip transactions that cannot be parsed, but abort at any other element.
+    SkipTransactions,
+
+    /// Skip any element that cannot be parsed.
+    Lenient,
+
+    /// Abort at the first element that cannot be parsed, but read files declaring any version of the file format.
+    AnyVersion,
+}
+
+impl HomeBankDb {
+    /// Parse a HomeBank database, skipping any elements that cannot be parsed.
+    ///
+    /// Every skipped element is recorded as a [`ParseWarning`][crate::db::db_warning::ParseWarning] describing what was skipped and why.
+    /// Errors that prevent the file from being read at all are still returned.
+    pub fn try_from_path_lenient(path: &Path) -> Result<(Self, Vec<ParseWarning>), HomeBankDbError> {
+        let (db, warnings, _) = Self::parse(path, ParseMode::Lenient)?;
+
+        Ok((db, warnings))
+    }
+
+    /// Parse a HomeBank database, skipping any transactions that cannot be parsed.
+    ///
+    /// Unlike [`try_from_path_lenient`][HomeBankDb::try_from_path_lenient], any other element that cannot be parsed is still an error.
+    pub fn try_from_path_skip_errors(path: &Path) -> Result<(Self, SkippedTransactions), HomeBankDbError> {
+        let (db, _, skipped) = Self::parse(path, ParseMode::SkipTransactions)?;
 
-        let xhb_buf = BufReader::new(xhb_file);
-        let parser = EventReader::new(xhb_buf);
+        Ok((db, skipped))
+    }
+
+    /// Parse a HomeBank database, even if it declares a version of the file format outside of [`SUPPORTED_VERSIONS`][crate::db::db_version::SUPPORTED_VERSIONS].
+    ///
+    /// Elements and attributes that are new in that version are ignored, see [`parse_report`][HomeBankDb::parse_report].
+    pub fn try_from_path_any_version(path: &Path) -> Result<Self, HomeBankDbError> {
+        let (db, _, _) = Self::parse(path, ParseMode::AnyVersion)?;
+
+        Ok(db)
+    }
+
+    /// Parse a HomeBank database from an XML file, which may be compressed or in a legacy encoding.
+    ///
+    /// In [`ParseMode::Strict`], the first element that cannot be parsed aborts parsing.
+    /// Otherwise, the element is skipped and a warning or skipped transaction is recorded.
+    pub(crate) fn parse(path: &Path, mode: ParseMode) -> Result<(Self, Vec<ParseWarning>, SkippedTransactions), HomeBankDbError> {
+        let xml = read_xml(path)?;
+
+        Self::parse_reader(xml.as_bytes(), mode)
+    }
+
+    /// Parse a HomeBank database from XML read from any source.
+    pub(crate) fn parse_reader<R: Read>(reader: R, mode: ParseMode) -> Result<(Self, Vec<ParseWarning>, SkippedTransactions), HomeBankDbError> {
+        let mut parser = EventReader::new(reader);
+        let lenient = mode == ParseMode::Lenient;
 
         // create the default HomeBankDb
         let mut db = HomeBankDb::empty();
+        let mut warnings: Vec<ParseWarning> = vec![];
+        let mut skipped: SkippedTransactions = vec![];
         // check if the XML is parsing the HomeBank data or not
         let mut in_info = false;
+        // the elements that are currently open, to report where malformed XML was found
+        let mut open_elements: Vec<String> = vec![];
 
         // using xml manual parsing to read in the file
         // not using some type of string parsing serde coercion because we
         // don't know how large the database is going to be
-        for event in parser {
+        loop {
+            let event = parser.next();
+            // rows are counted from 0 by the XML parser
+            let line = parser.position().row + 1;
+
             match event {
                 Ok(XmlEvent::StartElement {
                     name, attributes, ..
                 }) => {
-                    if name.local_name == "homebank" {
+                    let element = name.local_name.as_str();
+                    open_elements.push(element.to_string());
+
+                    if element == "homebank" || in_info {
+                        db.parse_report.record(element, &attributes);
+                    }
+
+                    if element == "homebank" {
                         in_info = true;
-                        if let Ok(ver) = HomeBankDbSchema::try_from(attributes) {
+                        // files that don't declare a version are assumed to be supported
+                        let declares_version = attributes.iter().any(|a| a.name.local_name == "v");
+                        let parsed = HomeBankDbSchema::try_from(attributes);
+                        if let Some(ver) = check_element(parsed, element, line, lenient, &mut warnings)? {
+                            if declares_version && !ver.is_supported() && mode != ParseMode::AnyVersion {
+                                let e = HomeBankDbError::UnsupportedVersion {
+                                    found: ver.version().clone(),
+                                    supported: SUPPORTED_VERSIONS.clone(),
+                                };
+
+                                if !lenient {
+                                    return Err(e);
+                                }
+                                warnings.push(ParseWarning::new(element, line, &e.to_string()));
+                            }
+
                             *db.mut_version() = ver;
                         }
                     } else if in_info {
                         // only add data if we're within the `<homebank></homebank>` tags
-                        match name.local_name.as_str() {
+                        match element {
                             "properties" => {
-                                if let Ok(props) = HomeBankDbProperties::try_from(attributes) {
+                                let parsed = HomeBankDbProperties::try_from(attributes);
+                                if let Some(props) = check_element(parsed, element, line, lenient, &mut warnings)? {
                                     *db.mut_properties() = props;
                                 }
                             }
                             "cur" => {
-                                if let Ok(curr) = Currency::try_from(attributes) {
+                                let parsed = Currency::try_from(attributes);
+                                if let Some(curr) = check_element(parsed, element, line, lenient, &mut warnings)? {
                                     db.mut_currencies().insert(curr.key(), curr);
                                 }
                             }
                             "grp" => {
-                                if let Ok(grp) = Group::try_from(attributes) {
+                                let parsed = Group::try_from(attributes);
+                                if let Some(grp) = check_element(parsed, element, line, lenient, &mut warnings)? {
                                     db.mut_groups().insert(grp.key(), grp);
                                 }
                             }
                             "account" => {
-                                if let Ok(acct) = Account::try_from(attributes) {
+                                let parsed = Account::try_from(attributes);
+                                if let Some(acct) = check_element(parsed, element, line, lenient, &mut warnings)? {
                                     db.mut_accounts().insert(acct.key(), acct);
                                 }
                             }
                             "pay" => {
-                                if let Ok(payee) = Payee::try_from(attributes) {
+                                let parsed = Payee::try_from(attributes);
+                                if let Some(payee) = check_element(parsed, element, line, lenient, &mut warnings)? {
                                     db.mut_payees().insert(payee.key(), payee);
                                 }
                             }
                             "cat" => {
-                                if let Ok(cat) = Category::try_from(attributes) {
+                                let parsed = Category::try_from(attributes);
+                                if let Some(cat) = check_element(parsed, element, line, lenient, &mut warnings)? {
                                     db.mut_categories().insert(cat.key(), cat);
                                 }
                             }
-                            "fav" => {}
-                            "ope" => {
-                                if let Ok(tr) = Transaction::try_from(attributes) {
-                                    db.mut_transactions().push(tr);
+                            "fav" => {
+                                let parsed = Favourite::try_from(attributes);
+                                if let Some(fav) = check_element(parsed, element, line, lenient, &mut warnings)? {
+                                    db.mut_favourites().insert(fav.key(), fav);
                                 }
                             }
+                            "ope" => match parse_transaction(attributes) {
+                                Err(e) if mode == ParseMode::SkipTransactions => skipped.push((line, e)),
+                                parsed => {
+                                    if let Some(tr) = check_element(parsed, element, line, lenient, &mut warnings)? {
+                                        db.mut_transactions().push(tr);
+                                    }
+                                }
+                            },
                             _ => {}
                         }
                     }
                 }
                 Ok(XmlEvent::EndElement { name }) => {
+                    open_elements.pop();
+
                     if name.local_name == "homebank" {
                         in_info = false;
                     }
                 }
+                Ok(XmlEvent::EndDocument) => break,
                 Ok(_) => {}
-                Err(_) => {}
+                Err(e) => {
+                    // the XML parser cannot recover from a malformed document,
+                    // so in lenient mode, keep whatever was parsed up until this point
+                    if lenient {
+                        let element = open_elements.last().map_or("homebank", |el| el.as_str());
+                        warnings.push(ParseWarning::new(element, line, e.msg()));
+                    } else if let Some(err) = malformed_error(&open_elements, line, parser.position().column, &e) {
+                        return Err(err);
+                    }
+                    break;
+                }
             }
         }
 
-        Ok(db)
+        db.rebuild_names();
+
+        Ok((db, warnings, skipped))
+    }
+}
+
+/// Describe an error from the XML parser, given the elements open when it was found, as a [`HomeBankDbError::MalformedElement`].
+///
+/// A document without any element (e.g. only an XML declaration) is an empty database rather than malformed, so it gives `None`.
+pub(crate) fn malformed_error(open_elements: &[String], line: u64, column: u64, e: &xml::reader::Error) -> Option<HomeBankDbError> {
+    // the XML parser only describes this case by its message
+    if open_elements.is_empty() && e.msg().ends_with("no root element found") {
+        return None;
+    }
+
+    Some(HomeBankDbError::MalformedElement {
+        // before any element is opened, the problem is with the whole document
+        element: open_elements.last().map_or("homebank", |el| el.as_str()).to_string(),
+        line,
+        // the parser points to the start of the event it failed to read, with columns counted from 0
+        position: column + 1,
+        message: e.msg().to_string(),
+    })
+}
+
+/// Check the result of parsing a single XML element.
+///
+/// Successfully parsed elements are returned as `Some`.
+/// Elements that fail to parse are an error in strict mode, and are recorded as a warning and skipped in lenient mode.
+fn check_element<T, E: Display>(
+    parsed: Result<T, E>,
+    element: &str,
+    line: u64,
+    lenient: bool,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<Option<T>, HomeBankDbError> {
+    match parsed {
+        Ok(val) => Ok(Some(val)),
+        Err(e) if lenient => {
+            warnings.push(ParseWarning::new(element, line, &e.to_string()));
+            Ok(None)
+        }
+        Err(e) => Err(HomeBankDbError::InvalidElement(
+            element.to_string(),
+            line,
+            e.to_string(),
+        )),
+    }
+}
+
+/// Parse a [`Transaction`] from the attributes of an `<ope>` element.
+///
+/// A transaction in the file must always have an amount, even though a [`Transaction`] defaults to an amount of 0.
+pub(crate) fn parse_transaction(attributes: Vec<OwnedAttribute>) -> Result<Transaction, TransactionError> {
+    if !attributes.iter().any(|a| a.name.local_name == "amount") {
+        return Err(TransactionError::InvalidAmount);
+    }
+
+    Transaction::try_from(attributes)
+}
+
+impl TryFrom<&Path> for HomeBankDb {
+    type Error = HomeBankDbError;
+
+    fn try_from(path: &Path) -> Result<Self, Self::Error> {
+        open_homebank(path)
+    }
+}
+
+impl FromStr for HomeBankDb {
+    type Err = HomeBankDbError;
+
+    /// Parse a HomeBank database from its XML text, like [`from_reader`][HomeBankDb::from_reader].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_reader(s.as_bytes())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::db::db_properties::ScheduleMode;
+    use crate::tests::example_db;
     use super::*;
+    use semver::Version;
 
     #[test]
     fn empty_hdb_props() {
@@ -235,24 +605,186 @@ mod tests {
         assert_eq!(expected, observed);
     }
 
+    #[test]
+    fn from_str_minimal() {
+        let xml = r#"<?xml version="1.0"?>
+<homebank v="1.3999999999999999" d="050504">
+<account key="1" flags="0" pos="1" type="1" curr="1" name="Chequing" initial="100" minimum="0" maximum="0"/>
+<ope date="738134" amount="-12.5" account="1" paymode="1" st="0" wording="Coffee"/>
+</homebank>"#;
+        let db: HomeBankDb = xml.parse().unwrap();
+
+        assert_eq!(Some("Chequing"), db.account(1).map(|acct| acct.name()));
+        assert_eq!(1, db.transactions().len());
+        assert_eq!(-12.5, *db.transactions()[0].total());
+        assert!(HomeBankDb::from_str("<homebank><ope").is_err());
+    }
+
+    #[test]
+    fn merge_payees() {
+        let mut db = example_db();
+
+        assert_eq!(None, db.merge_payees(&[3], 99));
+        assert_eq!(Some(6), db.merge_payees(&[3, 4, 2], 4));
+        assert_eq!(vec!["Employer", "Amazon.com (Prime)"], db.payees().values().map(|p| p.name()).collect::<Vec<_>>());
+        assert_eq!(7, db.transactions().iter().filter(|tr| *tr.payee() == Some(4)).count());
+        assert_eq!(Some("Amazon.com (Prime)"), db.payee_name(4));
+    }
+
+    #[test]
+    fn remove_transaction() {
+        let mut db = example_db();
+        let removed = db.remove_transaction(1).map(|tr| *tr.total());
+
+        assert_eq!(Some(2000.0), removed);
+        assert_eq!(10, db.transactions().len());
+        assert_eq!(&-1200.0, db.transactions()[1].total());
+        assert_eq!(None, db.remove_transaction(10));
+    }
+
+    #[test]
+    fn hbdb_title() {
+        let db = example_db();
+
+        assert_eq!(Some("Household"), db.title());
+        assert_eq!(1, db.properties().base_currency_idx());
+        assert_eq!(None, HomeBankDb::empty().title());
+    }
+
+    #[test]
+    fn lookups_by_key_and_name() {
+        let db = example_db();
+
+        assert_eq!(Some("Landlord"), db.payee(2).map(|payee| payee.name()));
+        assert_eq!(Some(2), db.payee_by_name("LANDLORD").map(|payee| payee.key()));
+        assert_eq!(Some("Savings"), db.account_by_name("savings").map(|acct| acct.name()));
+        assert_eq!(Some("Groceries"), db.category_by_name("food:groceries").map(|cat| cat.name()));
+        assert_eq!(Some("CAD"), db.currency(1).map(|curr| curr.iso()));
+        assert!(db.payee_by_name("Land").is_none());
+        assert!(db.category(99).is_none());
+    }
+
+    #[test]
+    fn collections_in_key_order() {
+        // parsing the same file twice always lists everything in the same order
+        let first = example_db();
+        let second = example_db();
+        let payee_names = |db: &HomeBankDb| -> Vec<String> { db.payees().values().map(|p| p.name().to_string()).collect() };
+
+        assert_eq!(payee_names(&first), payee_names(&second));
+        assert_eq!(vec!["Employer", "Landlord", "Grocer", "Amazon.com (Prime)"], payee_names(&first));
+        assert_eq!(vec![&1, &2, &3], first.accounts().keys().collect::<Vec<&usize>>());
+    }
+
     #[test]
     fn empty_hbdb_is_expected() {
         let observed = HomeBankDb::empty();
         let expected = HomeBankDb {
             homebank_version: HomeBankDbSchema::empty(),
             properties: HomeBankDbProperties::empty(),
-            currencies: HashMap::new(),
-            groups: HashMap::new(),
-            accounts: HashMap::new(),
-            payees: HashMap::new(),
-            categories: HashMap::new(),
-            // favourites: vec![],
+            currencies: BTreeMap::new(),
+            groups: BTreeMap::new(),
+            accounts: BTreeMap::new(),
+            payees: BTreeMap::new(),
+            categories: BTreeMap::new(),
+            favourites: BTreeMap::new(),
             transactions: vec![],
+            parse_report: ParseReport::default(),
+            names: NameIndex::default(),
         };
 
         assert_eq!(expected, observed);
     }
 
+    /// A database with a three-level category hierarchy, and a category whose parent is missing.
+    fn category_tree_db() -> HomeBankDb {
+        let xml = r#"<?xml version="1.0"?>
+<homebank v="1.3999999999999999" d="050504">
+<cat key="1" flags="0" name="Vehicle"/>
+<cat key="2" parent="1" flags="1" name="Insurance"/>
+<cat key="3" parent="1" flags="1" name="Fuel"/>
+<cat key="4" parent="3" flags="1" name="Diesel"/>
+<cat key="5" flags="0" name="Food"/>
+<cat key="6" parent="9" flags="1" name="Orphan"/>
+</homebank>
+"#;
+
+        HomeBankDb::from_reader(xml.as_bytes()).unwrap()
+    }
+
+    #[track_caller]
+    fn check_convert_to_base(amount: f32, currency_idx: usize, expected: Option<f32>) {
+        let db = HomeBankDb::try_from(std::path::Path::new("tests/currencies.xhb")).unwrap();
+
+        assert_eq!(expected, db.convert_to_base(amount, currency_idx));
+    }
+
+    #[test]
+    fn base_currency() {
+        let db = HomeBankDb::try_from(std::path::Path::new("tests/currencies.xhb")).unwrap();
+
+        assert_eq!(Some("CAD"), db.base_currency().map(|curr| curr.iso()));
+    }
+
+    #[test]
+    fn convert_base_to_base() {
+        check_convert_to_base(-20.0, 1, Some(-20.0));
+    }
+
+    #[test]
+    fn convert_with_rate() {
+        check_convert_to_base(-10.0, 2, Some(-15.0));
+    }
+
+    #[test]
+    fn convert_without_rate() {
+        check_convert_to_base(-5.0, 3, None);
+        check_convert_to_base(-5.0, 9, None);
+    }
+
+    #[track_caller]
+    fn check_category_names(expected: &[&str], observed: Vec<&Category>) {
+        let names: Vec<&str> = observed.iter().map(|cat| cat.name()).collect();
+
+        assert_eq!(expected.to_vec(), names);
+    }
+
+    #[test]
+    fn root_categories() {
+        check_category_names(&["Food", "Orphan", "Vehicle"], category_tree_db().root_categories());
+    }
+
+    #[test]
+    fn child_categories() {
+        let db = category_tree_db();
+
+        check_category_names(&["Fuel", "Insurance"], db.child_categories(1));
+        check_category_names(&["Diesel"], db.child_categories(3));
+        check_category_names(&[], db.child_categories(4));
+        check_category_names(&[], db.child_categories(42));
+    }
+
+    #[test]
+    fn category_predicates() {
+        let db = category_tree_db();
+        let observed: Vec<(&str, bool, bool, bool)> = db
+            .categories()
+            .values()
+            .map(|cat| (cat.name(), cat.has_parent(), cat.is_root(), cat.is_leaf(&db)))
+            .collect();
+        let expected = vec![
+            ("Vehicle", false, true, false),
+            ("Insurance", true, false, true),
+            ("Fuel", true, false, false),
+            ("Diesel", true, false, true),
+            ("Food", false, true, true),
+            ("Orphan", true, false, true),
+        ];
+
+        assert_eq!(expected, observed);
+        check_category_names(&["Fuel", "Insurance"], db.children_of(1));
+    }
+
     #[test]
     fn parse_empty_db() {
         let path = Path::new("tests/empty.xhb");
@@ -262,6 +794,171 @@ mod tests {
         assert_eq!(Ok(expected), observed);
     }
 
+    #[test]
+    fn parse_empty_payee_strict() {
+        let path = Path::new("tests/empty_payee.xhb");
+        let observed = HomeBankDb::try_from(path);
+        let expected = HomeBankDbError::InvalidElement(
+            "ope".to_string(),
+            9,
+            "Missing payee from transaction.".to_string(),
+        );
+
+        assert_eq!(Err(expected), observed);
+    }
+
+    #[test]
+    fn parse_empty_payee_lenient() {
+        let path = Path::new("tests/empty_payee.xhb");
+        let (db, warnings) = HomeBankDb::try_from_path_lenient(path).unwrap();
+        let expected_warnings = vec![ParseWarning::new(
+            "ope",
+            9,
+            "Missing payee from transaction.",
+        )];
+
+        assert_eq!(expected_warnings, warnings);
+        assert_eq!(1, db.transactions().len());
+        assert_eq!(1, db.payees().len());
+    }
+
+    #[test]
+    fn parse_empty_db_lenient() {
+        let path = Path::new("tests/empty.xhb");
+        let observed = HomeBankDb::try_from_path_lenient(path);
+        let expected = (
+            HomeBankDb::empty(),
+            vec![ParseWarning::new(
+                "homebank",
+                2,
+                "Unexpected end of stream: no root element found",
+            )],
+        );
+
+        assert_eq!(Ok(expected), observed);
+    }
+
+    #[test]
+    fn parse_truncated_db() {
+        let input = r#"<?xml version="1.0"?>
+<homebank v="1.3999999999999999" d="050504">
+<properties title="Truncated" curr="1"/>
+<ope date="738134" amount="-1200" account="1" paymode="4" st="2" payee="2" wording="Decemb"#;
+        let observed = HomeBankDb::parse_reader(input.as_bytes(), ParseMode::Strict);
+
+        match observed {
+            Err(HomeBankDbError::MalformedElement { element, line, .. }) => {
+                assert_eq!("homebank", element);
+                assert_eq!(4, line);
+            }
+            _ => panic!("Expected a malformed element error, found `{:?}`", observed),
+        }
+    }
+
+    #[test]
+    fn parse_truncated_element() {
+        let input = r#"<?xml version="1.0"?>
+<homebank v="1.3999999999999999" d="050504">
+<properties title="Truncated" curr="1">
+<cur key="1" iso="CAD" name="Canadian Dollar"
+"#;
+        let observed = HomeBankDb::parse_reader(input.as_bytes(), ParseMode::Strict).unwrap_err();
+
+        assert!(
+            observed.to_string().starts_with("Malformed XML in `<properties>` element at line 4, column 1."),
+            "{observed}"
+        );
+    }
+
+    #[test]
+    fn parse_malformed_before_root() {
+        let input = "<?xml version=\"1.0\"?>\n</homebank>\n";
+        let observed = HomeBankDb::parse_reader(input.as_bytes(), ParseMode::Strict);
+
+        assert!(matches!(observed, Err(HomeBankDbError::MalformedElement { ref element, .. }) if element == "homebank"), "{observed:?}");
+        assert!(HomeBankDb::parse_reader(input.as_bytes(), ParseMode::Lenient).is_ok());
+    }
+
+    #[test]
+    fn parse_truncated_db_lenient() {
+        let input = r#"<?xml version="1.0"?>
+<homebank v="1.3999999999999999" d="050504">
+<pay key="1" name="Employer"/>
+<pay key="2" name="Landl"#;
+        let (db, warnings, _) = HomeBankDb::parse_reader(input.as_bytes(), ParseMode::Lenient).unwrap();
+
+        assert_eq!(1, db.payees().len());
+        assert_eq!(1, warnings.len());
+    }
+
+    #[test]
+    fn parse_missing_amount_strict() {
+        let path = Path::new("tests/missing_amount.xhb");
+        let expected = HomeBankDbError::InvalidElement(
+            "ope".to_string(),
+            10,
+            "Missing transaction amount.".to_string(),
+        );
+
+        assert_eq!(Err(expected), HomeBankDb::try_from(path));
+    }
+
+    #[test]
+    fn parse_missing_amount_skip_errors() {
+        let path = Path::new("tests/missing_amount.xhb");
+        let (db, skipped) = HomeBankDb::try_from_path_skip_errors(path).unwrap();
+
+        assert_eq!(vec![(10, TransactionError::InvalidAmount)], skipped);
+        assert_eq!(3, db.transactions().len());
+    }
+
+    #[test]
+    fn skip_errors_still_fails_on_other_elements() {
+        let path = Path::new("tests/empty_payee.xhb");
+
+        // the empty payee is in a transaction, so it is skipped
+        assert_eq!(1, HomeBankDb::try_from_path_skip_errors(path).unwrap().1.len());
+
+        let input = r#"<?xml version="1.0"?>
+<homebank v="1.3999999999999999" d="050504">
+<cur key="one" iso="CAD"/>
+</homebank>"#;
+
+        assert!(HomeBankDb::parse_reader(input.as_bytes(), ParseMode::SkipTransactions).is_err());
+    }
+
+    #[test]
+    fn parse_future_version_strict() {
+        let path = Path::new("tests/future_version.xhb");
+        let expected = || HomeBankDbError::UnsupportedVersion {
+            found: Version::new(2, 0, 0),
+            supported: SUPPORTED_VERSIONS.clone(),
+        };
+
+        assert_eq!(Err(expected()), HomeBankDb::try_from(path));
+        assert_eq!(Err(expected()), HomeBankDb::try_from_path_skip_errors(path).map(|(db, _)| db));
+    }
+
+    #[test]
+    fn parse_future_version_lenient() {
+        let (db, warnings) = HomeBankDb::try_from_path_lenient(Path::new("tests/future_version.xhb")).unwrap();
+
+        assert_eq!(
+            vec!["Unsupported XHB file version 2.0.0, only versions >=1.0.0, <2.0.0 can be read."],
+            warnings.iter().map(|w| w.reason()).collect::<Vec<&str>>()
+        );
+        assert_eq!(1, db.transactions().len());
+    }
+
+    #[test]
+    fn parse_future_version_any_version() {
+        let db = HomeBankDb::try_from_path_any_version(Path::new("tests/future_version.xhb")).unwrap();
+
+        assert_eq!(&Version::new(2, 0, 0), db.version().version());
+        assert_eq!(&Some(Version::new(6, 1, 2)), db.version().app_version());
+        assert_eq!(1, db.transactions().len());
+    }
+
     // #[test]
     // fn parse_minimal_db() {
     //     let path = Path::new("tests/minimal.xhb");
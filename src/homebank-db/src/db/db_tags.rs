@@ -0,0 +1,192 @@
+//! Look up the transactions in the HomeBank database by their tags, and summarize how each tag is used.
+
+use super::HomeBankDb;
+use crate::{transaction::tag_words, Transaction};
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// How a tag is used by the transactions in the HomeBank database.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TagStats {
+    /// Number of transactions with the tag.
+    count: usize,
+
+    /// Sum of the amounts of the transactions with the tag.
+    total: f64,
+
+    /// Every way the tag is written, e.g. `Holiday` and `holiday` when case is ignored.
+    spellings: BTreeSet<String>,
+}
+
+impl TagStats {
+    /// Retrieve the number of transactions with the tag
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Retrieve the sum of the amounts of the transactions with the tag
+    pub fn total(&self) -> f64 {
+        self.total
+    }
+
+    /// Retrieve every way the tag is written, in sorted order
+    pub fn spellings(&self) -> &BTreeSet<String> {
+        &self.spellings
+    }
+}
+
+impl HomeBankDb {
+    /// Retrieve the transactions with the tag `tag`, ignoring case.
+    pub fn find_transactions_by_tag(&self, tag: &str) -> Vec<&Transaction> {
+        let tag = tag.to_lowercase();
+
+        self.transactions()
+            .iter()
+            .filter(|tr| tr.normalized_tags().contains(&tag))
+            .collect()
+    }
+
+    /// Retrieve the transactions with at least one tag that matches `re`.
+    ///
+    /// Like [`find_transactions_by_tag`][HomeBankDb::find_transactions_by_tag], each word of a tag is matched on its own,
+    /// so `^food$` matches a transaction tagged `food,restaurant`.
+    pub fn find_transactions_by_tag_regex(&self, re: &Regex) -> Vec<&Transaction> {
+        self.transactions()
+            .iter()
+            .filter(|tr| tr.tags().iter().flatten().flat_map(|tag| tag_words(tag)).any(|word| re.is_match(word)))
+            .collect()
+    }
+
+    /// Retrieve every tag used by a transaction, lowercased and sorted, e.g. to offer them as completions.
+    pub fn all_tags(&self) -> Vec<String> {
+        let tags: BTreeSet<String> = self
+            .transactions()
+            .iter()
+            .flat_map(|tr| tr.normalized_tags())
+            .collect();
+
+        tags.into_iter().collect()
+    }
+
+    /// Count the transactions with each tag, and total their amounts, by tag.
+    ///
+    /// Tags are split on commas and whitespace like [`Transaction::normalized_tags`], but keep their case,
+    /// and with `case_insensitive`, tags that differ only by case are combined under their lowercase name.
+    /// Every transaction counts, including voided ones and transfers, so that no use of a tag is hidden,
+    /// but like the other reports, voided transactions are left out of the total.
+    pub fn tags(&self, case_insensitive: bool) -> BTreeMap<String, TagStats> {
+        let mut tags: BTreeMap<String, TagStats> = BTreeMap::new();
+
+        for tr in self.transactions() {
+            // a tag written more than once on a transaction is still counted once
+            let mut counted: BTreeSet<String> = BTreeSet::new();
+
+            for word in tr.tags().iter().flatten().flat_map(|tag| tag_words(tag)) {
+                let name = match case_insensitive {
+                    true => word.to_lowercase(),
+                    false => word.to_string(),
+                };
+                let stats = tags.entry(name.clone()).or_default();
+
+                if counted.insert(name) {
+                    stats.count += 1;
+                    if !tr.is_void() {
+                        stats.total += f64::from(*tr.total());
+                    }
+                }
+                stats.spellings.insert(word.to_string());
+            }
+        }
+
+        tags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    fn tagged_db() -> HomeBankDb {
+        let xml = r#"<homebank v="1.4" d="050504">
+<ope date="738170" amount="-150.25" account="1" paymode="6" st="1" wording="Groceries" tags="food weekly"/>
+<ope date="738198" amount="-60" account="1" paymode="1" st="0" wording="Dinner" tags="Food restaurant"/>
+<ope date="738199" amount="-5" account="1" paymode="1" st="0" wording="Untagged"/>
+</homebank>"#;
+
+        HomeBankDb::from_reader(xml.as_bytes()).unwrap()
+    }
+
+    #[track_caller]
+    fn check_memos(expected: &[&str], observed: Vec<&Transaction>) {
+        let memos: Vec<&str> = observed.iter().map(|tr| tr.memo().as_deref().unwrap_or_default()).collect();
+
+        assert_eq!(expected, memos);
+    }
+
+    #[test]
+    fn by_tag_ignores_case() {
+        let db = tagged_db();
+
+        check_memos(&["Groceries", "Dinner"], db.find_transactions_by_tag("FOOD"));
+        check_memos(&["Dinner"], db.find_transactions_by_tag("restaurant"));
+        check_memos(&[], db.find_transactions_by_tag("foo"));
+    }
+
+    #[test]
+    fn by_tag_regex() {
+        let db = tagged_db();
+
+        check_memos(&["Groceries", "Dinner"], db.find_transactions_by_tag_regex(&Regex::new("^(weekly|restaurant)$").unwrap()));
+        check_memos(&[], db.find_transactions_by_tag_regex(&Regex::new("^foo$").unwrap()));
+    }
+
+    #[test]
+    fn by_tag_regex_matches_each_word() {
+        let db = example_db();
+
+        assert_eq!(2, db.find_transactions_by_tag_regex(&Regex::new("^food$").unwrap()).len());
+        assert_eq!(1, db.find_transactions_by_tag_regex(&Regex::new("^restaurant$").unwrap()).len());
+    }
+
+    #[test]
+    fn tags_by_case() {
+        let db = tagged_db();
+        let observed = db.tags(false);
+
+        assert_eq!(vec!["Food", "food", "restaurant", "weekly"], observed.keys().collect::<Vec<_>>());
+        assert_eq!((1, -60.0), (observed["Food"].count(), observed["Food"].total()));
+
+        let food = &db.tags(true)["food"];
+        assert_eq!((2, -210.25), (food.count(), food.total()));
+        assert_eq!(vec!["Food", "food"], food.spellings().iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn tags_counted_once_per_transaction() {
+        let xml = r#"<homebank v="1.4" d="050504">
+<ope date="738170" amount="-10" account="1" paymode="6" st="1" tags="food Food,food"/>
+</homebank>"#;
+        let db = HomeBankDb::from_reader(xml.as_bytes()).unwrap();
+
+        assert_eq!(vec![("food", 1)], db.tags(false).iter().map(|(name, stats)| (name.as_str(), stats.count())).collect::<Vec<_>>());
+        assert_eq!(1, db.tags(true)["food"].count());
+    }
+
+    #[test]
+    fn void_counted_but_not_totalled() {
+        let xml = r#"<homebank v="1.4" d="050504">
+<ope date="738170" amount="-10" account="1" paymode="6" st="1" tags="food"/>
+<ope date="738171" amount="-99" account="1" paymode="6" st="4" tags="food"/>
+</homebank>"#;
+        let food = &HomeBankDb::from_reader(xml.as_bytes()).unwrap().tags(false)["food"];
+
+        assert_eq!((2, -10.0), (food.count(), food.total()));
+    }
+
+    #[test]
+    fn all_tags_sorted_and_deduplicated() {
+        assert_eq!(vec!["food", "restaurant", "weekly"], tagged_db().all_tags());
+        assert!(HomeBankDb::from_reader("<homebank/>".as_bytes()).unwrap().all_tags().is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+//! Pair up the two halves of each transfer between accounts in the HomeBank database.
+
+use super::HomeBankDb;
+use crate::Transaction;
+use std::collections::{HashMap, HashSet};
+
+impl HomeBankDb {
+    /// Find the index of the other half of the transfer at index `i`, if it is a transfer and its counterpart exists.
+    ///
+    /// The counterpart shares the transfer key (`kxfer`) and is recorded in the destination account.
+    pub fn transfer_partner(&self, i: usize) -> Option<usize> {
+        let tr = self.transactions().get(i)?;
+        let (key, dst) = (tr.transfer_key()?, tr.transfer_destination()?);
+
+        self.transactions()
+            .iter()
+            .enumerate()
+            .find(|(j, other)| *j != i && other.transfer_key() == Some(key) && other.account() == *dst)
+            .map(|(j, _)| j)
+    }
+
+    /// Find the index of the other half of every transfer whose counterpart exists, by the index of each half.
+    ///
+    /// This groups the transactions by transfer key once,
+    /// instead of searching every transaction for each counterpart as [`transfer_partner`][Self::transfer_partner] does.
+    pub fn transfer_partners(&self) -> HashMap<usize, usize> {
+        let mut by_key: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (i, tr) in self.transactions().iter().enumerate() {
+            if let Some(key) = tr.transfer_key() {
+                by_key.entry(*key).or_default().push(i);
+            }
+        }
+
+        self.transactions()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, tr)| {
+                let (key, dst) = (tr.transfer_key()?, tr.transfer_destination()?);
+                let partner = by_key[key]
+                    .iter()
+                    .find(|&&j| j != i && self.transactions()[j].account() == *dst)?;
+
+                Some((i, *partner))
+            })
+            .collect()
+    }
+
+    /// Retrieve each transfer as a pair of its two halves, in the order the first half appears in the database.
+    ///
+    /// Transfers whose counterpart is missing are left out.
+    pub fn transfers(&self) -> Vec<(&Transaction, &Transaction)> {
+        let partners = self.transfer_partners();
+        let mut paired: HashSet<usize> = HashSet::new();
+        let mut transfers = vec![];
+
+        for (i, tr) in self.transactions().iter().enumerate() {
+            if paired.contains(&i) {
+                continue;
+            }
+
+            if let Some(&j) = partners.get(&i) {
+                paired.insert(j);
+                transfers.push((tr, &self.transactions()[j]));
+            }
+        }
+
+        transfers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A transfer whose counterpart is missing, and a complete one.
+    const INPUT: &str = r#"<homebank v="1.4" d="050504">
+<account key="1" flags="0" pos="1" type="1" curr="1" name="Chequing" initial="0"/>
+<account key="2" flags="0" pos="2" type="1" curr="1" name="Savings" initial="0"/>
+<ope date="738170" amount="-75" account="1" paymode="5" dst_account="2" kxfer="1"/>
+<ope date="738175" amount="-100" account="1" paymode="5" dst_account="2" kxfer="2"/>
+<ope date="738176" amount="-12" account="1" paymode="1"/>
+<ope date="738175" amount="100" account="2" paymode="5" dst_account="1" kxfer="2"/>
+</homebank>"#;
+
+    #[test]
+    fn pair_transfers() {
+        let db = HomeBankDb::from_reader(INPUT.as_bytes()).unwrap();
+        let observed: Vec<(f32, f32)> = db.transfers().iter().map(|(a, b)| (*a.total(), *b.total())).collect();
+
+        assert_eq!(vec![(-100.0, 100.0)], observed);
+    }
+
+    #[test]
+    fn partner_of_each_half() {
+        let db = HomeBankDb::from_reader(INPUT.as_bytes()).unwrap();
+
+        assert_eq!(Some(3), db.transfer_partner(1));
+        assert_eq!(Some(1), db.transfer_partner(3));
+        assert_eq!(None, db.transfer_partner(0));
+        assert_eq!(None, db.transfer_partner(2));
+        assert_eq!(None, db.transfer_partner(10));
+    }
+
+    #[test]
+    fn partners_match_partner_of_each_half() {
+        let db = HomeBankDb::from_reader(INPUT.as_bytes()).unwrap();
+        let partners = db.transfer_partners();
+
+        assert_eq!(HashMap::from([(1, 3), (3, 1)]), partners);
+        for i in 0..db.transactions().len() {
+            assert_eq!(db.transfer_partner(i), partners.get(&i).copied());
+        }
+    }
+}
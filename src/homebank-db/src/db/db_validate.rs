@@ -0,0 +1,374 @@
+//! Check the HomeBank database for references that are missing or inconsistent.
+
+use super::HomeBankDb;
+use crate::Transaction;
+use std::fmt;
+
+/// Largest difference between the sum of split amounts and the total amount of a transaction that is still considered equal.
+const SPLIT_EPSILON: f32 = 0.005;
+
+/// How serious a [`ValidationIssue`] is.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+pub enum Severity {
+    /// Something that looks wrong, but doesn't change any amounts.
+    Warning,
+
+    /// Something that makes the database inconsistent.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// The kind of problem found by a [`ValidationIssue`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum IssueCode {
+    /// A transaction (or transfer destination) refers to an account that doesn't exist.
+    MissingAccount,
+
+    /// A transaction refers to a payee that doesn't exist.
+    MissingPayee,
+
+    /// A transaction refers to a category that doesn't exist.
+    MissingCategory,
+
+    /// The amounts of a split transaction don't add up to its total.
+    SplitSumMismatch,
+
+    /// A category refers to a parent category that doesn't exist.
+    MissingParentCategory,
+
+    /// A transfer has no matching transaction in the other account.
+    MissingTransferPartner,
+
+    /// An account refers to a currency that doesn't exist.
+    MissingCurrency,
+}
+
+impl IssueCode {
+    /// Retrieve the stable, machine-readable code for this kind of problem
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IssueCode::MissingAccount => "missing-account",
+            IssueCode::MissingPayee => "missing-payee",
+            IssueCode::MissingCategory => "missing-category",
+            IssueCode::SplitSumMismatch => "split-sum-mismatch",
+            IssueCode::MissingParentCategory => "missing-parent-category",
+            IssueCode::MissingTransferPartner => "missing-transfer-partner",
+            IssueCode::MissingCurrency => "missing-currency",
+        }
+    }
+
+    /// Retrieve how serious this kind of problem is
+    pub fn severity(&self) -> Severity {
+        match self {
+            // a missing payee only affects how the transaction is displayed
+            IssueCode::MissingPayee => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for IssueCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A reference from a transaction to something that doesn't exist in the [`HomeBankDb`], with the missing key.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OrphanKind {
+    /// The transaction is in an account that doesn't exist.
+    MissingAccount(usize),
+
+    /// The transaction has a payee that doesn't exist.
+    MissingPayee(usize),
+
+    /// The transaction has a category that doesn't exist.
+    MissingCategory(usize),
+
+    /// One of the splits of the transaction has a category that doesn't exist.
+    MissingSplitCategory(usize),
+}
+
+impl OrphanKind {
+    /// Retrieve the kind of [`ValidationIssue`] reported for the missing reference
+    pub fn code(&self) -> IssueCode {
+        match self {
+            OrphanKind::MissingAccount(_) => IssueCode::MissingAccount,
+            OrphanKind::MissingPayee(_) => IssueCode::MissingPayee,
+            OrphanKind::MissingCategory(_) | OrphanKind::MissingSplitCategory(_) => IssueCode::MissingCategory,
+        }
+    }
+
+    /// Describe the missing reference of the transaction identified by `id`
+    fn message(&self, id: &str) -> String {
+        match self {
+            OrphanKind::MissingAccount(key) => format!("{id} is in account {key}, which does not exist."),
+            OrphanKind::MissingPayee(key) => format!("{id} has payee {key}, which does not exist."),
+            OrphanKind::MissingCategory(key) => format!("{id} has category {key}, which does not exist."),
+            OrphanKind::MissingSplitCategory(key) => format!("{id} has split category {key}, which does not exist."),
+        }
+    }
+}
+
+/// Find the account, payee, and categories of `tr` that don't exist in `db`.
+fn transaction_orphans(db: &HomeBankDb, tr: &Transaction) -> Vec<OrphanKind> {
+    let mut orphans = vec![];
+
+    if !db.accounts().contains_key(&tr.account()) {
+        orphans.push(OrphanKind::MissingAccount(tr.account()));
+    }
+
+    if let Some(payee) = tr.payee() {
+        if !db.payees().contains_key(payee) {
+            orphans.push(OrphanKind::MissingPayee(*payee));
+        }
+    }
+
+    for &cat in tr.categories().into_iter().flatten() {
+        if !db.categories().contains_key(&cat) {
+            orphans.push(match tr.is_split() {
+                true => OrphanKind::MissingSplitCategory(cat),
+                false => OrphanKind::MissingCategory(cat),
+            });
+        }
+    }
+
+    orphans
+}
+
+/// A problem found when validating the [`HomeBankDb`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct ValidationIssue {
+    /// Kind of problem.
+    code: IssueCode,
+
+    /// Human-readable description of the problem.
+    message: String,
+}
+
+impl ValidationIssue {
+    /// Create a new validation issue.
+    pub fn new(code: IssueCode, message: &str) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+
+    /// Retrieve the kind of problem.
+    pub fn code(&self) -> IssueCode {
+        self.code
+    }
+
+    /// Retrieve how serious the problem is.
+    pub fn severity(&self) -> Severity {
+        self.code.severity()
+    }
+
+    /// Check if the problem makes the database inconsistent.
+    pub fn is_error(&self) -> bool {
+        self.severity() == Severity::Error
+    }
+
+    /// Retrieve the description of the problem.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]: {}", self.severity(), self.code, self.message)
+    }
+}
+
+/// Check the [`HomeBankDb`] for references that are missing or inconsistent.
+pub fn validate(db: &HomeBankDb) -> Vec<ValidationIssue> {
+    let mut issues = vec![];
+
+    // sort the categories and accounts by their keys, so that issues are reported in a stable order
+    let mut categories: Vec<_> = db.categories().iter().collect();
+    categories.sort_by_key(|(key, _)| **key);
+    for (key, cat) in categories {
+        if let Some(parent) = cat.parent_key() {
+            if !db.categories().contains_key(&parent) {
+                issues.push(ValidationIssue::new(
+                    IssueCode::MissingParentCategory,
+                    &format!(
+                        "Category {key} (`{}`) has parent category {parent}, which does not exist.",
+                        cat.name()
+                    ),
+                ));
+            }
+        }
+    }
+
+    let mut accounts: Vec<_> = db.accounts().iter().collect();
+    accounts.sort_by_key(|(key, _)| **key);
+    for (key, acct) in accounts {
+        if !db.currencies().contains_key(&acct.currency()) {
+            issues.push(ValidationIssue::new(
+                IssueCode::MissingCurrency,
+                &format!(
+                    "Account {key} (`{}`) uses currency {}, which does not exist.",
+                    acct.name(),
+                    acct.currency()
+                ),
+            ));
+        }
+    }
+
+    let partners = db.transfer_partners();
+    for (i, tr) in db.transactions().iter().enumerate() {
+        // identify transactions by their position in the file, since they have no key
+        let id = format!("Transaction #{} ({})", i + 1, tr.date());
+
+        for orphan in transaction_orphans(db, tr) {
+            issues.push(ValidationIssue::new(orphan.code(), &orphan.message(&id)));
+        }
+
+        if tr.is_split() {
+            let split_sum: f32 = tr.amounts().into_iter().sum();
+
+            if (split_sum - tr.total()).abs() > SPLIT_EPSILON {
+                issues.push(ValidationIssue::new(
+                    IssueCode::SplitSumMismatch,
+                    &format!(
+                        "{id} has a total of {:.2}, but its splits add up to {split_sum:.2}.",
+                        tr.total()
+                    ),
+                ));
+            }
+        }
+
+        if let (Some(dst), Some(xfer_key)) = (tr.transfer_destination(), tr.transfer_key()) {
+            if !db.accounts().contains_key(dst) {
+                issues.push(ValidationIssue::new(
+                    IssueCode::MissingAccount,
+                    &format!("{id} is a transfer to account {dst}, which does not exist."),
+                ));
+            }
+
+            if !partners.contains_key(&i) {
+                issues.push(ValidationIssue::new(
+                    IssueCode::MissingTransferPartner,
+                    &format!(
+                        "{id} is a transfer with key {xfer_key}, but account {dst} has no matching transaction."
+                    ),
+                ));
+            }
+        }
+    }
+
+    issues
+}
+
+impl HomeBankDb {
+    /// Check the database for references that are missing or inconsistent.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        validate(self)
+    }
+
+    /// Find the transactions that refer to an account, payee, or category that doesn't exist.
+    ///
+    /// Each missing reference is returned with the index of its transaction, so a transaction can appear more than once.
+    pub fn orphaned_transactions(&self) -> Vec<(usize, OrphanKind)> {
+        self.transactions()
+            .iter()
+            .enumerate()
+            .flat_map(|(i, tr)| transaction_orphans(self, tr).into_iter().map(move |orphan| (i, orphan)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[track_caller]
+    fn check_codes(path: &str, expected: Vec<IssueCode>) {
+        let db = HomeBankDb::try_from(Path::new(path)).unwrap();
+        let observed: Vec<IssueCode> = db.validate().iter().map(|issue| issue.code()).collect();
+
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    fn consistent_db() {
+        check_codes("tests/transactions.xhb", vec![]);
+    }
+
+    #[test]
+    fn inconsistent_db() {
+        let expected = vec![
+            IssueCode::MissingParentCategory,
+            IssueCode::MissingCurrency,
+            IssueCode::MissingAccount,
+            IssueCode::MissingPayee,
+            IssueCode::MissingCategory,
+            IssueCode::SplitSumMismatch,
+            IssueCode::MissingTransferPartner,
+        ];
+
+        check_codes("tests/inconsistent.xhb", expected);
+    }
+
+    #[test]
+    fn split_sum_message() {
+        let db = HomeBankDb::try_from(Path::new("tests/inconsistent.xhb")).unwrap();
+        let issue = db
+            .validate()
+            .into_iter()
+            .find(|issue| issue.code() == IssueCode::SplitSumMismatch)
+            .unwrap();
+        let expected = "error [split-sum-mismatch]: Transaction #5 (2022-01-09) has a total of -100.00, but its splits add up to -110.00.";
+
+        assert_eq!(expected, issue.to_string());
+    }
+
+    #[test]
+    fn orphans_of_inconsistent_db() {
+        let db = HomeBankDb::try_from(Path::new("tests/inconsistent.xhb")).unwrap();
+        let expected = vec![
+            (1, OrphanKind::MissingAccount(5)),
+            (2, OrphanKind::MissingPayee(7)),
+            (3, OrphanKind::MissingCategory(8)),
+        ];
+
+        assert_eq!(expected, db.orphaned_transactions());
+    }
+
+    #[test]
+    fn orphaned_split_category() {
+        let xml = r#"<homebank v="1.4" d="050504">
+<cur key="1" flags="0" iso="CAD" name="Canadian dollar" symb="$" syprf="1" dchar="." gchar="," frac="2" rate="0" mdate="0"/>
+<account key="1" flags="0" pos="1" type="1" curr="1" name="Chequing" initial="0"/>
+<cat key="1" flags="0" name="Food"/>
+<ope date="738134" amount="-30" account="1" paymode="1" scat="1||4" samt="-10||-20"/>
+</homebank>"#;
+        let db = HomeBankDb::from_reader(xml.as_bytes()).unwrap();
+
+        assert_eq!(vec![(0, OrphanKind::MissingSplitCategory(4))], db.orphaned_transactions());
+        assert_eq!(
+            "error [missing-category]: Transaction #1 (2021-12-10) has split category 4, which does not exist.",
+            db.validate()[0].to_string()
+        );
+    }
+
+    #[test]
+    fn missing_payee_is_warning() {
+        let issue = ValidationIssue::new(IssueCode::MissingPayee, "");
+
+        assert!(!issue.is_error());
+        assert_eq!(Severity::Warning, issue.severity());
+    }
+}
@@ -3,15 +3,23 @@
 use super::HomeBankDbError;
 use crate::transaction::{julian_date_from_u32, transaction_date::unclamped_julian_date_from_u32};
 use chrono::NaiveDate;
-use semver::Version;
+use lazy_static::lazy_static;
+use semver::{Version, VersionReq};
 use std::str::FromStr;
 use xml::attribute::OwnedAttribute;
 
+lazy_static! {
+    /// Versions of HomeBank's file format that can be parsed.
+    /// Newer minor versions only add elements and attributes, which are ignored, so only a new major version is refused.
+    pub static ref SUPPORTED_VERSIONS: VersionReq = VersionReq::parse(">=1.0.0, <2.0.0").unwrap();
+}
+
 /// Version information for the HomeBank database.
 #[derive(Debug, PartialEq)]
 pub struct HomeBankDbSchema {
     version: Version,
     date: NaiveDate,
+    app_version: Option<Version>,
 }
 
 impl HomeBankDbSchema {
@@ -20,8 +28,29 @@ impl HomeBankDbSchema {
         Self {
             version: Version::new(0, 0, 1),
             date: julian_date_from_u32(50504),
+            app_version: None,
         }
     }
+
+    /// Retrieve the version of HomeBank's file format
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
+    /// Retrieve the date of HomeBank's file format
+    pub fn date(&self) -> &NaiveDate {
+        &self.date
+    }
+
+    /// Retrieve the version of HomeBank that saved the file, e.g. `5.8.5` from `d="050805"`, if the `d` attribute has that form
+    pub fn app_version(&self) -> &Option<Version> {
+        &self.app_version
+    }
+
+    /// Check whether this version of HomeBank's file format can be parsed
+    pub fn is_supported(&self) -> bool {
+        SUPPORTED_VERSIONS.matches(&self.version)
+    }
 }
 
 impl Default for HomeBankDbSchema {
@@ -47,10 +76,13 @@ impl TryFrom<Vec<OwnedAttribute>> for HomeBankDbSchema {
                         Err(e) => return Err(e),
                     }
                 }
-                "d" => match u32::from_str(&i.value) {
-                    Ok(d) => db_ver.date = unclamped_julian_date_from_u32(d),
-                    Err(_) => return Err(HomeBankDbError::InvalidDate),
-                },
+                "d" => {
+                    match u32::from_str(&i.value) {
+                        Ok(d) => db_ver.date = unclamped_julian_date_from_u32(d),
+                        Err(_) => return Err(HomeBankDbError::InvalidDate),
+                    }
+                    db_ver.app_version = parse_app_version(&i.value);
+                }
                 _ => {}
             }
         }
@@ -73,6 +105,17 @@ fn parse_version_string(s: &str) -> Result<Version, HomeBankDbError> {
     }
 }
 
+/// Parse the version of HomeBank from two digits each for its major, minor, and patch versions, e.g. `050805`.
+fn parse_app_version(s: &str) -> Option<Version> {
+    if s.len() != 6 || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let part = |i: usize| s[i..i + 2].parse().ok();
+
+    Some(Version::new(part(0)?, part(2)?, part(4)?))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -115,4 +158,29 @@ mod tests {
 
         check_try_from_single_str(input, expected);
     }
+
+    #[test]
+    fn parse_app_version_alongside_version() {
+        let input = r#"<homebank v="1.5" d="050805">"#;
+        let expected = Ok(HomeBankDbSchema {
+            version: Version::new(1, 5, 0),
+            date: unclamped_julian_date_from_u32(50805),
+            app_version: Some(Version::new(5, 8, 5)),
+        });
+
+        check_try_from_single_str(input, expected);
+    }
+
+    #[test]
+    fn supported_versions() {
+        let schema = |major, minor| HomeBankDbSchema {
+            version: Version::new(major, minor, 0),
+            ..Default::default()
+        };
+
+        assert!(schema(1, 4).is_supported());
+        assert!(schema(1, 6).is_supported());
+        assert!(!schema(2, 0).is_supported());
+        assert!(!schema(0, 9).is_supported());
+    }
 }
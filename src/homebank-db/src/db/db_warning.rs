@@ -0,0 +1,65 @@
+//! Warnings produced when leniently parsing a HomeBank database XML file.
+
+use std::fmt;
+
+/// An element of the HomeBank XML file that was skipped while parsing in lenient mode.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParseWarning {
+    /// Name of the XML element that was skipped (e.g. `ope`).
+    element: String,
+
+    /// Line in the XML file where the element is found, counting from 1.
+    line: u64,
+
+    /// Reason the element was skipped.
+    reason: String,
+}
+
+impl ParseWarning {
+    /// Create a new parsing warning.
+    pub fn new(element: &str, line: u64, reason: &str) -> Self {
+        Self {
+            element: element.to_string(),
+            line,
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Retrieve the name of the XML element that was skipped.
+    pub fn element(&self) -> &str {
+        &self.element
+    }
+
+    /// Retrieve the line in the XML file where the skipped element is found.
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+
+    /// Retrieve the reason the element was skipped.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}: skipped `<{}>` element. {}",
+            self.line, self.element, self.reason
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_warning() {
+        let warning = ParseWarning::new("ope", 12, "Missing payee from transaction.");
+        let expected = "line 12: skipped `<ope>` element. Missing payee from transaction.";
+
+        assert_eq!(expected, warning.to_string());
+    }
+}
@@ -0,0 +1,310 @@
+//! Add new elements to an existing HomeBank database file.
+//!
+//! Only a subset of the HomeBank file is parsed into a [`HomeBankDb`][crate::db::db_struct::HomeBankDb], so rewriting the file with
+//! [`to_writer`][crate::db::db_struct::HomeBankDb::to_writer] would lose the rest.
+//! Instead, new elements are inserted into the original text, keeping everything else exactly as it was.
+
+use super::{
+    db_open::{declared_encoding, is_gzipped, xml_from_bytes},
+    db_xml::{cat_attributes, ope_attributes, pay_attributes, Attributes},
+    HomeBankDbError,
+};
+use crate::{Category, Payee, Transaction};
+use flate2::{write::GzEncoder, Compression};
+use std::{fs, io::Write, path::Path};
+
+/// Elements that come after the payees in a HomeBank file, in the order HomeBank writes them.
+const AFTER_PAYEES: [&str; 6] = ["<cat ", "<tag ", "<fav ", "<asg ", "<ope ", "</homebank>"];
+
+/// Elements that come after the categories in a HomeBank file, in the order HomeBank writes them.
+const AFTER_CATEGORIES: [&str; 5] = ["<tag ", "<fav ", "<asg ", "<ope ", "</homebank>"];
+
+/// Closing tag of the HomeBank file.
+const CLOSING_TAG: &str = "</homebank>";
+
+/// Add payees, categories, and transactions to the HomeBank file at `path`.
+///
+/// Payees and categories are added after the existing ones and transactions are added at the end of the file.
+/// A gzipped file stays gzipped, but the text is always written as UTF-8, and its XML declaration is changed to say so.
+pub fn append_to_xhb(
+    path: &Path,
+    payees: &[Payee],
+    categories: &[Category],
+    transactions: &[Transaction],
+) -> Result<(), HomeBankDbError> {
+    let raw = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(_) => return Err(HomeBankDbError::CouldNotOpen(path.to_path_buf())),
+    };
+    let gzipped = is_gzipped(&raw);
+    let xml = xml_from_bytes(raw)?;
+
+    let updated = match insert_elements(&xml, payees, categories, transactions) {
+        Some(s) => s,
+        None => return Err(HomeBankDbError::CouldNotParse(path.to_path_buf())),
+    };
+
+    write_xhb(path, updated, gzipped)
+}
+
+/// Replace the HomeBank file at `path` with the text of the updated file, gzipping it if the original was gzipped.
+///
+/// The text is written as UTF-8, so an encoding declared by the original file is replaced with UTF-8.
+/// It is written to a temporary file next to the original, which then replaces it,
+/// so that the HomeBank file is never left half written.
+pub(crate) fn write_xhb(path: &Path, updated: String, gzipped: bool) -> Result<(), HomeBankDbError> {
+    let updated = declare_utf8(updated);
+    let bytes = if gzipped {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        match encoder.write_all(updated.as_bytes()).and_then(|_| encoder.finish()) {
+            Ok(b) => b,
+            Err(_) => return Err(HomeBankDbError::CouldNotWrite(path.to_path_buf())),
+        }
+    } else {
+        updated.into_bytes()
+    };
+
+    let file_name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    let temp = path.with_file_name(format!(".{file_name}.tmp"));
+
+    let written = fs::write(&temp, bytes)
+        .and_then(|_| fs::metadata(path))
+        .and_then(|meta| fs::set_permissions(&temp, meta.permissions()))
+        .and_then(|_| fs::rename(&temp, path));
+
+    match written {
+        Ok(_) => Ok(()),
+        Err(_) => {
+            let _ = fs::remove_file(&temp);
+            Err(HomeBankDbError::CouldNotWrite(path.to_path_buf()))
+        }
+    }
+}
+
+/// Change the encoding in the XML declaration of `xml` to UTF-8, if it declares a different one.
+fn declare_utf8(xml: String) -> String {
+    let label = match declared_encoding(xml.as_bytes()) {
+        Some(label) if !label.eq_ignore_ascii_case("utf-8") => label,
+        _ => return xml,
+    };
+
+    // the declaration ends at the first `?>`, and its encoding is the first quoted label after `encoding`
+    let end = xml.find("?>").unwrap_or_default();
+    let position = xml[..end]
+        .find("encoding")
+        .and_then(|start| xml[start..end].find(&label).map(|offset| start + offset));
+
+    match position {
+        Some(start) => format!("{}UTF-8{}", &xml[..start], &xml[start + label.len()..]),
+        None => xml,
+    }
+}
+
+/// Insert the elements into the XML text of a HomeBank file.
+/// Returns `None` if the text isn't a complete HomeBank file.
+fn insert_elements(xml: &str, payees: &[Payee], categories: &[Category], transactions: &[Transaction]) -> Option<String> {
+    let newline = if xml.contains("\r\n") { "\r\n" } else { "\n" };
+    let closing = xml.rfind(CLOSING_TAG)?;
+    let payee_pos = insert_position(xml, "<pay ", &AFTER_PAYEES, newline)?;
+    let category_pos = insert_position(xml, "<cat ", &AFTER_CATEGORIES, newline)?;
+
+    let payee_text: String = payees.iter().map(|p| pay_element(p) + newline).collect();
+    let category_text: String = categories.iter().map(|cat| cat_element(cat) + newline).collect();
+    let ope_text: String = transactions.iter().map(|tr| ope_element(tr) + newline).collect();
+
+    let mut out = String::with_capacity(xml.len() + payee_text.len() + category_text.len() + ope_text.len());
+    out.push_str(&xml[..payee_pos]);
+    out.push_str(&payee_text);
+    out.push_str(&xml[payee_pos..category_pos]);
+    out.push_str(&category_text);
+    out.push_str(&xml[category_pos..closing]);
+    out.push_str(&ope_text);
+    out.push_str(&xml[closing..]);
+
+    Some(out)
+}
+
+/// Find where to insert new elements starting with `tag`: after the end of the last one, or before whatever comes after them.
+fn insert_position(xml: &str, tag: &str, after: &[&str], newline: &str) -> Option<usize> {
+    match xml.rfind(tag) {
+        Some(start) => {
+            let end = start + xml[start..].find("/>")? + 2;
+            match xml[end..].starts_with(newline) {
+                true => Some(end + newline.len()),
+                false => Some(end),
+            }
+        }
+        None => after.iter().filter_map(|tag| xml.find(tag)).min(),
+    }
+}
+
+/// Write a payee as a `<pay>` element.
+fn pay_element(payee: &Payee) -> String {
+    element("pay", &pay_attributes(payee))
+}
+
+/// Write a category as a `<cat>` element.
+fn cat_element(cat: &Category) -> String {
+    element("cat", &cat_attributes(cat))
+}
+
+/// Write a transaction as an `<ope>` element.
+fn ope_element(tr: &Transaction) -> String {
+    element("ope", &ope_attributes(tr))
+}
+
+/// Write an element without children on a single line, escaping the values of its attributes.
+fn element(name: &str, attributes: &Attributes) -> String {
+    let joined: Vec<String> = attributes
+        .iter()
+        .map(|(attr, value)| format!(r#"{attr}="{}""#, escape(value)))
+        .collect();
+
+    format!("<{name} {}/>", joined.join(" "))
+}
+
+/// Escape text for use in an attribute value.
+pub(crate) fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\n' => out.push_str("&#10;"),
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        transaction::{SimpleTransaction, TransactionComplexity},
+        HomeBankDb, PayMode, TransactionFlags, TransactionStatus, TransactionType,
+    };
+    use chrono::NaiveDate;
+
+    fn new_transaction() -> Transaction {
+        transaction_with_flags(1 << 9)
+    }
+
+    fn transaction_with_flags(bits: u32) -> Transaction {
+        Transaction::new(
+            &NaiveDate::from_ymd_opt(2022, 2, 10).unwrap(),
+            -12.5,
+            1,
+            &PayMode::DebitCard,
+            &TransactionStatus::Cleared,
+            &Some(TransactionFlags::new(bits)),
+            &Some(5),
+            &Some("Fish & chips".to_string()),
+            &None,
+            &None,
+            &TransactionType::Expense,
+            &TransactionComplexity::Simple(SimpleTransaction::new(Some(5), -12.5, Some("Fish & chips".to_string()))),
+        )
+    }
+
+    #[test]
+    fn escape_attribute() {
+        assert_eq!("&quot;A&amp;B&quot; &lt;c&gt;", escape(r#""A&B" <c>"#));
+    }
+
+    #[test]
+    fn ope_element_roundtrip() {
+        let xml = format!(
+            "<?xml version=\"1.0\"?>\n<homebank v=\"1.3999999999999999\" d=\"050504\">\n{}\n</homebank>\n",
+            ope_element(&new_transaction())
+        );
+        let db = HomeBankDb::from_reader(xml.as_bytes()).unwrap();
+
+        assert_eq!(&vec![new_transaction()], db.transactions());
+    }
+
+    #[test]
+    fn ope_element_keeps_unnamed_flags() {
+        // bits 6 (`CHEQ2`) and 12 have no accessor, but are written back unchanged
+        let bits = (1 << 6) | (1 << 9) | (1 << 12);
+        let xml = format!("<homebank>\n{}\n</homebank>\n", ope_element(&transaction_with_flags(bits)));
+        let db = HomeBankDb::from_reader(xml.as_bytes()).unwrap();
+
+        assert_eq!(Some(TransactionFlags::new(bits)), *db.transactions()[0].flags());
+    }
+
+    #[test]
+    fn insert_after_last_payee() {
+        let xml = "<homebank>\n<pay key=\"1\" name=\"A\"/>\n<cat key=\"1\" name=\"C\"/>\n<ope date=\"1\"/>\n</homebank>\n";
+        let expected = "<homebank>\n<pay key=\"1\" name=\"A\"/>\n<pay key=\"5\" name=\"B\"/>\n<cat key=\"1\" name=\"C\"/>\n<ope date=\"1\"/>\n";
+        let observed = insert_elements(xml, &[Payee::new(5, "B", None, None)], &[], &[new_transaction()]).unwrap();
+
+        assert!(observed.starts_with(expected), "{observed}");
+        assert!(observed.ends_with("wording=\"Fish &amp; chips\"/>\n</homebank>\n"), "{observed}");
+    }
+
+    #[test]
+    fn insert_without_payees() {
+        let xml = "<homebank>\r\n<cat key=\"1\" name=\"C\"/>\r\n</homebank>\r\n";
+        let expected = "<homebank>\r\n<pay key=\"1\" name=\"B\"/>\r\n<cat key=\"1\" name=\"C\"/>\r\n</homebank>\r\n";
+
+        assert_eq!(Some(expected.to_string()), insert_elements(xml, &[Payee::new(1, "B", None, None)], &[], &[]));
+    }
+
+    #[test]
+    fn insert_after_last_category() {
+        let xml = "<homebank>\n<pay key=\"1\" name=\"A\"/>\n<cat key=\"1\" flags=\"0\" name=\"Food\"/>\n<fav key=\"1\"/>\n</homebank>\n";
+        let categories = [Category::new(2, 1, "Snacks & treats", Some(1)), Category::new(3, 2, "Gifts", None)];
+        let expected = "<homebank>\n<pay key=\"1\" name=\"A\"/>\n<cat key=\"1\" flags=\"0\" name=\"Food\"/>\n<cat key=\"2\" parent=\"1\" flags=\"1\" name=\"Snacks &amp; treats\"/>\n<cat key=\"3\" flags=\"2\" name=\"Gifts\"/>\n<fav key=\"1\"/>\n</homebank>\n";
+
+        assert_eq!(Some(expected.to_string()), insert_elements(xml, &[], &categories, &[]));
+    }
+
+    #[test]
+    fn insert_into_incomplete_file() {
+        assert_eq!(None, insert_elements("<homebank>\n<pay key=\"1\"", &[], &[], &[]));
+    }
+
+    #[test]
+    fn declaration_changed_to_utf8() {
+        let xml = "<?xml version=\"1.0\" encoding='windows-1252'?>\n<homebank/>\n".to_string();
+
+        assert_eq!("<?xml version=\"1.0\" encoding='UTF-8'?>\n<homebank/>\n", declare_utf8(xml));
+        assert_eq!("<homebank/>", declare_utf8("<homebank/>".to_string()));
+    }
+
+    #[test]
+    fn append_keeps_legacy_text() {
+        let dir = std::env::temp_dir().join(format!("hb-write-legacy-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("legacy.xhb");
+        let mut raw = b"<?xml version=\"1.0\" encoding=\"windows-1252\"?>\n<homebank v=\"1.3999999999999999\" d=\"050504\">\n<pay key=\"1\" name=\"Caf\xe9\"/>\n".to_vec();
+        raw.extend_from_slice(b"</homebank>\n");
+        fs::write(&path, raw).unwrap();
+
+        append_to_xhb(&path, &[Payee::new(2, "Crème", None, None)], &[], &[]).unwrap();
+        let db = HomeBankDb::try_from(path.as_path()).unwrap();
+
+        assert_eq!(Some("Café"), db.payee_name(1));
+        assert_eq!(Some("Crème"), db.payee_name(2));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn write_replaces_file() {
+        let dir = std::env::temp_dir().join(format!("hb-write-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("replace.xhb");
+        fs::write(&path, "<homebank/>").unwrap();
+
+        write_xhb(&path, "<homebank></homebank>".to_string(), false).unwrap();
+
+        assert_eq!("<homebank></homebank>", fs::read_to_string(&path).unwrap());
+        assert_eq!(1, fs::read_dir(&dir).unwrap().count());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
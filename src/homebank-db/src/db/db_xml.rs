@@ -0,0 +1,327 @@
+//! Write a [`HomeBankDb`][crate::db::db_struct::HomeBankDb] back out as the XML of a HomeBank file.
+
+use super::{HomeBankDb, HomeBankDbError, HomeBankDbProperties, ScheduleMode};
+use crate::{
+    transaction::{u32_from_julian_date, SPLIT_SEPARATOR},
+    Account, Category, Currency, Group, Payee, Transaction, TransactionType,
+};
+use std::io::Write;
+use xml::writer::{EmitterConfig, EventWriter, XmlEvent};
+
+/// Names and (unescaped) values of the attributes of an element, in the order HomeBank writes them.
+pub(crate) type Attributes = Vec<(&'static str, String)>;
+
+impl HomeBankDb {
+    /// Write the database as the XML of a HomeBank file, one element per line.
+    ///
+    /// Dates are written as the number of days since HomeBank's epoch, and split transactions as `||`-delimited values, like HomeBank does.
+    /// Favourites are parsed into the database but not written yet, and neither is any element or attribute in the [`parse_report`][HomeBankDb::parse_report].
+    pub fn to_writer<W: Write>(&self, w: W) -> Result<(), HomeBankDbError> {
+        let mut writer = EmitterConfig::new()
+            .perform_indent(true)
+            .indent_string("")
+            .pad_self_closing(false)
+            .create_writer(w);
+
+        let schema = self.version();
+        let root = vec![
+            ("v", format!("{}.{}", schema.version().major, schema.version().minor)),
+            ("d", format!("{:06}", u32_from_julian_date(*schema.date()))),
+        ];
+        start_element(&mut writer, "homebank", &root)?;
+
+        write_element(&mut writer, "properties", &properties_attributes(self.properties()))?;
+        for cur in self.currencies().values() {
+            write_element(&mut writer, "cur", &cur_attributes(cur))?;
+        }
+        for grp in self.groups().values() {
+            write_element(&mut writer, "grp", &grp_attributes(grp))?;
+        }
+        for acct in self.accounts().values() {
+            write_element(&mut writer, "account", &account_attributes(acct))?;
+        }
+        for payee in self.payees().values() {
+            write_element(&mut writer, "pay", &pay_attributes(payee))?;
+        }
+        for cat in self.categories().values() {
+            write_element(&mut writer, "cat", &cat_attributes(cat))?;
+        }
+        for tr in self.transactions() {
+            write_element(&mut writer, "ope", &ope_attributes(tr))?;
+        }
+
+        end_element(&mut writer)?;
+
+        // end the file with a newline, like HomeBank does
+        match writer.into_inner().write_all(b"\n") {
+            Ok(_) => Ok(()),
+            Err(_) => Err(HomeBankDbError::CouldNotWriteOutput),
+        }
+    }
+}
+
+/// Write an element and its attributes, leaving it open for child elements.
+fn start_element<W: Write>(writer: &mut EventWriter<W>, name: &str, attributes: &Attributes) -> Result<(), HomeBankDbError> {
+    let mut event = XmlEvent::start_element(name);
+    for (attr, value) in attributes {
+        event = event.attr(*attr, value);
+    }
+
+    match writer.write(event) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(HomeBankDbError::CouldNotWriteOutput),
+    }
+}
+
+/// Close the last element that was started.
+fn end_element<W: Write>(writer: &mut EventWriter<W>) -> Result<(), HomeBankDbError> {
+    match writer.write(XmlEvent::end_element()) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(HomeBankDbError::CouldNotWriteOutput),
+    }
+}
+
+/// Write an element without children, e.g. `<pay key="1" name="Grocer"/>`.
+fn write_element<W: Write>(writer: &mut EventWriter<W>, name: &str, attributes: &Attributes) -> Result<(), HomeBankDbError> {
+    start_element(writer, name, attributes)?;
+    end_element(writer)
+}
+
+/// Attributes of the `<properties>` element.
+fn properties_attributes(props: &HomeBankDbProperties) -> Attributes {
+    let mut attributes = vec![];
+
+    if let Some(title) = props.title() {
+        attributes.push(("title", title.to_string()));
+    }
+    if let Some(notes) = props.notes() {
+        attributes.push(("notes", notes.to_string()));
+    }
+    attributes.push(("curr", props.currency().to_string()));
+    attributes.push(("car_category", props.car_category_idx().to_string()));
+
+    match props.sched_mode() {
+        ScheduleMode::AddUntil(day) => {
+            attributes.push(("auto_smode", "0".to_string()));
+            attributes.push(("auto_weekday", day.to_string()));
+        }
+        ScheduleMode::Add(days) => {
+            attributes.push(("auto_smode", "1".to_string()));
+            attributes.push(("auto_nbdays", days.to_string()));
+        }
+        ScheduleMode::NotCurrentlySet(day, days) => {
+            if let Some(day) = day {
+                attributes.push(("auto_weekday", day.to_string()));
+            }
+            if let Some(days) = days {
+                attributes.push(("auto_nbdays", days.to_string()));
+            }
+        }
+    }
+
+    attributes
+}
+
+/// Attributes of a `<cur>` element.
+fn cur_attributes(cur: &Currency) -> Attributes {
+    vec![
+        ("key", cur.key().to_string()),
+        ("flags", cur.flags().to_string()),
+        ("iso", cur.iso().to_string()),
+        ("name", cur.name().to_string()),
+        ("symb", cur.symbol().to_string()),
+        ("syprf", (cur.symbol_is_prefix() as u8).to_string()),
+        ("dchar", cur.decimal_char().to_string()),
+        // an empty separator means that digits aren't grouped
+        ("gchar", cur.group_char().map(String::from).unwrap_or_default()),
+        ("frac", cur.decimal_len().to_string()),
+        ("rate", cur.conversion_rate().to_string()),
+        ("mdate", u32_from_julian_date(*cur.modified_date()).to_string()),
+    ]
+}
+
+/// Attributes of a `<grp>` element.
+fn grp_attributes(grp: &Group) -> Attributes {
+    vec![("key", grp.key().to_string()), ("name", grp.name().to_string())]
+}
+
+/// Attributes of an `<account>` element.
+fn account_attributes(acct: &Account) -> Attributes {
+    // the variants of `AccountType` are declared in the order of their numbers in the file
+    let mut attributes = vec![
+        ("key", acct.key().to_string()),
+        ("flags", acct.flags().to_string()),
+        ("pos", acct.position().to_string()),
+        ("type", (*acct.atype() as usize).to_string()),
+        ("curr", acct.currency().to_string()),
+        ("name", acct.name().to_string()),
+    ];
+
+    if !acct.institution().is_empty() {
+        attributes.push(("bankname", acct.institution().to_string()));
+    }
+    attributes.push(("initial", acct.initial_amount().to_string()));
+    attributes.push(("minimum", acct.minimum_amount().to_string()));
+    attributes.push(("maximum", acct.maximum_amount().to_string()));
+    if let Some(notes) = acct.notes() {
+        attributes.push(("notes", notes.to_string()));
+    }
+    if let Some(grp) = acct.group() {
+        attributes.push(("grp", grp.to_string()));
+    }
+    attributes.push(("rdate", u32_from_julian_date(*acct.reconciled_date()).to_string()));
+
+    attributes
+}
+
+/// Attributes of a `<pay>` element.
+pub(crate) fn pay_attributes(payee: &Payee) -> Attributes {
+    let mut attributes = vec![("key", payee.key().to_string()), ("name", payee.name().to_string())];
+
+    if let Some(cat) = payee.category() {
+        attributes.push(("category", cat.to_string()));
+    }
+    if let Some(paymode) = payee.paymode() {
+        attributes.push(("paymode", paymode.to_string()));
+    }
+
+    attributes
+}
+
+/// Attributes of a `<cat>` element, including its budget.
+pub(crate) fn cat_attributes(cat: &Category) -> Attributes {
+    let mut attributes = vec![("key", cat.key().to_string())];
+
+    if let Some(parent) = cat.parent_key() {
+        attributes.push(("parent", parent.to_string()));
+    }
+    attributes.push(("flags", cat.flags().to_string()));
+    attributes.push(("name", cat.name().to_string()));
+
+    const BUDGET_ATTRIBUTES: [&str; 13] = ["b0", "b1", "b2", "b3", "b4", "b5", "b6", "b7", "b8", "b9", "b10", "b11", "b12"];
+    for (index, attr) in BUDGET_ATTRIBUTES.iter().enumerate() {
+        if let Some(amount) = cat.budget().stored_amount(index) {
+            attributes.push((*attr, amount.to_string()));
+        }
+    }
+
+    attributes
+}
+
+/// Attributes of an `<ope>` element, for simple, split, and transfer transactions.
+pub(crate) fn ope_attributes(tr: &Transaction) -> Attributes {
+    // the variants of `TransactionStatus` are declared in the order of their numbers in the file
+    let mut attributes = vec![
+        ("date", u32_from_julian_date(*tr.date()).to_string()),
+        ("amount", tr.total().to_string()),
+        ("account", tr.account().to_string()),
+    ];
+
+    if let Some(dst) = tr.transfer_destination() {
+        attributes.push(("dst_account", dst.to_string()));
+    }
+    attributes.push(("paymode", tr.pay_mode().number().to_string()));
+    attributes.push(("st", (*tr.status() as usize).to_string()));
+
+    if let Some(flags) = tr.flags() {
+        attributes.push(("flags", flags.bits().to_string()));
+    }
+    if let Some(payee) = tr.payee() {
+        attributes.push(("payee", payee.to_string()));
+    }
+    if let (false, Some(Some(cat))) = (tr.is_split(), tr.categories().first()) {
+        attributes.push(("category", cat.to_string()));
+    }
+    if let Some(memo) = tr.memo() {
+        attributes.push(("wording", memo.to_string()));
+    }
+    if let Some(info) = tr.info() {
+        attributes.push(("info", info.to_string()));
+    }
+    if let Some(tags) = tr.tags_attribute() {
+        attributes.push(("tags", tags));
+    }
+    if let TransactionType::Transfer(xfer) = tr.ttype() {
+        attributes.push(("kxfer", xfer.transfer_key().to_string()));
+    }
+
+    if tr.is_split() {
+        // HomeBank writes 0 for a split without a category
+        let categories: Vec<String> = tr.categories().iter().map(|cat| cat.unwrap_or(0).to_string()).collect();
+        let amounts: Vec<String> = tr.amounts().iter().map(|amount| amount.to_string()).collect();
+        let memos: Vec<String> = tr.memos().iter().map(|memo| memo.as_deref().unwrap_or_default().to_string()).collect();
+
+        attributes.push(("scat", categories.join(SPLIT_SEPARATOR)));
+        attributes.push(("samt", amounts.join(SPLIT_SEPARATOR)));
+        attributes.push(("smem", memos.join(SPLIT_SEPARATOR)));
+    }
+
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    /// Everything in the database that is written, i.e. all but the favourites, which are parsed but not written yet.
+    macro_rules! written_parts {
+        ($db:expr) => {
+            (
+                $db.version(),
+                $db.properties(),
+                $db.currencies(),
+                $db.groups(),
+                $db.accounts(),
+                $db.payees(),
+                $db.categories(),
+                $db.transactions(),
+            )
+        };
+    }
+
+    #[track_caller]
+    fn check_round_trip(path: &str) {
+        let original = HomeBankDb::try_from(Path::new(path)).unwrap();
+        let mut xml = vec![];
+        original.to_writer(&mut xml).unwrap();
+
+        let written = HomeBankDb::from_reader(xml.as_slice()).unwrap();
+
+        assert_eq!(written_parts!(original), written_parts!(written), "{}", String::from_utf8_lossy(&xml));
+    }
+
+    #[test]
+    fn round_trip_example() {
+        check_round_trip("tests/transactions.xhb");
+    }
+
+    #[test]
+    fn round_trip_currencies() {
+        check_round_trip("tests/currencies.xhb");
+    }
+
+    #[test]
+    fn round_trip_split_and_transfer() {
+        let xml = r#"<homebank v="1.4" d="050504">
+<properties title="Splits &amp; transfers" curr="1" car_category="1" auto_smode="0" auto_weekday="5"/>
+<account key="1" flags="0" pos="1" type="1" curr="1" name="Chequing" initial="0"/>
+<account key="2" flags="0" pos="2" type="2" curr="1" name="Savings" initial="0"/>
+<cat key="1" flags="0" name="Food" b0="-50.5"/>
+<cat key="2" flags="1" name="Bills" b1="-10" b12="-20"/>
+<ope date="738134" amount="-30" account="1" paymode="1" st="1" scat="1||2" samt="-10||-20" smem="Lunch||"/>
+<ope date="738135" amount="-100" account="1" dst_account="2" paymode="5" st="0" flags="2" wording="To savings" tags="move,money Money" kxfer="1"/>
+</homebank>"#;
+        let original = HomeBankDb::from_reader(xml.as_bytes()).unwrap();
+        let mut written = vec![];
+        original.to_writer(&mut written).unwrap();
+        let text = String::from_utf8(written).unwrap();
+
+        assert!(text.contains("\n<ope date=\"738134\" amount=\"-30\" account=\"1\" paymode=\"1\" st=\"1\" scat=\"1||2\" samt=\"-10||-20\" smem=\"Lunch||\"/>\n"), "{text}");
+        assert!(text.contains("title=\"Splits &amp; transfers\""), "{text}");
+        // tags are written as they were read, not as they are split
+        assert!(text.contains(" tags=\"move,money Money\" "), "{text}");
+        assert!(text.ends_with("</homebank>\n"), "{text}");
+        assert_eq!(original, HomeBankDb::from_reader(text.as_bytes()).unwrap());
+    }
+}
@@ -1,11 +1,34 @@
 //! Data structure for the HomeBank database.
 
+pub mod db_analytics;
+pub mod db_duplicates;
 pub mod db_error;
+pub mod db_names;
+pub mod db_open;
+pub mod db_parse_report;
 pub mod db_properties;
+pub mod db_stats;
+pub mod db_stream;
 pub mod db_struct;
+pub mod db_tags;
+pub mod db_transfers;
+pub mod db_validate;
 pub mod db_version;
+pub mod db_warning;
+pub mod db_write;
+pub mod db_xml;
 
-pub use db_struct::HomeBankDb;
+pub use db_duplicates::{DuplicateKey, DEFAULT_DUPLICATE_KEYS};
+pub use db_stats::DbStats;
+pub use db_stream::{stream_transactions, TransactionStream};
+pub use db_tags::TagStats;
+pub use db_struct::{HomeBankDb, SkippedTransactions};
 pub use db_error::HomeBankDbError;
-pub use db_properties::HomeBankDbProperties;
-pub use db_version::HomeBankDbSchema;
+pub use db_names::NameIndex;
+pub use db_open::open_homebank;
+pub use db_parse_report::ParseReport;
+pub use db_properties::{HomeBankDbProperties, ScheduleMode};
+pub use db_validate::{validate, IssueCode, OrphanKind, Severity, ValidationIssue};
+pub use db_version::{HomeBankDbSchema, SUPPORTED_VERSIONS};
+pub use db_warning::ParseWarning;
+pub use db_write::append_to_xhb;
@@ -0,0 +1,220 @@
+//! Rename a category, or merge it into another one.
+
+use super::{Edit, EditError};
+use crate::{db::db_struct::same_name, Category, HomeBankDb};
+use clap::Parser;
+
+/// Rename a category, or merge it into another one.
+#[derive(Debug, Parser)]
+#[command(about = "Rename a category, or merge it into another one")]
+pub struct EditCategory {
+    /// Full name of a category, followed by its new name without the parent category, e.g. `--rename Food:Dining Restaurants`.
+    #[arg(
+        long = "rename",
+        num_args = 2,
+        value_names = ["category", "new name"],
+        required_unless_present = "merge",
+        conflicts_with = "merge"
+    )]
+    rename: Option<Vec<String>>,
+
+    /// Full name of a category to merge into the one given with `--into`, then remove.
+    #[arg(long = "merge", value_name = "category", requires = "into")]
+    merge: Option<String>,
+
+    /// Full name of the category that `--merge` is merged into.
+    #[arg(long = "into", value_name = "category", requires = "merge")]
+    into: Option<String>,
+
+    /// Merge an income category into an expense category, or the other way around.
+    #[arg(long = "allow-type-change")]
+    allow_type_change: bool,
+
+    /// Print what would change without changing the HomeBank file.
+    #[arg(short = 'n', long = "dry-run")]
+    dry_run: bool,
+}
+
+impl EditCategory {
+    /// Create an edit renaming the category with the full name `category` to `new_name`
+    pub fn rename(category: &str, new_name: &str) -> Self {
+        Self {
+            rename: Some(vec![category.to_string(), new_name.to_string()]),
+            merge: None,
+            into: None,
+            allow_type_change: false,
+            dry_run: false,
+        }
+    }
+
+    /// Create an edit merging the category with the full name `from` into the one named `into`
+    pub fn merge(from: &str, into: &str) -> Self {
+        Self {
+            rename: None,
+            merge: Some(from.to_string()),
+            into: Some(into.to_string()),
+            allow_type_change: false,
+            dry_run: false,
+        }
+    }
+
+    /// Allow merging an income category into an expense category, or the other way around
+    pub fn allow_type_change(mut self) -> Self {
+        self.allow_type_change = true;
+        self
+    }
+
+    /// Check whether the HomeBank file should be left unchanged
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Find the categories being edited in the database, and check that the edit makes sense.
+    pub fn plan(&self, db: &HomeBankDb) -> Result<Edit, EditError> {
+        match (&self.rename, &self.merge, &self.into) {
+            (Some(names), _, _) if names.len() == 2 => plan_rename(db, &names[0], &names[1]),
+            (_, Some(from), Some(into)) => plan_merge(db, from, into, self.allow_type_change),
+            _ => Err(EditError::UnknownCategory(String::new())),
+        }
+    }
+}
+
+/// Find the category with this full name.
+fn find<'db>(db: &'db HomeBankDb, name: &str) -> Result<&'db Category, EditError> {
+    db.category_by_name(name)
+        .ok_or_else(|| EditError::UnknownCategory(name.to_string()))
+}
+
+/// Check that the category can take the new name without clashing with another category under the same parent.
+fn plan_rename(db: &HomeBankDb, old_name: &str, new_name: &str) -> Result<Edit, EditError> {
+    let cat = find(db, old_name)?;
+    let new_name = new_name.trim();
+
+    if new_name.is_empty() || new_name.contains(':') {
+        return Err(EditError::InvalidName(new_name.to_string()));
+    }
+
+    let taken = db
+        .categories()
+        .values()
+        .find(|other| other.key() != cat.key() && other.parent_key() == cat.parent_key() && same_name(other.name(), new_name));
+    if let Some(other) = taken {
+        return Err(EditError::NameTaken(other.full_name(db)));
+    }
+
+    Ok(Edit::RenameCategory {
+        key: cat.key(),
+        name: new_name.to_string(),
+    })
+}
+
+/// Check that the category `from` can be merged into the category `into`.
+fn plan_merge(db: &HomeBankDb, from: &str, into: &str, allow_type_change: bool) -> Result<Edit, EditError> {
+    let from = find(db, from)?;
+    let into = find(db, into)?;
+    let (from_name, into_name) = (from.full_name(db), into.full_name(db));
+
+    if from.key() == into.key() {
+        return Err(EditError::SameCategory(from_name));
+    }
+    if into.parent_key() == Some(from.key()) {
+        return Err(EditError::IntoSubcategory(from_name, into_name));
+    }
+    if into.has_parent() && !db.child_categories(from.key()).is_empty() {
+        return Err(EditError::TooDeep(from_name, into_name));
+    }
+    // the subcategories of `from` move under `into`, so they can't have the same name as one of its own
+    let into_children = db.child_categories(into.key());
+    let taken = db
+        .child_categories(from.key())
+        .into_iter()
+        .find_map(|child| into_children.iter().find(|other| same_name(other.name(), child.name())));
+    if let Some(other) = taken {
+        return Err(EditError::NameTaken(other.full_name(db)));
+    }
+    if from.is_income() != into.is_income() && !allow_type_change {
+        let kind = |cat: &Category| if cat.is_income() { "income" } else { "expense" };
+        return Err(EditError::IncomeMismatch(from_name, into_name, kind(from), kind(into)));
+    }
+
+    Ok(Edit::MergeCategories {
+        from: from.key(),
+        into: into.key(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    #[track_caller]
+    fn check_plan(edit: EditCategory, expected: Result<Edit, EditError>) {
+        assert_eq!(expected, edit.plan(&example_db()));
+    }
+
+    #[test]
+    fn plan_renames() {
+        check_plan(
+            EditCategory::rename("food:dining", " Restaurants "),
+            Ok(Edit::RenameCategory { key: 5, name: "Restaurants".to_string() }),
+        );
+        check_plan(
+            EditCategory::rename("Food:Dining", "dining"),
+            Ok(Edit::RenameCategory { key: 5, name: "dining".to_string() }),
+        );
+        check_plan(
+            EditCategory::rename("Food:Dining", "groceries"),
+            Err(EditError::NameTaken("Food:Groceries".to_string())),
+        );
+        check_plan(
+            EditCategory::rename("Food:Dining", "Food:Restaurants"),
+            Err(EditError::InvalidName("Food:Restaurants".to_string())),
+        );
+        check_plan(EditCategory::rename("Dining", "Restaurants"), Err(EditError::UnknownCategory("Dining".to_string())));
+    }
+
+    #[test]
+    fn plan_merges() {
+        check_plan(EditCategory::merge("Food:Dining", "Food:Groceries"), Ok(Edit::MergeCategories { from: 5, into: 4 }));
+        check_plan(
+            EditCategory::merge("Food", "Food"),
+            Err(EditError::SameCategory("Food".to_string())),
+        );
+        check_plan(
+            EditCategory::merge("Food", "Food:Dining"),
+            Err(EditError::IntoSubcategory("Food".to_string(), "Food:Dining".to_string())),
+        );
+        check_plan(
+            EditCategory::merge("Food", "Housing:Rent"),
+            Err(EditError::TooDeep("Food".to_string(), "Housing:Rent".to_string())),
+        );
+    }
+
+    #[test]
+    fn merge_with_same_subcategory_name() {
+        let xml = r#"<homebank v="1.4" d="050504">
+<cat key="1" flags="0" name="Car"/>
+<cat key="2" parent="1" flags="1" name="Fuel"/>
+<cat key="3" flags="0" name="Van"/>
+<cat key="4" parent="3" flags="1" name="fuel"/>
+</homebank>"#;
+        let db = HomeBankDb::from_reader(xml.as_bytes()).unwrap();
+
+        assert_eq!(Err(EditError::NameTaken("Car:Fuel".to_string())), EditCategory::merge("Van", "Car").plan(&db));
+    }
+
+    #[test]
+    fn income_into_expense_needs_allow_type_change() {
+        let observed = EditCategory::merge("Salary", "Entertainment").plan(&example_db()).unwrap_err();
+
+        assert_eq!(
+            "Cannot merge income category `Salary` into expense category `Entertainment`. Use `--allow-type-change` to merge them anyway.",
+            observed.to_string()
+        );
+        check_plan(
+            EditCategory::merge("Salary", "Entertainment").allow_type_change(),
+            Ok(Edit::MergeCategories { from: 6, into: 7 }),
+        );
+    }
+}
@@ -0,0 +1,59 @@
+//! Errors when editing items in the HomeBank database.
+
+use thiserror::Error;
+
+/// Errors when editing items in the HomeBank database.
+#[derive(Debug, Error, PartialEq)]
+pub enum EditError {
+    /// When no category has the full name being edited.
+    #[error("No category named `{0}`. Use the full name of an existing category, e.g. `Food:Groceries`.")]
+    UnknownCategory(String),
+
+    /// When a new name is empty or contains the `:` separating categories from their subcategories.
+    #[error("Invalid category name `{0}`. Give only the new name, without its parent category, e.g. `Restaurants`.")]
+    InvalidName(String),
+
+    /// When another category with the same parent already has the new name.
+    #[error("A category named `{0}` already exists. Use `--merge` to combine them.")]
+    NameTaken(String),
+
+    /// When a category is merged into itself.
+    #[error("Cannot merge `{0}` into itself.")]
+    SameCategory(String),
+
+    /// When a category is merged into one of its own subcategories.
+    #[error("Cannot merge `{0}` into its subcategory `{1}`.")]
+    IntoSubcategory(String, String),
+
+    /// When merging would give subcategories a subcategory as their parent, which HomeBank doesn't support.
+    #[error("Cannot merge `{0}` into the subcategory `{1}`, because `{0}` has subcategories of its own.")]
+    TooDeep(String, String),
+
+    /// When no payee has the name being edited.
+    #[error("No payee named `{0}`.")]
+    UnknownPayee(String),
+
+    /// When a new payee name is empty.
+    #[error("Invalid payee name `{0}`. The name cannot be empty.")]
+    InvalidPayeeName(String),
+
+    /// When another payee already has the new name.
+    #[error("A payee named `{0}` already exists. Use `--merge` to combine them.")]
+    PayeeNameTaken(String),
+
+    /// When no payee other than the one being merged into matches the regular expression.
+    #[error("No payees match `{0}`, other than the one they would be merged into.")]
+    NoMatchingPayees(String),
+
+    /// When no transaction has the tag being edited.
+    #[error("No transaction has the tag `{0}`. Use `--case-insensitive` to match it regardless of case.")]
+    UnknownTag(String),
+
+    /// When a new tag is empty or contains a space or comma, which separate tags.
+    #[error("Invalid tag `{0}`. A tag cannot be empty or contain spaces or commas.")]
+    InvalidTagName(String),
+
+    /// When an income category is merged into an expense category, or the other way around.
+    #[error("Cannot merge {2} category `{0}` into {3} category `{1}`. Use `--allow-type-change` to merge them anyway.")]
+    IncomeMismatch(String, String, &'static str, &'static str),
+}
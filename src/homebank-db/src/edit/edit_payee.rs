@@ -0,0 +1,178 @@
+//! Rename a payee, or merge several payees into one.
+
+use super::{Edit, EditError};
+use crate::{db::db_struct::same_name, HomeBankDb};
+use clap::Parser;
+use regex::Regex;
+
+/// Rename a payee, or merge several payees into one.
+#[derive(Debug, Parser)]
+#[command(about = "Rename a payee, or merge several payees into one")]
+pub struct EditPayee {
+    /// Name of a payee, followed by its new name, e.g. `--rename "AMAZON EU SARL" Amazon`.
+    #[arg(
+        long = "rename",
+        num_args = 2,
+        value_names = ["payee", "new name"],
+        required_unless_present = "merge",
+        conflicts_with = "merge"
+    )]
+    rename: Option<Vec<String>>,
+
+    /// Merge every payee whose name matches the regular expression into the one given with `--into`, then remove them.
+    #[arg(long = "merge", value_name = "regex", requires = "into")]
+    merge: Option<Regex>,
+
+    /// Name of the payee that `--merge` is merged into. If there is no such payee, the first matching one is renamed to it.
+    #[arg(long = "into", value_name = "name", requires = "merge")]
+    into: Option<String>,
+
+    /// Print what would change without changing the HomeBank file.
+    #[arg(short = 'n', long = "dry-run")]
+    dry_run: bool,
+}
+
+impl EditPayee {
+    /// Create an edit renaming the payee `payee` to `new_name`
+    pub fn rename(payee: &str, new_name: &str) -> Self {
+        Self {
+            rename: Some(vec![payee.to_string(), new_name.to_string()]),
+            merge: None,
+            into: None,
+            dry_run: false,
+        }
+    }
+
+    /// Create an edit merging the payees matching `pattern` into the payee named `into`
+    pub fn merge(pattern: Regex, into: &str) -> Self {
+        Self {
+            rename: None,
+            merge: Some(pattern),
+            into: Some(into.to_string()),
+            dry_run: false,
+        }
+    }
+
+    /// Check whether the HomeBank file should be left unchanged
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Find the payees being edited in the database, and check that the edit makes sense.
+    ///
+    /// Merging into a payee that doesn't exist yet renames the first matching payee (by key) instead,
+    /// so the plan is a rename followed by a merge into it.
+    pub fn plan(&self, db: &HomeBankDb) -> Result<Vec<Edit>, EditError> {
+        match (&self.rename, &self.merge, &self.into) {
+            (Some(names), _, _) if names.len() == 2 => Ok(vec![plan_rename(db, &names[0], &names[1])?]),
+            (_, Some(pattern), Some(into)) => plan_merge(db, pattern, into),
+            _ => Err(EditError::UnknownPayee(String::new())),
+        }
+    }
+}
+
+/// Check that the payee can take the new name without clashing with another payee.
+fn plan_rename(db: &HomeBankDb, old_name: &str, new_name: &str) -> Result<Edit, EditError> {
+    let payee = db
+        .payee_by_name(old_name)
+        .ok_or_else(|| EditError::UnknownPayee(old_name.to_string()))?;
+    let new_name = new_name.trim();
+
+    if new_name.is_empty() {
+        return Err(EditError::InvalidPayeeName(new_name.to_string()));
+    }
+    if let Some(other) = db.payees().values().find(|other| other.key() != payee.key() && same_name(other.name(), new_name)) {
+        return Err(EditError::PayeeNameTaken(other.name().to_string()));
+    }
+
+    Ok(Edit::RenamePayee {
+        key: payee.key(),
+        name: new_name.to_string(),
+    })
+}
+
+/// Find the payees matching `pattern`, and the payee they are merged into.
+fn plan_merge(db: &HomeBankDb, pattern: &Regex, into: &str) -> Result<Vec<Edit>, EditError> {
+    let into = into.trim();
+    if into.is_empty() {
+        return Err(EditError::InvalidPayeeName(into.to_string()));
+    }
+
+    let target = db.payee_by_name(into).map(|payee| payee.key());
+    let mut sources: Vec<usize> = db
+        .payees()
+        .values()
+        .filter(|payee| pattern.is_match(payee.name()) && Some(payee.key()) != target)
+        .map(|payee| payee.key())
+        .collect();
+
+    if sources.is_empty() {
+        return Err(EditError::NoMatchingPayees(pattern.to_string()));
+    }
+
+    let mut edits = vec![];
+    let target = match target {
+        Some(key) => key,
+        None => {
+            let key = sources.remove(0);
+            edits.push(Edit::RenamePayee { key, name: into.to_string() });
+            key
+        }
+    };
+
+    if !sources.is_empty() {
+        edits.push(Edit::MergePayees { from: sources, into: target });
+    }
+
+    Ok(edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    #[track_caller]
+    fn check_plan(edit: EditPayee, expected: Result<Vec<Edit>, EditError>) {
+        let db = example_db();
+
+        assert_eq!(expected, edit.plan(&db));
+    }
+
+    #[test]
+    fn plan_renames() {
+        check_plan(
+            EditPayee::rename("grocer", "Corner Store"),
+            Ok(vec![Edit::RenamePayee { key: 3, name: "Corner Store".to_string() }]),
+        );
+        check_plan(EditPayee::rename("Grocer", "landlord"), Err(EditError::PayeeNameTaken("Landlord".to_string())));
+        check_plan(EditPayee::rename("Baker", "Bakery"), Err(EditError::UnknownPayee("Baker".to_string())));
+    }
+
+    #[test]
+    fn plan_merge_into_existing() {
+        check_plan(
+            EditPayee::merge(Regex::new("^(Grocer|Landlord|Employer)$").unwrap(), "landlord"),
+            Ok(vec![Edit::MergePayees { from: vec![1, 3], into: 2 }]),
+        );
+        check_plan(
+            EditPayee::merge(Regex::new("^Landlord$").unwrap(), "Landlord"),
+            Err(EditError::NoMatchingPayees("^Landlord$".to_string())),
+        );
+    }
+
+    #[test]
+    fn plan_merge_into_new_payee() {
+        check_plan(
+            EditPayee::merge(Regex::new("^(Grocer|Landlord)$").unwrap(), "Bills"),
+            Ok(vec![
+                Edit::RenamePayee { key: 2, name: "Bills".to_string() },
+                Edit::MergePayees { from: vec![3], into: 2 },
+            ]),
+        );
+        check_plan(
+            EditPayee::merge(Regex::new("^Grocer$").unwrap(), "Corner Store"),
+            Ok(vec![Edit::RenamePayee { key: 3, name: "Corner Store".to_string() }]),
+        );
+    }
+}
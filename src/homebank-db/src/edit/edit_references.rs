@@ -0,0 +1,814 @@
+//! Rewrite the elements of a HomeBank file that refer to categories, payees, or tags.
+//!
+//! Like [`append_to_xhb`][crate::db::db_write::append_to_xhb], the edits are made to the original text,
+//! so the elements and attributes that aren't parsed into a [`HomeBankDb`][crate::HomeBankDb] are kept exactly as they were.
+
+use crate::{
+    category::category_struct::{GF_CUMULATIVE, GF_CUSTOM},
+    db::{
+        db_open::{is_gzipped, xml_from_bytes},
+        db_write::{escape, write_xhb},
+        HomeBankDbError,
+    },
+    transaction::is_tag_separator,
+};
+use std::{collections::BTreeMap, fmt, fs, path::Path};
+
+/// Attributes holding the budget of a category.
+const BUDGET_ATTRIBUTES: [&str; 13] = ["b0", "b1", "b2", "b3", "b4", "b5", "b6", "b7", "b8", "b9", "b10", "b11", "b12"];
+
+/// Flags of a category that describe its budget, and move along with it.
+const BUDGET_FLAGS: usize = GF_CUSTOM | GF_CUMULATIVE;
+
+/// Elements with a `tags` attribute, holding a list of tags separated by spaces.
+const TAGGED_ELEMENTS: [&str; 2] = ["ope", "fav"];
+
+/// A change to the HomeBank file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edit {
+    /// Give the category with the key a new name, keeping its parent.
+    RenameCategory { key: usize, name: String },
+
+    /// Point everything that refers to the category `from` to the category `into` instead, then remove `from`.
+    MergeCategories { from: usize, into: usize },
+
+    /// Give the payee with the key a new name.
+    RenamePayee { key: usize, name: String },
+
+    /// Point everything that refers to any of the payees `from` to the payee `into` instead, then remove them.
+    ///
+    /// The payee `into` must already exist in the file, so [`EditPayee::plan`][crate::edit::edit_payee::EditPayee::plan] renames one of `from` to create it.
+    /// [`HomeBankDb::merge_payees`][crate::HomeBankDb::merge_payees] makes the same change to a database in memory.
+    MergePayees { from: Vec<usize>, into: usize },
+
+    /// Replace the tag `from` with the tag `into` on every transaction and template, ignoring the case of `from` with `ignore_case`.
+    RenameTag { from: String, into: String, ignore_case: bool },
+
+    /// Remove the tag from every transaction and template, ignoring its case with `ignore_case`.
+    RemoveTag { tag: String, ignore_case: bool },
+}
+
+impl Edit {
+    /// Retrieve the kind of item being edited, and the keys of the items whose references are rewritten or counted.
+    /// Tags are written out on each transaction rather than referred to by a key, so edits to them have no reference.
+    pub fn reference(&self) -> Option<(ReferenceKind, Vec<usize>)> {
+        match self {
+            Self::RenameCategory { key, .. } => Some((ReferenceKind::Category, vec![*key])),
+            Self::MergeCategories { from, .. } => Some((ReferenceKind::Category, vec![*from])),
+            Self::RenamePayee { key, .. } => Some((ReferenceKind::Payee, vec![*key])),
+            Self::MergePayees { from, .. } => Some((ReferenceKind::Payee, from.clone())),
+            Self::RenameTag { .. } | Self::RemoveTag { .. } => None,
+        }
+    }
+
+    /// Apply the change to the XML text of a HomeBank file.
+    /// Returns `None` if the text isn't a complete HomeBank file.
+    pub fn apply(&self, xml: &str) -> Option<(String, EditSummary)> {
+        let tags = scan_tags(xml)?;
+        let reference = self.reference();
+        let mut summary = EditSummary::default();
+
+        let budget = match self {
+            Self::MergeCategories { from, into } => budget_to_move(&tags, *from, *into, &mut summary),
+            _ => None,
+        };
+
+        let mut out = String::with_capacity(xml.len());
+        let mut copied = 0;
+
+        for tag in tags {
+            let (start, end) = (tag.start, tag.end);
+            let end_tag = (!tag.self_closing).then(|| format!("</{}>", tag.name));
+
+            if let Some((kind, keys)) = &reference {
+                if tag.name != kind.element() && tag.refers_to(*kind, keys) {
+                    *summary.references.entry(tag.name.clone()).or_default() += 1;
+                }
+            }
+
+            let updated = match self {
+                Self::RenameCategory { key, name } => rename(tag, ReferenceKind::Category, *key, name),
+                Self::MergeCategories { from, into } => {
+                    merge(tag, ReferenceKind::Category, &[*from], *into, budget.as_ref(), &mut summary)
+                }
+                Self::RenamePayee { key, name } => rename(tag, ReferenceKind::Payee, *key, name),
+                Self::MergePayees { from, into } => merge(tag, ReferenceKind::Payee, from, *into, None, &mut summary),
+                Self::RenameTag { from, into, ignore_case } => retag(tag, from, Some(into), *ignore_case, &mut summary),
+                Self::RemoveTag { tag: name, ignore_case } => retag(tag, name, None, *ignore_case, &mut summary),
+            };
+
+            match updated {
+                Change::Keep => (),
+                Change::Replace(text) => {
+                    out.push_str(&xml[copied..start]);
+                    out.push_str(&text);
+                    copied = end;
+                }
+                Change::Remove => {
+                    let end = match &end_tag {
+                        Some(end_tag) => element_end(xml, end, end_tag)?,
+                        None => end,
+                    };
+                    out.push_str(&xml[copied..start]);
+                    copied = end + line_ending(&xml[end..]).len();
+                }
+            }
+        }
+
+        out.push_str(&xml[copied..]);
+
+        Some((out, summary))
+    }
+}
+
+/// Apply changes to the HomeBank file at `path` one after the other, and count what they change.
+///
+/// With `dry_run`, the file is read but left unchanged.
+/// A gzipped file stays gzipped, but the text is always written as UTF-8.
+pub fn edit_xhb(path: &Path, edits: &[Edit], dry_run: bool) -> Result<EditSummary, HomeBankDbError> {
+    let raw = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(_) => return Err(HomeBankDbError::CouldNotOpen(path.to_path_buf())),
+    };
+    let gzipped = is_gzipped(&raw);
+    let mut updated = xml_from_bytes(raw)?;
+    let mut summary = EditSummary::default();
+
+    for edit in edits {
+        let (text, changed) = match edit.apply(&updated) {
+            Some(applied) => applied,
+            None => return Err(HomeBankDbError::CouldNotParse(path.to_path_buf())),
+        };
+
+        updated = text;
+        summary.add(changed);
+    }
+
+    if !dry_run {
+        write_xhb(path, updated, gzipped)?;
+    }
+
+    Ok(summary)
+}
+
+/// Kinds of items that other elements of the HomeBank file refer to by their key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceKind {
+    Category,
+    Payee,
+}
+
+impl ReferenceKind {
+    /// Element that defines items of this kind
+    pub fn element(&self) -> &'static str {
+        match self {
+            Self::Category => "cat",
+            Self::Payee => "pay",
+        }
+    }
+
+    /// Attributes holding the key of a single item of this kind
+    fn attributes(&self) -> &'static [&'static str] {
+        match self {
+            Self::Category => &["category", "car_category"],
+            Self::Payee => &["payee"],
+        }
+    }
+
+    /// Attribute holding the keys of several items of this kind, separated by `||`, as in split transactions
+    fn list_attribute(&self) -> Option<&'static str> {
+        match self {
+            Self::Category => Some("scat"),
+            Self::Payee => None,
+        }
+    }
+}
+
+/// What an edit changed in the HomeBank file.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EditSummary {
+    /// Number of elements that refer to the edited item, by element name, e.g. `ope` for transactions.
+    references: BTreeMap<String, usize>,
+
+    /// Whether the budget of a merged category was moved to the category it was merged into.
+    moved_budget: bool,
+
+    /// Whether the budget of a merged category was dropped, because the category it was merged into has its own.
+    dropped_budget: bool,
+}
+
+impl EditSummary {
+    /// Retrieve the number of elements that refer to the edited item, by element name
+    pub fn references(&self) -> &BTreeMap<String, usize> {
+        &self.references
+    }
+
+    /// Retrieve the number of elements with this name that refer to the edited item
+    pub fn count(&self, element: &str) -> usize {
+        self.references.get(element).copied().unwrap_or_default()
+    }
+
+    /// Check whether the budget of a merged category was moved to the category it was merged into
+    pub fn moved_budget(&self) -> bool {
+        self.moved_budget
+    }
+
+    /// Check whether the budget of a merged category was dropped, because the category it was merged into has its own
+    pub fn dropped_budget(&self) -> bool {
+        self.dropped_budget
+    }
+
+    /// Add what another edit changed
+    fn add(&mut self, other: EditSummary) {
+        for (element, count) in other.references {
+            *self.references.entry(element).or_default() += count;
+        }
+        self.moved_budget |= other.moved_budget;
+        self.dropped_budget |= other.dropped_budget;
+    }
+}
+
+impl fmt::Display for EditSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "  {} transaction(s)", self.count("ope"))?;
+
+        for (element, count) in self.references.iter().filter(|(element, _)| *element != "ope") {
+            let label = match element.as_str() {
+                "cat" => "subcategory(ies)",
+                "pay" => "payee default(s)",
+                "fav" => "template(s)",
+                "asg" => "assignment rule(s)",
+                "properties" => "file setting(s)",
+                other => other,
+            };
+            writeln!(f, "  {count} {label}")?;
+        }
+
+        if self.moved_budget {
+            writeln!(f, "  the budget was moved")?;
+        }
+        if self.dropped_budget {
+            writeln!(f, "  the budget was dropped, keeping the existing one")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A start tag in the text of a HomeBank file, with the raw (still escaped) values of its attributes.
+#[derive(Debug, Clone, PartialEq)]
+struct Tag {
+    /// Name of the element.
+    name: String,
+
+    /// Attributes in the order they appear.
+    attributes: Vec<(String, String)>,
+
+    /// Whether the element is closed in the same tag, e.g. `<pay key="1" name="Grocer"/>`.
+    self_closing: bool,
+
+    /// Position of the opening `<`.
+    start: usize,
+
+    /// Position just after the closing `>`.
+    end: usize,
+}
+
+impl Tag {
+    /// Retrieve the raw value of an attribute
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(attr, _)| attr == name).map(|(_, value)| value.as_str())
+    }
+
+    /// Retrieve the value of an attribute holding a key
+    fn key_attr(&self, name: &str) -> Option<usize> {
+        self.attr(name).and_then(|value| value.parse().ok())
+    }
+
+    /// Set the raw value of an attribute, adding it at the end if the tag doesn't have it
+    fn set_attr(&mut self, name: &str, value: String) {
+        match self.attributes.iter_mut().find(|(attr, _)| attr == name) {
+            Some((_, old)) => *old = value,
+            None => self.attributes.push((name.to_string(), value)),
+        }
+    }
+
+    /// Check whether the attribute holds one of the keys
+    fn has_key(&self, attr: &str, keys: &[usize]) -> bool {
+        self.key_attr(attr).is_some_and(|key| keys.contains(&key))
+    }
+
+    /// Check whether any of the attributes holding keys of `kind` refer to one of the keys
+    fn refers_to(&self, kind: ReferenceKind, keys: &[usize]) -> bool {
+        let single = kind.attributes().iter().any(|attr| self.has_key(attr, keys));
+        let listed = kind
+            .list_attribute()
+            .and_then(|attr| self.attr(attr))
+            .is_some_and(|list| list.split("||").any(|k| k.parse().is_ok_and(|key| keys.contains(&key))));
+
+        single || listed
+    }
+
+    /// Point the attributes holding keys of `kind` from any of the keys `from` to `into`
+    fn repoint(&mut self, kind: ReferenceKind, from: &[usize], into: usize) {
+        for attr in kind.attributes() {
+            if self.has_key(attr, from) {
+                self.set_attr(attr, into.to_string());
+            }
+        }
+
+        if let Some(attr) = kind.list_attribute() {
+            if let Some(keys) = self.attr(attr) {
+                let keys: Vec<String> = keys
+                    .split("||")
+                    .map(|k| match k.parse().is_ok_and(|key| from.contains(&key)) {
+                        true => into.to_string(),
+                        false => k.to_string(),
+                    })
+                    .collect();
+                self.set_attr(attr, keys.join("||"));
+            }
+        }
+    }
+
+    /// Write the tag back as text
+    fn to_text(&self) -> String {
+        let mut text = format!("<{}", self.name);
+
+        for (attr, value) in &self.attributes {
+            match value.contains('"') {
+                true => text.push_str(&format!(" {attr}='{value}'")),
+                false => text.push_str(&format!(r#" {attr}="{value}""#)),
+            }
+        }
+        text.push_str(if self.self_closing { "/>" } else { ">" });
+
+        text
+    }
+}
+
+/// How a tag is changed by an edit.
+#[derive(Debug, PartialEq)]
+enum Change {
+    Keep,
+    Replace(String),
+    Remove,
+}
+
+/// Give the element defining the item of `kind` with the key a new name.
+fn rename(mut tag: Tag, kind: ReferenceKind, key: usize, name: &str) -> Change {
+    match tag.name == kind.element() && tag.key_attr("key") == Some(key) {
+        true => {
+            tag.set_attr("name", escape(name));
+            Change::Replace(tag.to_text())
+        }
+        false => Change::Keep,
+    }
+}
+
+/// Point a tag that refers to any of `from` to `into`, or remove it if it defines one of them.
+/// When merging categories, the subcategories of `from` are moved under `into`, along with its budget.
+fn merge(
+    mut tag: Tag,
+    kind: ReferenceKind,
+    from: &[usize],
+    into: usize,
+    budget: Option<&Budget>,
+    summary: &mut EditSummary,
+) -> Change {
+    if tag.name != kind.element() {
+        return match tag.refers_to(kind, from) {
+            true => {
+                tag.repoint(kind, from, into);
+                Change::Replace(tag.to_text())
+            }
+            false => Change::Keep,
+        };
+    }
+
+    if tag.has_key("key", from) {
+        return Change::Remove;
+    }
+
+    if kind == ReferenceKind::Category && tag.has_key("parent", from) {
+        *summary.references.entry(tag.name.clone()).or_default() += 1;
+        tag.set_attr("parent", into.to_string());
+        return Change::Replace(tag.to_text());
+    }
+
+    match budget {
+        Some(budget) if tag.key_attr("key") == Some(into) => {
+            tag.attributes.retain(|(attr, _)| !BUDGET_ATTRIBUTES.contains(&attr.as_str()));
+            let flags = tag.key_attr("flags").unwrap_or_default() & !BUDGET_FLAGS;
+            tag.set_attr("flags", (flags | budget.flags).to_string());
+            tag.attributes.extend(budget.amounts.iter().cloned());
+            Change::Replace(tag.to_text())
+        }
+        _ => Change::Keep,
+    }
+}
+
+/// Replace the tag `from` in the tag list of a transaction or template with `into`, or remove it if there is no `into`.
+///
+/// Only the matching words are changed, and the rest of the list is kept as it was written.
+/// A word renamed to a tag the list already has is removed instead, so the tag is kept only once,
+/// and the `tags` attribute is removed once it has no tags left.
+fn retag(mut tag: Tag, from: &str, into: Option<&str>, ignore_case: bool, summary: &mut EditSummary) -> Change {
+    if !TAGGED_ELEMENTS.contains(&tag.name.as_str()) {
+        return Change::Keep;
+    }
+    let Some(list) = tag.attr("tags") else {
+        return Change::Keep;
+    };
+
+    // the tag list is compared with its raw (still escaped) value
+    let (from, into) = (escape(from), into.map(escape));
+    let same = |word: &str| match ignore_case {
+        true => word.to_lowercase() == from.to_lowercase(),
+        false => word == from,
+    };
+    let tokens = tag_tokens(list);
+
+    if !tokens.iter().any(|(is_word, text)| *is_word && same(text)) {
+        return Change::Keep;
+    }
+    *summary.references.entry(tag.name.clone()).or_default() += 1;
+
+    // the renamed tag is kept only once, and not at all if the list already has it
+    let mut has_into = into.as_ref().is_some_and(|into| {
+        tokens
+            .iter()
+            .any(|(is_word, text)| *is_word && !same(text) && text.to_lowercase() == into.to_lowercase())
+    });
+
+    let mut updated: Vec<(bool, &str)> = vec![];
+    let mut skip_separator = false;
+    for (i, &(is_word, text)) in tokens.iter().enumerate() {
+        if !is_word {
+            if !std::mem::take(&mut skip_separator) {
+                updated.push((false, text));
+            }
+            continue;
+        }
+
+        match &into {
+            _ if !same(text) => updated.push((true, text)),
+            Some(into) if !has_into => {
+                has_into = true;
+                updated.push((true, into));
+            }
+            // remove the word along with the separator after it, or before it if no word follows
+            _ if tokens[i + 1..].iter().any(|(is_word, _)| *is_word) => skip_separator = true,
+            _ => {
+                if updated.last().is_some_and(|(is_word, _)| !is_word) {
+                    updated.pop();
+                }
+            }
+        }
+    }
+
+    let updated: String = updated.into_iter().map(|(_, text)| text).collect();
+    match updated.trim().is_empty() {
+        true => tag.attributes.retain(|(attr, _)| attr != "tags"),
+        false => tag.set_attr("tags", updated),
+    }
+
+    Change::Replace(tag.to_text())
+}
+
+/// Split a tag list into its words and the separators between them, as `(is_word, text)`, keeping every character.
+fn tag_tokens(list: &str) -> Vec<(bool, &str)> {
+    let mut tokens: Vec<(bool, &str)> = vec![];
+    let mut rest = list;
+
+    while let Some(c) = rest.chars().next() {
+        let is_word = !is_tag_separator(c);
+        let len = rest.find(|c: char| is_tag_separator(c) == is_word).unwrap_or(rest.len());
+        tokens.push((is_word, &rest[..len]));
+        rest = &rest[len..];
+    }
+
+    tokens
+}
+
+/// The budget of a category, as the raw attributes holding its amounts and the flags describing it.
+#[derive(Debug, Clone, PartialEq)]
+struct Budget {
+    amounts: Vec<(String, String)>,
+    flags: usize,
+}
+
+/// Find the budget of the category `from`, if it should be moved to the category `into`.
+/// It is only moved if `into` has no budget of its own, otherwise the budget of `into` is kept.
+fn budget_to_move(tags: &[Tag], from: usize, into: usize, summary: &mut EditSummary) -> Option<Budget> {
+    let category = |key: usize| {
+        tags.iter()
+            .find(|tag| tag.name == "cat" && tag.key_attr("key") == Some(key))
+    };
+    let budget_of = |tag: &Tag| -> Vec<(String, String)> {
+        tag.attributes
+            .iter()
+            .filter(|(attr, value)| BUDGET_ATTRIBUTES.contains(&attr.as_str()) && value.parse::<f32>().is_ok_and(|v| v != 0.0))
+            .cloned()
+            .collect()
+    };
+
+    let from_tag = category(from)?;
+    let amounts = budget_of(from_tag);
+    if amounts.is_empty() {
+        return None;
+    }
+
+    match category(into).is_some_and(|tag| !budget_of(tag).is_empty()) {
+        true => {
+            summary.dropped_budget = true;
+            None
+        }
+        false => {
+            summary.moved_budget = true;
+            Some(Budget {
+                amounts,
+                flags: from_tag.key_attr("flags").unwrap_or_default() & BUDGET_FLAGS,
+            })
+        }
+    }
+}
+
+/// Find the start tags of every element in the XML text, skipping comments, declarations, and closing tags.
+/// Returns `None` if a tag isn't complete.
+fn scan_tags(xml: &str) -> Option<Vec<Tag>> {
+    let mut tags = vec![];
+    let mut pos = 0;
+
+    while let Some(offset) = xml[pos..].find('<') {
+        let start = pos + offset;
+        let rest = &xml[start..];
+
+        pos = if rest.starts_with("<!--") {
+            start + rest.find("-->")? + 3
+        } else if rest.starts_with("<?") || rest.starts_with("<!") || rest.starts_with("</") {
+            start + rest.find('>')? + 1
+        } else {
+            let tag = parse_tag(xml, start)?;
+            let end = tag.end;
+            tags.push(tag);
+            end
+        };
+    }
+
+    Some(tags)
+}
+
+/// Read the start tag beginning at `start`.
+fn parse_tag(xml: &str, start: usize) -> Option<Tag> {
+    let name_start = start + 1;
+    let name_end = name_start + xml[name_start..].find(|c: char| c.is_whitespace() || c == '/' || c == '>')?;
+    let mut tag = Tag {
+        name: xml[name_start..name_end].to_string(),
+        attributes: vec![],
+        self_closing: false,
+        start,
+        end: name_end,
+    };
+
+    let mut pos = name_end;
+    loop {
+        let rest = xml[pos..].trim_start();
+        pos = xml.len() - rest.len();
+
+        if rest.starts_with("/>") || rest.starts_with('>') {
+            tag.self_closing = rest.starts_with("/>");
+            tag.end = pos + if tag.self_closing { 2 } else { 1 };
+            return Some(tag);
+        }
+
+        let eq = rest.find('=')?;
+        let attr = rest[..eq].trim();
+        let value = rest[eq + 1..].trim_start();
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value_start = xml.len() - value.len() + 1;
+        let value_end = value_start + xml[value_start..].find(quote)?;
+
+        if attr.is_empty() {
+            return None;
+        }
+        tag.attributes.push((attr.to_string(), xml[value_start..value_end].to_string()));
+        pos = value_end + 1;
+    }
+}
+
+/// Find the end of an element that isn't self-closing, from the end of its start tag, e.g. `<cat key="1" name="Food"></cat>`.
+/// Returns `None` if the element has children, which the elements being removed never have.
+fn element_end(xml: &str, start_tag_end: usize, end_tag: &str) -> Option<usize> {
+    let content = &xml[start_tag_end..];
+    let len = content.find(end_tag)?;
+
+    match content[..len].trim().is_empty() {
+        true => Some(start_tag_end + len + end_tag.len()),
+        false => None,
+    }
+}
+
+/// Retrieve the line ending at the start of the text, if there is one.
+fn line_ending(text: &str) -> &str {
+    match text {
+        t if t.starts_with("\r\n") => "\r\n",
+        t if t.starts_with('\n') => "\n",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HomeBankDb;
+
+    fn example_xml() -> String {
+        fs::read_to_string("tests/transactions.xhb").unwrap()
+    }
+
+    #[track_caller]
+    fn check_apply(edit: Edit) -> (HomeBankDb, EditSummary) {
+        let (updated, summary) = edit.apply(&example_xml()).unwrap();
+
+        (HomeBankDb::from_reader(updated.as_bytes()).unwrap(), summary)
+    }
+
+    #[test]
+    fn parse_tags() {
+        let xml = r#"<?xml version="1.0"?><!-- <ope category="2"/> --><homebank v="1.4">
+<cat key="1" parent = '3' name="Fish &amp; chips"/></homebank>"#;
+        let tags = scan_tags(xml).unwrap();
+
+        assert_eq!(vec!["homebank", "cat"], tags.iter().map(|t| t.name.as_str()).collect::<Vec<_>>());
+        assert_eq!(Some(3), tags[1].key_attr("parent"));
+        assert_eq!(Some("Fish &amp; chips"), tags[1].attr("name"));
+        assert!(tags[1].self_closing);
+        assert_eq!(r#"<cat key="1" parent="3" name="Fish &amp; chips"/>"#, tags[1].to_text());
+        assert_eq!(None, scan_tags(r#"<homebank><cat key="1"</homebank>"#));
+    }
+
+    #[test]
+    fn rename_keeps_everything_else() {
+        let xml = example_xml();
+        let (updated, summary) = Edit::RenameCategory { key: 5, name: "Fish & chips".to_string() }.apply(&xml).unwrap();
+
+        assert_eq!(
+            xml.replace(r#"name="Dining""#, r#"name="Fish &amp; chips""#),
+            updated
+        );
+        assert_eq!(2, summary.count("ope"));
+    }
+
+    #[test]
+    fn merge_categories() {
+        let (db, summary) = check_apply(Edit::MergeCategories { from: 5, into: 4 });
+
+        assert_eq!(None, db.category(5));
+        assert_eq!(2, summary.count("ope"));
+        assert!(!summary.moved_budget());
+        assert!(db.transactions().iter().all(|tr| !tr.categories().contains(&&Some(5))));
+        assert_eq!(3, db.transactions().iter().filter(|tr| tr.categories().contains(&&Some(4))).count());
+    }
+
+    #[test]
+    fn merge_moves_subcategories_and_budget() {
+        let (db, summary) = check_apply(Edit::MergeCategories { from: 3, into: 7 });
+
+        assert_eq!(2, summary.count("cat"));
+        assert_eq!(Some(7), db.category(4).unwrap().parent_key());
+        assert_eq!(Some(7), db.category(5).unwrap().parent_key());
+
+        let (db, summary) = check_apply(Edit::MergeCategories { from: 4, into: 5 });
+        assert!(summary.moved_budget());
+        assert_eq!(Some(-300.0), db.category(5).unwrap().budget_amount(1));
+
+        let (db, summary) = check_apply(Edit::MergeCategories { from: 4, into: 2 });
+        assert!(summary.dropped_budget());
+        assert_eq!(Some(-1200.0), db.category(2).unwrap().budget_amount(1));
+    }
+
+    #[test]
+    fn merge_payees() {
+        let (db, summary) = check_apply(Edit::MergePayees { from: vec![2, 3], into: 4 });
+
+        assert_eq!(6, summary.count("ope"));
+        assert_eq!(vec!["Employer", "Amazon.com (Prime)"], db.payees().values().map(|p| p.name()).collect::<Vec<_>>());
+        assert_eq!(7, db.transactions().iter().filter(|tr| *tr.payee() == Some(4)).count());
+
+        let (db, _) = check_apply(Edit::RenamePayee { key: 4, name: "Amazon".to_string() });
+        assert_eq!(Some("Amazon"), db.payee_name(4));
+    }
+
+    #[test]
+    fn rename_tag() {
+        let (db, summary) = check_apply(Edit::RenameTag {
+            from: "FOOD".to_string(),
+            into: "groceries".to_string(),
+            ignore_case: true,
+        });
+
+        assert_eq!(2, summary.count("ope"));
+        assert_eq!(vec!["groceries", "restaurant"], db.all_tags());
+
+        let (_, summary) = check_apply(Edit::RenameTag {
+            from: "FOOD".to_string(),
+            into: "groceries".to_string(),
+            ignore_case: false,
+        });
+        assert_eq!(0, summary.count("ope"));
+    }
+
+    #[test]
+    fn rename_tag_into_existing_one() {
+        let (updated, _) = Edit::RenameTag {
+            from: "restaurant".to_string(),
+            into: "Food".to_string(),
+            ignore_case: false,
+        }
+        .apply(&example_xml())
+        .unwrap();
+
+        assert!(updated.contains(r#"category="5" tags="food"/>"#));
+    }
+
+    #[test]
+    fn remove_tag() {
+        let (updated, summary) = Edit::RemoveTag {
+            tag: "food".to_string(),
+            ignore_case: false,
+        }
+        .apply(&example_xml())
+        .unwrap();
+        let db = HomeBankDb::from_reader(updated.as_bytes()).unwrap();
+
+        assert_eq!(2, summary.count("ope"));
+        assert_eq!(vec!["restaurant"], db.all_tags());
+        // the attribute is removed along with the last tag
+        assert!(updated.contains(r#"category="4" wording="Weekly groceries"/>"#));
+    }
+
+    #[test]
+    fn merge_removes_element_with_end_tag() {
+        let xml = "<homebank>\n<pay key=\"1\" name=\"A\"></pay>\n<pay key=\"2\" name=\"B\"/>\n</homebank>\n";
+        let (updated, _) = Edit::MergePayees { from: vec![1], into: 2 }.apply(xml).unwrap();
+
+        assert_eq!("<homebank>\n<pay key=\"2\" name=\"B\"/>\n</homebank>\n", updated);
+        assert_eq!(None, Edit::MergePayees { from: vec![1], into: 2 }.apply("<homebank><pay key=\"1\"><x/></pay></homebank>"));
+    }
+
+    #[track_caller]
+    fn check_retag(list: &str, edit: Edit, expected: Option<&str>) {
+        let xml = format!(r#"<ope date="1" tags="{list}"/>"#);
+        let (updated, _) = edit.apply(&xml).unwrap();
+        let expected = match expected {
+            Some(tags) => format!(r#"<ope date="1" tags="{tags}"/>"#),
+            None => r#"<ope date="1"/>"#.to_string(),
+        };
+
+        assert_eq!(expected, updated);
+    }
+
+    #[test]
+    fn retag_keeps_rest_of_list() {
+        let rename = |from: &str, into: &str| Edit::RenameTag {
+            from: from.to_string(),
+            into: into.to_string(),
+            ignore_case: false,
+        };
+        let remove = |tag: &str| Edit::RemoveTag {
+            tag: tag.to_string(),
+            ignore_case: false,
+        };
+
+        check_retag("food,restaurant", rename("food", "meals"), Some("meals,restaurant"));
+        check_retag("food Food weekly", remove("food"), Some("Food weekly"));
+        check_retag("weekly, food", remove("food"), Some("weekly"));
+        check_retag("food weekly food", rename("food", "weekly"), Some("weekly"));
+        check_retag("food", remove("food"), None);
+    }
+
+    #[test]
+    fn merge_rewrites_other_elements() {
+        let xml = r#"<?xml version="1.0"?>
+<homebank v="1.4" d="050504">
+<properties title="Cars" curr="1" car_category="2"/>
+<pay key="1" name="Garage" category="2"/>
+<cat key="1" flags="0" name="Car"/>
+<cat key="2" flags="0" name="Fuel"/>
+<fav key="1" amount="-40" account="1" paymode="1" flags="0" payee="1" category="2" wording="Fill up" nextdate="738200" unit="2" every="1" limit="0" weekend="0"/>
+<asg key="1" flags="1" field="0" name="Shell" category="2"/>
+</homebank>"#;
+        let (updated, summary) = Edit::MergeCategories { from: 2, into: 1 }.apply(xml).unwrap();
+
+        assert_eq!(
+            xml.replace(r#"category="2""#, r#"category="1""#)
+                .replace("<cat key=\"2\" flags=\"0\" name=\"Fuel\"/>\n", ""),
+            updated
+        );
+        assert_eq!(
+            vec![("asg", 1), ("fav", 1), ("pay", 1), ("properties", 1)],
+            summary.references().iter().map(|(e, n)| (e.as_str(), *n)).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! Rename or remove a tag on every transaction.
+
+use super::{Edit, EditError};
+use crate::{transaction::TAG_SEPARATOR, HomeBankDb};
+use clap::Parser;
+
+/// Rename or remove a tag on every transaction.
+#[derive(Debug, Parser)]
+#[command(about = "Rename or remove a tag on every transaction and template")]
+pub struct EditTag {
+    /// Tag, followed by its new name, e.g. `--rename holidays holiday`. A transaction that already has the new tag keeps only one.
+    #[arg(
+        long = "rename",
+        num_args = 2,
+        value_names = ["tag", "new tag"],
+        required_unless_present = "remove",
+        conflicts_with = "remove"
+    )]
+    rename: Option<Vec<String>>,
+
+    /// Tag to remove.
+    #[arg(long = "remove", value_name = "tag")]
+    remove: Option<String>,
+
+    /// Match the tag being renamed or removed regardless of case, e.g. `holiday` also matches `Holiday`.
+    #[arg(long = "case-insensitive")]
+    case_insensitive: bool,
+
+    /// Print what would change without changing the HomeBank file.
+    #[arg(short = 'n', long = "dry-run")]
+    dry_run: bool,
+}
+
+impl EditTag {
+    /// Create an edit renaming the tag `tag` to `new_tag`
+    pub fn rename(tag: &str, new_tag: &str) -> Self {
+        Self {
+            rename: Some(vec![tag.to_string(), new_tag.to_string()]),
+            remove: None,
+            case_insensitive: false,
+            dry_run: false,
+        }
+    }
+
+    /// Create an edit removing the tag `tag`
+    pub fn remove(tag: &str) -> Self {
+        Self {
+            rename: None,
+            remove: Some(tag.to_string()),
+            case_insensitive: false,
+            dry_run: false,
+        }
+    }
+
+    /// Match the tag regardless of case
+    pub fn case_insensitive(mut self) -> Self {
+        self.case_insensitive = true;
+        self
+    }
+
+    /// Check whether the HomeBank file should be left unchanged
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Check that a transaction has the tag being edited, and that its new name is a valid tag.
+    pub fn plan(&self, db: &HomeBankDb) -> Result<Edit, EditError> {
+        let tag = match (&self.rename, &self.remove) {
+            (Some(names), _) if names.len() == 2 => &names[0],
+            (_, Some(tag)) => tag,
+            _ => return Err(EditError::UnknownTag(String::new())),
+        };
+
+        let known = match self.case_insensitive {
+            true => db.tags(true).contains_key(&tag.to_lowercase()),
+            false => db.tags(false).contains_key(tag),
+        };
+        if !known {
+            return Err(EditError::UnknownTag(tag.to_string()));
+        }
+
+        match &self.rename {
+            Some(names) => {
+                let into = names[1].trim();
+                if into.is_empty() || into.contains(|c: char| c == TAG_SEPARATOR || c == ',' || c.is_whitespace()) {
+                    return Err(EditError::InvalidTagName(into.to_string()));
+                }
+
+                Ok(Edit::RenameTag {
+                    from: tag.to_string(),
+                    into: into.to_string(),
+                    ignore_case: self.case_insensitive,
+                })
+            }
+            None => Ok(Edit::RemoveTag {
+                tag: tag.to_string(),
+                ignore_case: self.case_insensitive,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    #[track_caller]
+    fn check_plan(edit: EditTag, expected: Result<Edit, EditError>) {
+        let db = example_db();
+
+        assert_eq!(expected, edit.plan(&db));
+    }
+
+    #[test]
+    fn plan_rename() {
+        check_plan(
+            EditTag::rename("food", "groceries"),
+            Ok(Edit::RenameTag {
+                from: "food".to_string(),
+                into: "groceries".to_string(),
+                ignore_case: false,
+            }),
+        );
+        check_plan(EditTag::rename("food", "weekly shop"), Err(EditError::InvalidTagName("weekly shop".to_string())));
+    }
+
+    #[test]
+    fn plan_unknown_tag() {
+        check_plan(EditTag::remove("Food"), Err(EditError::UnknownTag("Food".to_string())));
+        check_plan(
+            EditTag::remove("Food").case_insensitive(),
+            Ok(Edit::RemoveTag {
+                tag: "Food".to_string(),
+                ignore_case: true,
+            }),
+        );
+    }
+}
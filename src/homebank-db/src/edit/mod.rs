@@ -0,0 +1,37 @@
+//! Edit categories, payees, and tags in the HomeBank database file, rewriting every element that refers to them.
+
+pub mod edit_category;
+pub mod edit_error;
+pub mod edit_payee;
+pub mod edit_references;
+pub mod edit_tag;
+
+pub use edit_category::EditCategory;
+pub use edit_error::EditError;
+pub use edit_payee::EditPayee;
+pub use edit_references::{edit_xhb, Edit, EditSummary, ReferenceKind};
+pub use edit_tag::EditTag;
+
+use clap::Parser;
+
+/// A subcommand to edit items in the HomeBank file from the CLI.
+#[derive(Debug, Parser)]
+pub struct EditOpts {
+    #[command(subcommand)]
+    edit_type: EditType,
+}
+
+impl EditOpts {
+    /// Retrieve the kind of item being edited
+    pub fn etype(&self) -> &EditType {
+        &self.edit_type
+    }
+}
+
+/// Differentiate between the different kinds of items to edit from the CLI
+#[derive(Debug, Parser)]
+pub enum EditType {
+    Category(EditCategory),
+    Payee(EditPayee),
+    Tag(EditTag),
+}
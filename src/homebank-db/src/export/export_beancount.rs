@@ -0,0 +1,482 @@
+//! Export the HomeBank database as a [beancount](https://beancount.github.io) ledger.
+
+use super::{exported_transactions, format_amount, opening_accounts, AccountNames, ASSETS, UNKNOWN};
+use crate::{HomeBankDb, Transaction};
+use chrono::NaiveDate;
+use clap::Parser;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+/// Account balancing the initial amounts of all accounts.
+const OPENING_BALANCES: &str = "Equity:Opening-Balances";
+
+/// Commodity used for currencies without a usable ISO code, which is the ISO code for "no currency".
+const NO_CURRENCY: &str = "XXX";
+
+/// Indentation of postings below the first line of a transaction.
+const INDENT: &str = "  ";
+
+/// Export transactions as a beancount ledger.
+#[derive(Debug, Parser)]
+#[command(about = "Export transactions as a beancount ledger")]
+pub struct ExportBeancount {
+    /// Include transactions starting from (and including) this date.
+    #[arg(
+        short = 'd',
+        long = "date-from",
+        value_parser = NaiveDate::from_str,
+        value_name = "date"
+    )]
+    date_from: Option<NaiveDate>,
+
+    /// Include transactions up to (and excluding) this date.
+    #[arg(
+        short = 'D',
+        long = "date-to",
+        value_parser = NaiveDate::from_str,
+        value_name = "date"
+    )]
+    date_to: Option<NaiveDate>,
+}
+
+impl ExportBeancount {
+    /// Create a new beancount export
+    pub fn new(date_from: Option<NaiveDate>, date_to: Option<NaiveDate>) -> Self {
+        Self { date_from, date_to }
+    }
+
+    /// Retrieve the earliest date of the exported transactions
+    pub fn date_from(&self) -> &Option<NaiveDate> {
+        &self.date_from
+    }
+
+    /// Retrieve the date that all exported transactions occur before
+    pub fn date_to(&self) -> &Option<NaiveDate> {
+        &self.date_to
+    }
+
+    /// Write the transactions in the [`HomeBankDb`] as a beancount ledger.
+    ///
+    /// Every account used by a posting is opened on the date of the first transaction, at the top of the ledger.
+    /// Each transfer is written as a single transaction, with a posting for each account.
+    /// If no starting date is given, the initial amounts of the accounts are written as an opening balance transaction.
+    pub fn ledger(&self, db: &HomeBankDb) -> String {
+        let ledger = Ledger::new(db);
+        let mut entries: Vec<Entry> = exported_transactions(db, self.date_from(), self.date_to())
+            .into_iter()
+            .map(|(tr, partner)| match tr.is_transfer() {
+                true => ledger.transfer_entry(tr, partner),
+                false => ledger.entry(tr),
+            })
+            .collect();
+
+        if self.date_from().is_none() {
+            if let Some(opening) = entries.first().and_then(|first| ledger.opening_entry(first.date)) {
+                entries.insert(0, opening);
+            }
+        }
+
+        ledger.render(&entries)
+    }
+}
+
+/// A single posting of a transaction.
+#[derive(Debug, PartialEq, Clone)]
+struct Posting {
+    /// Beancount account name, e.g. `Expenses:Food:Groceries`.
+    account: String,
+
+    /// Amount and commodity, or `None` to let beancount balance the transaction with it.
+    amount: Option<String>,
+
+    /// Comment written after the posting.
+    comment: Option<String>,
+}
+
+impl Posting {
+    /// Create a posting without a comment
+    fn new(account: String, amount: Option<String>) -> Self {
+        Self {
+            account,
+            amount,
+            comment: None,
+        }
+    }
+}
+
+/// A single transaction in the ledger.
+#[derive(Debug, PartialEq, Clone)]
+struct Entry {
+    /// Date of the transaction.
+    date: NaiveDate,
+
+    /// `*` for transactions that have cleared, `!` for the others.
+    flag: char,
+
+    /// Payee of the transaction, if any.
+    payee: Option<String>,
+
+    /// Description of the transaction.
+    narration: String,
+
+    /// Postings of the transaction, which must balance.
+    postings: Vec<Posting>,
+}
+
+impl Entry {
+    /// Write the transaction line and its postings.
+    fn render(&self) -> String {
+        let mut out = format!("{} {}", self.date.format("%Y-%m-%d"), self.flag);
+        if let Some(payee) = &self.payee {
+            out.push_str(&format!(" {}", quote(payee)));
+        }
+        out.push_str(&format!(" {}\n", quote(&self.narration)));
+
+        for posting in &self.postings {
+            out.push_str(INDENT);
+            out.push_str(&posting.account);
+            if let Some(amount) = &posting.amount {
+                out.push_str(&format!("  {amount}"));
+            }
+            if let Some(comment) = posting.comment.as_deref().filter(|c| !c.is_empty()) {
+                out.push_str(&format!("  ; {}", single_line(comment)));
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+}
+
+/// Names and commodities of the accounts and categories in a [`HomeBankDb`], as they are written in a beancount ledger.
+struct Ledger<'db> {
+    /// The database being exported.
+    db: &'db HomeBankDb,
+
+    /// Names of the accounts and categories.
+    names: AccountNames<'db>,
+
+    /// Commodity of each currency, by currency key.
+    commodities: HashMap<usize, String>,
+}
+
+impl<'db> Ledger<'db> {
+    /// Collect the commodities of all currencies in the database.
+    fn new(db: &'db HomeBankDb) -> Self {
+        let commodities = db
+            .currencies()
+            .iter()
+            .map(|(key, curr)| {
+                let iso = curr.iso().to_uppercase();
+                let commodity = if (2..=24).contains(&iso.len()) && iso.chars().all(|c| c.is_ascii_uppercase()) {
+                    iso
+                } else {
+                    NO_CURRENCY.to_string()
+                };
+
+                (*key, commodity)
+            })
+            .collect();
+
+        let names = AccountNames { db, level: account_component };
+
+        Self { db, names, commodities }
+    }
+
+    /// Retrieve the commodity of an account, falling back to the base currency.
+    fn commodity(&self, account: usize) -> &str {
+        self.names.commodity(&self.commodities, account).map_or(NO_CURRENCY, |c| c.as_str())
+    }
+
+    /// Write an amount in the commodity of an account, to the decimal places of its currency.
+    fn amount(&self, account: usize, amount: f32) -> String {
+        let decimal_len = self
+            .db
+            .account(account)
+            .and_then(|acct| self.db.currency(acct.currency()))
+            .map_or(2, |curr| curr.decimal_len());
+
+        format!("{} {}", format_amount(amount, decimal_len), self.commodity(account))
+    }
+
+    /// Start a transaction with its date, flag, payee, and narration, but no postings.
+    fn header(&self, tr: &Transaction, fallback: &str) -> Entry {
+        let flag = if tr.status().is_reconciled() || tr.status().is_cleared() { '*' } else { '!' };
+        let narration = match tr.memo() {
+            Some(memo) if !memo.is_empty() => memo.clone(),
+            _ => fallback.to_string(),
+        };
+
+        Entry {
+            date: *tr.date(),
+            flag,
+            payee: tr.payee_name(self.db),
+            narration,
+            postings: vec![],
+        }
+    }
+
+    /// Write an income or expense, with a posting for the account and one for each split.
+    fn entry(&self, tr: &Transaction) -> Entry {
+        let mut entry = self.header(tr, "");
+        let account = self.names.account(tr.account());
+
+        if tr.is_split() {
+            let splits = tr.categories().into_iter().zip(tr.amounts()).zip(tr.memos());
+            for ((cat, amount), memo) in splits {
+                entry.postings.push(Posting {
+                    account: self.names.category(cat, *amount),
+                    amount: Some(self.amount(tr.account(), -amount)),
+                    comment: memo.clone(),
+                });
+            }
+
+            // the account balances the splits, in case they don't add up to the total
+            entry.postings.push(Posting::new(account, None));
+        } else {
+            let cat = tr.categories().into_iter().next().cloned().flatten();
+            entry
+                .postings
+                .push(Posting::new(account, Some(self.amount(tr.account(), *tr.total()))));
+            entry.postings.push(Posting::new(self.names.category(&cat, *tr.total()), None));
+        }
+
+        entry
+    }
+
+    /// Write a transfer as a single transaction, with a posting for each account.
+    fn transfer_entry(&self, tr: &Transaction, partner: Option<&Transaction>) -> Entry {
+        let mut entry = self.header(tr, "Transfer");
+        let mut source = Posting::new(self.names.account(tr.account()), Some(self.amount(tr.account(), *tr.total())));
+
+        let destination = match (partner, tr.transfer_destination()) {
+            // between currencies, the total price converts the amount into the other commodity so that it balances
+            (Some(other), _) if self.commodity(other.account()) != self.commodity(tr.account()) => {
+                let price = self.amount(other.account(), other.total().abs());
+                source.amount = source.amount.map(|amount| format!("{amount} @@ {price}"));
+
+                Posting::new(self.names.account(other.account()), Some(self.amount(other.account(), *other.total())))
+            }
+            (Some(other), _) => Posting::new(self.names.account(other.account()), None),
+            (None, Some(dst)) => Posting::new(self.names.account(*dst), None),
+            (None, None) => Posting::new(format!("{ASSETS}:{UNKNOWN}"), None),
+        };
+
+        entry.postings = vec![source, destination];
+
+        entry
+    }
+
+    /// Write the initial amounts of all accounts as a single transaction.
+    /// Returns `None` if all accounts start from zero.
+    fn opening_entry(&self, date: NaiveDate) -> Option<Entry> {
+        let accounts = opening_accounts(self.db);
+        if accounts.is_empty() {
+            return None;
+        }
+
+        let mut postings: Vec<Posting> = accounts
+            .into_iter()
+            .map(|(key, acct)| Posting::new(self.names.account(key), Some(self.amount(key, acct.initial_amount()))))
+            .collect();
+        postings.push(Posting::new(OPENING_BALANCES.to_string(), None));
+
+        Some(Entry {
+            date,
+            flag: '*',
+            payee: None,
+            narration: "Opening balances".to_string(),
+            postings,
+        })
+    }
+
+    /// Write an `open` directive for every account used by a posting, followed by all transactions.
+    fn render(&self, entries: &[Entry]) -> String {
+        let Some(first) = entries.first() else {
+            return String::new();
+        };
+
+        // only accounts in the database hold a single commodity, categories can be used with any of them
+        let commodities: HashMap<String, &str> = self
+            .db
+            .accounts()
+            .keys()
+            .map(|key| (self.names.account(*key), self.commodity(*key)))
+            .collect();
+        let used_accounts: BTreeMap<&str, Option<&&str>> = entries
+            .iter()
+            .flat_map(|entry| entry.postings.iter())
+            .map(|posting| (posting.account.as_str(), commodities.get(&posting.account)))
+            .collect();
+
+        let mut out = String::new();
+        for (name, commodity) in used_accounts {
+            let opened = format!("{} open {name}", first.date.format("%Y-%m-%d"));
+            match commodity {
+                Some(c) => out.push_str(&format!("{opened} {c}\n")),
+                None => out.push_str(&format!("{opened}\n")),
+            }
+        }
+
+        for entry in entries {
+            out.push('\n');
+            out.push_str(&entry.render());
+        }
+
+        out
+    }
+}
+
+/// Make a name usable as a single component of a beancount account.
+///
+/// Components can only contain letters, digits, and dashes, and must start with a capital letter or a digit,
+/// so other characters are replaced by dashes and the first letter is capitalized.
+fn account_component(name: &str) -> String {
+    let dashed: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect();
+    let joined = dashed
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-");
+
+    let mut chars = joined.chars();
+    match chars.next() {
+        None => UNKNOWN.to_string(),
+        Some(c) if c.is_numeric() => joined,
+        Some(c) => {
+            let capitalized: String = c.to_uppercase().chain(chars).collect();
+
+            // letters without a capital form (e.g. in scripts without case) need a prefix
+            if capitalized.starts_with(char::is_uppercase) {
+                capitalized
+            } else {
+                format!("X-{capitalized}")
+            }
+        }
+    }
+}
+
+/// Keep free text on one line.
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Write free text as a beancount string, escaping quotes and backslashes.
+fn quote(text: &str) -> String {
+    format!("\"{}\"", single_line(text).replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    #[track_caller]
+    fn check_account_component(input: &str, expected: &str) {
+        assert_eq!(expected, account_component(input));
+    }
+
+    #[test]
+    fn component_with_spaces() {
+        check_account_component("Credit Card", "Credit-Card");
+    }
+
+    #[test]
+    fn component_with_punctuation() {
+        check_account_component("  amazon.com (Prime)", "Amazon-com-Prime");
+    }
+
+    #[test]
+    fn component_starting_with_digit() {
+        check_account_component("401(k)", "401-k");
+    }
+
+    #[test]
+    fn component_empty() {
+        check_account_component(" () ", "Unknown");
+    }
+
+    #[test]
+    fn quote_text() {
+        assert_eq!(r#""12\" sub \\ fries""#, quote("12\" sub \\\n fries"));
+    }
+
+    #[test]
+    fn ledger_split_and_transfer() {
+        let export = ExportBeancount::new(
+            NaiveDate::from_ymd_opt(2022, 1, 20),
+            NaiveDate::from_ymd_opt(2022, 2, 1),
+        );
+        let expected = r#"2022-01-20 open Assets:Chequing CAD
+2022-01-20 open Assets:Savings CAD
+2022-01-20 open Expenses:Food:Dining
+2022-01-20 open Expenses:Food:Groceries
+
+2022-01-20 * "Grocer" ""
+  Expenses:Food:Groceries  80.00 CAD  ; Groceries
+  Expenses:Food:Dining  20.00 CAD  ; Deli lunch
+  Assets:Chequing
+
+2022-01-25 * "Savings"
+  Assets:Chequing  -500.00 CAD
+  Assets:Savings
+"#;
+
+        assert_eq!(expected, export.ledger(&example_db()));
+    }
+
+    #[test]
+    fn ledger_opening_balances() {
+        let ledger = ExportBeancount::new(None, None).ledger(&example_db());
+        let expected = r#"
+2021-12-10 * "Opening balances"
+  Assets:Chequing  1000.00 CAD
+  Assets:Savings  5000.00 CAD
+  Liabilities:Credit-Card  -250.00 CAD
+  Equity:Opening-Balances
+
+2021-12-10 * "Landlord" "December rent"
+  Assets:Chequing  -1200.00 CAD
+  Expenses:Housing:Rent
+"#;
+
+        assert!(ledger.starts_with("2021-12-10 open Assets:Chequing CAD\n"), "{ledger}");
+        assert!(ledger.contains("2021-12-10 open Equity:Opening-Balances\n"), "{ledger}");
+        assert!(ledger.contains(expected), "{ledger}");
+    }
+
+    #[test]
+    fn ledger_income_category() {
+        let export = ExportBeancount::new(
+            NaiveDate::from_ymd_opt(2022, 1, 5),
+            NaiveDate::from_ymd_opt(2022, 1, 6),
+        );
+        let expected = r#"2022-01-05 open Assets:Chequing CAD
+2022-01-05 open Income:Salary
+
+2022-01-05 * "Employer" "Paycheque"
+  Assets:Chequing  2000.00 CAD
+  Income:Salary
+"#;
+
+        assert_eq!(expected, export.ledger(&example_db()));
+    }
+
+    #[test]
+    fn ledger_pending_transactions() {
+        let ledger = ExportBeancount::new(NaiveDate::from_ymd_opt(2022, 2, 10), None).ledger(&example_db());
+
+        assert!(ledger.contains("2022-02-14 ! \"Amazon.com (Prime)\" \"Streaming\"\n  Assets:Chequing  -45.50 CAD\n  Expenses:Entertainment\n"));
+        assert!(!ledger.contains("Opening balances"));
+    }
+
+    #[test]
+    fn ledger_empty() {
+        let export = ExportBeancount::new(NaiveDate::from_ymd_opt(2030, 1, 1), None);
+
+        assert_eq!("", export.ledger(&example_db()));
+    }
+}
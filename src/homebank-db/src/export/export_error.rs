@@ -0,0 +1,11 @@
+//! Errors when exporting the HomeBank database.
+
+use thiserror::Error;
+
+/// Errors when exporting the HomeBank database.
+#[derive(Debug, Error, PartialEq)]
+pub enum ExportError {
+    /// When a date format contains a specifier that is not supported.
+    #[error("Invalid date format `{0}`. See https://docs.rs/chrono/latest/chrono/format/strftime for the supported specifiers.")]
+    InvalidDateFormat(String),
+}
@@ -0,0 +1,503 @@
+//! Export the HomeBank database as a [ledger](https://ledger-cli.org) or [hledger](https://hledger.org) journal.
+
+use super::{exported_transactions, format_amount, opening_accounts, AccountNames, ASSETS, UNKNOWN};
+use crate::{HomeBankDb, Transaction};
+use chrono::NaiveDate;
+use clap::Parser;
+use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
+
+/// Account balancing the initial amounts of all accounts.
+const OPENING_BALANCES: &str = "Equity:Opening Balances";
+
+/// Indentation of postings below the first line of an entry.
+const INDENT: &str = "    ";
+
+/// Characters that can't be used as a commodity symbol without quoting it.
+const RESERVED_SYMBOLS: &str = "-+.,;:@=*!\"'()[]{}<>|#&%/\\^~`";
+
+/// Export transactions as a ledger or hledger journal.
+#[derive(Debug, Parser)]
+#[command(about = "Export transactions as a ledger/hledger journal")]
+pub struct ExportLedger {
+    /// Include transactions starting from (and including) this date.
+    #[arg(
+        short = 'd',
+        long = "date-from",
+        value_parser = NaiveDate::from_str,
+        value_name = "date"
+    )]
+    date_from: Option<NaiveDate>,
+
+    /// Include transactions up to (and excluding) this date.
+    #[arg(
+        short = 'D',
+        long = "date-to",
+        value_parser = NaiveDate::from_str,
+        value_name = "date"
+    )]
+    date_to: Option<NaiveDate>,
+}
+
+impl ExportLedger {
+    /// Create a new ledger export
+    pub fn new(date_from: Option<NaiveDate>, date_to: Option<NaiveDate>) -> Self {
+        Self { date_from, date_to }
+    }
+
+    /// Retrieve the earliest date of the exported transactions
+    pub fn date_from(&self) -> &Option<NaiveDate> {
+        &self.date_from
+    }
+
+    /// Retrieve the date that all exported transactions occur before
+    pub fn date_to(&self) -> &Option<NaiveDate> {
+        &self.date_to
+    }
+
+    /// Write the transactions in the [`HomeBankDb`] as a journal.
+    ///
+    /// Each transfer is written as a single entry, with a posting for each account.
+    /// If no starting date is given, the initial amounts of the accounts are written as an opening balance entry.
+    pub fn journal(&self, db: &HomeBankDb) -> String {
+        let journal = Journal::new(db);
+        let mut entries: Vec<(NaiveDate, Vec<String>)> = exported_transactions(db, self.date_from(), self.date_to())
+            .into_iter()
+            .map(|(tr, partner)| {
+                let lines = match tr.is_transfer() {
+                    true => journal.transfer_entry(tr, partner),
+                    false => journal.entry(tr),
+                };
+
+                (*tr.date(), lines)
+            })
+            .collect();
+
+        if self.date_from().is_none() {
+            if let Some((first_date, _)) = entries.first() {
+                let opening = journal.opening_entry(*first_date);
+                if !opening.is_empty() {
+                    entries.insert(0, (*first_date, opening));
+                }
+            }
+        }
+
+        journal.render(entries)
+    }
+}
+
+/// How amounts of a currency are written in the journal.
+#[derive(Debug, PartialEq, Clone)]
+struct Commodity {
+    /// Symbol written alongside each amount.
+    symbol: String,
+
+    /// Whether the symbol is written before the amount.
+    prefix: bool,
+
+    /// Number of digits after the decimal point.
+    decimal_len: usize,
+}
+
+impl Commodity {
+    /// Write an amount with this commodity's symbol.
+    fn format(&self, amount: f32) -> String {
+        let value = format_amount(amount, self.decimal_len);
+
+        match (self.symbol.is_empty(), self.prefix) {
+            (true, _) => value,
+            (false, true) => format!("{}{value}", self.symbol),
+            (false, false) => format!("{value} {}", self.symbol),
+        }
+    }
+}
+
+impl Default for Commodity {
+    fn default() -> Self {
+        Self {
+            symbol: "".to_string(),
+            prefix: false,
+            decimal_len: 2,
+        }
+    }
+}
+
+/// Names and commodities of the accounts and categories in a [`HomeBankDb`], as they are written in a journal.
+struct Journal<'db> {
+    /// The database being exported.
+    db: &'db HomeBankDb,
+
+    /// Names of the accounts and categories.
+    names: AccountNames<'db>,
+
+    /// Commodity of each currency, by currency key.
+    commodities: HashMap<usize, Commodity>,
+}
+
+impl<'db> Journal<'db> {
+    /// Collect the commodities of all currencies in the database.
+    fn new(db: &'db HomeBankDb) -> Self {
+        let mut symbol_counts: HashMap<&str, usize> = HashMap::new();
+        for curr in db.currencies().values() {
+            *symbol_counts.entry(curr.symbol()).or_default() += 1;
+        }
+
+        let commodities = db
+            .currencies()
+            .iter()
+            .map(|(key, curr)| {
+                let sym = curr.symbol();
+                let usable_symbol = sym
+                    .chars()
+                    .all(|c| !c.is_alphanumeric() && !c.is_whitespace() && !RESERVED_SYMBOLS.contains(c))
+                    // currencies sharing a symbol (e.g. `$`) would be merged, so they're told apart by their codes
+                    && symbol_counts.get(sym) == Some(&1);
+
+                let commodity = if usable_symbol {
+                    Commodity {
+                        symbol: sym.to_string(),
+                        prefix: curr.symbol_is_prefix(),
+                        decimal_len: curr.decimal_len(),
+                    }
+                } else if !curr.iso().is_empty() && curr.iso().chars().all(|c| c.is_ascii_alphabetic()) {
+                    Commodity {
+                        symbol: curr.iso().to_string(),
+                        prefix: false,
+                        decimal_len: curr.decimal_len(),
+                    }
+                } else {
+                    Commodity {
+                        decimal_len: curr.decimal_len(),
+                        ..Default::default()
+                    }
+                };
+
+                (*key, commodity)
+            })
+            .collect();
+
+        let names = AccountNames { db, level: sanitize_name };
+
+        Self { db, names, commodities }
+    }
+
+    /// Retrieve the commodity of an account, falling back to the base currency.
+    fn commodity(&self, account: usize) -> Commodity {
+        self.names.commodity(&self.commodities, account).cloned().unwrap_or_default()
+    }
+
+    /// Write the first line of an entry.
+    fn header(&self, tr: &Transaction, fallback: &str) -> String {
+        let mark = if tr.status().is_reconciled() {
+            "* "
+        } else if tr.status().is_cleared() {
+            "! "
+        } else {
+            ""
+        };
+        let description = match (tr.payee_name(self.db), tr.memo()) {
+            (Some(payee), _) => payee,
+            (None, Some(memo)) if !memo.is_empty() => memo.clone(),
+            _ => fallback.to_string(),
+        };
+
+        format!("{} {mark}{}", tr.date().format("%Y-%m-%d"), sanitize_text(&description))
+    }
+
+    /// Write an income or expense, with a posting for each split and one for the account.
+    fn entry(&self, tr: &Transaction) -> Vec<String> {
+        let commodity = self.commodity(tr.account());
+        let account = self.names.account(tr.account());
+        let mut lines = vec![self.header(tr, "")];
+
+        if tr.is_split() {
+            let splits = tr.categories().into_iter().zip(tr.amounts()).zip(tr.memos());
+            for ((cat, amount), memo) in splits {
+                let name = self.names.category(cat, *amount);
+                lines.push(posting(&name, Some(&commodity.format(-amount)), memo.as_deref()));
+            }
+
+            // the account balances the splits, in case they don't add up to the total
+            lines.push(posting(&account, None, None));
+        } else {
+            if let (Some(_), Some(memo)) = (tr.payee(), tr.memo()) {
+                if !memo.is_empty() {
+                    lines.push(format!("{INDENT}; {}", sanitize_text(memo)));
+                }
+            }
+
+            let cat = tr.categories().into_iter().next().cloned().flatten();
+            lines.push(posting(&account, Some(&commodity.format(*tr.total())), None));
+            lines.push(posting(&self.names.category(&cat, *tr.total()), None, None));
+        }
+
+        lines
+    }
+
+    /// Write a transfer as a single entry, with a posting for each account.
+    fn transfer_entry(&self, tr: &Transaction, partner: Option<&Transaction>) -> Vec<String> {
+        let commodity = self.commodity(tr.account());
+        let mut lines = vec![self.header(tr, "Transfer")];
+
+        lines.push(posting(
+            &self.names.account(tr.account()),
+            Some(&commodity.format(*tr.total())),
+            None,
+        ));
+
+        match (partner, tr.transfer_destination()) {
+            // between currencies, both amounts are needed to know the exchange rate
+            (Some(other), _) if self.commodity(other.account()) != commodity => {
+                let other_commodity = self.commodity(other.account());
+                lines.push(posting(
+                    &self.names.account(other.account()),
+                    Some(&other_commodity.format(*other.total())),
+                    None,
+                ));
+            }
+            (Some(other), _) => lines.push(posting(&self.names.account(other.account()), None, None)),
+            (None, Some(dst)) => lines.push(posting(&self.names.account(*dst), None, None)),
+            (None, None) => lines.push(posting(&format!("{ASSETS}:{UNKNOWN}"), None, None)),
+        }
+
+        lines
+    }
+
+    /// Write the initial amounts of all accounts as a single entry.
+    /// Returns no lines if all accounts start from zero.
+    fn opening_entry(&self, date: NaiveDate) -> Vec<String> {
+        let accounts = opening_accounts(self.db);
+        if accounts.is_empty() {
+            return vec![];
+        }
+
+        let mut lines = vec![format!("{} * Opening balances", date.format("%Y-%m-%d"))];
+        for (key, acct) in accounts {
+            let amount = self.commodity(key).format(acct.initial_amount());
+            lines.push(posting(&self.names.account(key), Some(&amount), None));
+        }
+        lines.push(posting(OPENING_BALANCES, None, None));
+
+        lines
+    }
+
+    /// Write the account and commodity declarations, followed by all entries.
+    fn render(&self, entries: Vec<(NaiveDate, Vec<String>)>) -> String {
+        // collect the names of all accounts used in postings, so that they can be declared
+        let used_accounts: BTreeSet<String> = entries
+            .iter()
+            .flat_map(|(_, lines)| lines.iter().skip(1))
+            .filter(|line| !line.trim_start().starts_with(';'))
+            .map(|line| match line.trim_start().split_once("  ") {
+                Some((name, _)) => name.to_string(),
+                None => line.trim().to_string(),
+            })
+            .collect();
+        let mut commodities: Vec<&Commodity> = self
+            .commodities
+            .values()
+            .filter(|c| !c.symbol.is_empty())
+            .collect();
+        commodities.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        commodities.dedup();
+
+        let mut out = String::new();
+        for commodity in commodities {
+            out.push_str(&format!("commodity {}\n", commodity.format(1000.0)));
+        }
+        if !out.is_empty() {
+            out.push('\n');
+        }
+
+        for name in &used_accounts {
+            out.push_str(&format!("account {name}\n"));
+        }
+
+        for (_, lines) in entries {
+            out.push('\n');
+            for line in lines {
+                out.push_str(&line);
+                out.push('\n');
+            }
+        }
+
+        out
+    }
+}
+
+/// Write a single posting, with an optional amount and comment.
+fn posting(account: &str, amount: Option<&str>, comment: Option<&str>) -> String {
+    let mut line = format!("{INDENT}{account}");
+
+    if let Some(a) = amount {
+        // ledger needs at least two spaces between the account name and the amount
+        line.push_str(&format!("  {a}"));
+    }
+    if let Some(c) = comment.filter(|c| !c.is_empty()) {
+        line.push_str(&format!("  ; {}", sanitize_text(c)));
+    }
+
+    line
+}
+
+/// Make a name usable as a single level of a ledger account.
+///
+/// `:` separates the levels of an account, so it is replaced.
+/// Brackets and parentheses are removed, since they mark virtual postings.
+/// Runs of whitespace are collapsed into single spaces, since two spaces end an account name.
+fn sanitize_name(name: &str) -> String {
+    let replaced: String = name
+        .chars()
+        .filter(|c| !matches!(c, '(' | ')' | '[' | ']'))
+        .map(|c| match c {
+            ':' | ';' => '-',
+            c if c.is_whitespace() => ' ',
+            c => c,
+        })
+        .collect();
+    let collapsed = replaced.split_whitespace().collect::<Vec<&str>>().join(" ");
+
+    if collapsed.is_empty() {
+        UNKNOWN.to_string()
+    } else {
+        collapsed
+    }
+}
+
+/// Make free text usable in a description or comment, by keeping it on one line and outside of comments.
+fn sanitize_text(text: &str) -> String {
+    text.replace(';', ",")
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    #[track_caller]
+    fn check_sanitize_name(input: &str, expected: &str) {
+        assert_eq!(expected, sanitize_name(input));
+    }
+
+    #[test]
+    fn sanitize_plain_name() {
+        check_sanitize_name("Credit Card", "Credit Card");
+    }
+
+    #[test]
+    fn sanitize_separators() {
+        check_sanitize_name("Visa: Gold; Travel", "Visa- Gold- Travel");
+    }
+
+    #[test]
+    fn sanitize_brackets_and_spaces() {
+        check_sanitize_name("  Amazon.com  (Prime)\t", "Amazon.com Prime");
+    }
+
+    #[test]
+    fn sanitize_empty_name() {
+        check_sanitize_name(" () ", "Unknown");
+    }
+
+    #[test]
+    fn commodity_format() {
+        let prefix = Commodity {
+            symbol: "$".to_string(),
+            prefix: true,
+            decimal_len: 2,
+        };
+        let suffix = Commodity {
+            symbol: "€".to_string(),
+            prefix: false,
+            decimal_len: 2,
+        };
+
+        assert_eq!("$-1200.00", prefix.format(-1200.0));
+        assert_eq!("0.50 €", suffix.format(0.5));
+        assert_eq!("$0.00", prefix.format(-0.0));
+    }
+
+    #[test]
+    fn journal_split_and_transfer() {
+        let export = ExportLedger::new(
+            NaiveDate::from_ymd_opt(2022, 1, 20),
+            NaiveDate::from_ymd_opt(2022, 2, 1),
+        );
+        let expected = "commodity $1000.00
+
+account Assets:Chequing
+account Assets:Savings
+account Expenses:Food:Dining
+account Expenses:Food:Groceries
+
+2022-01-20 ! Grocer
+    Expenses:Food:Groceries  $80.00  ; Groceries
+    Expenses:Food:Dining  $20.00  ; Deli lunch
+    Assets:Chequing
+
+2022-01-25 ! Savings
+    Assets:Chequing  $-500.00
+    Assets:Savings
+";
+
+        assert_eq!(expected, export.journal(&example_db()));
+    }
+
+    #[test]
+    fn journal_expense_and_transfer() {
+        let journal = ExportLedger::new(NaiveDate::from_ymd_opt(2022, 1, 15), NaiveDate::from_ymd_opt(2022, 1, 26)).journal(&example_db());
+        let expense = "
+2022-01-15 ! Grocer
+    ; Weekly groceries
+    Assets:Chequing  $-150.25
+    Expenses:Food:Groceries
+";
+        let transfer = "
+2022-01-25 ! Savings
+    Assets:Chequing  $-500.00
+    Assets:Savings
+";
+
+        assert!(journal.contains(expense), "{journal}");
+        assert!(journal.ends_with(transfer), "{journal}");
+    }
+
+    #[test]
+    fn journal_single_transfer_entry() {
+        let journal = ExportLedger::new(None, None).journal(&example_db());
+
+        // the two halves of the transfer are a single entry
+        assert_eq!(1, journal.matches("Savings\n    Assets:Chequing  $-500.00").count());
+        assert!(!journal.contains("$500.00"));
+    }
+
+    #[test]
+    fn journal_opening_balances() {
+        let journal = ExportLedger::new(None, None).journal(&example_db());
+        let expected = "
+2021-12-10 * Opening balances
+    Assets:Chequing  $1000.00
+    Assets:Savings  $5000.00
+    Liabilities:Credit Card  $-250.00
+    Equity:Opening Balances
+
+2021-12-10 * Landlord
+    ; December rent
+    Assets:Chequing  $-1200.00
+    Expenses:Housing:Rent
+";
+
+        assert!(journal.contains(expected), "{journal}");
+        assert!(journal.contains("2022-01-05 * Employer\n    ; Paycheque\n    Assets:Chequing  $2000.00\n    Income:Salary\n"));
+    }
+
+    #[test]
+    fn journal_without_opening_balances() {
+        let export = ExportLedger::new(NaiveDate::from_ymd_opt(2022, 2, 1), None);
+
+        assert!(!export.journal(&example_db()).contains("Opening balances"));
+    }
+}
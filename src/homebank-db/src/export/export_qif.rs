@@ -0,0 +1,220 @@
+//! Export the HomeBank database as a [QIF](https://en.wikipedia.org/wiki/Quicken_Interchange_Format) file.
+
+use super::{format_amount, ExportError};
+use crate::{Account, HomeBankDb, Transaction};
+use chrono::format::{Item, StrftimeItems};
+use clap::Parser;
+use regex::Regex;
+
+/// Date format used by most QIF consumers.
+const DEFAULT_DATE_FORMAT: &str = "%m/%d/%Y";
+
+/// Check that a date format only uses supported specifiers.
+fn parse_date_format(s: &str) -> Result<String, ExportError> {
+    if StrftimeItems::new(s).any(|item| matches!(item, Item::Error)) {
+        return Err(ExportError::InvalidDateFormat(s.to_string()));
+    }
+
+    Ok(s.to_string())
+}
+
+/// Export transactions as a QIF file.
+#[derive(Debug, Parser)]
+#[command(about = "Export transactions as a QIF file")]
+pub struct ExportQif {
+    /// Include accounts whose names match this regular expression.
+    #[arg(short = 'a', long = "account", value_name = "regex")]
+    account: Option<Regex>,
+
+    /// Format of the transaction dates, using `strftime` specifiers.
+    #[arg(
+        long = "date-format",
+        default_value = DEFAULT_DATE_FORMAT,
+        value_parser = parse_date_format,
+        value_name = "format"
+    )]
+    date_format: String,
+}
+
+impl ExportQif {
+    /// Create a new QIF export
+    pub fn new(account: Option<Regex>, date_format: &str) -> Self {
+        Self {
+            account,
+            date_format: date_format.to_string(),
+        }
+    }
+
+    /// Retrieve the regex of the exported account names
+    pub fn account(&self) -> &Option<Regex> {
+        &self.account
+    }
+
+    /// Retrieve the format of the transaction dates
+    pub fn date_format(&self) -> &str {
+        &self.date_format
+    }
+
+    /// Write the transactions in the [`HomeBankDb`] as QIF.
+    ///
+    /// Each matching account gets an `!Account` block followed by its transactions.
+    /// Both halves of a transfer are written, one in each account, with the other account as the category.
+    /// Voided transactions are skipped.
+    pub fn qif(&self, db: &HomeBankDb) -> String {
+        let mut accounts: Vec<(&usize, &Account)> = db
+            .accounts()
+            .iter()
+            .filter(|(_, acct)| match self.account() {
+                Some(re) => re.is_match(acct.name()),
+                None => true,
+            })
+            .collect();
+        accounts.sort_by_key(|(key, acct)| (acct.position(), **key));
+
+        let mut out = String::new();
+        for (key, acct) in accounts {
+            let decimal_len = db
+                .currency(acct.currency())
+                .map(|curr| curr.decimal_len())
+                .unwrap_or(2);
+
+            out.push_str(&format!("!Account\nN{}\nTBank\n^\n", acct.name()));
+            out.push_str("!Type:Bank\n");
+
+            let mut transactions: Vec<&Transaction> = db
+                .transactions()
+                .iter()
+                .filter(|tr| tr.account() == *key && !tr.is_void())
+                .collect();
+            transactions.sort_by_key(|tr| *tr.date());
+
+            for tr in transactions {
+                self.push_record(&mut out, db, tr, decimal_len);
+            }
+        }
+
+        out
+    }
+
+    /// Write a single transaction record, ending with `^`.
+    fn push_record(&self, out: &mut String, db: &HomeBankDb, tr: &Transaction, decimal_len: usize) {
+        out.push_str(&format!("D{}\n", tr.date().format(&self.date_format)));
+        out.push_str(&format!("T{}\n", format_amount(*tr.total(), decimal_len)));
+
+        if let Some(payee) = tr.payee_name(db) {
+            out.push_str(&format!("P{}\n", single_line(&payee)));
+        }
+        if let Some(memo) = tr.memo().as_ref().filter(|m| !m.is_empty()) {
+            out.push_str(&format!("M{}\n", single_line(memo)));
+        }
+
+        if tr.is_transfer() {
+            let dst = tr
+                .transfer_destination()
+                .and_then(|dst| db.account(*dst))
+                .map(|acct| acct.name().to_string())
+                .unwrap_or_default();
+
+            out.push_str(&format!("L[{dst}]\n"));
+        } else if tr.is_split() {
+            let names = tr.category_names(db);
+            let splits = names.iter().zip(tr.amounts()).zip(tr.memos());
+
+            for ((name, amount), memo) in splits {
+                out.push_str(&format!("S{}\n", name.as_deref().unwrap_or_default()));
+                if let Some(m) = memo.as_ref().filter(|m| !m.is_empty()) {
+                    out.push_str(&format!("E{}\n", single_line(m)));
+                }
+                out.push_str(&format!("${}\n", format_amount(*amount, decimal_len)));
+            }
+        } else if let Some(Some(name)) = tr.category_names(db).first() {
+            out.push_str(&format!("L{name}\n"));
+        }
+
+        out.push_str("^\n");
+    }
+}
+
+/// Keep free text on one line, since each line of a record is a separate field.
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn example_db() -> HomeBankDb {
+        HomeBankDb::try_from(Path::new("tests/qif.xhb")).unwrap()
+    }
+
+    #[test]
+    fn qif_all_accounts() {
+        let export = ExportQif::new(None, DEFAULT_DATE_FORMAT);
+        let expected = "!Account
+NChequing
+TBank
+^
+!Type:Bank
+D01/05/2022
+T2000.00
+PEmployer
+MPaycheque
+LSalary
+^
+D01/20/2022
+T-100.00
+PGrocer
+SFood:Groceries
+EGroceries
+$-80.00
+SFood:Dining
+EDeli lunch
+$-20.00
+^
+D01/25/2022
+T-500.00
+MSavings
+L[Savings]
+^
+!Account
+NSavings
+TBank
+^
+!Type:Bank
+D01/25/2022
+T500.00
+MSavings
+L[Chequing]
+^
+";
+
+        assert_eq!(expected, export.qif(&example_db()));
+    }
+
+    #[test]
+    fn qif_account_and_date_format() {
+        let export = ExportQif::new(Some(Regex::new("^Sav").unwrap()), "%Y-%m-%d");
+        let expected = "!Account
+NSavings
+TBank
+^
+!Type:Bank
+D2022-01-25
+T500.00
+MSavings
+L[Chequing]
+^
+";
+
+        assert_eq!(expected, export.qif(&example_db()));
+    }
+
+    #[test]
+    fn parse_invalid_date_format() {
+        let expected = Err(ExportError::InvalidDateFormat("%Q".to_string()));
+
+        assert_eq!(expected, parse_date_format("%Q"));
+    }
+}
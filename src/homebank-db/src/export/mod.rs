@@ -0,0 +1,163 @@
+//! Export the [`Transaction`s][crate::transaction::transaction_struct::Transaction] in the HomeBank database to other formats.
+
+pub mod export_beancount;
+pub mod export_error;
+pub mod export_ledger;
+pub mod export_qif;
+
+pub use export_beancount::ExportBeancount;
+pub use export_error::ExportError;
+pub use export_ledger::ExportLedger;
+pub use export_qif::ExportQif;
+
+use crate::{Account, HomeBankDb, Transaction};
+use chrono::NaiveDate;
+use clap::Parser;
+use std::collections::{HashMap, HashSet};
+
+/// Top level account for accounts that hold money.
+const ASSETS: &str = "Assets";
+
+/// Top level account for credit cards and other debts.
+const LIABILITIES: &str = "Liabilities";
+
+/// Top level account for expense categories.
+const EXPENSES: &str = "Expenses";
+
+/// Top level account for income categories.
+const INCOME: &str = "Income";
+
+/// Name used for transactions without a category.
+const UNCATEGORIZED: &str = "Uncategorized";
+
+/// Name used for accounts or categories that are empty or missing from the database.
+const UNKNOWN: &str = "Unknown";
+
+/// A subcommand to export the database from the CLI.
+#[derive(Debug, Parser)]
+pub struct ExportOpts {
+    #[command(subcommand)]
+    export_type: ExportType,
+}
+
+impl ExportOpts {
+    /// Retrieve the format being exported to
+    pub fn etype(&self) -> &ExportType {
+        &self.export_type
+    }
+}
+
+/// Differentiate between the different export formats from the CLI
+#[derive(Debug, Parser)]
+pub enum ExportType {
+    Beancount(ExportBeancount),
+    Ledger(ExportLedger),
+    Qif(ExportQif),
+}
+
+/// Names of the accounts and categories in a [`HomeBankDb`], as they are written in a double-entry ledger (beancount or ledger).
+///
+/// Accounts are under `Assets` or `Liabilities`, and categories under `Income` or `Expenses`, with their parent category as the level above them.
+/// Only the characters allowed in each level of an account differ between the formats.
+struct AccountNames<'db> {
+    /// The database being exported.
+    db: &'db HomeBankDb,
+
+    /// Make a name usable as a single level of an account.
+    level: fn(&str) -> String,
+}
+
+impl<'db> AccountNames<'db> {
+    /// Retrieve the name of an account.
+    fn account(&self, account: usize) -> String {
+        match self.db.account(account) {
+            Some(acct) => {
+                let root = if acct.atype().is_liability() { LIABILITIES } else { ASSETS };
+
+                format!("{root}:{}", (self.level)(acct.name()))
+            }
+            None => format!("{ASSETS}:{}", (self.level)(&format!("{UNKNOWN} {account}"))),
+        }
+    }
+
+    /// Retrieve the name of a category, with its parent category as the level above it.
+    fn category(&self, category: &Option<usize>, amount: f32) -> String {
+        // without a category, the direction of the money is the only hint
+        let sign_root = if amount > 0.0 { INCOME } else { EXPENSES };
+
+        match category.and_then(|key| self.db.category(key)) {
+            Some(cat) => {
+                let parent = cat.parent_key().and_then(|key| self.db.category(key));
+                let is_income = cat.is_income() || parent.is_some_and(|p| p.is_income());
+                let root = if is_income { INCOME } else { EXPENSES };
+
+                match parent {
+                    Some(p) => format!("{root}:{}:{}", (self.level)(p.name()), (self.level)(cat.name())),
+                    None => format!("{root}:{}", (self.level)(cat.name())),
+                }
+            }
+            None => format!("{sign_root}:{UNCATEGORIZED}"),
+        }
+    }
+
+    /// Retrieve the commodity of an account from the commodity of each currency, falling back to the base currency.
+    fn commodity<'c, C>(&self, commodities: &'c HashMap<usize, C>, account: usize) -> Option<&'c C> {
+        let base = self.db.properties().currency();
+        let curr = self.db.account(account).map_or(base, |acct| acct.currency());
+
+        commodities.get(&curr).or_else(|| commodities.get(&base))
+    }
+}
+
+/// Retrieve the transactions exported between `date_from` (inclusive) and `date_to` (exclusive), sorted by date,
+/// each along with the other half of the transfer if it is one.
+///
+/// Each transfer is only included once, from whichever half comes first.
+/// Voided transactions are left out, since they never affected any balance.
+fn exported_transactions<'db>(
+    db: &'db HomeBankDb,
+    date_from: &Option<NaiveDate>,
+    date_to: &Option<NaiveDate>,
+) -> Vec<(&'db Transaction, Option<&'db Transaction>)> {
+    let includes = |d: &NaiveDate| date_from.iter().all(|from| d >= from) && date_to.iter().all(|to| d < to);
+    let partners = db.transfer_partners();
+    let mut exported = vec![];
+    let mut paired: HashSet<usize> = HashSet::new();
+
+    for (i, tr) in db.transactions().iter().enumerate() {
+        if tr.is_void() || paired.contains(&i) || !includes(tr.date()) {
+            continue;
+        }
+
+        let partner = partners.get(&i).copied();
+        if let Some(j) = partner {
+            paired.insert(j);
+        }
+
+        exported.push((tr, partner.map(|j| &db.transactions()[j])));
+    }
+
+    // ledgers don't require transactions to be in order, but they are much easier to read that way
+    exported.sort_by_key(|(tr, _)| *tr.date());
+
+    exported
+}
+
+/// Retrieve the accounts with an initial amount, in the order HomeBank lists them, to write as opening balances.
+fn opening_accounts(db: &HomeBankDb) -> Vec<(usize, &Account)> {
+    let mut accounts: Vec<(usize, &Account)> = db
+        .accounts()
+        .iter()
+        .filter(|(_, acct)| acct.initial_amount() != 0.0)
+        .map(|(key, acct)| (*key, acct))
+        .collect();
+    accounts.sort_by_key(|(key, acct)| (acct.position(), *key));
+
+    accounts
+}
+
+/// Write an amount without any currency symbol or thousands separator.
+fn format_amount(amount: f32, decimal_len: usize) -> String {
+    // adding 0.0 turns -0.0 into 0.0, so that zero isn't written with a sign
+    format!("{:.*}", decimal_len, amount + 0.0)
+}
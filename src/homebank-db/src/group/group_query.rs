@@ -6,10 +6,10 @@ use regex::Regex;
 
 /// Options for filtering [`Group`s][crate::group::group_struct::Group] from the [`HomeBankDb`].
 #[derive(Debug, Parser)]
-#[clap(name = "groups", visible_alias = "g", about = "Query account groups")]
+#[command(name = "groups", visible_alias = "g", about = "Query account groups")]
 pub struct QueryGroups {
     /// Include groups whose names match this regular expression.
-    #[clap(value_name = "regex")]
+    #[arg(value_name = "regex")]
     name: Option<Regex>,
 }
 
@@ -6,6 +6,7 @@ use xml::attribute::OwnedAttribute;
 
 /// User-provided groups that an [`Account`][crate::account::account_struct::Account] belongs to.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Group {
     key: usize,
     name: String,
@@ -0,0 +1,518 @@
+//! Import transactions from a CSV file, such as one downloaded from a bank.
+
+use super::{
+    import_result::{account_key, imported_transaction},
+    ImportError, ImportResult,
+};
+use crate::{HomeBankDb, Transaction};
+use chrono::{
+    format::{Item, StrftimeItems},
+    NaiveDate,
+};
+use clap::Parser;
+use csv::{ReaderBuilder, StringRecord};
+use std::{fs::File, io::Read, path::PathBuf, str::FromStr};
+
+/// Default format of the dates in the CSV file.
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Check that a date format only uses supported specifiers.
+fn parse_date_format(s: &str) -> Result<String, ImportError> {
+    if StrftimeItems::new(s).any(|item| matches!(item, Item::Error)) {
+        return Err(ImportError::InvalidDateFormat(s.to_string()));
+    }
+
+    Ok(s.to_string())
+}
+
+/// Check that a column delimiter is a single ASCII character.
+fn parse_delimiter(s: &str) -> Result<u8, ImportError> {
+    match s.as_bytes() {
+        [b] if b.is_ascii() => Ok(*b),
+        _ => Err(ImportError::InvalidDelimiter(s.to_string())),
+    }
+}
+
+/// Which column of the CSV file holds each field of a transaction, counting from 0.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ColumnMap {
+    date: usize,
+    amount: usize,
+    payee: Option<usize>,
+    category: Option<usize>,
+    memo: Option<usize>,
+    info: Option<usize>,
+}
+
+impl ColumnMap {
+    /// Create a new column map
+    pub fn new(
+        date: usize,
+        amount: usize,
+        payee: Option<usize>,
+        category: Option<usize>,
+        memo: Option<usize>,
+        info: Option<usize>,
+    ) -> Self {
+        Self {
+            date,
+            amount,
+            payee,
+            category,
+            memo,
+            info,
+        }
+    }
+
+    /// Retrieve the column of the date
+    pub fn date(&self) -> usize {
+        self.date
+    }
+
+    /// Retrieve the column of the amount
+    pub fn amount(&self) -> usize {
+        self.amount
+    }
+
+    /// Retrieve the column of the payee, if there is one
+    pub fn payee(&self) -> Option<usize> {
+        self.payee
+    }
+
+    /// Retrieve the column of the category, if there is one
+    pub fn category(&self) -> Option<usize> {
+        self.category
+    }
+
+    /// Retrieve the column of the memo, if there is one
+    pub fn memo(&self) -> Option<usize> {
+        self.memo
+    }
+
+    /// Retrieve the column of the info, if there is one
+    pub fn info(&self) -> Option<usize> {
+        self.info
+    }
+}
+
+impl FromStr for ColumnMap {
+    type Err = ImportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ImportError::InvalidMap(s.to_string());
+
+        let (mut date, mut amount, mut payee, mut category, mut memo, mut info) = (None, None, None, None, None, None);
+        for pair in s.split(',') {
+            let (field, column) = pair.split_once('=').ok_or_else(invalid)?;
+            let column = usize::from_str(column.trim()).map_err(|_| invalid())?;
+
+            let slot = match field.trim() {
+                "date" => &mut date,
+                "amount" => &mut amount,
+                "payee" => &mut payee,
+                "category" => &mut category,
+                "memo" => &mut memo,
+                "info" => &mut info,
+                _ => return Err(invalid()),
+            };
+
+            // each field can only be given once
+            if slot.replace(column).is_some() {
+                return Err(invalid());
+            }
+        }
+
+        match (date, amount) {
+            (Some(date), Some(amount)) => Ok(Self::new(date, amount, payee, category, memo, info)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Import transactions from a CSV file.
+#[derive(Debug, Parser)]
+#[command(about = "Import transactions from a CSV file")]
+pub struct ImportCsv {
+    /// CSV file to import.
+    #[arg(value_name = "file.csv")]
+    file: PathBuf,
+
+    /// Name of the account to add the transactions to.
+    #[arg(short = 'a', long = "account", value_name = "name")]
+    account: String,
+
+    /// Columns of each field, counting from 0. The fields are `date`, `amount`, `payee`, `category`, `memo`, and `info`.
+    #[arg(short = 'm', long = "map", value_name = "field=column,...")]
+    map: ColumnMap,
+
+    /// Format of the dates, using `strftime` specifiers.
+    #[arg(
+        long = "date-format",
+        default_value = DEFAULT_DATE_FORMAT,
+        value_parser = parse_date_format,
+        value_name = "format"
+    )]
+    date_format: String,
+
+    /// Character separating the columns.
+    #[arg(long = "delimiter", default_value = ",", value_parser = parse_delimiter, value_name = "char")]
+    delimiter: u8,
+
+    /// Read the first row as a transaction, instead of as a header.
+    #[arg(long = "no-header")]
+    no_header: bool,
+
+    /// Add payees that are not in the HomeBank file, instead of reporting them as errors.
+    #[arg(long = "create-payees")]
+    create_payees: bool,
+
+    /// Add categories that are not in the HomeBank file, instead of reporting them as errors.
+    /// Categories are given by their full name, e.g. `Food:Groceries`.
+    #[arg(long = "create-categories")]
+    create_categories: bool,
+
+    /// Import transactions that are already in the account on the same date with the same amount.
+    #[arg(long = "allow-duplicates")]
+    allow_duplicates: bool,
+
+    /// Print the transactions that would be imported without changing the HomeBank file.
+    #[arg(short = 'n', long = "dry-run")]
+    dry_run: bool,
+}
+
+impl ImportCsv {
+    /// Create a new CSV import with the default options
+    pub fn new(file: PathBuf, account: &str, map: ColumnMap) -> Self {
+        Self {
+            file,
+            account: account.to_string(),
+            map,
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            delimiter: b',',
+            no_header: false,
+            create_payees: false,
+            create_categories: false,
+            allow_duplicates: false,
+            dry_run: false,
+        }
+    }
+
+    /// Retrieve the path of the CSV file
+    pub fn file(&self) -> &PathBuf {
+        &self.file
+    }
+
+    /// Retrieve the name of the account to add the transactions to
+    pub fn account(&self) -> &str {
+        &self.account
+    }
+
+    /// Retrieve the columns of each field
+    pub fn map(&self) -> &ColumnMap {
+        &self.map
+    }
+
+    /// Retrieve the format of the dates
+    pub fn date_format(&self) -> &str {
+        &self.date_format
+    }
+
+    /// Check whether new payees are created
+    pub fn create_payees(&self) -> bool {
+        self.create_payees
+    }
+
+    /// Check whether new categories are created
+    pub fn create_categories(&self) -> bool {
+        self.create_categories
+    }
+
+    /// Check whether duplicate transactions are imported
+    pub fn allow_duplicates(&self) -> bool {
+        self.allow_duplicates
+    }
+
+    /// Check whether the HomeBank file should be left unchanged
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Read the transactions in the CSV file.
+    pub fn read(&self, db: &HomeBankDb) -> Result<ImportResult, ImportError> {
+        match File::open(&self.file) {
+            Ok(f) => self.read_from(f, db),
+            Err(_) => Err(ImportError::CouldNotRead(self.file.clone())),
+        }
+    }
+
+    /// Read the transactions from any source of CSV text.
+    ///
+    /// Rows that cannot be read don't stop the import, and are kept in the [`ImportResult`] instead.
+    pub fn read_from<R: Read>(&self, reader: R, db: &HomeBankDb) -> Result<ImportResult, ImportError> {
+        let account = account_key(&self.account, db)?;
+
+        let mut csv_reader = ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .has_headers(!self.no_header)
+            .flexible(true)
+            .from_reader(reader);
+
+        let mut result = ImportResult::default();
+        for row in csv_reader.records() {
+            let (line, parsed) = match row {
+                Ok(record) => (
+                    record.position().map(|pos| pos.line()).unwrap_or_default(),
+                    self.transaction(&record, account, db, &mut result),
+                ),
+                Err(e) => (
+                    e.position().map(|pos| pos.line()).unwrap_or_default(),
+                    Err(ImportError::InvalidRow(e.to_string())),
+                ),
+            };
+
+            match parsed {
+                Ok(tr) => result.push(line, tr, db, self.allow_duplicates),
+                Err(e) => result.push_error(line, e),
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Build a transaction in `account` from a row of the CSV file.
+    fn transaction(
+        &self,
+        record: &StringRecord,
+        account: usize,
+        db: &HomeBankDb,
+        result: &mut ImportResult,
+    ) -> Result<Transaction, ImportError> {
+        let column = |idx: usize| record.get(idx).map(str::trim).ok_or(ImportError::MissingColumn(idx));
+        let optional = |idx: Option<usize>| -> Result<Option<String>, ImportError> {
+            match idx {
+                Some(i) => Ok(Some(column(i)?.to_string()).filter(|s| !s.is_empty())),
+                None => Ok(None),
+            }
+        };
+
+        let raw_date = column(self.map.date())?;
+        let date = match NaiveDate::parse_from_str(raw_date, &self.date_format) {
+            Ok(d) => d,
+            Err(_) => return Err(ImportError::InvalidDate(raw_date.to_string(), self.date_format.clone())),
+        };
+
+        let raw_amount = column(self.map.amount())?;
+        let amount = match f32::from_str(raw_amount) {
+            Ok(a) => a,
+            Err(_) => return Err(ImportError::InvalidAmount(raw_amount.to_string())),
+        };
+
+        let memo = optional(self.map.memo())?;
+        let info = optional(self.map.info())?;
+        let payee = match optional(self.map.payee())? {
+            Some(name) => result.payee_key(&name, db, self.create_payees)?,
+            None => None,
+        };
+        let category = match optional(self.map.category())? {
+            Some(name) => result.category_key(&name, db, self.create_categories, amount > 0.0)?,
+            None => None,
+        };
+
+        Ok(imported_transaction(&date, amount, account, &payee, &category, &memo, &info))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+    use crate::{Category, TransactionType};
+
+    fn example_import(map: &str) -> ImportCsv {
+        let mut import = ImportCsv::new(PathBuf::from("bank.csv"), "Chequing", ColumnMap::from_str(map).unwrap());
+        import.date_format = "%d.%m.%Y".to_string();
+
+        import
+    }
+
+    #[track_caller]
+    fn check_map(input: &str, expected: Result<ColumnMap, ImportError>) {
+        assert_eq!(expected, ColumnMap::from_str(input));
+    }
+
+    #[test]
+    fn map_all_fields() {
+        check_map(
+            "date=0,amount=3,payee=1,category=5,memo=2,info=4",
+            Ok(ColumnMap::new(0, 3, Some(1), Some(5), Some(2), Some(4))),
+        );
+    }
+
+    #[test]
+    fn map_required_fields() {
+        check_map("amount=1, date=0", Ok(ColumnMap::new(0, 1, None, None, None, None)));
+    }
+
+    #[test]
+    fn map_without_amount() {
+        check_map("date=0,payee=1", Err(ImportError::InvalidMap("date=0,payee=1".to_string())));
+    }
+
+    #[test]
+    fn map_repeated_field() {
+        check_map("date=0,amount=1,date=2", Err(ImportError::InvalidMap("date=0,amount=1,date=2".to_string())));
+    }
+
+    #[test]
+    fn map_unknown_field() {
+        check_map("date=0,amount=1,tags=2", Err(ImportError::InvalidMap("date=0,amount=1,tags=2".to_string())));
+    }
+
+    #[test]
+    fn delimiter() {
+        assert_eq!(Ok(b';'), parse_delimiter(";"));
+        assert_eq!(Err(ImportError::InvalidDelimiter("ab".to_string())), parse_delimiter("ab"));
+    }
+
+    #[test]
+    fn read_rows() {
+        let input = "Date,Payee,Memo,Amount\n10.03.2022,grocer,Weekly groceries,-80.5\n11.03.2022,,Refund,15\n";
+        let result = example_import("date=0,amount=3,payee=1,memo=2")
+            .read_from(input.as_bytes(), &example_db())
+            .unwrap();
+
+        assert!(result.errors().is_empty());
+        assert!(result.payees().is_empty());
+
+        let transactions = result.transactions();
+        assert_eq!(2, transactions.len());
+
+        let (line, tr) = &transactions[0];
+        assert_eq!(2, *line);
+        assert_eq!(&NaiveDate::from_ymd_opt(2022, 3, 10).unwrap(), tr.date());
+        assert_eq!(1, tr.account());
+        assert_eq!(&Some(3), tr.payee());
+        assert_eq!(&Some("Weekly groceries".to_string()), tr.memo());
+        assert_eq!(&TransactionType::Expense, tr.ttype());
+        assert!(tr.flags().unwrap().is_imported());
+
+        let (line, tr) = &transactions[1];
+        assert_eq!(3, *line);
+        assert_eq!(&None, tr.payee());
+        assert_eq!(&TransactionType::Income, tr.ttype());
+        assert!(tr.flags().unwrap().is_income());
+    }
+
+    #[test]
+    fn collect_row_errors() {
+        let input = "Date,Amount\n2022-03-10,-1\n10.03.2022,abc\n11.03.2022\n12.03.2022,5\n";
+        let result = example_import("date=0,amount=1")
+            .read_from(input.as_bytes(), &example_db())
+            .unwrap();
+
+        let expected = vec![
+            (2, ImportError::InvalidDate("2022-03-10".to_string(), "%d.%m.%Y".to_string())),
+            (3, ImportError::InvalidAmount("abc".to_string())),
+            (4, ImportError::MissingColumn(1)),
+        ];
+
+        assert_eq!(&expected, result.errors());
+        assert_eq!(1, result.transactions().len());
+        assert_eq!(5, result.transactions()[0].0);
+    }
+
+    #[test]
+    fn unknown_payee() {
+        let input = "01.03.2022,Baker,-4\n02.03.2022,baker,-6\n";
+        let mut import = example_import("date=0,payee=1,amount=2");
+        import.no_header = true;
+
+        let result = import.read_from(input.as_bytes(), &example_db()).unwrap();
+        assert_eq!(2, result.errors().len());
+        assert_eq!((1, ImportError::UnknownPayee("Baker".to_string())), result.errors()[0]);
+
+        import.create_payees = true;
+        let result = import.read_from(input.as_bytes(), &example_db()).unwrap();
+        let db = example_db();
+
+        assert_eq!(&vec![crate::Payee::new(5, "Baker", None, None)], result.payees());
+        assert!(result.transactions().iter().all(|(_, tr)| tr.payee() == &Some(5)));
+        assert_eq!(Some("Baker".to_string()), result.payee_name(&result.transactions()[1].1, &db));
+    }
+
+    #[test]
+    fn read_categories() {
+        let input = "Date,Amount,Payee,Category,Memo\n01.03.2022,-80,Grocer,food:groceries,Weekly\n02.03.2022,-12,,Food:Snacks,\n03.03.2022,50,,Gifts,Birthday\n";
+        let db = example_db();
+        let mut import = example_import("date=0,amount=1,payee=2,category=3,memo=4");
+
+        let result = import.read_from(input.as_bytes(), &db).unwrap();
+        assert_eq!(1, result.transactions().len());
+        assert_eq!(
+            vec![
+                (3, ImportError::UnknownRowCategory("Food:Snacks".to_string())),
+                (4, ImportError::UnknownRowCategory("Gifts".to_string())),
+            ],
+            *result.errors()
+        );
+
+        import.create_categories = true;
+        let result = import.read_from(input.as_bytes(), &db).unwrap();
+        assert!(result.errors().is_empty());
+
+        // new categories are numbered after the existing ones, and are for income if their first transaction is
+        let expected = vec![Category::new(8, 1, "Snacks", Some(3)), Category::new(9, 2, "Gifts", None)];
+        assert_eq!(&expected, result.categories());
+
+        let observed: Vec<(f32, Option<String>)> = result
+            .transactions()
+            .iter()
+            .map(|(_, tr)| (*tr.total(), result.category_name(tr, &db)))
+            .collect();
+        let expected = vec![
+            (-80.0, Some("Food:Groceries".to_string())),
+            (-12.0, Some("Food:Snacks".to_string())),
+            (50.0, Some("Gifts".to_string())),
+        ];
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    fn skip_duplicates() {
+        // the grocery transaction on 2022-01-15 is already in the file
+        let input = "15.01.2022,-150.25\n16.01.2022,-150.25\n";
+        let mut import = example_import("date=0,amount=1");
+        import.no_header = true;
+
+        let result = import.read_from(input.as_bytes(), &example_db()).unwrap();
+        assert_eq!(1, result.duplicates().len());
+        assert_eq!(1, result.duplicates()[0].0);
+        assert_eq!(1, result.transactions().len());
+
+        import.allow_duplicates = true;
+        let result = import.read_from(input.as_bytes(), &example_db()).unwrap();
+        assert!(result.duplicates().is_empty());
+        assert_eq!(2, result.transactions().len());
+    }
+
+    #[test]
+    fn unknown_account() {
+        let import = ImportCsv::new(PathBuf::from("bank.csv"), "Brokerage", ColumnMap::new(0, 1, None, None, None, None));
+
+        assert_eq!(
+            Err(ImportError::UnknownAccount("Brokerage".to_string())),
+            import.read_from("".as_bytes(), &example_db())
+        );
+    }
+
+    #[test]
+    fn account_ignores_case() {
+        let input = "15.02.2022,-20\n";
+        let mut import = example_import("date=0,amount=1");
+        import.account = "chequing".to_string();
+        import.no_header = true;
+
+        let result = import.read_from(input.as_bytes(), &example_db()).unwrap();
+        assert_eq!(1, result.transactions()[0].1.account());
+    }
+}
@@ -0,0 +1,64 @@
+//! Errors when importing transactions into the HomeBank database.
+
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Errors when importing transactions into the HomeBank database.
+#[derive(Debug, Error, PartialEq)]
+pub enum ImportError {
+    /// When the file being imported cannot be opened or read.
+    #[error("Error reading import file `{0}`.")]
+    CouldNotRead(PathBuf),
+
+    /// When no account has the name that transactions are imported into.
+    #[error("No account named `{0}`.")]
+    UnknownAccount(String),
+
+    /// When a column map cannot be parsed.
+    #[error("Invalid column map `{0}`. Expected comma-separated `field=column` pairs with at least `date` and `amount`, e.g. `date=0,amount=3,payee=1`.")]
+    InvalidMap(String),
+
+    /// When a date format contains a specifier that is not supported.
+    #[error("Invalid date format `{0}`. See https://docs.rs/chrono/latest/chrono/format/strftime for the supported specifiers.")]
+    InvalidDateFormat(String),
+
+    /// When a column delimiter is not a single ASCII character.
+    #[error("Invalid delimiter `{0}`. The delimiter must be a single ASCII character.")]
+    InvalidDelimiter(String),
+
+    /// When the file being imported is not an OFX statement.
+    #[error("Not an OFX statement: missing the `<OFX>` element.")]
+    NotOfx,
+
+    /// When a row of the file cannot be read.
+    #[error("Invalid row: {0}.")]
+    InvalidRow(String),
+
+    /// When a transaction does not have a required field.
+    #[error("Missing `{0}`.")]
+    MissingField(String),
+
+    /// When a row does not have one of the mapped columns.
+    #[error("Missing column {0}.")]
+    MissingColumn(usize),
+
+    /// When a date does not match the date format.
+    #[error("Date `{0}` does not match the format `{1}`.")]
+    InvalidDate(String, String),
+
+    /// When an amount is not a number.
+    #[error("Invalid amount `{0}`.")]
+    InvalidAmount(String),
+
+    /// When a payee does not exist and new payees are not created.
+    #[error("No payee named `{0}`. Use `--create-payees` to add it.")]
+    UnknownPayee(String),
+
+    /// When a row refers to a category that does not exist and new categories are not created.
+    #[error("No category named `{0}`. Use `--create-categories` to add it.")]
+    UnknownRowCategory(String),
+
+    /// When a rule refers to a category that does not exist.
+    #[error("No category named `{0}`. Rules must use the full name of an existing category, e.g. `Food:Groceries`.")]
+    UnknownCategory(String),
+}
@@ -0,0 +1,320 @@
+//! Import transactions from an [OFX](https://www.ofx.net) (or QFX) statement.
+//!
+//! OFX 1.x files are SGML, where elements holding a value are usually not closed, while OFX 2.x files are XML.
+//! Both are read the same way: the value of an element is the text after its opening tag, up to the next tag.
+
+use super::{
+    import_result::{account_key, imported_transaction},
+    ImportError, ImportResult,
+};
+use crate::{db::db_open::xml_from_bytes, HomeBankDb, Transaction};
+use chrono::NaiveDate;
+use clap::Parser;
+use std::{fs::File, io::Read, path::PathBuf, str::FromStr};
+
+/// Format of the date at the start of OFX date-time values.
+const OFX_DATE_FORMAT: &str = "%Y%m%d";
+
+/// Import transactions from an OFX or QFX statement.
+#[derive(Debug, Parser)]
+#[command(about = "Import transactions from an OFX or QFX statement")]
+pub struct ImportOfx {
+    /// OFX or QFX file to import.
+    #[arg(value_name = "file.ofx")]
+    file: PathBuf,
+
+    /// Name of the account to add the transactions to.
+    #[arg(short = 'a', long = "account", value_name = "name")]
+    account: String,
+
+    /// Add payees that are not in the HomeBank file, instead of reporting them as errors.
+    #[arg(long = "create-payees")]
+    create_payees: bool,
+
+    /// Import transactions whose ID (`FITID`) is already in the account.
+    #[arg(long = "allow-duplicates")]
+    allow_duplicates: bool,
+
+    /// Print the transactions that would be imported without changing the HomeBank file.
+    #[arg(short = 'n', long = "dry-run")]
+    dry_run: bool,
+}
+
+impl ImportOfx {
+    /// Create a new OFX import with the default options
+    pub fn new(file: PathBuf, account: &str) -> Self {
+        Self {
+            file,
+            account: account.to_string(),
+            create_payees: false,
+            allow_duplicates: false,
+            dry_run: false,
+        }
+    }
+
+    /// Retrieve the path of the OFX file
+    pub fn file(&self) -> &PathBuf {
+        &self.file
+    }
+
+    /// Retrieve the name of the account to add the transactions to
+    pub fn account(&self) -> &str {
+        &self.account
+    }
+
+    /// Check whether new payees are created
+    pub fn create_payees(&self) -> bool {
+        self.create_payees
+    }
+
+    /// Check whether duplicate transactions are imported
+    pub fn allow_duplicates(&self) -> bool {
+        self.allow_duplicates
+    }
+
+    /// Check whether the HomeBank file should be left unchanged
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Read the transactions in the OFX file.
+    pub fn read(&self, db: &HomeBankDb) -> Result<ImportResult, ImportError> {
+        match File::open(&self.file) {
+            Ok(f) => self.read_from(f, db),
+            Err(_) => Err(ImportError::CouldNotRead(self.file.clone())),
+        }
+    }
+
+    /// Read the transactions from any source of OFX text.
+    ///
+    /// Each `FITID` is stored as the transaction's info, and transactions whose `FITID` is already in the account are skipped.
+    /// Transactions that cannot be read don't stop the import, and are kept in the [`ImportResult`] instead.
+    pub fn read_from<R: Read>(&self, mut reader: R, db: &HomeBankDb) -> Result<ImportResult, ImportError> {
+        let mut raw = vec![];
+        if reader.read_to_end(&mut raw).is_err() {
+            return Err(ImportError::CouldNotRead(self.file.clone()));
+        }
+
+        // OFX 1.x files declare their character set in a header that isn't XML, so legacy encodings are detected instead
+        let text = match xml_from_bytes(raw) {
+            Ok(t) => t,
+            Err(_) => return Err(ImportError::CouldNotRead(self.file.clone())),
+        };
+
+        let account = account_key(&self.account, db)?;
+        if !text.contains("<OFX>") {
+            return Err(ImportError::NotOfx);
+        }
+
+        let mut result = ImportResult::default();
+        for (start, element) in statement_transactions(&text) {
+            let line = text[..start].matches('\n').count() as u64 + 1;
+
+            match self.transaction(element, account, db, &mut result) {
+                Ok(tr) if tr.info().is_some() => result.push_by_info(line, tr, db, self.allow_duplicates),
+                Ok(tr) => result.push(line, tr, db, self.allow_duplicates),
+                Err(e) => result.push_error(line, e),
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Build a transaction in `account` from the contents of a `<STMTTRN>` element.
+    fn transaction(
+        &self,
+        element: &str,
+        account: usize,
+        db: &HomeBankDb,
+        result: &mut ImportResult,
+    ) -> Result<Transaction, ImportError> {
+        let required = |name: &str| field(element, name).ok_or_else(|| ImportError::MissingField(name.to_string()));
+
+        let raw_date = required("DTPOSTED")?;
+        let date = parse_date(&raw_date)?;
+
+        let raw_amount = required("TRNAMT")?;
+        let amount = parse_amount(&raw_amount)?;
+
+        let payee = match field(element, "NAME") {
+            Some(name) => result.payee_key(&name, db, self.create_payees)?,
+            None => None,
+        };
+
+        Ok(imported_transaction(
+            &date,
+            amount,
+            account,
+            &payee,
+            &None,
+            &field(element, "MEMO"),
+            &field(element, "FITID"),
+        ))
+    }
+}
+
+/// Find each `<STMTTRN>` element, along with the position it starts at.
+fn statement_transactions(text: &str) -> Vec<(usize, &str)> {
+    const OPEN: &str = "<STMTTRN>";
+    const CLOSE: &str = "</STMTTRN>";
+
+    let mut elements = vec![];
+    let mut pos = 0;
+    while let Some(offset) = text[pos..].find(OPEN) {
+        let start = pos + offset;
+        let contents = start + OPEN.len();
+
+        // the closing tag is required in both versions, but stop at the next transaction if it is missing
+        let end = [text[contents..].find(CLOSE), text[contents..].find(OPEN)]
+            .into_iter()
+            .flatten()
+            .min()
+            .map(|len| contents + len)
+            .unwrap_or(text.len());
+
+        elements.push((start, &text[contents..end]));
+        pos = end;
+    }
+
+    elements
+}
+
+/// Find the value of an element, if it is given and isn't empty.
+fn field(element: &str, name: &str) -> Option<String> {
+    let open = format!("<{name}>");
+    let start = element.find(&open)? + open.len();
+    let len = element[start..].find('<').unwrap_or(element.len() - start);
+
+    Some(unescape(element[start..start + len].trim())).filter(|value| !value.is_empty())
+}
+
+/// Replace the character entities allowed in OFX values.
+fn unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// Parse the date of an OFX date-time value, which may have a time and time zone after it (e.g. `20220315120000.000[-5:EST]`).
+fn parse_date(s: &str) -> Result<NaiveDate, ImportError> {
+    let invalid = || ImportError::InvalidDate(s.to_string(), OFX_DATE_FORMAT.to_string());
+
+    match s.get(..8) {
+        Some(day) => NaiveDate::parse_from_str(day, OFX_DATE_FORMAT).map_err(|_| invalid()),
+        None => Err(invalid()),
+    }
+}
+
+/// Parse an OFX amount, which may use a comma as the decimal separator.
+fn parse_amount(s: &str) -> Result<f32, ImportError> {
+    match f32::from_str(&s.replace(',', ".")) {
+        Ok(a) => Ok(a),
+        Err(_) => Err(ImportError::InvalidAmount(s.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    #[track_caller]
+    fn check_statement(path: &str, lines: [u64; 3]) {
+        let mut import = ImportOfx::new(PathBuf::from(path), "Credit Card");
+        import.create_payees = true;
+        let result = import.read(&example_db()).unwrap();
+
+        assert!(result.errors().is_empty(), "{:?}", result.errors());
+        assert_eq!(
+            lines.to_vec(),
+            result.transactions().iter().map(|(line, _)| *line).collect::<Vec<u64>>()
+        );
+
+        let (_, tr) = &result.transactions()[0];
+        assert_eq!(&NaiveDate::from_ymd_opt(2022, 2, 3).unwrap(), tr.date());
+        assert_eq!(-23.45, *tr.total());
+        assert_eq!(3, tr.account());
+        assert_eq!(&Some(3), tr.payee());
+        assert_eq!(&Some("Milk & eggs".to_string()), tr.memo());
+        assert_eq!(&Some("2022020301".to_string()), tr.info());
+
+        let names: Vec<&str> = result.payees().iter().map(|payee| payee.name()).collect();
+        assert_eq!(vec!["Streaming Co", "Payment received"], names);
+    }
+
+    #[test]
+    fn read_sgml_statement() {
+        check_statement("tests/statement_v1.ofx", [37, 45, 52]);
+    }
+
+    #[test]
+    fn read_xml_statement() {
+        check_statement("tests/statement_v2.ofx", [29, 37, 44]);
+    }
+
+    #[test]
+    fn skip_known_fitid() {
+        // the streaming transaction in the fixture has `INV-2022-02` as its info
+        let input = "<OFX>
+<STMTTRN><DTPOSTED>20220301<TRNAMT>-45.50<FITID>INV-2022-02</STMTTRN>
+<STMTTRN><DTPOSTED>20220301<TRNAMT>-45.50<FITID>INV-2022-03</STMTTRN>
+<STMTTRN><DTPOSTED>20220301<TRNAMT>-45.50<FITID>INV-2022-03</STMTTRN>
+</OFX>";
+        let mut import = ImportOfx::new(PathBuf::from("statement.ofx"), "Chequing");
+
+        let result = import.read_from(input.as_bytes(), &example_db()).unwrap();
+        assert_eq!(vec![2, 4], result.duplicates().iter().map(|(line, _)| *line).collect::<Vec<u64>>());
+        assert_eq!(1, result.transactions().len());
+
+        import.allow_duplicates = true;
+        let result = import.read_from(input.as_bytes(), &example_db()).unwrap();
+        assert_eq!(3, result.transactions().len());
+    }
+
+    #[test]
+    fn collect_errors() {
+        let input = "<OFX>
+<STMTTRN><TRNAMT>-1</STMTTRN>
+<STMTTRN><DTPOSTED>2022<TRNAMT>-1</STMTTRN>
+<STMTTRN><DTPOSTED>20220301<TRNAMT>1,50<NAME>Baker</STMTTRN>
+</OFX>";
+        let result = ImportOfx::new(PathBuf::from("statement.ofx"), "Chequing")
+            .read_from(input.as_bytes(), &example_db())
+            .unwrap();
+
+        let expected = vec![
+            (2, ImportError::MissingField("DTPOSTED".to_string())),
+            (3, ImportError::InvalidDate("2022".to_string(), OFX_DATE_FORMAT.to_string())),
+            (4, ImportError::UnknownPayee("Baker".to_string())),
+        ];
+        assert_eq!(&expected, result.errors());
+    }
+
+    #[test]
+    fn not_ofx() {
+        let import = ImportOfx::new(PathBuf::from("statement.csv"), "Chequing");
+
+        assert_eq!(
+            Err(ImportError::NotOfx),
+            import.read_from("Date,Amount\n".as_bytes(), &example_db())
+        );
+    }
+
+    #[test]
+    fn comma_amount() {
+        assert_eq!(Ok(1.5), parse_amount("1,50"));
+        assert_eq!(Err(ImportError::InvalidAmount("1.5.0".to_string())), parse_amount("1.5.0"));
+    }
+
+    #[test]
+    fn field_without_closing_tag() {
+        let element = "<TRNTYPE>DEBIT\n<NAME>A &amp; B\n<MEMO></MEMO>";
+
+        assert_eq!(Some("A & B".to_string()), field(element, "NAME"));
+        assert_eq!(None, field(element, "MEMO"));
+        assert_eq!(None, field(element, "FITID"));
+    }
+}
@@ -0,0 +1,264 @@
+//! Transactions read from another format, before they are added to the HomeBank database.
+
+use super::ImportError;
+use crate::{
+    category::category_struct::{GF_INCOME, GF_SUB},
+    db::{append_to_xhb, HomeBankDbError},
+    transaction::{SimpleTransaction, TransactionComplexity},
+    Category, HomeBankDb, PayMode, Payee, Transaction, TransactionFlag, TransactionFlags, TransactionStatus, TransactionType,
+};
+use chrono::NaiveDate;
+use std::path::Path;
+
+/// Amounts closer than this are considered equal when looking for duplicates.
+const AMOUNT_TOLERANCE: f32 = 0.005;
+
+/// Transactions read from another format, along with the line of the file each one was found on.
+///
+/// Rows that could not be read, and rows that are already in the database, are kept separately so that they can all be reported at once.
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportResult {
+    transactions: Vec<(u64, Transaction)>,
+    payees: Vec<Payee>,
+    categories: Vec<Category>,
+    duplicates: Vec<(u64, Transaction)>,
+    errors: Vec<(u64, ImportError)>,
+}
+
+impl ImportResult {
+    /// Retrieve the transactions to add, along with their line numbers
+    pub fn transactions(&self) -> &Vec<(u64, Transaction)> {
+        &self.transactions
+    }
+
+    /// Retrieve the payees to add, which the transactions refer to
+    pub fn payees(&self) -> &Vec<Payee> {
+        &self.payees
+    }
+
+    /// Retrieve the categories to add, which the transactions refer to
+    pub fn categories(&self) -> &Vec<Category> {
+        &self.categories
+    }
+
+    /// Retrieve the transactions that were skipped because they are already in the database, along with their line numbers
+    pub fn duplicates(&self) -> &Vec<(u64, Transaction)> {
+        &self.duplicates
+    }
+
+    /// Retrieve the rows that could not be read, along with their line numbers
+    pub fn errors(&self) -> &Vec<(u64, ImportError)> {
+        &self.errors
+    }
+
+    /// Retrieve the name of a transaction's payee, whether it is already in the database or is being added.
+    pub fn payee_name(&self, tr: &Transaction, db: &HomeBankDb) -> Option<String> {
+        tr.payee_name(db).or_else(|| {
+            self.payees
+                .iter()
+                .find(|payee| Some(payee.key()) == *tr.payee())
+                .map(|payee| payee.name().to_string())
+        })
+    }
+
+    /// Retrieve the full name of a transaction's category, whether it is already in the database or is being added.
+    pub fn category_name(&self, tr: &Transaction, db: &HomeBankDb) -> Option<String> {
+        let key = tr.categories().first().copied().copied().flatten()?;
+
+        self.category(key, db).map(|cat| self.category_full_name(cat, db))
+    }
+
+    /// Retrieve the transactions to add, so that they can be changed before they are written
+    pub(crate) fn transactions_mut(&mut self) -> &mut Vec<(u64, Transaction)> {
+        &mut self.transactions
+    }
+
+    /// Add the payees, categories, and transactions to the HomeBank file at `path`.
+    pub fn write(&self, path: &Path) -> Result<(), HomeBankDbError> {
+        let transactions: Vec<Transaction> = self.transactions.iter().map(|(_, tr)| tr.clone()).collect();
+
+        append_to_xhb(path, &self.payees, &self.categories, &transactions)
+    }
+
+    /// Keep a transaction read from `line`, unless it is already in the database.
+    pub(crate) fn push(&mut self, line: u64, tr: Transaction, db: &HomeBankDb, allow_duplicates: bool) {
+        if !allow_duplicates && is_duplicate(&tr, db) {
+            self.duplicates.push((line, tr));
+        } else {
+            self.transactions.push((line, tr));
+        }
+    }
+
+    /// Keep a transaction read from `line`, unless the account already has a transaction with the same info.
+    ///
+    /// This is for formats that give each transaction an ID (e.g. the `FITID` in OFX), which is stored as the info.
+    pub(crate) fn push_by_info(&mut self, line: u64, tr: Transaction, db: &HomeBankDb, allow_duplicates: bool) {
+        let seen = db
+            .transactions()
+            .iter()
+            .chain(self.transactions.iter().map(|(_, existing)| existing))
+            .any(|existing| existing.account() == tr.account() && tr.info().is_some() && existing.info() == tr.info());
+
+        if !allow_duplicates && seen {
+            self.duplicates.push((line, tr));
+        } else {
+            self.transactions.push((line, tr));
+        }
+    }
+
+    /// Keep an error for the row at `line`.
+    pub(crate) fn push_error(&mut self, line: u64, e: ImportError) {
+        self.errors.push((line, e));
+    }
+
+    /// Find the key of the payee with this name, ignoring case.
+    ///
+    /// Payees that don't exist yet are added to the result if `create` is set, and reused by later rows.
+    pub(crate) fn payee_key(&mut self, name: &str, db: &HomeBankDb, create: bool) -> Result<Option<usize>, ImportError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Ok(None);
+        }
+
+        let existing = db
+            .payees()
+            .values()
+            .chain(self.payees.iter())
+            .find(|payee| payee.name().to_lowercase() == name.to_lowercase());
+
+        match existing {
+            Some(payee) => Ok(Some(payee.key())),
+            None if create => {
+                let key = db
+                    .payees()
+                    .keys()
+                    .copied()
+                    .chain(self.payees.iter().map(|payee| payee.key()))
+                    .max()
+                    .unwrap_or(0)
+                    + 1;
+                self.payees.push(Payee::new(key, name, None, None));
+
+                Ok(Some(key))
+            }
+            None => Err(ImportError::UnknownPayee(name.to_string())),
+        }
+    }
+
+    /// Find the key of the category with this full name (e.g. `Food:Groceries`), ignoring case.
+    ///
+    /// Categories that don't exist yet are added to the result if `create` is set, along with their parent, and reused by later rows.
+    /// New categories are for income if `income` is set, and for expenses otherwise.
+    pub(crate) fn category_key(
+        &mut self,
+        full_name: &str,
+        db: &HomeBankDb,
+        create: bool,
+        income: bool,
+    ) -> Result<Option<usize>, ImportError> {
+        let full_name = full_name.trim();
+        if full_name.is_empty() {
+            return Ok(None);
+        }
+
+        let existing = db
+            .categories()
+            .values()
+            .chain(self.categories.iter())
+            .find(|cat| self.category_full_name(cat, db).to_lowercase() == full_name.to_lowercase());
+        if let Some(cat) = existing {
+            return Ok(Some(cat.key()));
+        }
+        if !create {
+            return Err(ImportError::UnknownRowCategory(full_name.to_string()));
+        }
+
+        let (parent_key, name) = match full_name.split_once(':') {
+            Some((parent, name)) => (self.category_key(parent, db, create, income)?, name.trim()),
+            None => (None, full_name),
+        };
+        let key = db
+            .categories()
+            .keys()
+            .copied()
+            .chain(self.categories.iter().map(|cat| cat.key()))
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let flags = match (parent_key.is_some(), income) {
+            (true, true) => GF_SUB | GF_INCOME,
+            (true, false) => GF_SUB,
+            (false, true) => GF_INCOME,
+            (false, false) => 0,
+        };
+        self.categories.push(Category::new(key, flags, name, parent_key));
+
+        Ok(Some(key))
+    }
+
+    /// Find the category with this key, whether it is already in the database or is being added.
+    fn category<'a>(&'a self, key: usize, db: &'a HomeBankDb) -> Option<&'a Category> {
+        db.category(key).or_else(|| self.categories.iter().find(|cat| cat.key() == key))
+    }
+
+    /// Retrieve the full name of a category, whose parent may be in the database or being added.
+    pub fn category_full_name(&self, cat: &Category, db: &HomeBankDb) -> String {
+        match cat.parent_key().and_then(|key| self.category(key, db)) {
+            Some(parent) => format!("{}:{}", parent.name(), cat.name()),
+            None => cat.name().to_string(),
+        }
+    }
+}
+
+/// Find the key of the account with this name, ignoring case.
+pub(crate) fn account_key(name: &str, db: &HomeBankDb) -> Result<usize, ImportError> {
+    match db.account_by_name(name) {
+        Some(acct) => Ok(acct.key()),
+        None => Err(ImportError::UnknownAccount(name.to_string())),
+    }
+}
+
+/// Create a transaction that is marked as added by an import.
+pub(crate) fn imported_transaction(
+    date: &NaiveDate,
+    amount: f32,
+    account: usize,
+    payee: &Option<usize>,
+    category: &Option<usize>,
+    memo: &Option<String>,
+    info: &Option<String>,
+) -> Transaction {
+    let mut flags = TransactionFlags::default()
+        .with(&TransactionFlag::Added)
+        .with(&TransactionFlag::Imported);
+    let ttype = if amount > 0.0 {
+        flags = flags.with(&TransactionFlag::Income);
+        TransactionType::Income
+    } else {
+        TransactionType::Expense
+    };
+
+    Transaction::new(
+        date,
+        amount,
+        account,
+        &PayMode::None,
+        &TransactionStatus::None,
+        &Some(flags),
+        payee,
+        memo,
+        info,
+        &None,
+        &ttype,
+        &TransactionComplexity::Simple(SimpleTransaction::new(*category, amount, memo.clone())),
+    )
+}
+
+/// Check whether the database already has a transaction on the same date, in the same account, for the same amount.
+fn is_duplicate(tr: &Transaction, db: &HomeBankDb) -> bool {
+    db.transactions().iter().any(|existing| {
+        existing.date() == tr.date()
+            && existing.account() == tr.account()
+            && (existing.total() - tr.total()).abs() < AMOUNT_TOLERANCE
+    })
+}
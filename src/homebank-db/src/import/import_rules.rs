@@ -0,0 +1,236 @@
+//! Rules that fill in the category, pay mode, and tags of transactions from matching payees.
+
+use super::{ImportError, ImportResult};
+use crate::{HomeBankDb, PayMode, Transaction};
+use regex::Regex;
+
+/// A rule that fills in the details of transactions whose payee matches a regular expression.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    /// Regular expression that the payee's name must match.
+    payee: Regex,
+
+    /// Full name of the category to set, e.g. `Food:Groceries`.
+    category: Option<String>,
+
+    /// Payment method to set.
+    pay_mode: Option<PayMode>,
+
+    /// Tags to add.
+    tags: Vec<String>,
+}
+
+impl Rule {
+    /// Create a new `Rule`
+    pub fn new(payee: Regex, category: Option<&str>, pay_mode: Option<PayMode>, tags: &[String]) -> Self {
+        Self {
+            payee,
+            category: category.map(|s| s.to_string()),
+            pay_mode,
+            tags: tags.to_vec(),
+        }
+    }
+
+    /// Retrieve the regular expression that the payee's name must match
+    pub fn payee(&self) -> &Regex {
+        &self.payee
+    }
+
+    /// Retrieve the full name of the category to set
+    pub fn category(&self) -> Option<&str> {
+        self.category.as_deref()
+    }
+
+    /// Retrieve the payment method to set
+    pub fn pay_mode(&self) -> Option<&PayMode> {
+        self.pay_mode.as_ref()
+    }
+
+    /// Retrieve the tags to add
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+}
+
+impl PartialEq for Rule {
+    fn eq(&self, other: &Self) -> bool {
+        self.payee.as_str() == other.payee.as_str()
+            && self.category == other.category
+            && self.pay_mode == other.pay_mode
+            && self.tags == other.tags
+    }
+}
+
+/// An ordered list of [`Rule`]s, with their categories found in a HomeBank database.
+///
+/// Only the first rule that matches a transaction's payee is applied.
+#[derive(Debug, Default, PartialEq)]
+pub struct Rules {
+    rules: Vec<(Rule, Option<usize>)>,
+}
+
+impl Rules {
+    /// Find the categories of the rules in the database.
+    ///
+    /// Fails if any rule refers to a category that doesn't exist, so that a typo doesn't leave transactions uncategorised.
+    pub fn new(rules: &[Rule], db: &HomeBankDb) -> Result<Self, ImportError> {
+        let rules = rules
+            .iter()
+            .map(|rule| match rule.category() {
+                Some(name) => category_key(name, db).map(|key| (rule.clone(), Some(key))),
+                None => Ok((rule.clone(), None)),
+            })
+            .collect::<Result<Vec<(Rule, Option<usize>)>, ImportError>>()?;
+
+        Ok(Self { rules })
+    }
+
+    /// Check whether there are no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Apply the first rule matching the transaction's payee.
+    ///
+    /// Only uncategorised, non-split transactions are changed.
+    /// Returns whether a rule was applied.
+    pub fn apply(&self, tr: &mut Transaction, db: &HomeBankDb) -> bool {
+        let payee = tr.payee_name(db);
+
+        self.apply_for_payee(tr, payee.as_deref())
+    }
+
+    /// Apply the rules to every transaction being imported.
+    ///
+    /// Returns the number of transactions that a rule was applied to.
+    pub fn apply_to_import(&self, result: &mut ImportResult, db: &HomeBankDb) -> usize {
+        // payees created by the import aren't in the database, so look up each name before changing the transactions
+        let payees: Vec<Option<String>> = result
+            .transactions()
+            .iter()
+            .map(|(_, tr)| result.payee_name(tr, db))
+            .collect();
+
+        let mut applied = 0;
+        for ((_, tr), payee) in result.transactions_mut().iter_mut().zip(payees) {
+            if self.apply_for_payee(tr, payee.as_deref()) {
+                applied += 1;
+            }
+        }
+
+        applied
+    }
+
+    /// Apply the first rule matching `payee`, the name of the transaction's payee.
+    fn apply_for_payee(&self, tr: &mut Transaction, payee: Option<&str>) -> bool {
+        let payee = match payee {
+            Some(name) if !tr.is_split() && tr.categories().iter().all(|cat| cat.is_none()) => name,
+            _ => return false,
+        };
+
+        match self.rules.iter().find(|(rule, _)| rule.payee().is_match(payee)) {
+            Some((rule, category)) => {
+                if let Some(key) = category {
+                    tr.set_category(*key);
+                }
+                if let Some(pay_mode) = rule.pay_mode() {
+                    tr.set_pay_mode(pay_mode);
+                }
+                if !rule.tags().is_empty() {
+                    tr.add_tags(rule.tags());
+                }
+
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Find the key of the category with this full name (e.g. `Food:Groceries`), ignoring case.
+fn category_key(name: &str, db: &HomeBankDb) -> Result<usize, ImportError> {
+    match db.category_by_name(name) {
+        Some(cat) => Ok(cat.key()),
+        None => Err(ImportError::UnknownCategory(name.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+    use crate::import::import_result::imported_transaction;
+    use chrono::NaiveDate;
+
+    fn rule(payee: &str, category: Option<&str>, pay_mode: Option<PayMode>, tags: &[&str]) -> Rule {
+        let tags: Vec<String> = tags.iter().map(|tag| tag.to_string()).collect();
+
+        Rule::new(Regex::new(payee).unwrap(), category, pay_mode, &tags)
+    }
+
+    /// An uncategorised grocery purchase, like one read from a bank statement.
+    fn uncategorised() -> Transaction {
+        let date = NaiveDate::from_ymd_opt(2022, 3, 1).unwrap();
+
+        imported_transaction(&date, -62.1, 1, &Some(3), &None, &None, &None)
+    }
+
+    #[test]
+    fn unknown_category() {
+        let rules = [rule("Grocer", Some("Food:Snacks"), None, &[])];
+
+        assert_eq!(
+            Err(ImportError::UnknownCategory("Food:Snacks".to_string())),
+            Rules::new(&rules, &example_db())
+        );
+    }
+
+    #[test]
+    fn apply_first_match() {
+        let db = example_db();
+        let rules = Rules::new(
+            &[
+                rule("^Groc", Some("Food:Groceries"), Some(PayMode::DebitCard), &["weekly"]),
+                rule("Grocer", Some("Food:Dining"), None, &[]),
+            ],
+            &db,
+        )
+        .unwrap();
+
+        let mut tr = uncategorised();
+        assert!(rules.apply(&mut tr, &db));
+        assert_eq!(vec![&Some(4)], tr.categories());
+        assert_eq!(&PayMode::DebitCard, tr.pay_mode());
+        assert_eq!(&Some(vec!["weekly".to_string()]), tr.tags());
+    }
+
+    #[test]
+    fn category_ignores_case() {
+        let db = example_db();
+        let rules = Rules::new(&[rule("Grocer", Some("food:groceries"), None, &[])], &db).unwrap();
+
+        let mut tr = uncategorised();
+        assert!(rules.apply(&mut tr, &db));
+        assert_eq!(vec![&Some(4)], tr.categories());
+    }
+
+    #[test]
+    fn skip_categorised() {
+        let db = example_db();
+        let rules = Rules::new(&[rule("Grocer", Some("Food:Dining"), None, &[])], &db).unwrap();
+
+        let mut tr = db.transactions()[3].clone();
+        assert!(!rules.apply(&mut tr, &db));
+        assert_eq!(&db.transactions()[3], &tr);
+    }
+
+    #[test]
+    fn no_match() {
+        let db = example_db();
+        let rules = Rules::new(&[rule("Landlord", Some("Housing:Rent"), None, &[])], &db).unwrap();
+
+        let mut tr = uncategorised();
+        assert!(!rules.apply(&mut tr, &db));
+        assert_eq!(vec![&None], tr.categories());
+    }
+}
@@ -0,0 +1,36 @@
+//! Import [`Transaction`s][crate::transaction::transaction_struct::Transaction] from other formats into the HomeBank database.
+
+pub mod import_csv;
+pub mod import_error;
+pub mod import_ofx;
+pub mod import_result;
+pub mod import_rules;
+
+pub use import_csv::{ColumnMap, ImportCsv};
+pub use import_error::ImportError;
+pub use import_ofx::ImportOfx;
+pub use import_result::ImportResult;
+pub use import_rules::{Rule, Rules};
+
+use clap::Parser;
+
+/// A subcommand to import transactions from the CLI.
+#[derive(Debug, Parser)]
+pub struct ImportOpts {
+    #[command(subcommand)]
+    import_type: ImportType,
+}
+
+impl ImportOpts {
+    /// Retrieve the format being imported from
+    pub fn itype(&self) -> &ImportType {
+        &self.import_type
+    }
+}
+
+/// Differentiate between the different import formats from the CLI
+#[derive(Debug, Parser)]
+pub enum ImportType {
+    Csv(ImportCsv),
+    Ofx(ImportOfx),
+}
@@ -4,31 +4,73 @@ pub mod account;
 pub mod category;
 pub mod currency;
 pub mod db;
+pub mod edit;
+pub mod export;
 pub mod group;
+pub mod import;
 pub mod payee;
 pub mod paymode;
 pub mod query;
-// pub mod template;
+pub mod report;
+pub mod template;
 pub mod transaction;
 
-pub use account::{Account, AccountError, AccountType, QueryAccounts};
+pub use account::{Account, AccountError, AccountSort, AccountType, QueryAccounts};
 pub use category::{Category, CategoryError, QueryCategories};
 pub use currency::{Currency, CurrencyError, QueryCurrencies};
-pub use db::{HomeBankDb, HomeBankDbProperties, HomeBankDbSchema};
+pub use edit::{edit_xhb, Edit, EditCategory, EditError, EditOpts, EditPayee, EditSummary, EditTag, EditType, ReferenceKind};
+pub use export::{ExportError, ExportOpts, ExportType};
+pub use db::{open_homebank, DbStats, HomeBankDb, HomeBankDbProperties, HomeBankDbSchema, NameIndex, OrphanKind, ParseReport, ParseWarning, SkippedTransactions, stream_transactions, TagStats, TransactionStream, ValidationIssue, SUPPORTED_VERSIONS};
 pub use group::{Group, QueryGroups};
+pub use import::{ImportError, ImportOpts, ImportType, Rule, Rules};
 pub use payee::{Payee, PayeeError, QueryPayees};
 pub use paymode::PayMode;
-pub use query::{Query, QueryOpts, QueryType};
-// pub use template::{QueryTemplates, Template};
+pub use query::{Query, QueryOpts, QueryTags, QueryType, WatchOpts};
+pub use report::{ReportFormat, ReportOpts, ReportType};
+pub use template::{Favourite, QueryTemplates, TemplateError};
 pub use transaction::{
-    QueryTransactions, Transaction, TransactionError, TransactionStatus, TransactionType,
+    QueryTransactions, Transaction, TransactionBuilder, TransactionError, TransactionFilter, TransactionFlag, TransactionFlags,
+    TransactionStatus, TransactionType,
 };
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use clap::ValueEnum;
+    use std::{fmt::Debug, path::Path, str::FromStr};
+
+    /// Load the example HomeBank file, with a few months of transactions in three accounts, that most tests use.
+    pub(crate) fn example_db() -> HomeBankDb {
+        HomeBankDb::try_from(Path::new("tests/transactions.xhb")).unwrap()
+    }
+
     #[test]
     fn it_works() {
         let result = 2 + 2;
         assert_eq!(result, 4);
     }
+
+    /// The enums parsed from the command line list their canonical names as possible values, for `--help` and shell completions,
+    /// and the other names accepted by their `from_str` as aliases, so that those are still accepted from the CLI.
+    #[track_caller]
+    fn check_possible_values<T>()
+    where
+        T: ValueEnum + FromStr + PartialEq + Debug,
+        T::Err: Debug,
+    {
+        for variant in T::value_variants() {
+            let value = variant.to_possible_value().unwrap();
+
+            for name in value.get_name_and_aliases() {
+                assert_eq!(variant, &<T as FromStr>::from_str(name).unwrap(), "`{name}` does not parse");
+            }
+        }
+    }
+
+    #[test]
+    fn possible_values_match_from_str() {
+        check_possible_values::<PayMode>();
+        check_possible_values::<ReportFormat>();
+        check_possible_values::<TransactionStatus>();
+    }
 }
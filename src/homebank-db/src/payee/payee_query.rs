@@ -6,14 +6,14 @@ use regex::Regex;
 
 /// Options for filtering [`Payee`s][crate::payee::payee_struct::Payee] from the [`HomeBankDb`].
 #[derive(Debug, Parser)]
-#[clap(
+#[command(
     name = "payees",
     visible_alias = "p",
     about = "Query transaction payees, to and from"
 )]
 pub struct QueryPayees {
     /// Name of the payee.
-    #[clap(value_name = "regex")]
+    #[arg(value_name = "regex")]
     name: Option<Regex>,
 }
 
@@ -6,6 +6,7 @@ use xml::attribute::OwnedAttribute;
 
 /// The donor or recipient of a [`Transaction`][crate::transaction::transaction_struct::Transaction].
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Payee {
     /// Unique key for the payee in the database.
     key: usize,
@@ -1,7 +1,8 @@
 //! Payment method for a [`Transaction`][crate::transaction::transaction_struct::Transaction].
 
 use crate::TransactionError;
-use std::str::FromStr;
+use clap::{builder::PossibleValue, ValueEnum};
+use std::{fmt, str::FromStr};
 
 /// Payment method for a [`Transaction`][crate::transaction::transaction_struct::Transaction].
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -17,6 +18,75 @@ pub enum PayMode {
     Deposit,
     FinancialInstitutionFee,
     DirectDebit,
+
+    /// A payment method added in a newer version of HomeBank, kept as the number stored in the file.
+    Other(usize),
+}
+
+impl PayMode {
+    /// Retrieve the number that represents the payment method in the HomeBank file
+    pub fn number(&self) -> usize {
+        match self {
+            PayMode::None => 0,
+            PayMode::CreditCard => 1,
+            PayMode::Cheque => 2,
+            PayMode::Cash => 3,
+            PayMode::BankTransfer => 4,
+            PayMode::DebitCard => 5,
+            PayMode::StandingOrder => 6,
+            PayMode::ElectronicPayment => 7,
+            PayMode::Deposit => 8,
+            PayMode::FinancialInstitutionFee => 9,
+            PayMode::DirectDebit => 10,
+            PayMode::Other(u) => *u,
+        }
+    }
+
+    /// Retrieve every payment method known to this version, in the order HomeBank numbers them
+    pub fn variants() -> &'static [PayMode] {
+        &[
+            PayMode::None,
+            PayMode::CreditCard,
+            PayMode::Cheque,
+            PayMode::Cash,
+            PayMode::BankTransfer,
+            PayMode::DebitCard,
+            PayMode::StandingOrder,
+            PayMode::ElectronicPayment,
+            PayMode::Deposit,
+            PayMode::FinancialInstitutionFee,
+            PayMode::DirectDebit,
+        ]
+    }
+
+    /// Retrieve the canonical name of the payment method, as accepted on the command line.
+    /// Payment methods from newer versions of HomeBank are all named `other`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PayMode::None => "none",
+            PayMode::CreditCard => "credit",
+            PayMode::Cheque => "cheque",
+            PayMode::Cash => "cash",
+            PayMode::BankTransfer => "transfer",
+            PayMode::DebitCard => "debit",
+            PayMode::StandingOrder => "StandingOrder",
+            PayMode::ElectronicPayment => "etransfer",
+            PayMode::Deposit => "deposit",
+            PayMode::FinancialInstitutionFee => "fee",
+            PayMode::DirectDebit => "DirectDebit",
+            PayMode::Other(_) => "other",
+        }
+    }
+}
+
+impl fmt::Display for PayMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // the number is the only thing known about payment methods from newer versions
+            PayMode::Other(u) => write!(f, "{u}"),
+            _ => write!(f, "{}", self.as_str()),
+        }
+    }
 }
 
 impl Default for PayMode {
@@ -41,7 +111,8 @@ impl TryFrom<usize> for PayMode {
             8 => Ok(PayMode::Deposit),
             9 => Ok(PayMode::FinancialInstitutionFee),
             10 => Ok(PayMode::DirectDebit),
-            _ => Err(TransactionError::InvalidStatus),
+            // newer versions of HomeBank add payment methods, which shouldn't stop the file from being read
+            _ => Ok(PayMode::Other(u)),
         }
     }
 }
@@ -62,7 +133,108 @@ impl FromStr for PayMode {
             "8" | "Deposit" | "deposit" => Ok(PayMode::Deposit),
             "9" | "FIFee" | "Fee" | "fee" => Ok(PayMode::FinancialInstitutionFee),
             "10" | "DirectDebit" => Ok(PayMode::DirectDebit),
-            _ => Err(TransactionError::InvalidPayMode),
+            _ => match usize::from_str(s) {
+                Ok(u) if u > 10 => Ok(PayMode::Other(u)),
+                _ => Err(TransactionError::InvalidPayMode),
+            },
+        }
+    }
+}
+
+/// Serialized as its name, or its number if it comes from a newer version of HomeBank.
+#[cfg(feature = "serde")]
+impl serde::Serialize for PayMode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PayMode {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl ValueEnum for PayMode {
+    fn value_variants<'a>() -> &'a [Self] {
+        Self::variants()
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        let value = PossibleValue::new(self.as_str());
+        let value = match self {
+            PayMode::None => value.aliases(["0", "None"]),
+            PayMode::CreditCard => value.aliases(["1", "CreditCard", "Credit"]),
+            PayMode::Cheque => value.aliases(["2", "Cheque", "Check", "check"]),
+            PayMode::Cash => value.aliases(["3", "Cash"]),
+            PayMode::BankTransfer => value.aliases(["4", "BankTransfer"]),
+            PayMode::DebitCard => value.aliases(["5", "DebitCard", "Debit"]),
+            PayMode::StandingOrder => value.alias("6"),
+            PayMode::ElectronicPayment => value.aliases([
+                "7",
+                "ElectronicPayment",
+                "ETransfer",
+                "eTransfer",
+                "E-Transfer",
+                "e-Transfer",
+                "e-transfer",
+            ]),
+            PayMode::Deposit => value.aliases(["8", "Deposit"]),
+            PayMode::FinancialInstitutionFee => value.aliases(["9", "FIFee", "Fee"]),
+            PayMode::DirectDebit => value.alias("10"),
+            // not listed in `value_variants`, so there is nothing to show
+            PayMode::Other(_) => return None,
+        };
+
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variants_in_order() {
+        let numbers: Vec<usize> = PayMode::variants().iter().map(|mode| mode.number()).collect();
+
+        assert_eq!((0..=10).collect::<Vec<usize>>(), numbers);
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for mode in PayMode::variants().iter().chain(&[PayMode::Other(12)]) {
+            assert_eq!(Ok(*mode), <PayMode as FromStr>::from_str(&mode.to_string()));
         }
     }
+
+    #[track_caller]
+    fn check_try_from(input: usize, expected: PayMode) {
+        let observed = PayMode::try_from(input);
+
+        assert_eq!(Ok(expected), observed);
+        assert_eq!(input, expected.number());
+    }
+
+    #[test]
+    fn try_from_known() {
+        check_try_from(0, PayMode::None);
+        check_try_from(10, PayMode::DirectDebit);
+    }
+
+    #[test]
+    fn try_from_newer() {
+        check_try_from(11, PayMode::Other(11));
+        check_try_from(42, PayMode::Other(42));
+    }
+
+    #[test]
+    fn from_str_newer() {
+        assert_eq!(Ok(PayMode::Other(11)), <PayMode as FromStr>::from_str("11"));
+        assert_eq!(Err(TransactionError::InvalidPayMode), <PayMode as FromStr>::from_str("-1"));
+        assert_eq!(Err(TransactionError::InvalidPayMode), <PayMode as FromStr>::from_str("eleven"));
+    }
 }
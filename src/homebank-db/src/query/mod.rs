@@ -1,23 +1,43 @@
 //! Query the HomeBank database from the command line.
 
 use crate::{
-    currency::QueryCurrencies, group::QueryGroups, payee::QueryPayees,
+    currency::QueryCurrencies, group::QueryGroups, payee::QueryPayees, template::QueryTemplates,
     transaction::QueryTransactions, HomeBankDb, QueryAccounts, QueryCategories,
 };
 use clap::Parser;
 
+pub mod query_tags;
+pub mod query_watch;
+
+pub use query_tags::QueryTags;
+pub use query_watch::WatchOpts;
+
 /// A common way to execute queries of different data types in the HomeBank database.
 pub trait Query {
     type T;
 
     /// Execute the query
     fn exec(&self, db: &HomeBankDb) -> Vec<Self::T>;
+
+    /// Execute the query and keep only the first result, for queries that are expected to have one
+    fn exec_one(&self, db: &HomeBankDb) -> Option<Self::T> {
+        self.exec(db).into_iter().next()
+    }
+
+    /// Execute the query and reduce the results to a single value, starting from `init`
+    fn exec_scalar<U, F>(&self, db: &HomeBankDb, init: U, f: F) -> U
+    where
+        Self: Sized,
+        F: FnMut(U, Self::T) -> U,
+    {
+        self.exec(db).into_iter().fold(init, f)
+    }
 }
 
 /// A subcommand to query the database from the CLI.
 #[derive(Debug, Parser)]
 pub struct QueryOpts {
-    #[clap(subcommand)]
+    #[command(subcommand)]
     query_type: QueryType,
 }
 
@@ -29,6 +49,8 @@ impl QueryOpts {
 }
 
 /// Differentiate between the different query types from the CLI
+// only one is ever parsed from the command line, so the size of the largest variant doesn't matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Parser)]
 pub enum QueryType {
     Accounts(QueryAccounts),
@@ -36,6 +58,45 @@ pub enum QueryType {
     Currencies(QueryCurrencies),
     Groups(QueryGroups),
     Payees(QueryPayees),
-    // Templates(QueryTemplates),
+    Tags(QueryTags),
+    Templates(QueryTemplates),
     Transactions(QueryTransactions),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    #[track_caller]
+    fn check_query(args: &[&str]) -> (Option<f32>, f32) {
+        let db = example_db();
+        let query = QueryTransactions::try_parse_from(args).unwrap();
+
+        (
+            query.exec_one(&db).map(|tr| *tr.total()),
+            query.exec_scalar(&db, 0.0, |sum, tr| sum + tr.total()),
+        )
+    }
+
+    #[test]
+    fn exec_one_row() {
+        let args = ["transactions", "--payee", "Employer", "--date-from", "2022-02-01"];
+
+        assert_eq!((Some(2000.0), 2000.0), check_query(&args));
+    }
+
+    #[test]
+    fn exec_one_first_of_many() {
+        let args = ["transactions", "--payee", "Landlord"];
+
+        assert_eq!((Some(-1200.0), -3600.0), check_query(&args));
+    }
+
+    #[test]
+    fn exec_one_empty() {
+        let args = ["transactions", "--payee", "Nobody"];
+
+        assert_eq!((None, 0.0), check_query(&args));
+    }
+}
@@ -0,0 +1,72 @@
+//! Options for listing the tags of the [`Transaction`s][crate::transaction::transaction_struct::Transaction] in the [`HomeBankDb`].
+
+use crate::{HomeBankDb, Query, TagStats};
+use clap::Parser;
+use regex::Regex;
+
+/// Options for listing the tags of the [`Transaction`s][crate::transaction::transaction_struct::Transaction] in the [`HomeBankDb`].
+#[derive(Debug, Default, Parser)]
+#[command(
+    name = "tags",
+    about = "Query transaction tags, with their number of transactions and total amount"
+)]
+pub struct QueryTags {
+    /// Name of the tag.
+    #[arg(value_name = "regex")]
+    name: Option<Regex>,
+
+    /// Combine tags that differ only by case, e.g. `Holiday` and `holiday`, listing them by their lowercase name.
+    #[arg(long = "case-insensitive")]
+    case_insensitive: bool,
+}
+
+impl QueryTags {
+    /// Create a new query for the tags matching `name`
+    pub fn new(name: Option<Regex>, case_insensitive: bool) -> Self {
+        Self { name, case_insensitive }
+    }
+
+    /// Retrieve the regular expression for the tag name
+    pub fn name(&self) -> &Option<Regex> {
+        &self.name
+    }
+
+    /// Retrieve whether tags that differ only by case are combined
+    pub fn case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+}
+
+impl Query for QueryTags {
+    type T = (String, TagStats);
+
+    fn exec(&self, db: &HomeBankDb) -> Vec<Self::T> {
+        db.tags(self.case_insensitive)
+            .into_iter()
+            // filter out tags that don't match the regex, in any of the ways they are written
+            .filter(|(name, stats)| match self.name() {
+                Some(re) => re.is_match(name) || stats.spellings().iter().any(|tag| re.is_match(tag)),
+                None => true,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    #[test]
+    fn tags_of_example() {
+        let db = example_db();
+        let observed: Vec<(String, usize, f64)> = QueryTags::default()
+            .exec(&db)
+            .into_iter()
+            .map(|(name, stats)| (name, stats.count(), stats.total()))
+            .collect();
+
+        assert_eq!(vec![("food".to_string(), 2, -210.25), ("restaurant".to_string(), 1, -60.0)], observed);
+        assert!(QueryTags::new(Some(Regex::new("^rest").unwrap()), false).exec(&db).iter().all(|(name, _)| name == "restaurant"));
+    }
+}
@@ -0,0 +1,71 @@
+//! Options to re-run a query whenever the HomeBank file changes.
+
+use clap::Args;
+use std::time::Duration;
+
+/// Re-run a query whenever the HomeBank file changes, e.g. while it is open in HomeBank.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Args)]
+pub struct WatchOpts {
+    /// Watch the HomeBank file, and clear the terminal and re-run the query whenever it is saved.
+    #[arg(long = "watch")]
+    watch: bool,
+
+    /// Check the HomeBank file for changes every this many milliseconds, for filesystems that don't report them.
+    #[arg(long = "watch-interval", value_name = "ms", requires = "watch")]
+    watch_interval: Option<u64>,
+}
+
+impl WatchOpts {
+    /// Create new options to watch the HomeBank file, polling it every `interval` if one is given
+    pub fn new(watch: bool, interval: Option<u64>) -> Self {
+        Self {
+            watch,
+            watch_interval: interval,
+        }
+    }
+
+    /// Check whether the HomeBank file should be watched
+    pub fn watch(&self) -> bool {
+        self.watch
+    }
+
+    /// Retrieve how often the HomeBank file should be polled for changes, if events from the filesystem aren't used
+    pub fn poll_interval(&self) -> Option<Duration> {
+        self.watch_interval.map(Duration::from_millis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Debug, Parser)]
+    struct WatchCommand {
+        #[command(flatten)]
+        watch: WatchOpts,
+    }
+
+    #[track_caller]
+    fn check_watch(args: &[&str], expected: WatchOpts) {
+        let observed = WatchCommand::try_parse_from(args).unwrap().watch;
+
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    fn no_watch() {
+        check_watch(&["query"], WatchOpts::default());
+    }
+
+    #[test]
+    fn watch_with_interval() {
+        check_watch(&["query", "--watch", "--watch-interval", "500"], WatchOpts::new(true, Some(500)));
+        assert_eq!(Some(Duration::from_millis(500)), WatchOpts::new(true, Some(500)).poll_interval());
+    }
+
+    #[test]
+    fn interval_requires_watch() {
+        assert!(WatchCommand::try_parse_from(["query", "--watch-interval", "500"]).is_err());
+    }
+}
@@ -0,0 +1,97 @@
+//! Summary reports of the [`Transaction`s][crate::transaction::transaction_struct::Transaction] in the HomeBank database.
+
+pub mod report_cashflow;
+pub mod report_duplicates;
+pub mod report_error;
+pub mod report_format;
+pub mod report_monthly;
+pub mod report_net_worth;
+pub mod report_period;
+pub mod report_reconcile;
+pub mod report_stats;
+pub mod report_tags;
+pub mod report_top;
+pub(crate) mod report_totals;
+pub mod report_yearly;
+
+pub use report_cashflow::{cashflow, CashflowPeriod, ReportCashflow};
+pub use report_duplicates::ReportDuplicates;
+pub use crate::db::DuplicateKey;
+pub use report_error::ReportError;
+pub use report_format::ReportFormat;
+pub use report_monthly::{MonthlySummary, ReportMonthly};
+pub use report_net_worth::{net_worth, NetWorthPoint, ReportNetWorth};
+pub use report_period::Interval;
+pub use report_reconcile::{compare_statement, reconcile, ReconcileBalance, ReconcileStatement, ReportReconcile, StatementComparison};
+pub use report_stats::{CategoryStats, ReportStats};
+pub use report_tags::ReportTags;
+pub use report_top::{ReportTop, TopType};
+pub use report_yearly::{build_yearly_report, ReportYearly, YearlyReport};
+
+use clap::Parser;
+
+/// A subcommand to generate reports from the CLI.
+#[derive(Debug, Parser)]
+pub struct ReportOpts {
+    /// Output format of the report [default: `output_format` in the configuration file, or `table`]
+    #[arg(
+        short = 'f',
+        long = "format",
+        value_name = "format",
+        value_enum,
+        global = true
+    )]
+    format: Option<ReportFormat>,
+
+    /// Character between the whole and fractional parts of amounts in CSV output.
+    #[arg(
+        long = "decimal-sep",
+        default_value = ".",
+        value_name = "char",
+        global = true
+    )]
+    decimal_sep: char,
+
+    /// Character between groups of thousands in amounts in CSV output.
+    #[arg(long = "thousands-sep", value_name = "char", global = true)]
+    thousands_sep: Option<char>,
+
+    #[command(subcommand)]
+    report_type: ReportType,
+}
+
+impl ReportOpts {
+    /// Retrieve the output format of the report, if one was given
+    pub fn format(&self) -> Option<ReportFormat> {
+        self.format
+    }
+
+    /// Retrieve the decimal separator for amounts in CSV output
+    pub fn decimal_sep(&self) -> char {
+        self.decimal_sep
+    }
+
+    /// Retrieve the thousands separator for amounts in CSV output, if any
+    pub fn thousands_sep(&self) -> Option<char> {
+        self.thousands_sep
+    }
+
+    /// Retrieve the type of report being made
+    pub fn rtype(&self) -> &ReportType {
+        &self.report_type
+    }
+}
+
+/// Differentiate between the different reports from the CLI
+#[derive(Debug, Parser)]
+pub enum ReportType {
+    Monthly(ReportMonthly),
+    Yearly(ReportYearly),
+    Top(ReportTop),
+    NetWorth(ReportNetWorth),
+    Cashflow(ReportCashflow),
+    Duplicates(ReportDuplicates),
+    Reconcile(ReportReconcile),
+    Tags(ReportTags),
+    Stats(ReportStats),
+}
@@ -0,0 +1,215 @@
+//! Summarize income and expenses for each week, month, quarter, or year in the HomeBank database.
+
+use super::{report_totals::PeriodTotals, Interval};
+use crate::{HomeBankDb, Query};
+use chrono::NaiveDate;
+use clap::Parser;
+use std::{collections::BTreeMap, str::FromStr};
+
+/// Summarize income and expenses for each week, month, quarter, or year in the HomeBank database.
+#[derive(Debug, Parser)]
+#[command(about = "Summarize income and expenses for each week, month, quarter, or year")]
+pub struct ReportCashflow {
+    /// Length of each period: 'week', 'month', 'quarter', or 'year'.
+    #[arg(
+        short = 'i',
+        long = "interval",
+        default_value = "month",
+        value_name = "interval"
+    )]
+    interval: Interval,
+
+    /// Include transactions starting from (and including) this date.
+    #[arg(
+        short = 'd',
+        long = "date-from",
+        value_parser = NaiveDate::from_str,
+        value_name = "date"
+    )]
+    date_from: Option<NaiveDate>,
+
+    /// Include transactions up to (and including) this date.
+    #[arg(
+        short = 'D',
+        long = "date-to",
+        value_parser = NaiveDate::from_str,
+        value_name = "date"
+    )]
+    date_to: Option<NaiveDate>,
+}
+
+impl ReportCashflow {
+    /// Create a new cash flow report
+    pub fn new(interval: Interval, date_from: Option<NaiveDate>, date_to: Option<NaiveDate>) -> Self {
+        Self {
+            interval,
+            date_from,
+            date_to,
+        }
+    }
+
+    /// Retrieve the length of each period
+    pub fn interval(&self) -> &Interval {
+        &self.interval
+    }
+}
+
+/// The income and expenses within a single period.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CashflowPeriod {
+    /// Name of the period, e.g. `2024-W07` or `2024-02`.
+    label: String,
+
+    /// First day of the period.
+    start: NaiveDate,
+
+    /// Sum of all income.
+    income: f32,
+
+    /// Sum of all expenses (negative).
+    expenses: f32,
+}
+
+impl CashflowPeriod {
+    /// Create a new cash flow period
+    pub fn new(label: &str, start: NaiveDate, income: f32, expenses: f32) -> Self {
+        Self {
+            label: label.to_string(),
+            start,
+            income,
+            expenses,
+        }
+    }
+
+    /// Retrieve the name of the period
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Retrieve the first day of the period
+    pub fn start(&self) -> &NaiveDate {
+        &self.start
+    }
+
+    /// Retrieve the sum of all income
+    pub fn income(&self) -> f32 {
+        self.income
+    }
+
+    /// Retrieve the sum of all expenses
+    pub fn expenses(&self) -> f32 {
+        self.expenses
+    }
+
+    /// Retrieve the net cash flow (income + expenses, since expenses are negative)
+    pub fn net(&self) -> f32 {
+        self.income + self.expenses
+    }
+}
+
+/// Summarize the income and expenses in each period between `date_from` and `date_to` (inclusive).
+///
+/// Every period from the first to the last transaction is included, even if it has no transactions, so that averages over the periods are fair.
+pub fn cashflow(
+    db: &HomeBankDb,
+    interval: &Interval,
+    date_from: Option<NaiveDate>,
+    date_to: Option<NaiveDate>,
+) -> Vec<CashflowPeriod> {
+    // sorted by the start of each period so that the report is in chronological order
+    let mut periods: BTreeMap<NaiveDate, PeriodTotals> = BTreeMap::new();
+
+    db.transactions()
+        .iter()
+        // transfers move money between accounts, they are neither income nor expenses
+        .filter(|tr| !tr.is_transfer())
+        // voided transactions never affected any balance
+        .filter(|tr| !tr.is_void())
+        .filter(|tr| date_from.iter().all(|d| tr.date() >= d))
+        .filter(|tr| date_to.iter().all(|d| tr.date() <= d))
+        .for_each(|tr| {
+            periods.entry(interval.start(tr.date())).or_default().add(tr, db);
+        });
+
+    let (first, last) = match (periods.keys().next(), periods.keys().next_back()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return vec![],
+    };
+
+    interval
+        .starts(&first, &last)
+        .into_iter()
+        .map(|start| {
+            let (income, expenses) = periods
+                .get(&start)
+                .map_or((0.0, 0.0), |totals| (totals.income(), totals.expenses()));
+
+            CashflowPeriod::new(&interval.label(&start), start, income, expenses)
+        })
+        .collect()
+}
+
+impl Query for ReportCashflow {
+    type T = CashflowPeriod;
+
+    fn exec(&self, db: &HomeBankDb) -> Vec<Self::T> {
+        cashflow(db, &self.interval, self.date_from, self.date_to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[track_caller]
+    fn check_cashflow(interval: Interval, date_from: Option<NaiveDate>, expected: Vec<CashflowPeriod>) {
+        let db = example_db();
+
+        assert_eq!(expected, cashflow(&db, &interval, date_from, None));
+    }
+
+    #[test]
+    fn monthly_cashflow_matches_monthly_report() {
+        let expected = vec![
+            CashflowPeriod::new("2021-12", date(2021, 12, 1), 0.0, -1200.0),
+            CashflowPeriod::new("2022-01", date(2022, 1, 1), 2000.0, -1450.25),
+            CashflowPeriod::new("2022-02", date(2022, 2, 1), 2000.0, -1305.5),
+        ];
+
+        check_cashflow(Interval::Month, None, expected);
+    }
+
+    #[test]
+    fn yearly_cashflow() {
+        let expected = vec![
+            CashflowPeriod::new("2021", date(2021, 1, 1), 0.0, -1200.0),
+            CashflowPeriod::new("2022", date(2022, 1, 1), 4000.0, -2755.75),
+        ];
+
+        check_cashflow(Interval::Year, None, expected);
+    }
+
+    #[test]
+    fn weekly_cashflow_includes_empty_weeks() {
+        // the only transactions in the fourth week are a transfer, which is left out
+        let expected = vec![
+            CashflowPeriod::new("2022-W03", date(2022, 1, 17), 0.0, -100.0),
+            CashflowPeriod::new("2022-W04", date(2022, 1, 24), 0.0, 0.0),
+            CashflowPeriod::new("2022-W05", date(2022, 1, 31), 2000.0, 0.0),
+            CashflowPeriod::new("2022-W06", date(2022, 2, 7), 0.0, -1260.0),
+            CashflowPeriod::new("2022-W07", date(2022, 2, 14), 0.0, -45.5),
+        ];
+
+        check_cashflow(Interval::Week, Some(date(2022, 1, 20)), expected);
+    }
+
+    #[test]
+    fn empty_cashflow() {
+        check_cashflow(Interval::Month, Some(date(2030, 1, 1)), vec![]);
+    }
+}
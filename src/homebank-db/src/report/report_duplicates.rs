@@ -0,0 +1,160 @@
+//! Find groups of transactions in the HomeBank database that look like duplicates of each other.
+
+use super::ReportError;
+use crate::{db::DuplicateKey, HomeBankDb, Query, Transaction};
+use clap::Parser;
+use std::str::FromStr;
+
+impl FromStr for DuplicateKey {
+    type Err = ReportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "date" | "Date" => Ok(Self::Date),
+            "amount" | "Amount" => Ok(Self::Amount),
+            "account" | "Account" => Ok(Self::Account),
+            "payee" | "Payee" => Ok(Self::Payee),
+            "memo" | "Memo" => Ok(Self::Memo),
+            "info" | "Info" => Ok(Self::Info),
+            "category" | "Category" => Ok(Self::Category),
+            _ => Err(ReportError::InvalidDuplicateKey(s.to_string())),
+        }
+    }
+}
+
+/// Find groups of transactions that look like duplicates of each other.
+#[derive(Debug, Parser)]
+#[command(about = "List groups of transactions that look like duplicates of each other")]
+pub struct ReportDuplicates {
+    /// Fields that duplicate transactions must share, separated by commas: 'date', 'amount', 'account', 'payee', 'memo', 'info', or 'category'.
+    #[arg(
+        short = 'k',
+        long = "key",
+        default_value = "date,amount,account,payee",
+        value_delimiter = ',',
+        value_name = "field"
+    )]
+    keys: Vec<DuplicateKey>,
+
+    /// Number of days that the dates of duplicates can differ by.
+    #[arg(long = "tolerance-days", default_value = "0", value_name = "n")]
+    tolerance_days: u32,
+
+    /// Amount that the amounts of duplicates can differ by.
+    #[arg(long = "amount-epsilon", default_value = "0.005", value_name = "f")]
+    amount_epsilon: f64,
+}
+
+impl ReportDuplicates {
+    /// Create a new duplicates report
+    pub fn new(keys: &[DuplicateKey], tolerance_days: u32, amount_epsilon: f64) -> Self {
+        Self {
+            keys: keys.to_vec(),
+            tolerance_days,
+            amount_epsilon,
+        }
+    }
+
+    /// Retrieve the fields that duplicate transactions must share
+    pub fn keys(&self) -> &[DuplicateKey] {
+        &self.keys
+    }
+
+    /// Retrieve the number of days that the dates of duplicates can differ by
+    pub fn tolerance_days(&self) -> u32 {
+        self.tolerance_days
+    }
+
+    /// Retrieve the amount that the amounts of duplicates can differ by
+    pub fn amount_epsilon(&self) -> f64 {
+        self.amount_epsilon
+    }
+
+    /// Group the transactions that are likely duplicates, as found by [`HomeBankDb::find_duplicates_by`].
+    pub fn groups<'a>(&self, db: &'a HomeBankDb) -> Vec<Vec<&'a Transaction>> {
+        db.find_duplicates_by(&self.keys, self.tolerance_days, self.amount_epsilon)
+            .into_iter()
+            .map(|group| group.into_iter().map(|i| &db.transactions()[i]).collect())
+            .collect()
+    }
+}
+
+impl Query for ReportDuplicates {
+    type T = Vec<Transaction>;
+
+    fn exec(&self, db: &HomeBankDb) -> Vec<Self::T> {
+        self.groups(db)
+            .into_iter()
+            .map(|group| group.into_iter().cloned().collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two identical transactions, and a near-miss a day later.
+    const INPUT: &str = r#"<homebank v="1.4" d="050504">
+<account key="1" flags="0" pos="1" type="1" curr="1" name="Chequing" initial="0"/>
+<pay key="1" name="Grocer"/>
+<ope date="738170" amount="-42.1" account="1" paymode="1" payee="1" wording="Imported"/>
+<ope date="738170" amount="-42.1" account="1" paymode="1" payee="1" wording="Imported again"/>
+<ope date="738171" amount="-42.1" account="1" paymode="1" payee="1" wording="Imported"/>
+<ope date="738172" amount="-8" account="1" paymode="1"/>
+</homebank>"#;
+
+    #[track_caller]
+    fn check_duplicates(keys: &[DuplicateKey], expected: Vec<Vec<Option<&str>>>) {
+        let db = HomeBankDb::from_reader(INPUT.as_bytes()).unwrap();
+        let observed: Vec<Vec<Option<&str>>> = ReportDuplicates::new(keys, 0, 0.005)
+            .groups(&db)
+            .iter()
+            .map(|group| group.iter().map(|tr| tr.memo().as_deref()).collect())
+            .collect();
+
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    fn only_true_pair_is_flagged() {
+        let keys = ReportDuplicates::try_parse_from(["duplicates"]).unwrap();
+
+        check_duplicates(keys.keys(), vec![vec![Some("Imported"), Some("Imported again")]]);
+    }
+
+    #[test]
+    fn memo_in_key() {
+        let keys = [DuplicateKey::Date, DuplicateKey::Amount, DuplicateKey::Memo];
+
+        check_duplicates(&keys, vec![]);
+    }
+
+    #[test]
+    fn without_date_in_key() {
+        let keys = [DuplicateKey::Amount, DuplicateKey::Payee];
+
+        check_duplicates(&keys, vec![vec![Some("Imported"), Some("Imported again"), Some("Imported")]]);
+    }
+
+    #[test]
+    fn parse_keys() {
+        let report = ReportDuplicates::try_parse_from(["duplicates", "--key", "date,amount,memo"]).unwrap();
+
+        assert_eq!(&[DuplicateKey::Date, DuplicateKey::Amount, DuplicateKey::Memo], report.keys());
+    }
+
+    #[test]
+    fn parse_tolerances() {
+        let report = ReportDuplicates::try_parse_from(["duplicates", "--tolerance-days", "3", "--amount-epsilon", "0.5"]).unwrap();
+
+        assert_eq!((3, 0.5), (report.tolerance_days(), report.amount_epsilon()));
+    }
+
+    #[test]
+    fn parse_invalid_key() {
+        let expected = Err(ReportError::InvalidDuplicateKey("colour".to_string()));
+
+        assert_eq!(expected, DuplicateKey::from_str("colour"));
+    }
+}
@@ -0,0 +1,31 @@
+//! Errors when generating reports.
+
+use thiserror::Error;
+
+/// Errors when generating reports.
+#[derive(Debug, Error, PartialEq)]
+pub enum ReportError {
+    /// When the output format of a report is not supported.
+    #[error("Invalid report format `{0}`. Must be 'table' or 'csv'.")]
+    InvalidFormat(String),
+
+    /// When the type of entries to rank is not supported.
+    #[error("Invalid type `{0}` to rank. Must be 'payees', 'categories', or 'transactions'.")]
+    InvalidTopType(String),
+
+    /// When the interval between points of a report is not supported.
+    #[error("Invalid interval `{0}`. Must be 'week', 'month', 'quarter', or 'year'.")]
+    InvalidInterval(String),
+
+    /// When a field to find duplicate transactions by is not supported.
+    #[error("Invalid duplicate key `{0}`. Must be 'date', 'amount', 'account', 'payee', 'memo', 'info', or 'category'.")]
+    InvalidDuplicateKey(String),
+
+    /// When no account matches the name of the account on a statement.
+    #[error("No account matches `{0}`.")]
+    NoMatchingAccount(String),
+
+    /// When more than one account matches the name of the account on a statement.
+    #[error("`{0}` matches more than one account: {}. A statement is for a single account.", .1.join(", "))]
+    AmbiguousAccount(String, Vec<String>),
+}
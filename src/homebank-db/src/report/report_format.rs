@@ -0,0 +1,70 @@
+//! Output formats for reports.
+
+use super::ReportError;
+use clap::{builder::PossibleValue, ValueEnum};
+use std::str::FromStr;
+
+/// Output formats for reports.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum ReportFormat {
+    /// A table with aligned columns, for reading in the terminal.
+    #[default]
+    Table,
+
+    /// Comma-separated values, for use in other programs.
+    Csv,
+}
+
+impl FromStr for ReportFormat {
+    type Err = ReportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" | "Table" => Ok(Self::Table),
+            "csv" | "CSV" | "Csv" => Ok(Self::Csv),
+            _ => Err(ReportError::InvalidFormat(s.to_string())),
+        }
+    }
+}
+
+impl ValueEnum for ReportFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[ReportFormat::Table, ReportFormat::Csv]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        let value = match self {
+            ReportFormat::Table => PossibleValue::new("table").alias("Table"),
+            ReportFormat::Csv => PossibleValue::new("csv").aliases(["CSV", "Csv"]),
+        };
+
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check_from_str(input: &str, expected: Result<ReportFormat, ReportError>) {
+        let observed = <ReportFormat as FromStr>::from_str(input);
+
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    fn parse_table() {
+        check_from_str("table", Ok(ReportFormat::Table));
+    }
+
+    #[test]
+    fn parse_csv() {
+        check_from_str("csv", Ok(ReportFormat::Csv));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        check_from_str("json", Err(ReportError::InvalidFormat("json".to_string())));
+    }
+}
@@ -0,0 +1,295 @@
+//! Summarize income and expenses for each month in the HomeBank database.
+
+use super::{report_totals::PeriodTotals, Interval};
+use crate::{HomeBankDb, Query};
+use chrono::{Datelike, NaiveDate};
+use clap::Parser;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Number of categories to include in the breakdown of each month.
+const NUM_TOP_CATEGORIES: usize = 3;
+
+/// Summarize income and expenses for each month in the HomeBank database.
+#[derive(Debug, Parser)]
+#[command(about = "Summarize income and expenses for each month")]
+pub struct ReportMonthly {
+    /// Only include months in this calendar year.
+    #[arg(short = 'y', long = "year", value_name = "YYYY")]
+    year: Option<i32>,
+
+    /// Include months without any transactions, with zero income and expenses.
+    /// With `--year`, every month of the year is included, otherwise every month between the first and last transactions.
+    #[arg(long = "fill-empty")]
+    fill_empty: bool,
+}
+
+impl ReportMonthly {
+    /// Create a new monthly report
+    pub fn new(year: Option<i32>) -> Self {
+        Self { year, fill_empty: false }
+    }
+
+    /// Include months without any transactions in the report
+    pub fn with_fill_empty(mut self, fill_empty: bool) -> Self {
+        self.fill_empty = fill_empty;
+        self
+    }
+
+    /// Retrieve the calendar year the report is restricted to
+    fn year(&self) -> &Option<i32> {
+        &self.year
+    }
+
+    /// Check whether months without any transactions are included
+    pub fn fill_empty(&self) -> bool {
+        self.fill_empty
+    }
+}
+
+/// The income and expenses within a single month.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MonthlySummary {
+    /// Calendar year.
+    year: i32,
+
+    /// Month of the year, starting from 1.
+    month: u32,
+
+    /// Sum of all income.
+    income: f32,
+
+    /// Sum of all expenses (negative).
+    expenses: f32,
+
+    /// The highest-spending categories and their sums, in order of spending.
+    top_categories: Vec<(String, f32)>,
+}
+
+impl MonthlySummary {
+    /// Create a new monthly summary
+    pub fn new(
+        year: i32,
+        month: u32,
+        income: f32,
+        expenses: f32,
+        top_categories: Vec<(String, f32)>,
+    ) -> Self {
+        Self {
+            year,
+            month,
+            income,
+            expenses,
+            top_categories,
+        }
+    }
+
+    /// Retrieve the calendar year
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    /// Retrieve the month of the year, starting from 1
+    pub fn month(&self) -> u32 {
+        self.month
+    }
+
+    /// Retrieve the full name of the month (e.g. `January`)
+    pub fn month_name(&self) -> String {
+        match NaiveDate::from_ymd_opt(self.year, self.month, 1) {
+            Some(d) => d.format("%B").to_string(),
+            None => self.month.to_string(),
+        }
+    }
+
+    /// Retrieve the sum of all income
+    pub fn income(&self) -> f32 {
+        self.income
+    }
+
+    /// Retrieve the sum of all expenses
+    pub fn expenses(&self) -> f32 {
+        self.expenses
+    }
+
+    /// Retrieve the net cash flow (income + expenses, since expenses are negative)
+    pub fn net(&self) -> f32 {
+        self.income + self.expenses
+    }
+
+    /// Retrieve the highest-spending categories and their sums
+    pub fn top_categories(&self) -> &Vec<(String, f32)> {
+        &self.top_categories
+    }
+}
+
+impl Query for ReportMonthly {
+    type T = MonthlySummary;
+
+    fn exec(&self, db: &HomeBankDb) -> Vec<Self::T> {
+        // sorted by the first day of each month so that the report is in chronological order
+        let mut months: BTreeMap<NaiveDate, PeriodTotals> = BTreeMap::new();
+
+        db.transactions()
+            .iter()
+            // transfers move money between accounts, they are neither income nor expenses
+            .filter(|tr| !tr.is_transfer())
+            // voided transactions never affected any balance
+            .filter(|tr| !tr.is_void())
+            .filter(|tr| match self.year() {
+                Some(y) => tr.date().year() == *y,
+                None => true,
+            })
+            .for_each(|tr| {
+                months
+                    .entry(Interval::Month.start(tr.date()))
+                    .or_default()
+                    .add(tr, db);
+            });
+
+        if self.fill_empty() {
+            let bounds = match self.year() {
+                Some(y) => NaiveDate::from_ymd_opt(*y, 1, 1).zip(NaiveDate::from_ymd_opt(*y, 12, 1)),
+                None => months.keys().next().copied().zip(months.keys().last().copied()),
+            };
+
+            if let Some((first, last)) = bounds {
+                for start in Interval::Month.starts(&first, &last) {
+                    months.entry(start).or_default();
+                }
+            }
+        }
+
+        // the income and expenses come from the same calculation as `HomeBankDb::monthly_cash_flow`
+        let years: BTreeSet<i32> = months.keys().map(|start| start.year()).collect();
+        let cash_flow: HashMap<(i32, u32), (f64, f64)> = years
+            .into_iter()
+            .flat_map(|y| db.monthly_cash_flow(y).into_iter().map(move |(m, income, expenses)| ((y, m), (income, expenses))))
+            .collect();
+
+        months
+            .into_iter()
+            .map(|(start, totals)| {
+                let (income, expenses) = cash_flow.get(&(start.year(), start.month())).copied().unwrap_or_default();
+
+                MonthlySummary::new(
+                    start.year(),
+                    start.month(),
+                    income as f32,
+                    expenses as f32,
+                    totals.top_categories(NUM_TOP_CATEGORIES),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+    use std::path::Path;
+
+    #[track_caller]
+    fn check_report(year: Option<i32>, expected: Vec<MonthlySummary>) {
+        let db = example_db();
+        let observed = ReportMonthly::new(year).exec(&db);
+
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    fn monthly_report_all_years() {
+        let expected = vec![
+            MonthlySummary::new(2021, 12, 0.0, -1200.0, vec![("Housing:Rent".to_string(), -1200.0)]),
+            MonthlySummary::new(
+                2022,
+                1,
+                2000.0,
+                -1450.25,
+                vec![
+                    ("Housing:Rent".to_string(), -1200.0),
+                    ("Food:Groceries".to_string(), -230.25),
+                    ("Food:Dining".to_string(), -20.0),
+                ],
+            ),
+            MonthlySummary::new(
+                2022,
+                2,
+                2000.0,
+                -1305.5,
+                vec![
+                    ("Housing:Rent".to_string(), -1200.0),
+                    ("Food:Dining".to_string(), -60.0),
+                    ("Entertainment".to_string(), -45.5),
+                ],
+            ),
+        ];
+
+        check_report(None, expected);
+    }
+
+    #[test]
+    fn monthly_report_excludes_void() {
+        let db = HomeBankDb::try_from(Path::new("tests/void.xhb")).unwrap();
+        let expected = vec![MonthlySummary::new(
+            2022,
+            1,
+            1000.0,
+            -200.0,
+            vec![("Groceries".to_string(), -200.0)],
+        )];
+
+        assert_eq!(expected, ReportMonthly::new(None).exec(&db));
+    }
+
+    #[test]
+    fn monthly_report_single_year() {
+        let expected = vec![MonthlySummary::new(
+            2021,
+            12,
+            0.0,
+            -1200.0,
+            vec![("Housing:Rent".to_string(), -1200.0)],
+        )];
+
+        check_report(Some(2021), expected);
+    }
+
+    #[test]
+    fn monthly_report_empty_year() {
+        check_report(Some(1999), vec![]);
+    }
+
+    #[test]
+    fn fill_empty_year() {
+        let db = example_db();
+        let report = ReportMonthly::try_parse_from(["monthly", "--year", "2022", "--fill-empty"]).unwrap();
+        let observed: Vec<(u32, f32, f32)> = report
+            .exec(&db)
+            .iter()
+            .map(|summary| (summary.month(), summary.income(), summary.expenses()))
+            .collect();
+
+        assert_eq!(12, observed.len());
+        assert_eq!((2, 2000.0, -1305.5), observed[1]);
+        assert_eq!((3, 0.0, 0.0), observed[2]);
+        assert_eq!((12, 0.0, 0.0), observed[11]);
+    }
+
+    #[test]
+    fn fill_empty_between_transactions() {
+        let db = example_db();
+
+        // the fixture has transactions in every month from December to February, so nothing is added
+        assert_eq!(3, ReportMonthly::new(None).with_fill_empty(true).exec(&db).len());
+        assert_eq!(12, ReportMonthly::new(Some(1999)).with_fill_empty(true).exec(&db).len());
+    }
+
+    #[test]
+    fn summary_net_and_name() {
+        let summary = MonthlySummary::new(2022, 2, 2000.0, -1305.5, vec![]);
+
+        assert_eq!(694.5, summary.net());
+        assert_eq!("February", summary.month_name());
+    }
+}
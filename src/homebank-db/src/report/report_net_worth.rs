@@ -0,0 +1,239 @@
+//! Track the total balance of all open accounts over time.
+
+use super::Interval;
+use crate::{category::TODAY, HomeBankDb, Query};
+use chrono::NaiveDate;
+use clap::Parser;
+use std::{collections::BTreeMap, str::FromStr};
+
+/// Name of the group for accounts that aren't in one.
+const UNGROUPED: &str = "Ungrouped";
+
+/// Track the total balance of all open accounts over time.
+#[derive(Debug, Parser)]
+#[command(about = "Track the total balance of all open accounts over time")]
+pub struct ReportNetWorth {
+    /// Length of time between points: 'week', 'month', 'quarter', or 'year'.
+    #[arg(
+        short = 'i',
+        long = "interval",
+        default_value = "month",
+        value_name = "interval"
+    )]
+    interval: Interval,
+
+    /// Start with the interval including this date. Defaults to the date of the first transaction.
+    #[arg(
+        short = 'd',
+        long = "date-from",
+        value_parser = NaiveDate::from_str,
+        value_name = "date"
+    )]
+    date_from: Option<NaiveDate>,
+
+    /// End with the balances on this date. Defaults to today.
+    #[arg(
+        short = 'D',
+        long = "date-to",
+        value_parser = NaiveDate::from_str,
+        value_name = "date"
+    )]
+    date_to: Option<NaiveDate>,
+}
+
+impl ReportNetWorth {
+    /// Create a new net worth report
+    pub fn new(interval: Interval, date_from: Option<NaiveDate>, date_to: Option<NaiveDate>) -> Self {
+        Self {
+            interval,
+            date_from,
+            date_to,
+        }
+    }
+
+    /// Retrieve the length of time between points
+    pub fn interval(&self) -> &Interval {
+        &self.interval
+    }
+}
+
+/// The balances of all open accounts at the end of an interval.
+#[derive(Debug, PartialEq, Clone)]
+pub struct NetWorthPoint {
+    /// Date of the balances, including the transactions on that day.
+    date: NaiveDate,
+
+    /// Sum of the balances of the accounts in each group, by group name.
+    groups: Vec<(String, f32)>,
+}
+
+impl NetWorthPoint {
+    /// Create a new point
+    pub fn new(date: NaiveDate, groups: Vec<(String, f32)>) -> Self {
+        Self { date, groups }
+    }
+
+    /// Retrieve the date of the balances
+    pub fn date(&self) -> &NaiveDate {
+        &self.date
+    }
+
+    /// Retrieve the sum of the balances in each group, sorted by group name
+    pub fn groups(&self) -> &Vec<(String, f32)> {
+        &self.groups
+    }
+
+    /// Retrieve the sum of the balances of all groups
+    pub fn total(&self) -> f32 {
+        self.groups.iter().map(|(_, balance)| balance).sum()
+    }
+}
+
+impl Query for ReportNetWorth {
+    type T = NetWorthPoint;
+
+    fn exec(&self, db: &HomeBankDb) -> Vec<Self::T> {
+        let date_from = self
+            .date_from
+            .or_else(|| db.transactions().iter().map(|tr| *tr.date()).min())
+            .unwrap_or(*TODAY);
+        let date_to = self.date_to.unwrap_or(*TODAY);
+
+        net_worth(db, &self.interval, &date_from, &date_to)
+    }
+}
+
+/// Calculate the balances of all open accounts at the end of each interval from `date_from` to `date_to`.
+///
+/// The last point is on `date_to`, even if it falls partway through an interval.
+/// Balances are summed as they are, without converting between currencies.
+pub fn net_worth(db: &HomeBankDb, interval: &Interval, date_from: &NaiveDate, date_to: &NaiveDate) -> Vec<NetWorthPoint> {
+    let mut dates = vec![];
+    let mut end = interval.end(date_from);
+    while end < *date_to {
+        dates.push(end);
+        end = match end.succ_opt() {
+            Some(next) => interval.end(&next),
+            None => break,
+        };
+    }
+    if date_from <= date_to {
+        dates.push(*date_to);
+    }
+
+    let group_name = |account: usize| -> Option<String> {
+        let acct = db.account(account).filter(|acct| !acct.is_closed())?;
+        let name = acct
+            .group()
+            .and_then(|grp| db.groups().get(&grp))
+            .map(|grp| grp.name().to_string())
+            .unwrap_or_else(|| UNGROUPED.to_string());
+
+        Some(name)
+    };
+
+    // every group of open accounts is included, even before its accounts have any transactions
+    let mut groups: BTreeMap<String, f32> = BTreeMap::new();
+    for (key, acct) in db.accounts() {
+        if let Some(name) = group_name(*key) {
+            *groups.entry(name).or_default() += acct.initial_amount();
+        }
+    }
+
+    let mut changes: Vec<(NaiveDate, String, f32)> = db
+        .transactions()
+        .iter()
+        // voided transactions never affected any balance
+        .filter(|tr| !tr.is_void())
+        .filter_map(|tr| Some((*tr.date(), group_name(tr.account())?, *tr.total())))
+        .collect();
+    changes.sort_by_key(|(date, _, _)| *date);
+
+    // the dates are in order, so each point adds the transactions since the previous one to its balances
+    let mut changes = changes.into_iter().peekable();
+    dates
+        .into_iter()
+        .map(|date| {
+            while let Some((_, name, amount)) = changes.next_if(|(tr_date, _, _)| *tr_date <= date) {
+                *groups.entry(name).or_default() += amount;
+            }
+
+            NetWorthPoint::new(date, groups.clone().into_iter().collect())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn monthly_net_worth() {
+        let db = example_db();
+        let expected = vec![
+            NetWorthPoint::new(date(2021, 12, 31), vec![("Banking".to_string(), 4550.0)]),
+            NetWorthPoint::new(date(2022, 1, 31), vec![("Banking".to_string(), 5099.75)]),
+            NetWorthPoint::new(date(2022, 2, 15), vec![("Banking".to_string(), 5794.25)]),
+        ];
+
+        assert_eq!(expected, net_worth(&db, &Interval::Month, &date(2021, 12, 1), &date(2022, 2, 15)));
+    }
+
+    #[test]
+    fn groups_and_closed_accounts() {
+        let input = r#"<homebank v="1.4" d="050504">
+<cur key="1" flags="0" iso="CAD" name="Canadian Dollar" symb="$" syprf="1" dchar="." gchar="," frac="2" rate="0" mdate="738204"/>
+<grp key="1" name="Banking"/>
+<account key="1" flags="0" pos="1" type="1" curr="1" name="Chequing" initial="100" grp="1"/>
+<account key="2" flags="0" pos="2" type="4" curr="1" name="Credit Card" initial="-40"/>
+<account key="3" flags="2" pos="3" type="1" curr="1" name="Old Savings" initial="1000" grp="1"/>
+<ope date="738170" amount="-25" account="2" paymode="1"/>
+<ope date="738170" amount="-500" account="3" paymode="4"/>
+</homebank>"#;
+        let db = HomeBankDb::from_reader(input.as_bytes()).unwrap();
+
+        let observed = net_worth(&db, &Interval::Year, &date(2021, 6, 1), &date(2022, 3, 1));
+        let expected = vec![
+            NetWorthPoint::new(
+                date(2021, 12, 31),
+                vec![("Banking".to_string(), 100.0), (UNGROUPED.to_string(), -40.0)],
+            ),
+            NetWorthPoint::new(
+                date(2022, 3, 1),
+                vec![("Banking".to_string(), 100.0), (UNGROUPED.to_string(), -65.0)],
+            ),
+        ];
+
+        assert_eq!(expected, observed);
+        assert_eq!(35.0, observed[1].total());
+    }
+
+    #[test]
+    fn unordered_and_voided_transactions() {
+        let input = r#"<homebank v="1.4" d="050504">
+<account key="1" flags="0" pos="1" type="1" curr="1" name="Chequing" initial="100"/>
+<ope date="738170" amount="-30" account="1" paymode="1"/>
+<ope date="738134" amount="-20" account="1" paymode="1"/>
+<ope date="738140" amount="-500" account="1" paymode="1" st="4"/>
+</homebank>"#;
+        let db = HomeBankDb::from_reader(input.as_bytes()).unwrap();
+        let expected = vec![
+            NetWorthPoint::new(date(2021, 12, 31), vec![(UNGROUPED.to_string(), 80.0)]),
+            NetWorthPoint::new(date(2022, 1, 31), vec![(UNGROUPED.to_string(), 50.0)]),
+        ];
+
+        assert_eq!(expected, net_worth(&db, &Interval::Month, &date(2021, 12, 1), &date(2022, 1, 31)));
+    }
+
+    #[test]
+    fn empty_range() {
+        let db = example_db();
+
+        assert!(net_worth(&db, &Interval::Month, &date(2022, 3, 1), &date(2022, 2, 1)).is_empty());
+    }
+}
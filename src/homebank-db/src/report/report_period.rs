@@ -0,0 +1,162 @@
+//! Divide time into the periods that reports are broken down by.
+
+use super::ReportError;
+use chrono::{Datelike, Duration, NaiveDate};
+use std::str::FromStr;
+
+/// Length of the periods that a report is broken down by.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Interval {
+    /// ISO weeks, starting on Monday.
+    Week,
+
+    /// Calendar months.
+    #[default]
+    Month,
+
+    /// Calendar quarters, starting in January, April, July, and October.
+    Quarter,
+
+    /// Calendar years.
+    Year,
+}
+
+impl Interval {
+    /// Find the first day of the period that includes `date`.
+    pub fn start(&self, date: &NaiveDate) -> NaiveDate {
+        let first_month = match self {
+            Interval::Week => {
+                let since_monday = date.weekday().num_days_from_monday() as i64;
+                return *date - Duration::days(since_monday);
+            }
+            Interval::Month => date.month(),
+            Interval::Quarter => (date.month() - 1) / 3 * 3 + 1,
+            Interval::Year => 1,
+        };
+
+        NaiveDate::from_ymd_opt(date.year(), first_month, 1).unwrap_or(*date)
+    }
+
+    /// Find the last day of the period that includes `date`.
+    pub fn end(&self, date: &NaiveDate) -> NaiveDate {
+        let last_month = match self {
+            Interval::Week => {
+                let until_sunday = 6 - date.weekday().num_days_from_monday() as i64;
+                return date.checked_add_signed(Duration::days(until_sunday)).unwrap_or(NaiveDate::MAX);
+            }
+            Interval::Month => date.month(),
+            Interval::Quarter => (date.month() - 1) / 3 * 3 + 3,
+            Interval::Year => 12,
+        };
+        let (year, month) = match last_month {
+            12 => (date.year() + 1, 1),
+            m => (date.year(), m + 1),
+        };
+
+        // the day before the first of the following month
+        NaiveDate::from_ymd_opt(year, month, 1)
+            .and_then(|d| d.pred_opt())
+            .unwrap_or(NaiveDate::MAX)
+    }
+
+    /// Name the period that includes `date`, e.g. `2024-W07`, `2024-02`, `2024-Q1`, or `2024`.
+    pub fn label(&self, date: &NaiveDate) -> String {
+        match self {
+            // the ISO year of a week can differ from the calendar year around New Year's
+            Interval::Week => format!("{}-W{:02}", date.iso_week().year(), date.iso_week().week()),
+            Interval::Month => date.format("%Y-%m").to_string(),
+            Interval::Quarter => format!("{}-Q{}", date.year(), (date.month() - 1) / 3 + 1),
+            Interval::Year => date.year().to_string(),
+        }
+    }
+
+    /// Find the first day of every period from the one including `from` to the one including `to`.
+    pub fn starts(&self, from: &NaiveDate, to: &NaiveDate) -> Vec<NaiveDate> {
+        let mut starts = vec![];
+        let mut start = self.start(from);
+        while start <= *to {
+            starts.push(start);
+            start = match self.end(&start).succ_opt() {
+                Some(next) => next,
+                None => break,
+            };
+        }
+
+        starts
+    }
+}
+
+impl FromStr for Interval {
+    type Err = ReportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "week" | "Week" | "w" => Ok(Self::Week),
+            "month" | "Month" | "m" => Ok(Self::Month),
+            "quarter" | "Quarter" | "q" => Ok(Self::Quarter),
+            "year" | "Year" | "y" => Ok(Self::Year),
+            _ => Err(ReportError::InvalidInterval(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[track_caller]
+    fn check_period(interval: Interval, input: NaiveDate, start: NaiveDate, end: NaiveDate, label: &str) {
+        assert_eq!(start, interval.start(&input));
+        assert_eq!(end, interval.end(&input));
+        assert_eq!(label, interval.label(&input));
+    }
+
+    #[test]
+    fn week_period() {
+        // 2024-02-14 is a Wednesday
+        check_period(Interval::Week, date(2024, 2, 14), date(2024, 2, 12), date(2024, 2, 18), "2024-W07");
+    }
+
+    #[test]
+    fn week_period_across_new_year() {
+        check_period(Interval::Week, date(2021, 1, 1), date(2020, 12, 28), date(2021, 1, 3), "2020-W53");
+    }
+
+    #[test]
+    fn month_period() {
+        check_period(Interval::Month, date(2022, 2, 5), date(2022, 2, 1), date(2022, 2, 28), "2022-02");
+        check_period(Interval::Month, date(2021, 12, 31), date(2021, 12, 1), date(2021, 12, 31), "2021-12");
+    }
+
+    #[test]
+    fn quarter_period() {
+        check_period(Interval::Quarter, date(2022, 5, 1), date(2022, 4, 1), date(2022, 6, 30), "2022-Q2");
+        check_period(Interval::Quarter, date(2022, 11, 1), date(2022, 10, 1), date(2022, 12, 31), "2022-Q4");
+    }
+
+    #[test]
+    fn year_period() {
+        check_period(Interval::Year, date(2022, 5, 1), date(2022, 1, 1), date(2022, 12, 31), "2022");
+    }
+
+    #[test]
+    fn period_starts() {
+        let expected = vec![date(2021, 12, 1), date(2022, 1, 1), date(2022, 2, 1)];
+
+        assert_eq!(expected, Interval::Month.starts(&date(2021, 12, 10), &date(2022, 2, 1)));
+        assert!(Interval::Month.starts(&date(2022, 3, 1), &date(2022, 2, 1)).is_empty());
+    }
+
+    #[test]
+    fn parse_interval() {
+        assert_eq!(Ok(Interval::Week), Interval::from_str("week"));
+        assert_eq!(
+            Err(ReportError::InvalidInterval("fortnight".to_string())),
+            Interval::from_str("fortnight")
+        );
+    }
+}
@@ -0,0 +1,375 @@
+//! Compare the reconciled, cleared, and uncleared balances of accounts in the HomeBank database.
+
+use super::ReportError;
+use crate::{category::TODAY, Account, HomeBankDb, Query, Transaction};
+use chrono::NaiveDate;
+use clap::Parser;
+use regex::Regex;
+use std::str::FromStr;
+
+/// Compare the reconciled, cleared, and uncleared balances of accounts on a date.
+#[derive(Debug, Parser)]
+#[command(about = "Compare the reconciled, cleared, and uncleared balances of accounts")]
+pub struct ReportReconcile {
+    /// Name of the accounts. Defaults to every account.
+    #[arg(short = 'a', long = "account", value_name = "regex")]
+    account: Option<Regex>,
+
+    /// Include transactions up to (and including) this date. Defaults to today.
+    #[arg(
+        long = "as-of",
+        value_parser = NaiveDate::from_str,
+        value_name = "date"
+    )]
+    as_of: Option<NaiveDate>,
+}
+
+impl ReportReconcile {
+    /// Create a new reconciliation report
+    pub fn new(account: Option<Regex>, as_of: Option<NaiveDate>) -> Self {
+        Self { account, as_of }
+    }
+
+    /// Retrieve the regular expression for the account names
+    pub fn account(&self) -> &Option<Regex> {
+        &self.account
+    }
+
+    /// Retrieve the date of the balances
+    pub fn as_of(&self) -> NaiveDate {
+        self.as_of.unwrap_or(*TODAY)
+    }
+}
+
+/// The balances of an account on a date, split by the status of its transactions.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ReconcileBalance {
+    /// Name of the account.
+    account: String,
+
+    /// Initial amount of the account plus its reconciled transactions.
+    reconciled: f32,
+
+    /// Reconciled balance plus the cleared transactions.
+    cleared: f32,
+
+    /// Sum of the transactions that are neither cleared nor reconciled.
+    uncleared: f32,
+}
+
+impl ReconcileBalance {
+    /// Create a new set of balances
+    pub fn new(account: &str, reconciled: f32, cleared: f32, uncleared: f32) -> Self {
+        Self {
+            account: account.to_string(),
+            reconciled,
+            cleared,
+            uncleared,
+        }
+    }
+
+    /// Retrieve the name of the account
+    pub fn account(&self) -> &str {
+        &self.account
+    }
+
+    /// Retrieve the initial amount of the account plus its reconciled transactions
+    pub fn reconciled(&self) -> f32 {
+        self.reconciled
+    }
+
+    /// Retrieve the reconciled balance plus the cleared transactions, which should match the bank's balance
+    pub fn cleared(&self) -> f32 {
+        self.cleared
+    }
+
+    /// Retrieve the sum of the transactions that are neither cleared nor reconciled
+    pub fn uncleared(&self) -> f32 {
+        self.uncleared
+    }
+
+    /// Retrieve the balance including every transaction
+    pub fn balance(&self) -> f32 {
+        self.cleared + self.uncleared
+    }
+}
+
+/// Sum the transactions in `account` up to (and including) `as_of` by their status.
+pub fn reconcile(db: &HomeBankDb, account: &Account, as_of: &NaiveDate) -> ReconcileBalance {
+    let (mut reconciled, mut cleared, mut uncleared) = (account.initial_amount(), 0.0, 0.0);
+
+    db.transactions()
+        .iter()
+        .filter(|tr| tr.account() == account.key() && tr.date() <= as_of)
+        // voided transactions never affected any balance
+        .filter(|tr| !tr.is_void())
+        .for_each(|tr| {
+            if tr.status().is_reconciled() {
+                reconciled += tr.total();
+            } else if tr.status().is_cleared() {
+                cleared += tr.total();
+            } else {
+                uncleared += tr.total();
+            }
+        });
+
+    ReconcileBalance::new(account.name(), reconciled, reconciled + cleared, uncleared)
+}
+
+impl Query for ReportReconcile {
+    type T = ReconcileBalance;
+
+    fn exec(&self, db: &HomeBankDb) -> Vec<Self::T> {
+        let as_of = self.as_of();
+
+        db.accounts()
+            .values()
+            .filter(|acct| match self.account() {
+                Some(re) => re.is_match(acct.name()),
+                None => true,
+            })
+            .map(|acct| reconcile(db, acct, &as_of))
+            .collect()
+    }
+}
+
+/// Compare the cleared balance of an account to the balance on a bank statement.
+#[derive(Debug, Parser)]
+#[command(about = "Compare the cleared balance of an account to the balance on a bank statement")]
+pub struct ReconcileStatement {
+    /// Name of the account on the statement, which must match exactly one account.
+    #[arg(short = 'a', long = "account", value_name = "regex")]
+    account: Regex,
+
+    /// Date of the statement, including the transactions on that day.
+    #[arg(
+        short = 'd',
+        long = "statement-date",
+        value_parser = NaiveDate::from_str,
+        value_name = "date"
+    )]
+    statement_date: NaiveDate,
+
+    /// Closing balance on the statement.
+    #[arg(
+        short = 'b',
+        long = "statement-balance",
+        value_name = "amount",
+        allow_negative_numbers = true
+    )]
+    statement_balance: f32,
+}
+
+impl ReconcileStatement {
+    /// Create a new comparison against a statement
+    pub fn new(account: Regex, statement_date: NaiveDate, statement_balance: f32) -> Self {
+        Self {
+            account,
+            statement_date,
+            statement_balance,
+        }
+    }
+
+    /// Retrieve the date of the statement
+    pub fn statement_date(&self) -> &NaiveDate {
+        &self.statement_date
+    }
+
+    /// Retrieve the closing balance on the statement
+    pub fn statement_balance(&self) -> f32 {
+        self.statement_balance
+    }
+}
+
+/// The cleared balance of an account compared to a bank statement, with the transactions that could explain any difference.
+#[derive(Debug, PartialEq, Clone)]
+pub struct StatementComparison {
+    /// Balances of the account on the date of the statement.
+    balance: ReconcileBalance,
+
+    /// Closing balance on the statement.
+    statement_balance: f32,
+
+    /// Half of the smallest amount in the account's currency, below which balances are considered equal.
+    epsilon: f32,
+
+    /// Transactions up to the date of the statement that are neither cleared nor reconciled, highest amount first.
+    uncleared: Vec<Transaction>,
+}
+
+impl StatementComparison {
+    /// Retrieve the balances of the account on the date of the statement
+    pub fn balance(&self) -> &ReconcileBalance {
+        &self.balance
+    }
+
+    /// Retrieve the closing balance on the statement
+    pub fn statement_balance(&self) -> f32 {
+        self.statement_balance
+    }
+
+    /// Retrieve the amount missing from the cleared balance to match the statement
+    pub fn difference(&self) -> f32 {
+        self.statement_balance - self.balance.cleared()
+    }
+
+    /// Check if the cleared balance matches the statement, to the decimal places of the account's currency
+    pub fn is_balanced(&self) -> bool {
+        self.difference().abs() < self.epsilon
+    }
+
+    /// Retrieve the transactions up to the date of the statement that are neither cleared nor reconciled, highest amount first
+    pub fn uncleared(&self) -> &[Transaction] {
+        &self.uncleared
+    }
+}
+
+/// Compare the cleared balance of `account` on `statement_date` to `statement_balance`.
+pub fn compare_statement(db: &HomeBankDb, account: &Account, statement_date: &NaiveDate, statement_balance: f32) -> StatementComparison {
+    let decimal_len = db.currency(account.currency()).map_or(2, |curr| curr.decimal_len());
+
+    let mut uncleared: Vec<Transaction> = db
+        .transactions()
+        .iter()
+        .filter(|tr| tr.account() == account.key() && tr.date() <= statement_date)
+        .filter(|tr| !(tr.status().is_cleared() || tr.status().is_reconciled() || tr.is_void()))
+        .cloned()
+        .collect();
+    uncleared.sort_by(|a, b| b.total().total_cmp(a.total()));
+
+    StatementComparison {
+        balance: reconcile(db, account, statement_date),
+        statement_balance,
+        epsilon: 0.5 / 10_f32.powi(decimal_len as i32),
+        uncleared,
+    }
+}
+
+impl ReconcileStatement {
+    /// Compare the cleared balance of the account on the statement to its closing balance.
+    ///
+    /// A statement is for a single account, so this fails unless exactly one account matches its name.
+    pub fn compare(&self, db: &HomeBankDb) -> Result<StatementComparison, ReportError> {
+        let matching: Vec<&Account> = db.accounts().values().filter(|acct| self.account.is_match(acct.name())).collect();
+
+        match matching[..] {
+            [acct] => Ok(compare_statement(db, acct, &self.statement_date, self.statement_balance)),
+            [] => Err(ReportError::NoMatchingAccount(self.account.to_string())),
+            _ => Err(ReportError::AmbiguousAccount(
+                self.account.to_string(),
+                matching.iter().map(|acct| acct.name().to_string()).collect(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reconciled, cleared, uncleared, reminder, and void transactions in June, and one in July.
+    const INPUT: &str = r#"<homebank v="1.4" d="050504">
+<account key="1" flags="0" pos="1" type="1" curr="1" name="Checking" initial="100"/>
+<account key="2" flags="0" pos="2" type="1" curr="1" name="Savings" initial="0"/>
+<ope date="738675" amount="1000" account="1" paymode="4" st="2"/>
+<ope date="738680" amount="-200" account="1" paymode="4" st="2"/>
+<ope date="738685" amount="-50.5" account="1" paymode="1" st="1"/>
+<ope date="738690" amount="-30" account="1" paymode="1" st="0"/>
+<ope date="738691" amount="-12" account="1" paymode="1" st="3"/>
+<ope date="738692" amount="-99" account="1" paymode="1" st="4"/>
+<ope date="738705" amount="-75" account="1" paymode="1" st="0"/>
+<ope date="738680" amount="500" account="2" paymode="4" st="1"/>
+</homebank>"#;
+
+    #[track_caller]
+    fn check_reconcile(args: &[&str], expected: Vec<ReconcileBalance>) {
+        let db = HomeBankDb::from_reader(INPUT.as_bytes()).unwrap();
+        let report = ReportReconcile::try_parse_from(args).unwrap();
+
+        assert_eq!(expected, report.exec(&db));
+    }
+
+    #[test]
+    fn balances_by_status() {
+        check_reconcile(
+            &["reconcile", "--account", "Checking", "--as-of", "2023-06-30"],
+            vec![ReconcileBalance::new("Checking", 900.0, 849.5, -42.0)],
+        );
+    }
+
+    #[test]
+    fn every_account() {
+        check_reconcile(
+            &["reconcile", "--as-of", "2023-06-30"],
+            vec![
+                ReconcileBalance::new("Checking", 900.0, 849.5, -42.0),
+                ReconcileBalance::new("Savings", 0.0, 500.0, 0.0),
+            ],
+        );
+    }
+
+    #[test]
+    fn later_transactions() {
+        let db = HomeBankDb::from_reader(INPUT.as_bytes()).unwrap();
+        let balance = reconcile(&db, db.account(1).unwrap(), &NaiveDate::from_ymd_opt(2023, 7, 31).unwrap());
+
+        assert_eq!(732.5, balance.balance());
+    }
+
+    #[track_caller]
+    fn check_statement(statement_balance: &str, is_balanced: bool) -> StatementComparison {
+        let db = HomeBankDb::from_reader(INPUT.as_bytes()).unwrap();
+        let args = ["reconcile", "-a", "Checking", "-d", "2023-06-30", "-b", statement_balance];
+        let comparison = ReconcileStatement::try_parse_from(args).unwrap().compare(&db).unwrap();
+
+        assert_eq!(is_balanced, comparison.is_balanced());
+
+        comparison
+    }
+
+    #[test]
+    fn statement_matches() {
+        assert_eq!(0.0, check_statement("849.5", true).difference());
+    }
+
+    #[test]
+    fn statement_within_a_fraction_of_a_cent() {
+        // the account has no currency, so amounts are compared to 2 decimal places
+        check_statement("849.504", true);
+        check_statement("849.506", false);
+    }
+
+    #[test]
+    fn statement_explained_by_uncleared() {
+        assert_eq!(-30.0, check_statement("819.5", false).difference());
+    }
+
+    #[track_caller]
+    fn check_statement_account(account: &str, expected: ReportError) {
+        let db = HomeBankDb::from_reader(INPUT.as_bytes()).unwrap();
+        let args = ["reconcile", "-a", account, "-d", "2023-06-30", "-b", "0"];
+
+        assert_eq!(Err(expected), ReconcileStatement::try_parse_from(args).unwrap().compare(&db));
+    }
+
+    #[test]
+    fn statement_without_matching_account() {
+        check_statement_account("^Brokerage$", ReportError::NoMatchingAccount("^Brokerage$".to_string()));
+    }
+
+    #[test]
+    fn statement_with_several_matching_accounts() {
+        let names = vec!["Checking".to_string(), "Savings".to_string()];
+
+        check_statement_account("ing", ReportError::AmbiguousAccount("ing".to_string(), names));
+    }
+
+    #[test]
+    fn uncleared_highest_first() {
+        let db = HomeBankDb::from_reader(INPUT.as_bytes()).unwrap();
+        let comparison = compare_statement(&db, db.account(1).unwrap(), &NaiveDate::from_ymd_opt(2023, 7, 31).unwrap(), 0.0);
+        let observed: Vec<f32> = comparison.uncleared().iter().map(|tr| *tr.total()).collect();
+
+        assert_eq!(vec![-12.0, -30.0, -75.0], observed);
+    }
+}
@@ -0,0 +1,135 @@
+//! Describe the amounts of the transactions in each category of the HomeBank database.
+
+use crate::{
+    db::db_analytics::{mean, median, std_dev},
+    HomeBankDb,
+};
+use clap::Parser;
+use regex::Regex;
+
+/// Describe the amounts of the transactions in each category.
+#[derive(Debug, Default, Parser)]
+#[command(about = "Print the mean, median, and standard deviation of the transactions in each category")]
+pub struct ReportStats {
+    /// Full name of the categories, e.g. `^Food:`. Defaults to every category with transactions.
+    #[arg(short = 'c', long = "category", value_name = "regex")]
+    category: Option<Regex>,
+}
+
+impl ReportStats {
+    /// Create a new report of the statistics for the categories matching `category`
+    pub fn new(category: Option<Regex>) -> Self {
+        Self { category }
+    }
+
+    /// Retrieve the regular expression for the category names
+    pub fn category(&self) -> &Option<Regex> {
+        &self.category
+    }
+
+    /// Describe the transactions in each matching category that has any, sorted by the full name of the category
+    pub fn exec(&self, db: &HomeBankDb) -> Vec<CategoryStats> {
+        let mut stats: Vec<CategoryStats> = db
+            .amounts_by_category()
+            .into_iter()
+            .filter_map(|(key, mut amounts)| {
+                let name = db.category_full_name(key)?;
+                if self.category.as_ref().is_some_and(|re| !re.is_match(name)) {
+                    return None;
+                }
+
+                Some(CategoryStats {
+                    name: name.to_string(),
+                    count: amounts.len(),
+                    total: amounts.iter().sum(),
+                    average: mean(&amounts)?,
+                    std_dev: std_dev(&amounts)?,
+                    median: median(&mut amounts)?,
+                })
+            })
+            .collect();
+        stats.sort_by(|a, b| a.name.cmp(&b.name));
+
+        stats
+    }
+}
+
+/// The statistics of the amounts of the transactions in a category.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CategoryStats {
+    /// Full name of the category.
+    name: String,
+
+    /// Number of transactions, with each split of a transaction counted separately.
+    count: usize,
+
+    /// Sum of the amounts.
+    total: f64,
+
+    /// Mean amount.
+    average: f64,
+
+    /// Median amount.
+    median: f64,
+
+    /// Population standard deviation of the amounts.
+    std_dev: f64,
+}
+
+impl CategoryStats {
+    /// Retrieve the full name of the category
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Retrieve the number of transactions, with each split of a transaction counted separately
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Retrieve the sum of the amounts
+    pub fn total(&self) -> f64 {
+        self.total
+    }
+
+    /// Retrieve the mean amount
+    pub fn average(&self) -> f64 {
+        self.average
+    }
+
+    /// Retrieve the median amount
+    pub fn median(&self) -> f64 {
+        self.median
+    }
+
+    /// Retrieve the population standard deviation of the amounts
+    pub fn std_dev(&self) -> f64 {
+        self.std_dev
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    #[test]
+    fn food_categories() {
+        let db = example_db();
+        let observed = ReportStats::new(Some(Regex::new("^Food").unwrap())).exec(&db);
+
+        assert_eq!(vec!["Food:Dining", "Food:Groceries"], observed.iter().map(|s| s.name()).collect::<Vec<_>>());
+        assert_eq!((2, -80.0, -40.0, -40.0, 20.0), {
+            let dining = &observed[0];
+            (dining.count(), dining.total(), dining.average(), dining.median(), dining.std_dev())
+        });
+    }
+
+    #[test]
+    fn categories_without_transactions_left_out() {
+        let db = example_db();
+        let observed: Vec<String> = ReportStats::default().exec(&db).iter().map(|s| s.name().to_string()).collect();
+
+        assert_eq!(vec!["Entertainment", "Food:Dining", "Food:Groceries", "Housing:Rent", "Salary"], observed);
+    }
+}
@@ -0,0 +1,21 @@
+//! Total the spending for each tag in the HomeBank database.
+
+use crate::HomeBankDb;
+use clap::Parser;
+
+/// Total the spending for each tag in the HomeBank database.
+#[derive(Debug, Default, Parser)]
+#[command(about = "Total the expenses with each tag, largest first")]
+pub struct ReportTags {}
+
+impl ReportTags {
+    /// Create a new report of the spending for each tag
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Total the expenses with each tag, largest first
+    pub fn exec(&self, db: &HomeBankDb) -> Vec<(String, f64)> {
+        db.tag_spending_report()
+    }
+}
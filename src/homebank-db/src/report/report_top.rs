@@ -0,0 +1,122 @@
+//! Rank the payees, categories, or transactions in the HomeBank database.
+
+use super::ReportError;
+use clap::Parser;
+use std::str::FromStr;
+
+/// What to rank in a [`ReportTop`].
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum TopType {
+    /// Payees, by their number of transactions.
+    #[default]
+    Payees,
+
+    /// Categories, by their number of transactions.
+    Categories,
+
+    /// Transactions, by their amount.
+    Transactions,
+}
+
+impl FromStr for TopType {
+    type Err = ReportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "payees" | "Payees" | "p" => Ok(Self::Payees),
+            "categories" | "Categories" | "c" => Ok(Self::Categories),
+            "transactions" | "Transactions" | "t" => Ok(Self::Transactions),
+            _ => Err(ReportError::InvalidTopType(s.to_string())),
+        }
+    }
+}
+
+/// Rank the payees, categories, or transactions in the HomeBank database.
+#[derive(Debug, Parser)]
+#[command(about = "Rank the most frequent payees or categories, or the largest transactions")]
+pub struct ReportTop {
+    /// What to rank: 'payees', 'categories', or 'transactions'.
+    #[arg(
+        short = 't',
+        long = "type",
+        default_value = "payees",
+        value_name = "type"
+    )]
+    top_type: TopType,
+
+    /// Number of entries to include.
+    #[arg(
+        short = 'n',
+        long = "number",
+        visible_alias = "n",
+        default_value = "10",
+        value_name = "number"
+    )]
+    n: usize,
+
+    /// For transactions, rank the lowest amounts (i.e. the largest expenses) instead of the highest.
+    #[arg(long = "smallest")]
+    smallest: bool,
+}
+
+impl ReportTop {
+    /// Create a new ranking report
+    pub fn new(top_type: TopType, n: usize, smallest: bool) -> Self {
+        Self {
+            top_type,
+            n,
+            smallest,
+        }
+    }
+
+    /// Retrieve what is being ranked
+    pub fn top_type(&self) -> &TopType {
+        &self.top_type
+    }
+
+    /// Retrieve the number of entries to include
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Retrieve whether the lowest transaction amounts are ranked first
+    pub fn smallest(&self) -> bool {
+        self.smallest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check_parse(args: &[&str], top_type: TopType, n: usize, smallest: bool) {
+        let observed = ReportTop::try_parse_from(args).unwrap();
+
+        assert_eq!(&top_type, observed.top_type());
+        assert_eq!(n, observed.n());
+        assert_eq!(smallest, observed.smallest());
+    }
+
+    #[test]
+    fn parse_defaults() {
+        check_parse(&["top"], TopType::Payees, 10, false);
+    }
+
+    #[test]
+    fn parse_smallest_transactions() {
+        check_parse(
+            &["top", "--type", "transactions", "--n", "3", "--smallest"],
+            TopType::Transactions,
+            3,
+            true,
+        );
+    }
+
+    #[test]
+    fn parse_invalid_type() {
+        let expected = Err(ReportError::InvalidTopType("accounts".to_string()));
+
+        assert_eq!(expected, TopType::from_str("accounts"));
+    }
+}
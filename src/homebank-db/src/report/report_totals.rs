@@ -0,0 +1,70 @@
+//! Running totals of income and expenses over a period of time.
+
+use crate::{HomeBankDb, Transaction};
+use std::collections::HashMap;
+
+/// Running totals of income and expenses over a period of time.
+#[derive(Debug, Default)]
+pub(crate) struct PeriodTotals {
+    /// Sum of all income.
+    income: f32,
+
+    /// Sum of all expenses (negative).
+    expenses: f32,
+
+    /// Number of transactions added.
+    num_transactions: usize,
+
+    /// Sum of expenses in each category, by full category name.
+    categories: HashMap<String, f32>,
+}
+
+impl PeriodTotals {
+    /// Retrieve the sum of all income
+    pub(crate) fn income(&self) -> f32 {
+        self.income
+    }
+
+    /// Retrieve the sum of all expenses
+    pub(crate) fn expenses(&self) -> f32 {
+        self.expenses
+    }
+
+    /// Retrieve the number of transactions added
+    pub(crate) fn num_transactions(&self) -> usize {
+        self.num_transactions
+    }
+
+    /// Add a transaction to the totals.
+    /// Each split is considered separately, since they can be in different categories.
+    pub(crate) fn add(&mut self, tr: &Transaction, db: &HomeBankDb) {
+        self.num_transactions += 1;
+
+        for (amount, cat_name) in tr.amounts().iter().zip(tr.category_names(db)) {
+            if **amount > 0.0 {
+                self.income += *amount;
+            } else {
+                self.expenses += *amount;
+
+                let cat_name = cat_name.unwrap_or_default();
+                *self.categories.entry(cat_name).or_insert(0.0) += *amount;
+            }
+        }
+    }
+
+    /// Retrieve the `n` highest-spending categories and their sums, in order of spending.
+    pub(crate) fn top_categories(&self, n: usize) -> Vec<(String, f32)> {
+        let mut top_categories: Vec<(String, f32)> = self
+            .categories
+            .iter()
+            .filter(|(_, sum)| **sum < 0.0)
+            .map(|(name, sum)| (name.clone(), *sum))
+            .collect();
+
+        // most negative first, breaking ties by name so the order is stable
+        top_categories.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        top_categories.truncate(n);
+
+        top_categories
+    }
+}
@@ -0,0 +1,199 @@
+//! Summarize income and expenses for each year in the HomeBank database.
+
+use super::report_totals::PeriodTotals;
+use crate::{HomeBankDb, Query};
+use chrono::Datelike;
+use clap::Parser;
+use std::collections::BTreeMap;
+
+/// Number of categories to include in the breakdown of each year.
+const NUM_TOP_CATEGORIES: usize = 5;
+
+/// Summarize income and expenses for each year in the HomeBank database.
+#[derive(Debug, Parser)]
+#[command(about = "Summarize income and expenses for each year")]
+pub struct ReportYearly {
+    /// Include years starting from (and including) this year.
+    #[arg(long = "from-year", value_name = "YYYY")]
+    from_year: Option<i32>,
+
+    /// Include years up to (and including) this year.
+    #[arg(long = "to-year", value_name = "YYYY")]
+    to_year: Option<i32>,
+}
+
+impl ReportYearly {
+    /// Create a new yearly report
+    pub fn new(from_year: Option<i32>, to_year: Option<i32>) -> Self {
+        Self { from_year, to_year }
+    }
+}
+
+/// The income and expenses within a single calendar year.
+#[derive(Debug, PartialEq, Clone)]
+pub struct YearlyReport {
+    /// Calendar year.
+    year: i32,
+
+    /// Sum of all income.
+    income: f32,
+
+    /// Sum of all expenses (negative).
+    expenses: f32,
+
+    /// Number of transactions in the year, excluding transfers.
+    num_transactions: usize,
+
+    /// The highest-spending categories and their sums, in order of spending.
+    top_categories: Vec<(String, f32)>,
+}
+
+impl YearlyReport {
+    /// Create a new yearly report
+    pub fn new(
+        year: i32,
+        income: f32,
+        expenses: f32,
+        num_transactions: usize,
+        top_categories: Vec<(String, f32)>,
+    ) -> Self {
+        Self {
+            year,
+            income,
+            expenses,
+            num_transactions,
+            top_categories,
+        }
+    }
+
+    /// Retrieve the calendar year
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    /// Retrieve the sum of all income
+    pub fn income(&self) -> f32 {
+        self.income
+    }
+
+    /// Retrieve the sum of all expenses
+    pub fn expenses(&self) -> f32 {
+        self.expenses
+    }
+
+    /// Retrieve the net cash flow (income + expenses, since expenses are negative)
+    pub fn net(&self) -> f32 {
+        self.income + self.expenses
+    }
+
+    /// Retrieve the number of transactions in the year, excluding transfers
+    pub fn num_transactions(&self) -> usize {
+        self.num_transactions
+    }
+
+    /// Retrieve the highest-spending categories and their sums
+    pub fn top_categories(&self) -> &Vec<(String, f32)> {
+        &self.top_categories
+    }
+}
+
+/// Summarize the income and expenses for each year from `from` to `to` (inclusive).
+/// Years without any transactions are not included.
+pub fn build_yearly_report(db: &HomeBankDb, from: i32, to: i32) -> Vec<YearlyReport> {
+    // sorted by year so that the report is in chronological order
+    let mut years: BTreeMap<i32, PeriodTotals> = BTreeMap::new();
+
+    db.transactions()
+        .iter()
+        // transfers move money between accounts, they are neither income nor expenses
+        .filter(|tr| !tr.is_transfer())
+        // voided transactions never affected any balance
+        .filter(|tr| !tr.is_void())
+        .filter(|tr| (from..=to).contains(&tr.date().year()))
+        .for_each(|tr| {
+            years.entry(tr.date().year()).or_default().add(tr, db);
+        });
+
+    years
+        .into_iter()
+        .map(|(year, totals)| {
+            YearlyReport::new(
+                year,
+                totals.income(),
+                totals.expenses(),
+                totals.num_transactions(),
+                totals.top_categories(NUM_TOP_CATEGORIES),
+            )
+        })
+        .collect()
+}
+
+impl Query for ReportYearly {
+    type T = YearlyReport;
+
+    fn exec(&self, db: &HomeBankDb) -> Vec<Self::T> {
+        build_yearly_report(
+            db,
+            self.from_year.unwrap_or(i32::MIN),
+            self.to_year.unwrap_or(i32::MAX),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    #[track_caller]
+    fn check_yearly_report(from: i32, to: i32, expected: Vec<YearlyReport>) {
+        let db = example_db();
+        let observed = build_yearly_report(&db, from, to);
+
+        assert_eq!(expected, observed);
+    }
+
+    fn report_2021() -> YearlyReport {
+        YearlyReport::new(2021, 0.0, -1200.0, 1, vec![("Housing:Rent".to_string(), -1200.0)])
+    }
+
+    fn report_2022() -> YearlyReport {
+        YearlyReport::new(
+            2022,
+            4000.0,
+            -2755.75,
+            8,
+            vec![
+                ("Housing:Rent".to_string(), -2400.0),
+                ("Food:Groceries".to_string(), -230.25),
+                ("Food:Dining".to_string(), -80.0),
+                ("Entertainment".to_string(), -45.5),
+            ],
+        )
+    }
+
+    #[test]
+    fn yearly_report_all_years() {
+        check_yearly_report(i32::MIN, i32::MAX, vec![report_2021(), report_2022()]);
+    }
+
+    #[test]
+    fn yearly_report_from_year() {
+        check_yearly_report(2022, i32::MAX, vec![report_2022()]);
+    }
+
+    #[test]
+    fn yearly_report_to_year() {
+        check_yearly_report(i32::MIN, 2021, vec![report_2021()]);
+    }
+
+    #[test]
+    fn yearly_report_empty_range() {
+        check_yearly_report(2023, 2030, vec![]);
+    }
+
+    #[test]
+    fn yearly_report_net() {
+        assert_eq!(1244.25, report_2022().net());
+    }
+}
@@ -1,5 +1,11 @@
-pub mod template;
+//! Favourite (a.k.a. template or scheduled) transactions, and their schedules.
+
+pub mod template_error;
 pub mod template_query;
+pub mod template_recurrence;
+pub mod template_struct;
 
-pub use template::Template;
+pub use template_error::TemplateError;
 pub use template_query::QueryTemplates;
+pub use template_recurrence::{RepeatUnit, WeekendShift};
+pub use template_struct::Favourite;
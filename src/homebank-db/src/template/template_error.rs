@@ -0,0 +1,55 @@
+//! Errors when parsing [`Favourite`s][crate::template::template_struct::Favourite] from the [`HomeBankDb`][crate::db::db_struct::HomeBankDb].
+
+use thiserror::Error;
+
+/// Errors when parsing [`Favourite`s][crate::template::template_struct::Favourite] from the [`HomeBankDb`][crate::db::db_struct::HomeBankDb].
+#[derive(Debug, Error, PartialEq)]
+pub enum TemplateError {
+    /// When the key for the favourite is invalid.
+    #[error("Invalid favourite key.")]
+    InvalidKey,
+
+    /// When the amount is invalid.
+    #[error("Invalid favourite amount.")]
+    InvalidAmount,
+
+    /// When the key of the account, or of the destination account of a transfer, is invalid.
+    #[error("Invalid account key in favourite.")]
+    InvalidAccount,
+
+    /// When the key of the payee is invalid.
+    #[error("Invalid payee key in favourite.")]
+    InvalidPayee,
+
+    /// When the key of the category is invalid.
+    #[error("Invalid category key in favourite.")]
+    InvalidCategory,
+
+    /// When the pay mode is invalid.
+    #[error("Invalid payment method in favourite.")]
+    InvalidPayMode,
+
+    /// When the flags are not a bit field.
+    #[error("Invalid favourite flags. Must be a `u32` bit field.")]
+    InvalidFlags,
+
+    /// When the date of the next posting is invalid.
+    #[error("Invalid next date in favourite.")]
+    InvalidNextDate,
+
+    /// When the unit between postings is invalid.
+    #[error("Invalid repeat unit `{0}`. Must be 0-3 or 'day', 'week', 'month', or 'year'.")]
+    InvalidUnit(String),
+
+    /// When the number of units between postings is invalid.
+    #[error("Invalid repeat interval `{0}`. Must be a positive number.")]
+    InvalidEvery(String),
+
+    /// When the number of remaining postings is invalid.
+    #[error("Invalid posting limit `{0}`. Must be a positive number.")]
+    InvalidLimit(String),
+
+    /// When the way postings on a weekend are moved is invalid.
+    #[error("Invalid weekend shift `{0}`. Must be 0-3 or 'possible', 'before', 'after', or 'skip'.")]
+    InvalidWeekendShift(String),
+}
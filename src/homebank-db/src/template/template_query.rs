@@ -1,9 +1,41 @@
+//! Options for filtering [`Favourite`s][crate::template::template_struct::Favourite] from the [`HomeBankDb`].
+
+use crate::{Favourite, HomeBankDb, Query};
 use clap::Parser;
+use regex::Regex;
 
+/// Options for filtering [`Favourite`s][crate::template::template_struct::Favourite] from the [`HomeBankDb`].
 #[derive(Debug, Parser)]
-#[clap(
+#[command(
     name = "templates",
     visible_alias = "T",
     about = "Query templates and scheduled transactions"
 )]
-pub struct QueryTemplates {}
+pub struct QueryTemplates {
+    /// Memo (i.e. the name shown in HomeBank) of the template.
+    #[arg(value_name = "regex")]
+    name: Option<Regex>,
+}
+
+impl QueryTemplates {
+    /// Retrieve the regular expression for the template memo
+    fn name(&self) -> &Option<Regex> {
+        &self.name
+    }
+}
+
+impl Query for QueryTemplates {
+    type T = Favourite;
+
+    fn exec(&self, db: &HomeBankDb) -> Vec<Self::T> {
+        db.favourites()
+            .values()
+            // filter out templates whose memo doesn't match the regex
+            .filter(|&fav| match self.name() {
+                Some(re) => re.is_match(fav.memo().unwrap_or_default()),
+                None => true,
+            })
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,184 @@
+//! How often a [`Favourite`][crate::template::template_struct::Favourite] is posted, and what happens when a posting falls on a weekend.
+
+use super::TemplateError;
+use chrono::{Datelike, Duration, Months, NaiveDate, Weekday};
+use std::str::FromStr;
+
+/// Length of the step between two postings of a [`Favourite`][crate::template::template_struct::Favourite].
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RepeatUnit {
+    Day,
+    Week,
+    #[default]
+    Month,
+    Year,
+}
+
+impl RepeatUnit {
+    /// Find the date `n` steps of `every` units after `start`.
+    ///
+    /// Months and years are counted from `start` rather than from the previous posting, so a favourite on the 31st
+    /// lands on the last day of shorter months and goes back to the 31st afterwards.
+    /// Returns `None` if the date cannot be represented.
+    pub fn nth(&self, start: &NaiveDate, every: u32, n: u32) -> Option<NaiveDate> {
+        let steps = every.checked_mul(n)?;
+
+        match self {
+            Self::Day => start.checked_add_signed(Duration::days(steps.into())),
+            Self::Week => start.checked_add_signed(Duration::weeks(steps.into())),
+            Self::Month => start.checked_add_months(Months::new(steps)),
+            Self::Year => start.checked_add_months(Months::new(steps.checked_mul(12)?)),
+        }
+    }
+}
+
+impl TryFrom<usize> for RepeatUnit {
+    type Error = TemplateError;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Day),
+            1 => Ok(Self::Week),
+            2 => Ok(Self::Month),
+            3 => Ok(Self::Year),
+            _ => Err(TemplateError::InvalidUnit(value.to_string())),
+        }
+    }
+}
+
+impl FromStr for RepeatUnit {
+    type Err = TemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "day" | "Day" => Ok(Self::Day),
+            "week" | "Week" => Ok(Self::Week),
+            "month" | "Month" => Ok(Self::Month),
+            "year" | "Year" => Ok(Self::Year),
+            _ => match usize::from_str(s) {
+                Ok(unit) => Self::try_from(unit),
+                Err(_) => Err(TemplateError::InvalidUnit(s.to_string())),
+            },
+        }
+    }
+}
+
+/// What happens to a posting of a [`Favourite`][crate::template::template_struct::Favourite] that falls on a Saturday or Sunday.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WeekendShift {
+    /// Post on the weekend.
+    #[default]
+    Possible,
+
+    /// Post on the Friday before.
+    Before,
+
+    /// Post on the Monday after.
+    After,
+
+    /// Don't post at all.
+    Skip,
+}
+
+impl WeekendShift {
+    /// Move `date` off of the weekend, or `None` if the posting is skipped
+    pub fn apply(&self, date: NaiveDate) -> Option<NaiveDate> {
+        let days_into_weekend = match date.weekday() {
+            Weekday::Sat => 1,
+            Weekday::Sun => 2,
+            _ => return Some(date),
+        };
+
+        match self {
+            Self::Possible => Some(date),
+            Self::Before => date.checked_sub_signed(Duration::days(days_into_weekend)),
+            Self::After => date.checked_add_signed(Duration::days(3 - days_into_weekend)),
+            Self::Skip => None,
+        }
+    }
+}
+
+impl TryFrom<usize> for WeekendShift {
+    type Error = TemplateError;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Possible),
+            1 => Ok(Self::Before),
+            2 => Ok(Self::After),
+            3 => Ok(Self::Skip),
+            _ => Err(TemplateError::InvalidWeekendShift(value.to_string())),
+        }
+    }
+}
+
+impl FromStr for WeekendShift {
+    type Err = TemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "possible" | "Possible" => Ok(Self::Possible),
+            "before" | "Before" => Ok(Self::Before),
+            "after" | "After" => Ok(Self::After),
+            "skip" | "Skip" => Ok(Self::Skip),
+            _ => match usize::from_str(s) {
+                Ok(shift) => Self::try_from(shift),
+                Err(_) => Err(TemplateError::InvalidWeekendShift(s.to_string())),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[track_caller]
+    fn check_shift(shift: WeekendShift, input: NaiveDate, expected: Option<NaiveDate>) {
+        assert_eq!(expected, shift.apply(input));
+    }
+
+    #[test]
+    fn shift_saturday() {
+        let saturday = date(2024, 3, 2);
+
+        check_shift(WeekendShift::Possible, saturday, Some(saturday));
+        check_shift(WeekendShift::Before, saturday, Some(date(2024, 3, 1)));
+        check_shift(WeekendShift::After, saturday, Some(date(2024, 3, 4)));
+        check_shift(WeekendShift::Skip, saturday, None);
+    }
+
+    #[test]
+    fn shift_sunday() {
+        let sunday = date(2024, 3, 3);
+
+        check_shift(WeekendShift::Before, sunday, Some(date(2024, 3, 1)));
+        check_shift(WeekendShift::After, sunday, Some(date(2024, 3, 4)));
+    }
+
+    #[test]
+    fn weekdays_are_not_shifted() {
+        check_shift(WeekendShift::Skip, date(2024, 3, 4), Some(date(2024, 3, 4)));
+    }
+
+    #[test]
+    fn yearly_on_leap_day() {
+        let start = date(2024, 2, 29);
+
+        assert_eq!(Some(date(2025, 2, 28)), RepeatUnit::Year.nth(&start, 1, 1));
+        assert_eq!(Some(date(2028, 2, 29)), RepeatUnit::Year.nth(&start, 1, 4));
+    }
+
+    #[test]
+    fn parse_unit() {
+        assert_eq!(Ok(RepeatUnit::Week), RepeatUnit::from_str("1"));
+        assert_eq!(Ok(RepeatUnit::Year), RepeatUnit::from_str("year"));
+        assert_eq!(Err(TemplateError::InvalidUnit("4".to_string())), RepeatUnit::from_str("4"));
+    }
+}
@@ -0,0 +1,402 @@
+//! Favourite (a.k.a. template or scheduled) transactions, which HomeBank can post repeatedly.
+
+use super::{RepeatUnit, TemplateError, WeekendShift};
+use crate::{
+    transaction::{julian_date_from_u32, TransactionFlags},
+    PayMode,
+};
+use chrono::{Duration, NaiveDate};
+use std::str::FromStr;
+use xml::attribute::OwnedAttribute;
+
+/// A favourite transaction, which can be posted repeatedly on a schedule.
+///
+/// HomeBank stores these as `<fav>` elements.
+/// Split favourites are read with the category of their first split only.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Favourite {
+    /// Unique key for the favourite in the database.
+    key: usize,
+
+    /// Net sum of each posting.
+    amount: f32,
+
+    /// Key of the account each posting applies to.
+    account: usize,
+
+    /// Key of the destination account, if the favourite is a transfer.
+    dst_account: Option<usize>,
+
+    /// Payment method of each posting.
+    pay_mode: PayMode,
+
+    /// Flags on the favourite, including whether it is posted automatically and whether it has a limit.
+    flags: TransactionFlags,
+
+    /// Key of the payee.
+    payee: Option<usize>,
+
+    /// Key of the category.
+    category: Option<usize>,
+
+    /// Short form text about the favourite, which HomeBank also shows as its name.
+    memo: Option<String>,
+
+    /// Date of the next posting.
+    next_date: NaiveDate,
+
+    /// Unit of time between postings.
+    unit: RepeatUnit,
+
+    /// Number of units between postings.
+    every: u32,
+
+    /// Number of postings left, if the favourite has a limit.
+    limit: Option<u32>,
+
+    /// What happens to postings that fall on a weekend.
+    weekend: WeekendShift,
+}
+
+impl Favourite {
+    /// Create a new empty favourite.
+    pub fn empty() -> Self {
+        Self {
+            key: 0,
+            amount: 0.0,
+            account: 0,
+            dst_account: None,
+            pay_mode: PayMode::default(),
+            flags: TransactionFlags::default(),
+            payee: None,
+            category: None,
+            memo: None,
+            next_date: julian_date_from_u32(0),
+            unit: RepeatUnit::default(),
+            every: 1,
+            limit: None,
+            weekend: WeekendShift::default(),
+        }
+    }
+
+    /// Create a new favourite of `amount` in the account with key `account`, posted every `every` `unit`s from `next_date`.
+    pub fn new(key: usize, amount: f32, account: usize, next_date: NaiveDate, unit: RepeatUnit, every: u32) -> Self {
+        Self {
+            key,
+            amount,
+            account,
+            next_date,
+            unit,
+            every,
+            ..Self::empty()
+        }
+    }
+
+    /// Stop after `limit` more postings.
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Move postings that fall on a weekend.
+    pub fn with_weekend_shift(mut self, weekend: WeekendShift) -> Self {
+        self.weekend = weekend;
+        self
+    }
+
+    /// Retrieve the favourite's key from the database.
+    pub fn key(&self) -> usize {
+        self.key
+    }
+
+    /// Retrieve the net sum of each posting.
+    pub fn amount(&self) -> f32 {
+        self.amount
+    }
+
+    /// Retrieve the key of the account each posting applies to.
+    pub fn account(&self) -> usize {
+        self.account
+    }
+
+    /// Retrieve the key of the destination account, if the favourite is a transfer.
+    pub fn dst_account(&self) -> Option<usize> {
+        self.dst_account
+    }
+
+    /// Retrieve the payment method.
+    pub fn pay_mode(&self) -> &PayMode {
+        &self.pay_mode
+    }
+
+    /// Retrieve the flags.
+    pub fn flags(&self) -> &TransactionFlags {
+        &self.flags
+    }
+
+    /// Retrieve the key of the payee.
+    pub fn payee(&self) -> Option<usize> {
+        self.payee
+    }
+
+    /// Set the key of the payee.
+    pub(crate) fn set_payee(&mut self, payee: usize) {
+        self.payee = Some(payee);
+    }
+
+    /// Retrieve the key of the category.
+    pub fn category(&self) -> Option<usize> {
+        self.category
+    }
+
+    /// Retrieve the memo.
+    pub fn memo(&self) -> Option<&str> {
+        self.memo.as_deref()
+    }
+
+    /// Retrieve the date of the next posting.
+    pub fn next_date(&self) -> &NaiveDate {
+        &self.next_date
+    }
+
+    /// Retrieve the unit of time between postings.
+    pub fn unit(&self) -> &RepeatUnit {
+        &self.unit
+    }
+
+    /// Retrieve the number of units between postings.
+    pub fn every(&self) -> u32 {
+        self.every
+    }
+
+    /// Retrieve the number of postings left, if the favourite has a limit.
+    pub fn limit(&self) -> Option<u32> {
+        self.limit
+    }
+
+    /// Retrieve what happens to postings that fall on a weekend.
+    pub fn weekend_shift(&self) -> &WeekendShift {
+        &self.weekend
+    }
+
+    /// Check if HomeBank posts the favourite automatically when the file is opened.
+    pub fn is_auto_posted(&self) -> bool {
+        self.flags.is_auto()
+    }
+
+    /// Find the dates of every posting between `from` and `to` (inclusive), after moving them off of weekends.
+    ///
+    /// Postings are counted from the next date, so the limit also counts postings before `from`.
+    /// A favourite repeating every 0 units is only posted once, on its next date.
+    pub fn occurrences_between(&self, from: &NaiveDate, to: &NaiveDate) -> Vec<NaiveDate> {
+        let mut dates = vec![];
+        // shifting to the Friday before can bring a posting up to 2 days earlier
+        let last = to.checked_add_signed(Duration::days(2)).unwrap_or(*to);
+
+        for n in 0.. {
+            // every 0 units would post on the same date forever
+            if self.limit().is_some_and(|limit| n >= limit) || (n > 0 && self.every == 0) {
+                break;
+            }
+
+            let date = match self.unit.nth(&self.next_date, self.every, n) {
+                Some(d) if d <= last => d,
+                _ => break,
+            };
+
+            if let Some(posted) = self.weekend.apply(date) {
+                if from <= &posted && &posted <= to {
+                    dates.push(posted);
+                }
+            }
+        }
+
+        dates
+    }
+}
+
+impl Default for Favourite {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl TryFrom<Vec<OwnedAttribute>> for Favourite {
+    type Error = TemplateError;
+
+    fn try_from(v: Vec<OwnedAttribute>) -> Result<Self, Self::Error> {
+        let mut fav = Self::default();
+        // HomeBank keeps the last limit even after it is turned off, so it only applies with the `LIMIT` flag
+        let mut limit = None;
+
+        for i in v {
+            match i.name.local_name.as_str() {
+                "key" => {
+                    fav.key = usize::from_str(&i.value).map_err(|_| TemplateError::InvalidKey)?;
+                }
+                "amount" => {
+                    fav.amount = f32::from_str(&i.value).map_err(|_| TemplateError::InvalidAmount)?;
+                }
+                "account" => {
+                    fav.account = usize::from_str(&i.value).map_err(|_| TemplateError::InvalidAccount)?;
+                }
+                "dst_account" => {
+                    fav.dst_account = Some(usize::from_str(&i.value).map_err(|_| TemplateError::InvalidAccount)?);
+                }
+                "paymode" => {
+                    fav.pay_mode = usize::from_str(&i.value)
+                        .ok()
+                        .and_then(|pm| PayMode::try_from(pm).ok())
+                        .ok_or(TemplateError::InvalidPayMode)?;
+                }
+                "flags" => {
+                    fav.flags = TransactionFlags::from_str(&i.value).map_err(|_| TemplateError::InvalidFlags)?;
+                }
+                "payee" => {
+                    fav.payee = Some(usize::from_str(&i.value).map_err(|_| TemplateError::InvalidPayee)?);
+                }
+                "category" => {
+                    fav.category = Some(usize::from_str(&i.value).map_err(|_| TemplateError::InvalidCategory)?);
+                }
+                // the categories of each split, separated by `||`
+                "scat" if fav.category.is_none() => {
+                    let first = i.value.split("||").next().unwrap_or_default();
+                    fav.category = match usize::from_str(first) {
+                        // uncategorised splits are stored as 0
+                        Ok(0) => None,
+                        Ok(cat) => Some(cat),
+                        Err(_) => return Err(TemplateError::InvalidCategory),
+                    };
+                }
+                "wording" => {
+                    fav.memo = Some(i.value.clone()).filter(|s| !s.is_empty());
+                }
+                "nextdate" => {
+                    let d = u32::from_str(&i.value).map_err(|_| TemplateError::InvalidNextDate)?;
+                    fav.next_date = julian_date_from_u32(d);
+                }
+                "unit" => {
+                    fav.unit = RepeatUnit::from_str(&i.value)?;
+                }
+                "every" => {
+                    fav.every = match u32::from_str(&i.value) {
+                        Ok(every) if every > 0 => every,
+                        _ => return Err(TemplateError::InvalidEvery(i.value.clone())),
+                    };
+                }
+                "limit" => {
+                    limit = Some(u32::from_str(&i.value).map_err(|_| TemplateError::InvalidLimit(i.value.clone()))?);
+                }
+                "weekend" => {
+                    fav.weekend = WeekendShift::from_str(&i.value)?;
+                }
+                _ => {}
+            }
+        }
+
+        fav.limit = limit.filter(|_| fav.flags.is_limited());
+
+        Ok(fav)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HomeBankDb;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[track_caller]
+    fn check_occurrences(fav: Favourite, from: NaiveDate, to: NaiveDate, expected: Vec<NaiveDate>) {
+        assert_eq!(expected, fav.occurrences_between(&from, &to));
+    }
+
+    #[test]
+    fn monthly_on_31st() {
+        let fav = Favourite::new(1, -900.0, 1, date(2024, 1, 31), RepeatUnit::Month, 1);
+        let expected = vec![date(2024, 1, 31), date(2024, 2, 29), date(2024, 3, 31), date(2024, 4, 30)];
+
+        check_occurrences(fav, date(2024, 1, 1), date(2024, 4, 30), expected);
+    }
+
+    #[test]
+    fn monthly_on_31st_outside_leap_year() {
+        let fav = Favourite::new(1, -900.0, 1, date(2023, 1, 31), RepeatUnit::Month, 1);
+        let expected = vec![date(2023, 2, 28), date(2023, 3, 31)];
+
+        check_occurrences(fav, date(2023, 2, 1), date(2023, 3, 31), expected);
+    }
+
+    #[test]
+    fn every_2_weeks() {
+        let fav = Favourite::new(1, 1500.0, 1, date(2024, 1, 5), RepeatUnit::Week, 2);
+        let expected = vec![date(2024, 1, 19), date(2024, 2, 2), date(2024, 2, 16)];
+
+        check_occurrences(fav, date(2024, 1, 10), date(2024, 2, 29), expected);
+    }
+
+    #[test]
+    fn limit_counts_from_next_date() {
+        let fav = Favourite::new(1, -50.0, 1, date(2024, 1, 15), RepeatUnit::Month, 1).with_limit(3);
+        let expected = vec![date(2024, 2, 15), date(2024, 3, 15)];
+
+        check_occurrences(fav, date(2024, 2, 1), date(2024, 12, 31), expected);
+    }
+
+    #[test]
+    fn shift_weekend_before() {
+        // 2024-06-01 is a Saturday
+        let fav = Favourite::new(1, -50.0, 1, date(2024, 6, 1), RepeatUnit::Year, 1).with_weekend_shift(WeekendShift::Before);
+
+        check_occurrences(fav, date(2024, 5, 1), date(2024, 6, 1), vec![date(2024, 5, 31)]);
+    }
+
+    #[test]
+    fn parse_fav_element() {
+        let input = r#"<homebank v="1.4" d="050504">
+<fav key="3" amount="-12.99" account="1" paymode="4" flags="132" payee="2" category="7" wording="Streaming" nextdate="738917" every="1" unit="2" limit="5" weekend="2"/>
+</homebank>"#;
+        let db = HomeBankDb::from_reader(input.as_bytes()).unwrap();
+        let fav = db.favourite(3).unwrap();
+
+        assert_eq!(-12.99, fav.amount());
+        assert_eq!(Some(2), fav.payee());
+        assert_eq!(Some(7), fav.category());
+        assert_eq!(Some("Streaming"), fav.memo());
+        assert_eq!(&date(2024, 2, 1), fav.next_date());
+        assert_eq!(&RepeatUnit::Month, fav.unit());
+        assert_eq!(Some(5), fav.limit());
+        assert_eq!(&WeekendShift::After, fav.weekend_shift());
+        assert!(fav.is_auto_posted());
+    }
+
+    #[test]
+    fn limit_needs_flag() {
+        let input = r#"<homebank v="1.4" d="050504">
+<fav key="1" amount="-1" account="1" flags="4" nextdate="738917" every="1" unit="0" limit="5"/>
+</homebank>"#;
+        let db = HomeBankDb::from_reader(input.as_bytes()).unwrap();
+
+        assert_eq!(None, db.favourite(1).unwrap().limit());
+    }
+
+    #[test]
+    fn every_0_posted_once() {
+        let fav = Favourite::new(1, -1.0, 1, date(2024, 1, 10), RepeatUnit::Month, 0);
+
+        check_occurrences(fav, date(2024, 1, 1), date(2024, 12, 31), vec![date(2024, 1, 10)]);
+    }
+
+    #[test]
+    fn parse_invalid_every() {
+        let input = r#"<homebank v="1.4" d="050504">
+<fav key="1" amount="-1" account="1" nextdate="738917" every="0" unit="2"/>
+</homebank>"#;
+
+        assert!(HomeBankDb::from_reader(input.as_bytes()).is_err());
+    }
+}
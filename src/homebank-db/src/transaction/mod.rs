@@ -1,9 +1,16 @@
 //! Individual transactions applied to one or more [`Account`s][crate::account::account_struct::Account].
 
+pub mod transaction_builder;
 pub mod transaction_complexity;
 pub mod transaction_date;
 pub mod transaction_error;
+pub mod transaction_filter;
+pub mod transaction_flags;
+pub mod transaction_group;
+pub mod transaction_import;
+pub mod transaction_patterns;
 pub mod transaction_query;
+pub mod transaction_relative_date;
 pub mod transaction_simple;
 pub mod transaction_split;
 pub mod transaction_status;
@@ -12,14 +19,23 @@ pub mod transaction_tags;
 pub mod transaction_transfer;
 pub mod transaction_type;
 
+pub use transaction_builder::TransactionBuilder;
 pub use transaction_complexity::TransactionComplexity;
 pub(crate) use transaction_date::julian_date_from_u32;
+pub use transaction_date::u32_from_julian_date;
 pub use transaction_error::TransactionError;
+pub use transaction_filter::TransactionFilter;
+pub use transaction_flags::{TransactionFlag, TransactionFlags};
+pub use transaction_group::{group_transactions, GroupBy, GroupedResult, TransactionGroup};
+pub use transaction_import::{import_batches, last_import_batch, ImportMarker};
+pub use transaction_patterns::{pattern_to_regex, TransactionPatterns};
 pub use transaction_query::QueryTransactions;
+pub use transaction_relative_date::{parse_date_expr, relative_span};
 pub use transaction_simple::SimpleTransaction;
 pub use transaction_split::{parse_split_values, SplitTransaction};
+pub(crate) use transaction_split::SPLIT_SEPARATOR;
 pub use transaction_status::TransactionStatus;
 pub use transaction_struct::{sum_transactions, Transaction};
-pub(crate) use transaction_tags::split_tags;
+pub(crate) use transaction_tags::{is_tag_separator, split_tags, tag_words, TAG_SEPARATOR};
 pub use transaction_transfer::Transfer;
 pub use transaction_type::TransactionType;
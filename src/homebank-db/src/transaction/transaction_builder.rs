@@ -0,0 +1,246 @@
+//! Build [`Transaction`s][crate::transaction::transaction_struct::Transaction] one field at a time.
+
+use super::{
+    SimpleTransaction, SplitTransaction, TransactionComplexity, TransactionFlags, TransactionStatus,
+    TransactionType, Transfer,
+};
+use crate::{PayMode, Transaction};
+use chrono::NaiveDate;
+
+/// Build a [`Transaction`][crate::transaction::transaction_struct::Transaction] one field at a time, as if it had been read from a HomeBank file.
+///
+/// The type is `Income` or `Expense` depending on the sign of the amount, unless the transaction is a transfer.
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use homebank_db::{transaction::TransactionBuilder, TransactionStatus};
+///
+/// let tr = TransactionBuilder::new(NaiveDate::from_ymd_opt(2022, 1, 15).unwrap(), -150.25, 1)
+///     .status(TransactionStatus::Cleared)
+///     .payee(3)
+///     .category(4)
+///     .memo("Weekly groceries")
+///     .build();
+///
+/// assert_eq!(vec![&Some(4)], tr.categories());
+/// ```
+#[derive(Debug, Clone)]
+pub struct TransactionBuilder {
+    /// Date on which the transaction took place.
+    date: NaiveDate,
+
+    /// Net sum of the transaction.
+    amount: f32,
+
+    /// Key of the account the transaction applies to.
+    account: usize,
+
+    /// Payment method transacted.
+    pay_mode: PayMode,
+
+    /// Review status of the transaction.
+    status: TransactionStatus,
+
+    /// Any flags on the transaction.
+    flags: Option<TransactionFlags>,
+
+    /// Key of the payee.
+    payee: Option<usize>,
+
+    /// Short form text about the transaction.
+    memo: Option<String>,
+
+    /// Any info related to the transaction, such as a reference number.
+    info: Option<String>,
+
+    /// User-provided tags.
+    tags: Option<Vec<String>>,
+
+    /// Key of the category, for a simple transaction.
+    category: Option<usize>,
+
+    /// Categories, amounts, and memos of the splits, for a split transaction.
+    splits: Option<SplitTransaction>,
+
+    /// Transfer key and destination account, for a transfer.
+    transfer: Option<Transfer>,
+}
+
+impl TransactionBuilder {
+    /// Start building a transaction of `amount` in the account with key `account`
+    pub fn new(date: NaiveDate, amount: f32, account: usize) -> Self {
+        Self {
+            date,
+            amount,
+            account,
+            pay_mode: PayMode::default(),
+            status: TransactionStatus::default(),
+            flags: None,
+            payee: None,
+            memo: None,
+            info: None,
+            tags: None,
+            category: None,
+            splits: None,
+            transfer: None,
+        }
+    }
+
+    /// Set the payment method
+    pub fn pay_mode(mut self, pay_mode: PayMode) -> Self {
+        self.pay_mode = pay_mode;
+        self
+    }
+
+    /// Set the review status
+    pub fn status(mut self, status: TransactionStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Set the flags
+    pub fn flags(mut self, flags: TransactionFlags) -> Self {
+        self.flags = Some(flags);
+        self
+    }
+
+    /// Set the key of the payee
+    pub fn payee(mut self, payee: usize) -> Self {
+        self.payee = Some(payee);
+        self
+    }
+
+    /// Set the memo
+    pub fn memo(mut self, memo: &str) -> Self {
+        self.memo = Some(memo.to_string());
+        self
+    }
+
+    /// Set the info field
+    pub fn info(mut self, info: &str) -> Self {
+        self.info = Some(info.to_string());
+        self
+    }
+
+    /// Set the tags
+    pub fn tags(mut self, tags: &[&str]) -> Self {
+        self.tags = Some(tags.iter().map(|tag| tag.to_string()).collect());
+        self
+    }
+
+    /// Set the key of the category, replacing any splits
+    pub fn category(mut self, category: usize) -> Self {
+        self.category = Some(category);
+        self.splits = None;
+        self
+    }
+
+    /// Split the transaction into a category, amount, and memo for each split, replacing any single category
+    pub fn splits(mut self, splits: &[(Option<usize>, f32, Option<&str>)]) -> Self {
+        let categories: Vec<Option<usize>> = splits.iter().map(|(cat, _, _)| *cat).collect();
+        let amounts: Vec<f32> = splits.iter().map(|(_, amount, _)| *amount).collect();
+        let memos: Vec<Option<String>> = splits.iter().map(|(_, _, memo)| memo.map(|m| m.to_string())).collect();
+
+        self.splits = Some(SplitTransaction::new(splits.len(), &categories, &amounts, &memos));
+        self.category = None;
+        self
+    }
+
+    /// Make the transaction half of a transfer with key `transfer_key`, to the account with key `destination`
+    pub fn transfer(mut self, transfer_key: usize, destination: usize) -> Self {
+        self.transfer = Some(Transfer::new(transfer_key, destination));
+        self
+    }
+
+    /// Build the `Transaction`
+    pub fn build(&self) -> Transaction {
+        let ttype = match &self.transfer {
+            Some(xfer) => TransactionType::Transfer(xfer.clone()),
+            None if self.amount > 0.0 => TransactionType::Income,
+            None => TransactionType::Expense,
+        };
+
+        let complexity = match (&self.splits, self.category) {
+            (Some(split), _) => TransactionComplexity::Split(split.clone()),
+            (None, Some(category)) => {
+                TransactionComplexity::Simple(SimpleTransaction::new(Some(category), self.amount, self.memo.clone()))
+            }
+            // uncategorised transactions in a HomeBank file don't record their amount or memo a second time
+            (None, None) => TransactionComplexity::default(),
+        };
+
+        Transaction::new(
+            &self.date,
+            self.amount,
+            self.account,
+            &self.pay_mode,
+            &self.status,
+            &self.flags,
+            &self.payee,
+            &self.memo,
+            &self.info,
+            &self.tags,
+            &ttype,
+            &complexity,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[track_caller]
+    fn check_round_trip(idx: usize, builder: TransactionBuilder) {
+        let db = example_db();
+
+        assert_eq!(db.transactions()[idx], builder.build());
+    }
+
+    #[test]
+    fn build_simple() {
+        let builder = TransactionBuilder::new(date(2022, 1, 15), -150.25, 1)
+            .pay_mode(PayMode::StandingOrder)
+            .status(TransactionStatus::Cleared)
+            .payee(3)
+            .category(4)
+            .memo("Weekly groceries")
+            .tags(&["food"]);
+
+        check_round_trip(3, builder);
+    }
+
+    #[test]
+    fn build_split() {
+        let builder = TransactionBuilder::new(date(2022, 1, 20), -100.0, 1)
+            .pay_mode(PayMode::CreditCard)
+            .status(TransactionStatus::Cleared)
+            .payee(3)
+            .splits(&[(Some(4), -80.0, Some("Groceries")), (Some(5), -20.0, Some("Deli lunch"))]);
+
+        check_round_trip(4, builder);
+    }
+
+    #[test]
+    fn build_transfer() {
+        let builder = TransactionBuilder::new(date(2022, 1, 25), -500.0, 1)
+            .pay_mode(PayMode::DebitCard)
+            .status(TransactionStatus::Cleared)
+            .memo("Savings")
+            .transfer(1, 2);
+
+        check_round_trip(5, builder);
+    }
+
+    #[test]
+    fn build_income() {
+        let tr = TransactionBuilder::new(date(2022, 1, 5), 2000.0, 1).category(6).build();
+
+        assert_eq!(&TransactionType::Income, tr.ttype());
+    }
+}
@@ -4,6 +4,7 @@ use super::{SimpleTransaction, SplitTransaction};
 
 /// A wrapper to provide a shared interface for [`SimpleTransaction`s][crate::transaction::transaction_simple::SimpleTransaction] and [`SplitTransaction`s][crate::transaction::transaction_split::SplitTransaction].
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TransactionComplexity {
     Simple(SimpleTransaction),
     Split(SplitTransaction),
@@ -20,6 +21,14 @@ impl TransactionComplexity {
         self.is_split() == other.is_split()
     }
 
+    /// Multiply every amount by `factor`.
+    pub(crate) fn scale(&mut self, factor: f32) {
+        match self {
+            Self::Simple(simple_tr) => *simple_tr.mut_amount() *= factor,
+            Self::Split(split_tr) => split_tr.mut_amounts().iter_mut().for_each(|amount| *amount *= factor),
+        }
+    }
+
     /// Return the number of sub-transactions in a [`Transaction`][crate::transaction::transaction_struct::Transaction].
     /// 
     /// A [`SimpleTransaction`][crate::transaction::transaction_simple::SimpleTransaction] will return `0`.
@@ -14,7 +14,8 @@ lazy_static!{
     pub static ref HB_MAX_DATE: NaiveDate = NaiveDate::from_ymd_opt(2200, 12, 31).unwrap();
 
     /// The Julian-encoded day 0.
-    /// Dates in the [`HomeBankDb`][crate::db::db_struct::HomeBankDb] are stored as [Julian dates](https://en.wikipedia.org/wiki/Julian_calendar), with day 1 being 0001-01-01.
+    /// Dates in the [`HomeBankDb`][crate::db::db_struct::HomeBankDb] are stored as GLib "Julian" day numbers: the number of days in the proleptic Gregorian calendar, with day 1 being 0001-01-01.
+    /// Despite the name, this is not the [Julian calendar](https://en.wikipedia.org/wiki/Julian_calendar), nor the astronomical Julian day.
     /// We start from the previous day to avoid off-by-1 errors in calculations.
     pub static ref JULIAN_ZERO: NaiveDate = NaiveDate::from_ymd_opt(0000, 12, 31).unwrap();
 }
@@ -26,8 +27,10 @@ pub(crate) fn clamp_date(d: NaiveDate) -> NaiveDate {
 
 /// Convert a date from the Julian format (encoded as days since [`struct@JULIAN_ZERO`]) into a [`NaiveDate`].
 /// This will also clamp the date as described by [`clamp_date`].
+///
+/// All dates read from the HomeBank XML file should go through this function (or [`unclamped_julian_date_from_u32`]), so that the epoch is only defined in one place.
 pub(crate) fn julian_date_from_u32(d: u32) -> NaiveDate {
-    clamp_date(*JULIAN_ZERO + Duration::days(d.into()))
+    clamp_date(unclamped_julian_date_from_u32(d))
 }
 
 /// Convert a date from the Julian format (encoded as days since [`struct@JULIAN_ZERO`]) into a [`NaiveDate`].
@@ -36,6 +39,13 @@ pub(crate) fn unclamped_julian_date_from_u32(d: u32) -> NaiveDate {
     *JULIAN_ZERO + Duration::days(d.into())
 }
 
+/// Convert a [`NaiveDate`] into the Julian format (encoded as days since [`struct@JULIAN_ZERO`]).
+/// This is the inverse of [`unclamped_julian_date_from_u32`], and of [`julian_date_from_u32`] for dates between [`struct@HB_MIN_DATE`] and [`struct@HB_MAX_DATE`].
+/// Dates on or before [`struct@JULIAN_ZERO`] cannot be represented and are converted to `0`.
+pub fn u32_from_julian_date(d: NaiveDate) -> u32 {
+    u32::try_from((d - *JULIAN_ZERO).num_days()).unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -77,6 +87,60 @@ mod tests {
         check_date_conversion(input, expected);
     }
 
+    #[test]
+    fn convert_last_day_of_year() {
+        let input = 737424;
+        let expected = NaiveDate::from_ymd_opt(2019, 12, 31).unwrap();
+
+        check_date_conversion(input, expected);
+    }
+
+    #[test]
+    fn convert_first_day_of_year() {
+        let input = 737425;
+        let expected = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+
+        check_date_conversion(input, expected);
+    }
+
+    #[test]
+    fn convert_leap_day() {
+        let input = 737484;
+        let expected = NaiveDate::from_ymd_opt(2020, 2, 29).unwrap();
+
+        check_date_conversion(input, expected);
+    }
+
+    #[test]
+    fn convert_day_after_leap_day() {
+        let input = 737485;
+        let expected = NaiveDate::from_ymd_opt(2020, 3, 1).unwrap();
+
+        check_date_conversion(input, expected);
+    }
+
+    #[test]
+    fn convert_leap_day_divisible_by_400() {
+        let input = 730179;
+        let expected = NaiveDate::from_ymd_opt(2000, 2, 29).unwrap();
+
+        check_date_conversion(input, expected);
+    }
+
+    #[test]
+    fn convert_no_leap_day_divisible_by_100() {
+        // 2100 is not a leap year, so 2100-02-28 is followed by 2100-03-01
+        check_date_conversion(766703, NaiveDate::from_ymd_opt(2100, 2, 28).unwrap());
+        check_date_conversion(766704, NaiveDate::from_ymd_opt(2100, 3, 1).unwrap());
+    }
+
+    #[test]
+    fn convert_dates_one_year_apart() {
+        // 2020 is a leap year, so the same day one year later is 366 days away
+        check_date_conversion(737495, NaiveDate::from_ymd_opt(2020, 3, 11).unwrap());
+        check_date_conversion(737860, NaiveDate::from_ymd_opt(2021, 3, 11).unwrap());
+    }
+
     #[track_caller]
     fn check_clamp_date(input: u32, expected: NaiveDate) {
         let observed = julian_date_from_u32(input);
@@ -170,4 +234,46 @@ mod tests {
 
         check_unclamped_date(input, expected);
     }
+
+    #[track_caller]
+    fn check_inverse_date(input: NaiveDate, expected: u32) {
+        let observed = u32_from_julian_date(input);
+
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    fn inverse_unix_epoch_beginning() {
+        check_inverse_date(NaiveDate::from_ymd_opt(1970, 1, 1).unwrap(), 719163);
+    }
+
+    #[test]
+    fn inverse_leap_day() {
+        check_inverse_date(NaiveDate::from_ymd_opt(2020, 2, 29).unwrap(), 737484);
+    }
+
+    #[test]
+    fn inverse_first_day() {
+        check_inverse_date(NaiveDate::from_ymd_opt(1, 1, 1).unwrap(), 1);
+    }
+
+    #[test]
+    fn inverse_before_first_day() {
+        check_inverse_date(NaiveDate::from_ymd_opt(-1, 6, 1).unwrap(), 0);
+    }
+
+    #[test]
+    fn round_trip_supported_range() {
+        // every day that HomeBank supports, from 1900-01-01 to 2200-12-31
+        for d in 693596..=803533 {
+            assert_eq!(d, u32_from_julian_date(julian_date_from_u32(d)));
+        }
+    }
+
+    #[test]
+    fn round_trip_unclamped() {
+        for d in (1..=3_000_000).step_by(997) {
+            assert_eq!(d, u32_from_julian_date(unclamped_julian_date_from_u32(d)));
+        }
+    }
 }
@@ -42,9 +42,25 @@ pub enum TransactionError {
     MismatchedSplitNumber(usize, usize),
 
     /// When the flags on a transaction are invalid.
-    #[error("Invalid transaction flags. Must be a `usize` type.")]
+    #[error("Invalid transaction flags. Must be a `u32` type.")]
     InvalidFlags,
 
+    /// When a transaction flag name is not recognized.
+    #[error("Invalid transaction flag `{0}`. Must be 'scanned', 'income', 'auto', 'added', 'changed', 'remind', 'split', or 'imported'.")]
+    InvalidFlagName(String),
+
+    /// When a date filter is neither a date nor a relative date.
+    #[error("Invalid date `{0}`. Must be a date like '2024-01-31', 'today', 'yesterday', 'ytd', a number of days, weeks, months, or years ago like '-30d', or a period like 'this-month' or 'last-year'.")]
+    InvalidDateExpression(String),
+
+    /// When the field to group transactions by is not recognized.
+    #[error("Invalid group `{0}`. Must be 'account', 'category', 'payee', 'pay-mode', 'week', 'month', or 'year'.")]
+    InvalidGroupBy(String),
+
+    /// When an import batch marker is not recognized.
+    #[error("Invalid import marker `{0}`. Must be 'flag' or 'info'.")]
+    InvalidImportMarker(String),
+
     /// When a transfer's destination account is invalid or not found within the database.
     #[error("Invalid destination account identifier from transfer. Must be of type `usize` and cannot be 0.")]
     InvalidDestinationAccount,
@@ -0,0 +1,773 @@
+//! Lazily filter the [`Transaction`s][crate::transaction::transaction_struct::Transaction] in the [`HomeBankDb`].
+
+use super::{last_import_batch, ImportMarker, TransactionFlag, TransactionStatus, TransactionType};
+use crate::{HomeBankDb, PayMode, Transaction};
+use chrono::NaiveDate;
+use regex::Regex;
+use std::collections::HashSet;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Number of transactions in the database from which [`QueryTransactions::exec`][crate::QueryTransactions] filters them on several threads, with the `parallel` feature.
+pub const PARALLEL_THRESHOLD: usize = 10_000;
+
+/// A set of conditions that [`Transaction`s][crate::transaction::transaction_struct::Transaction] must meet, built up one predicate at a time.
+///
+/// Created by [`HomeBankDb::transactions_query`].
+/// A transaction is included only if it meets every predicate that has been set.
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use homebank_db::HomeBankDb;
+/// use regex::Regex;
+/// use std::path::Path;
+///
+/// let db = HomeBankDb::try_from(Path::new("tests/transactions.xhb")).unwrap();
+/// let groceries: Vec<_> = db
+///     .transactions_query()
+///     .date_from(NaiveDate::from_ymd_opt(2022, 1, 1).unwrap())
+///     .category_matches(&Regex::new("Groceries").unwrap())
+///     .payee(3)
+///     .iter()
+///     .collect();
+///
+/// assert_eq!(2, groceries.len());
+/// ```
+#[derive(Debug, Clone)]
+pub struct TransactionFilter<'a> {
+    /// Database that the transactions are read from.
+    db: &'a HomeBankDb,
+
+    /// Earliest date, inclusive.
+    date_from: Option<NaiveDate>,
+
+    /// Latest date, exclusive.
+    date_to: Option<NaiveDate>,
+
+    /// Lowest amount, inclusive.
+    amount_from: Option<f32>,
+
+    /// Highest amount, exclusive.
+    amount_to: Option<f32>,
+
+    /// Statuses to include.
+    status: Option<Vec<TransactionStatus>>,
+
+    /// Flags that must all be set.
+    flags: Option<Vec<TransactionFlag>>,
+
+    /// Pattern for the category names.
+    category: Option<Regex>,
+
+    /// Whether categories beneath a matching category also match.
+    include_subcategories: bool,
+
+    /// Pattern for the category names to leave out.
+    exclude_category: Option<Regex>,
+
+    /// Key of the payee.
+    payee: Option<usize>,
+
+    /// Pattern for the payee names.
+    payee_name: Option<Regex>,
+
+    /// Pattern for the payee names to leave out.
+    exclude_payee: Option<Regex>,
+
+    /// Key of the account.
+    account: Option<usize>,
+
+    /// Pattern for the account names.
+    account_name: Option<Regex>,
+
+    /// Keys of the accounts that the transaction, or the destination of a transfer, must be in.
+    accounts: Option<HashSet<usize>>,
+
+    /// Payment methods to include.
+    pay_mode: Option<Vec<PayMode>>,
+
+    /// Pattern for the memos.
+    memo: Option<Regex>,
+
+    /// Pattern for the info fields.
+    info: Option<Regex>,
+
+    /// Pattern for the tags.
+    tags: Option<Regex>,
+
+    /// Whether the memo must be present (`true`) or absent (`false`).
+    has_memo: Option<bool>,
+
+    /// Whether voided transactions are left out.
+    exclude_void: bool,
+
+    /// Transaction types to include.
+    transaction_type: Option<Vec<TransactionType>>,
+
+    /// Whether only transfers (`true`) or everything but transfers (`false`) are included.
+    transfers: Option<bool>,
+
+    /// What marks the most recent import, if only its transactions are included.
+    last_import: Option<ImportMarker>,
+
+    /// Whether amounts are converted into the base currency before filtering.
+    convert_to_base: bool,
+}
+
+impl HomeBankDb {
+    /// Start filtering the transactions in the database.
+    /// Until a predicate is added, every transaction is included.
+    pub fn transactions_query(&self) -> TransactionFilter<'_> {
+        TransactionFilter::new(self)
+    }
+}
+
+impl<'a> TransactionFilter<'a> {
+    /// Create a new filter that includes every transaction in `db`
+    pub fn new(db: &'a HomeBankDb) -> Self {
+        Self {
+            db,
+            date_from: None,
+            date_to: None,
+            amount_from: None,
+            amount_to: None,
+            status: None,
+            flags: None,
+            category: None,
+            include_subcategories: false,
+            exclude_category: None,
+            payee: None,
+            payee_name: None,
+            exclude_payee: None,
+            account: None,
+            account_name: None,
+            accounts: None,
+            pay_mode: None,
+            memo: None,
+            info: None,
+            tags: None,
+            has_memo: None,
+            exclude_void: false,
+            transaction_type: None,
+            transfers: None,
+            last_import: None,
+            convert_to_base: false,
+        }
+    }
+
+    /// Include transactions on or after `date` (inclusive).
+    ///
+    /// ```
+    /// # use chrono::NaiveDate;
+    /// # use homebank_db::HomeBankDb;
+    /// # use std::path::Path;
+    /// # let db = HomeBankDb::try_from(Path::new("tests/transactions.xhb")).unwrap();
+    /// let date = NaiveDate::from_ymd_opt(2022, 2, 5).unwrap();
+    /// let observed = db.transactions_query().date_from(date).iter().count();
+    ///
+    /// // the paycheque on 2022-02-05 is included
+    /// assert_eq!(4, observed);
+    /// ```
+    pub fn date_from(mut self, date: NaiveDate) -> Self {
+        self.date_from = Some(date);
+        self
+    }
+
+    /// Include transactions before `date` (exclusive).
+    ///
+    /// ```
+    /// # use chrono::NaiveDate;
+    /// # use homebank_db::HomeBankDb;
+    /// # use std::path::Path;
+    /// # let db = HomeBankDb::try_from(Path::new("tests/transactions.xhb")).unwrap();
+    /// let date = NaiveDate::from_ymd_opt(2022, 1, 5).unwrap();
+    /// let observed = db.transactions_query().date_to(date).iter().count();
+    ///
+    /// // the paycheque on 2022-01-05 is left out
+    /// assert_eq!(1, observed);
+    /// ```
+    pub fn date_to(mut self, date: NaiveDate) -> Self {
+        self.date_to = Some(date);
+        self
+    }
+
+    /// Include transactions whose total is at least `amount` (inclusive).
+    ///
+    /// ```
+    /// # use homebank_db::HomeBankDb;
+    /// # use std::path::Path;
+    /// # let db = HomeBankDb::try_from(Path::new("tests/transactions.xhb")).unwrap();
+    /// // both paycheques of exactly 2000 are included
+    /// assert_eq!(2, db.transactions_query().amount_from(2000.0).iter().count());
+    /// ```
+    pub fn amount_from(mut self, amount: f32) -> Self {
+        self.amount_from = Some(amount);
+        self
+    }
+
+    /// Include transactions whose total is below `amount` (exclusive).
+    ///
+    /// ```
+    /// # use homebank_db::HomeBankDb;
+    /// # use std::path::Path;
+    /// # let db = HomeBankDb::try_from(Path::new("tests/transactions.xhb")).unwrap();
+    /// // rent payments of exactly -1200 are left out
+    /// assert_eq!(0, db.transactions_query().amount_to(-1200.0).iter().count());
+    /// assert_eq!(3, db.transactions_query().amount_to(-1199.0).iter().count());
+    /// ```
+    pub fn amount_to(mut self, amount: f32) -> Self {
+        self.amount_to = Some(amount);
+        self
+    }
+
+    /// Include transactions with any of these statuses.
+    pub fn status(mut self, status: &[TransactionStatus]) -> Self {
+        self.status = Some(status.to_vec());
+        self
+    }
+
+    /// Include transactions with all of these flags set.
+    /// Transactions without any flags are left out, unless `flags` is empty.
+    pub fn flags(mut self, flags: &[TransactionFlag]) -> Self {
+        self.flags = Some(flags.to_vec());
+        self
+    }
+
+    /// Include transactions with at least one split whose category's full name (e.g. `Food:Groceries`) matches `re`.
+    /// Uncategorised transactions are left out.
+    ///
+    /// [`iter`][Self::iter] returns whole transactions, while [`splits`][Self::splits] reduces them to the matching splits.
+    ///
+    /// ```
+    /// # use homebank_db::HomeBankDb;
+    /// # use regex::Regex;
+    /// # use std::path::Path;
+    /// # let db = HomeBankDb::try_from(Path::new("tests/transactions.xhb")).unwrap();
+    /// let query = db.transactions_query().category_matches(&Regex::new("Dining").unwrap());
+    ///
+    /// // the split grocery trip includes a -20 deli lunch
+    /// assert_eq!(vec![-100.0, -60.0], query.iter().map(|tr| *tr.total()).collect::<Vec<f32>>());
+    /// assert_eq!(vec![-20.0, -60.0], query.splits().map(|tr| *tr.total()).collect::<Vec<f32>>());
+    /// ```
+    pub fn category_matches(mut self, re: &Regex) -> Self {
+        self.category = Some(re.clone());
+        self
+    }
+
+    /// Let [`category_matches`][Self::category_matches] also include the subcategories of any matching category, at any depth.
+    ///
+    /// ```
+    /// # use homebank_db::HomeBankDb;
+    /// # use regex::Regex;
+    /// # use std::path::Path;
+    /// # let db = HomeBankDb::try_from(Path::new("tests/transactions.xhb")).unwrap();
+    /// let food = Regex::new("^Food$").unwrap();
+    ///
+    /// // every food transaction is in a subcategory of `Food`
+    /// assert_eq!(0, db.transactions_query().category_matches(&food).iter().count());
+    /// assert_eq!(3, db.transactions_query().category_matches(&food).include_subcategories().iter().count());
+    /// ```
+    pub fn include_subcategories(mut self) -> Self {
+        self.include_subcategories = true;
+        self
+    }
+
+    /// Leave out the splits whose category's full name matches `re`, and transactions without any other splits.
+    /// This takes precedence over [`category_matches`][Self::category_matches]. Uncategorised splits are kept.
+    ///
+    /// ```
+    /// # use homebank_db::HomeBankDb;
+    /// # use regex::Regex;
+    /// # use std::path::Path;
+    /// # let db = HomeBankDb::try_from(Path::new("tests/transactions.xhb")).unwrap();
+    /// let query = db
+    ///     .transactions_query()
+    ///     .category_matches(&Regex::new("^Food").unwrap())
+    ///     .exclude_category_matches(&Regex::new("Dining").unwrap());
+    ///
+    /// // the deli lunch is left out of the split grocery trip
+    /// assert_eq!(vec![-150.25, -80.0], query.splits().map(|tr| *tr.total()).collect::<Vec<f32>>());
+    /// ```
+    pub fn exclude_category_matches(mut self, re: &Regex) -> Self {
+        self.exclude_category = Some(re.clone());
+        self
+    }
+
+    /// Include transactions with the payee whose key is `payee`.
+    ///
+    /// ```
+    /// # use homebank_db::HomeBankDb;
+    /// # use std::path::Path;
+    /// # let db = HomeBankDb::try_from(Path::new("tests/transactions.xhb")).unwrap();
+    /// // the landlord
+    /// assert_eq!(3, db.transactions_query().payee(2).iter().count());
+    /// ```
+    pub fn payee(mut self, payee: usize) -> Self {
+        self.payee = Some(payee);
+        self
+    }
+
+    /// Include transactions whose payee's name matches `re`.
+    /// Transactions without a payee are left out.
+    pub fn payee_matches(mut self, re: &Regex) -> Self {
+        self.payee_name = Some(re.clone());
+        self
+    }
+
+    /// Leave out transactions whose payee's name matches `re`, taking precedence over [`payee_matches`][Self::payee_matches].
+    /// Transactions without a payee are kept.
+    pub fn exclude_payee_matches(mut self, re: &Regex) -> Self {
+        self.exclude_payee = Some(re.clone());
+        self
+    }
+
+    /// Include transactions in the account whose key is `account`.
+    /// Transfers are included in the account they were recorded in, not their destination.
+    pub fn account(mut self, account: usize) -> Self {
+        self.account = Some(account);
+        self
+    }
+
+    /// Include transactions whose account's name matches `re`.
+    pub fn account_matches(mut self, re: &Regex) -> Self {
+        self.account_name = Some(re.clone());
+        self
+    }
+
+    /// Include transactions in any of the accounts whose keys are in `accounts`, or transfers into any of them.
+    pub fn accounts(mut self, accounts: &[usize]) -> Self {
+        self.accounts = Some(accounts.iter().copied().collect());
+        self
+    }
+
+    /// Include transactions with any of these payment methods.
+    pub fn pay_mode(mut self, pay_mode: &[PayMode]) -> Self {
+        self.pay_mode = Some(pay_mode.to_vec());
+        self
+    }
+
+    /// Include transactions whose memo matches `re`.
+    /// Transactions without a memo are left out.
+    pub fn memo_matches(mut self, re: &Regex) -> Self {
+        self.memo = Some(re.clone());
+        self
+    }
+
+    /// Include transactions whose info field matches `re`.
+    /// Transactions without an info field are left out.
+    pub fn info_matches(mut self, re: &Regex) -> Self {
+        self.info = Some(re.clone());
+        self
+    }
+
+    /// Include transactions whose tags, joined by commas (e.g. `food,restaurant`), match `re`.
+    /// Transactions without tags are left out.
+    pub fn tags_match(mut self, re: &Regex) -> Self {
+        self.tags = Some(re.clone());
+        self
+    }
+
+    /// Include only transactions with a non-empty memo (`true`), or only those without one (`false`).
+    /// An empty memo counts as no memo.
+    pub fn has_memo(mut self, has_memo: bool) -> Self {
+        self.has_memo = Some(has_memo);
+        self
+    }
+
+    /// Leave out voided transactions.
+    pub fn exclude_void(mut self) -> Self {
+        self.exclude_void = true;
+        self
+    }
+
+    /// Include transactions of any of these types.
+    pub fn transaction_type(mut self, transaction_type: &[TransactionType]) -> Self {
+        self.transaction_type = Some(transaction_type.to_vec());
+        self
+    }
+
+    /// Include only transfers between accounts (`true`), or leave them out (`false`).
+    /// Both halves of each transfer are included, one in each account.
+    pub fn transfers(mut self, transfers: bool) -> Self {
+        self.transfers = Some(transfers);
+        self
+    }
+
+    /// Include only transactions from the most recent import, as marked by `marker`.
+    ///
+    /// Batches depend on the neighbouring transactions in the database, so this is ignored by [`matches`][Self::matches].
+    pub fn last_import(mut self, marker: ImportMarker) -> Self {
+        self.last_import = Some(marker);
+        self
+    }
+
+    /// Compare amounts in the base currency, using the exchange rates in the database.
+    ///
+    /// [`iter`][Self::iter] still returns transactions as they are in the database, while [`splits`][Self::splits] returns the converted amounts.
+    pub fn convert_to_base(mut self) -> Self {
+        self.convert_to_base = true;
+        self
+    }
+
+    /// Check if a single `Transaction` meets every predicate, except for [`last_import`][Self::last_import].
+    pub fn matches(&self, tr: &Transaction) -> bool {
+        if self.convert_to_base {
+            self.matches_converted(&tr.to_base_currency(self.db))
+        } else {
+            self.matches_converted(tr)
+        }
+    }
+
+    /// Iterate over the transactions that meet every predicate, in the order they are in the database.
+    pub fn iter(&self) -> impl Iterator<Item = &'a Transaction> + '_ {
+        let batch = self.import_batch();
+
+        self.db
+            .transactions()
+            .iter()
+            .enumerate()
+            .filter(move |(i, _)| match &batch {
+                Some(batch) => batch.contains(i),
+                None => true,
+            })
+            .map(|(_, tr)| tr)
+            .filter(|tr| self.matches(tr))
+    }
+
+    /// Iterate over the transactions that meet every predicate, reduced to the splits whose categories match.
+    ///
+    /// Amounts are in the base currency if [`convert_to_base`][Self::convert_to_base] is set.
+    pub fn splits(&self) -> impl Iterator<Item = Transaction> + '_ {
+        self.iter().filter_map(|tr| self.converted_splits(tr))
+    }
+
+    /// Collect the same transactions as [`splits`][Self::splits], filtering the database on several threads.
+    ///
+    /// Transactions stay in the order they are in the database. Starting the threads only pays off for large databases.
+    #[cfg(feature = "parallel")]
+    pub fn par_splits(&self) -> Vec<Transaction> {
+        let batch = self.import_batch();
+
+        self.db
+            .transactions()
+            .par_iter()
+            .enumerate()
+            .filter(|(i, _)| match &batch {
+                Some(batch) => batch.contains(i),
+                None => true,
+            })
+            .filter(|(_, tr)| self.matches(tr))
+            .filter_map(|(_, tr)| self.converted_splits(tr))
+            .collect()
+    }
+
+    /// Find the indices of the transactions in the most recent import, if only those are included.
+    fn import_batch(&self) -> Option<HashSet<usize>> {
+        // batches depend on the neighbouring transactions, so they're found before filtering
+        self.last_import
+            .as_ref()
+            .map(|marker| last_import_batch(self.db.transactions(), marker).into_iter().collect())
+    }
+
+    /// Reduce a matching transaction to the splits whose categories match, in the base currency if requested.
+    fn converted_splits(&self, tr: &Transaction) -> Option<Transaction> {
+        if self.convert_to_base {
+            self.matching_splits(&tr.to_base_currency(self.db))
+        } else {
+            self.matching_splits(tr)
+        }
+    }
+
+    /// Check every predicate against a transaction whose amounts have already been converted, if requested.
+    fn matches_converted(&self, tr: &Transaction) -> bool {
+        self.filter_date(tr)
+            && self.filter_amount(tr)
+            && self.filter_status(tr)
+            && self.filter_void(tr)
+            && self.filter_flags(tr)
+            && self.filter_payee(tr)
+            && self.filter_account(tr)
+            && self.filter_paymode(tr)
+            && self.filter_ttype(tr)
+            && self.filter_transfers(tr)
+            && self.filter_tags(tr)
+            && self.filter_memo(tr)
+            && self.filter_memo_presence(tr)
+            && self.filter_info(tr)
+            && self.filter_category(tr)
+    }
+
+    /// Filter out dates before `date_from` or on or after `date_to`
+    fn filter_date(&self, tr: &Transaction) -> bool {
+        self.date_from.iter().all(|d| tr.date() >= d) && self.date_to.iter().all(|d| tr.date() < d)
+    }
+
+    /// Filter out amounts below `amount_from` or at or above `amount_to`
+    fn filter_amount(&self, tr: &Transaction) -> bool {
+        self.amount_from.iter().all(|a| tr.total() >= a) && self.amount_to.iter().all(|a| tr.total() < a)
+    }
+
+    /// Filter out by status
+    fn filter_status(&self, tr: &Transaction) -> bool {
+        match &self.status {
+            Some(v) => v.contains(tr.status()),
+            None => true,
+        }
+    }
+
+    /// Filter out voided transactions, if requested
+    fn filter_void(&self, tr: &Transaction) -> bool {
+        !(self.exclude_void && tr.is_void())
+    }
+
+    /// Filter by flags, keeping transactions with all queried flags set
+    fn filter_flags(&self, tr: &Transaction) -> bool {
+        match (&self.flags, tr.flags()) {
+            (Some(v), Some(flags)) => v.iter().all(|flag| flags.contains(flag)),
+            (Some(v), None) => v.is_empty(),
+            (None, _) => true,
+        }
+    }
+
+    /// Filter by payee key and name, leaving out excluded payees
+    fn filter_payee(&self, tr: &Transaction) -> bool {
+        let payee_name = tr.payee_name(self.db);
+        let key_matches = self.payee.is_none() || self.payee == *tr.payee();
+        let name_matches = match (&self.payee_name, &payee_name) {
+            // if there is a regex and there is a payee name
+            (Some(re), Some(payee_name)) => re.is_match(payee_name),
+            // if there is a regex but no payee
+            (Some(_), None) => false,
+            // if there is no regex
+            (None, _) => true,
+        };
+        let excluded = match (&self.exclude_payee, &payee_name) {
+            (Some(re), Some(payee_name)) => re.is_match(payee_name),
+            _ => false,
+        };
+
+        key_matches && name_matches && !excluded
+    }
+
+    /// Filter by account key and name
+    fn filter_account(&self, tr: &Transaction) -> bool {
+        let key_matches = self.account.iter().all(|&acct| acct == tr.account());
+        let name_matches = match (&self.account_name, tr.account_name(self.db)) {
+            // if there is a regex and there is an account name
+            (Some(re), Some(account_name)) => re.is_match(&account_name),
+            // if there is a regex but no account
+            (Some(_), None) => false,
+            // if there is no regex
+            (None, _) => true,
+        };
+        let any_matches = match &self.accounts {
+            Some(keys) => keys.contains(&tr.account()) || tr.transfer_destination().is_some_and(|dst| keys.contains(dst)),
+            None => true,
+        };
+
+        key_matches && name_matches && any_matches
+    }
+
+    /// Filter by payment method
+    fn filter_paymode(&self, tr: &Transaction) -> bool {
+        match &self.pay_mode {
+            Some(v) => v.contains(tr.pay_mode()),
+            None => true,
+        }
+    }
+
+    /// Filter by `TransactionType`
+    fn filter_ttype(&self, tr: &Transaction) -> bool {
+        match &self.transaction_type {
+            Some(v) => v
+                .iter()
+                // check transaction types without explicitly checking the values
+                .any(|queried_type| queried_type.is_similar_to(tr.ttype())),
+            None => true,
+        }
+    }
+
+    /// Filter by whether the transaction is a transfer
+    fn filter_transfers(&self, tr: &Transaction) -> bool {
+        self.transfers.iter().all(|&transfers| transfers == tr.is_transfer())
+    }
+
+    /// Filter by tags
+    fn filter_tags(&self, tr: &Transaction) -> bool {
+        match (&self.tags, tr.tags()) {
+            (Some(re), Some(tags)) => {
+                // combine all the tags back into a single string to perform a single regex match
+                // this avoids performing the costly match multiple times
+                let combined_tr_tags = tags.join(",");
+                re.is_match(&combined_tr_tags)
+            }
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+
+    /// Filter by memo
+    fn filter_memo(&self, tr: &Transaction) -> bool {
+        match (&self.memo, tr.memo()) {
+            (Some(re), Some(memo)) => re.is_match(memo),
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+
+    /// Filter by whether the memo is present and non-empty
+    fn filter_memo_presence(&self, tr: &Transaction) -> bool {
+        let tr_has_memo = matches!(tr.memo(), Some(memo) if !memo.is_empty());
+
+        self.has_memo.iter().all(|&has_memo| has_memo == tr_has_memo)
+    }
+
+    /// Filter by info
+    fn filter_info(&self, tr: &Transaction) -> bool {
+        match &self.info {
+            Some(re) => tr.info_matches(re),
+            None => true,
+        }
+    }
+
+    /// Filter by whether any split's category matches
+    fn filter_category(&self, tr: &Transaction) -> bool {
+        !matches!(self.category_indices(tr), Some(idx) if idx.is_empty())
+    }
+
+    /// Find the indices of the splits whose categories match and aren't excluded, if there is a category pattern
+    fn category_indices(&self, tr: &Transaction) -> Option<Vec<usize>> {
+        if self.category.is_none() && self.exclude_category.is_none() {
+            return None;
+        }
+
+        let matching_idx = tr
+            .categories()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, key)| {
+                let included = match &self.category {
+                    Some(re) => key.is_some_and(|key| self.category_matches_key(re, key)),
+                    None => true,
+                };
+                let excluded = match (&self.exclude_category, **key) {
+                    (Some(re), Some(key)) => self.db.category_full_name(key).is_some_and(|name| re.is_match(name)),
+                    _ => false,
+                };
+
+                (included && !excluded).then_some(i)
+            })
+            .collect();
+
+        Some(matching_idx)
+    }
+
+    /// Check whether the full name of the category with key `key`, or of one of its parents if subcategories are included, matches `re`
+    fn category_matches_key(&self, re: &Regex, key: usize) -> bool {
+        self.db.category_full_name(key).is_some_and(|name| re.is_match(name))
+            || (self.include_subcategories
+                && self.db.category(key).is_some_and(|cat| {
+                    cat.ancestors(self.db).iter().any(|parent| {
+                        self.db.category_full_name(parent.key()).is_some_and(|name| re.is_match(name))
+                    })
+                }))
+    }
+
+    /// Reduce the `Transaction` to the splits whose categories match
+    fn matching_splits(&self, tr: &Transaction) -> Option<Transaction> {
+        match self.category_indices(tr) {
+            // return the subset of the `Transaction` that matches the category query
+            Some(idx) => tr.subset(&idx),
+            None => Some(tr.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    #[track_caller]
+    fn check_totals(query: TransactionFilter, expected: Vec<f32>) {
+        let observed: Vec<f32> = query.iter().map(|tr| *tr.total()).collect();
+
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn par_splits_keep_order() {
+        let db = example_db();
+        let queries = [
+            db.transactions_query(),
+            db.transactions_query().category_matches(&Regex::new("Food").unwrap()),
+            db.transactions_query().amount_to(-50.0).exclude_void(),
+        ];
+
+        for query in queries {
+            let expected: Vec<Transaction> = query.splits().collect();
+
+            assert_eq!(expected, query.par_splits());
+        }
+    }
+
+    #[test]
+    fn no_predicates() {
+        let db = example_db();
+
+        assert_eq!(db.transactions().len(), db.transactions_query().iter().count());
+    }
+
+    #[test]
+    fn payee_key_and_name() {
+        let db = example_db();
+        let re = Regex::new("Grocer").unwrap();
+
+        check_totals(db.transactions_query().payee(3), vec![-150.25, -100.0, -60.0]);
+        check_totals(db.transactions_query().payee(3).payee_matches(&re), vec![-150.25, -100.0, -60.0]);
+        check_totals(db.transactions_query().payee(2).payee_matches(&re), vec![]);
+    }
+
+    #[test]
+    fn account_key() {
+        let db = example_db();
+
+        check_totals(db.transactions_query().account(2), vec![500.0]);
+    }
+
+    #[test]
+    fn any_of_accounts() {
+        let db = example_db();
+
+        // the transfer out of chequing is included for its destination
+        check_totals(db.transactions_query().accounts(&[2]), vec![-500.0, 500.0]);
+        check_totals(db.transactions_query().accounts(&[3]), vec![]);
+        assert_eq!(11, db.transactions_query().accounts(&[1, 2]).iter().count());
+    }
+
+    #[test]
+    fn transfers_only_or_excluded() {
+        let db = example_db();
+
+        check_totals(db.transactions_query().transfers(true), vec![-500.0, 500.0]);
+        assert_eq!(9, db.transactions_query().transfers(false).iter().count());
+    }
+
+    #[test]
+    fn without_memo() {
+        let db = example_db();
+
+        check_totals(db.transactions_query().has_memo(false).date_from(NaiveDate::from_ymd_opt(2022, 2, 1).unwrap()), vec![-60.0]);
+    }
+
+    #[test]
+    fn splits_without_category() {
+        let db = example_db();
+        let query = db.transactions_query().payee(3);
+        let observed: Vec<Transaction> = query.splits().collect();
+
+        assert_eq!(query.iter().cloned().collect::<Vec<Transaction>>(), observed);
+    }
+}
@@ -0,0 +1,291 @@
+//! Flags stored on a [`Transaction`][crate::transaction::transaction_struct::Transaction].
+
+use super::TransactionError;
+use std::str::FromStr;
+
+// The names of these bits match those in HomeBank's source code.
+
+/// Bit set by older versions of HomeBank once a transaction was checked against a statement.
+const OF_OLDVALID: u32 = 1 << 0;
+
+/// Bit set on transactions that add to an account.
+const OF_INCOME: u32 = 1 << 1;
+
+/// Bit set on transactions that were posted automatically from a scheduled transaction.
+const OF_AUTO: u32 = 1 << 2;
+
+/// Bit set on transactions added since the file was last saved.
+const OF_ADDED: u32 = 1 << 3;
+
+/// Bit set on transactions changed since the file was last saved.
+const OF_CHANGED: u32 = 1 << 4;
+
+/// Bit set by older versions of HomeBank on transactions to be reminded of.
+const OF_OLDREMIND: u32 = 1 << 5;
+
+/// Bit set on scheduled transactions that stop after a number of postings.
+const OF_LIMIT: u32 = 1 << 7;
+
+/// Bit set on transactions split across multiple categories.
+const OF_SPLIT: u32 = 1 << 8;
+
+/// Bit set on transactions added by importing a file.
+const OF_ISIMPORT: u32 = 1 << 9;
+
+/// Flags stored on a [`Transaction`][crate::transaction::transaction_struct::Transaction].
+///
+/// HomeBank stores these as a bit field in the `flags` attribute of each `<ope>` element.
+/// The raw value is kept as-is, so that bits without a named accessor are not lost.
+///
+/// HomeBank does not store a bit for transfers.
+/// Use [`Transaction::is_transfer`][crate::transaction::transaction_struct::Transaction::is_transfer] instead.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransactionFlags(u32);
+
+impl TransactionFlags {
+    /// Create a new set of flags from the raw bit field.
+    pub fn new(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    /// Retrieve the raw bit field.
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    /// Check if a bit is set.
+    fn has(&self, bit: u32) -> bool {
+        self.0 & bit != 0
+    }
+
+    /// Check if the transaction was checked against a statement (`OLDVALID`).
+    pub fn is_scanned(&self) -> bool {
+        self.has(OF_OLDVALID)
+    }
+
+    /// Check if the transaction adds to an account (`INCOME`).
+    pub fn is_income(&self) -> bool {
+        self.has(OF_INCOME)
+    }
+
+    /// Check if the transaction was posted automatically from a scheduled transaction (`AUTO`).
+    pub fn is_auto(&self) -> bool {
+        self.has(OF_AUTO)
+    }
+
+    /// Check if the transaction was posted automatically from a scheduled transaction, like [`is_auto`][Self::is_auto].
+    pub fn was_auto_posted(&self) -> bool {
+        self.is_auto()
+    }
+
+    /// Check if the transaction was added since the file was last saved (`ADDED`).
+    pub fn is_added(&self) -> bool {
+        self.has(OF_ADDED)
+    }
+
+    /// Check if the transaction was changed since the file was last saved (`CHANGED`).
+    pub fn is_changed(&self) -> bool {
+        self.has(OF_CHANGED)
+    }
+
+    /// Check if the transaction is marked to be reminded of (`OLDREMIND`).
+    pub fn is_remind(&self) -> bool {
+        self.has(OF_OLDREMIND)
+    }
+
+    /// Check if a scheduled transaction stops after a number of postings (`LIMIT`).
+    pub fn is_limited(&self) -> bool {
+        self.has(OF_LIMIT)
+    }
+
+    /// Check if the transaction is split across multiple categories (`SPLIT`).
+    pub fn is_split(&self) -> bool {
+        self.has(OF_SPLIT)
+    }
+
+    /// Check if the transaction was added by importing a file (`ISIMPORT`).
+    pub fn is_imported(&self) -> bool {
+        self.has(OF_ISIMPORT)
+    }
+
+    /// Return a copy of these flags with a named flag set.
+    pub fn with(&self, flag: &TransactionFlag) -> Self {
+        Self(self.0 | flag.bit())
+    }
+
+    /// Check if a named flag is set.
+    pub fn contains(&self, flag: &TransactionFlag) -> bool {
+        match flag {
+            TransactionFlag::Scanned => self.is_scanned(),
+            TransactionFlag::Income => self.is_income(),
+            TransactionFlag::Auto => self.is_auto(),
+            TransactionFlag::Added => self.is_added(),
+            TransactionFlag::Changed => self.is_changed(),
+            TransactionFlag::Remind => self.is_remind(),
+            TransactionFlag::Split => self.is_split(),
+            TransactionFlag::Imported => self.is_imported(),
+        }
+    }
+}
+
+impl From<u32> for TransactionFlags {
+    fn from(bits: u32) -> Self {
+        Self::new(bits)
+    }
+}
+
+impl FromStr for TransactionFlags {
+    type Err = TransactionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match u32::from_str(s) {
+            Ok(bits) => Ok(Self::new(bits)),
+            Err(_) => Err(TransactionError::InvalidFlags),
+        }
+    }
+}
+
+/// A single named flag that can be set in [`TransactionFlags`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TransactionFlag {
+    Scanned,
+    Income,
+    Auto,
+    Added,
+    Changed,
+    Remind,
+    Split,
+    Imported,
+}
+
+impl TransactionFlag {
+    /// Retrieve the bit of the flag in the raw bit field.
+    fn bit(&self) -> u32 {
+        match self {
+            TransactionFlag::Scanned => OF_OLDVALID,
+            TransactionFlag::Income => OF_INCOME,
+            TransactionFlag::Auto => OF_AUTO,
+            TransactionFlag::Added => OF_ADDED,
+            TransactionFlag::Changed => OF_CHANGED,
+            TransactionFlag::Remind => OF_OLDREMIND,
+            TransactionFlag::Split => OF_SPLIT,
+            TransactionFlag::Imported => OF_ISIMPORT,
+        }
+    }
+}
+
+impl FromStr for TransactionFlag {
+    type Err = TransactionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "scanned" | "Scanned" => Ok(TransactionFlag::Scanned),
+            "income" | "Income" => Ok(TransactionFlag::Income),
+            "auto" | "Auto" => Ok(TransactionFlag::Auto),
+            "added" | "Added" => Ok(TransactionFlag::Added),
+            "changed" | "Changed" => Ok(TransactionFlag::Changed),
+            "remind" | "Remind" => Ok(TransactionFlag::Remind),
+            "split" | "Split" => Ok(TransactionFlag::Split),
+            "imported" | "Imported" => Ok(TransactionFlag::Imported),
+            _ => Err(TransactionError::InvalidFlagName(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check_flags(bits: u32, scanned: bool, imported: bool, remind: bool) {
+        let flags = TransactionFlags::new(bits);
+
+        assert_eq!(scanned, flags.is_scanned());
+        assert_eq!(imported, flags.is_imported());
+        assert_eq!(remind, flags.is_remind());
+    }
+
+    #[test]
+    fn no_flags() {
+        check_flags(0, false, false, false);
+    }
+
+    #[test]
+    fn scanned_flag() {
+        check_flags(1, true, false, false);
+    }
+
+    #[test]
+    fn imported_flag() {
+        check_flags(512, false, true, false);
+    }
+
+    #[test]
+    fn remind_flag() {
+        check_flags(32, false, false, true);
+    }
+
+    #[test]
+    fn decode_income() {
+        // from an income transaction in a file saved by HomeBank
+        let flags = TransactionFlags::new(2);
+
+        assert!(flags.is_income());
+        assert!(!flags.is_split());
+        check_flags(2, false, false, false);
+    }
+
+    #[test]
+    fn decode_split() {
+        // from a split transaction in a file saved by HomeBank
+        let flags = TransactionFlags::new(256);
+
+        assert!(flags.is_split());
+        assert!(!flags.is_income());
+    }
+
+    #[test]
+    fn decode_added_and_changed() {
+        let flags = TransactionFlags::new(8 | 16 | 4);
+
+        assert!(flags.is_added());
+        assert!(flags.is_changed());
+        assert!(flags.is_auto());
+        assert!(flags.was_auto_posted());
+        assert!(!flags.is_income());
+    }
+
+    #[test]
+    fn unnamed_bits_are_kept() {
+        // bit 6 (`CHEQ2`) has no accessor, but must not be dropped
+        let flags = TransactionFlags::new(64 | 512);
+
+        check_flags(flags.bits(), false, true, false);
+        assert_eq!(576, flags.bits());
+    }
+
+    #[test]
+    fn parse_flag_name() {
+        assert_eq!(Ok(TransactionFlag::Imported), TransactionFlag::from_str("imported"));
+        assert_eq!(Ok(TransactionFlag::Remind), TransactionFlag::from_str("Remind"));
+        assert_eq!(Ok(TransactionFlag::Split), TransactionFlag::from_str("split"));
+    }
+
+    #[test]
+    fn parse_bad_flag_name() {
+        let expected = Err(TransactionError::InvalidFlagName("transfer".to_string()));
+
+        assert_eq!(expected, TransactionFlag::from_str("transfer"));
+    }
+
+    #[test]
+    fn with_flags() {
+        let flags = TransactionFlags::default()
+            .with(&TransactionFlag::Added)
+            .with(&TransactionFlag::Imported);
+
+        assert_eq!(8 | 512, flags.bits());
+        assert!(flags.contains(&TransactionFlag::Imported));
+    }
+}
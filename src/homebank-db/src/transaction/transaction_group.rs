@@ -0,0 +1,236 @@
+//! Collapse [`Transaction`s][crate::transaction::transaction_struct::Transaction] into totals for each account, category, payee, pay mode, or period.
+
+use super::TransactionError;
+use crate::{report::Interval, HomeBankDb, Transaction};
+use std::{collections::HashMap, str::FromStr};
+
+/// What to group [`Transaction`s][crate::transaction::transaction_struct::Transaction] by.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum GroupBy {
+    Account,
+    /// Each split of a split transaction is counted in its own category.
+    Category,
+    Payee,
+    PayMode,
+    /// ISO weeks, e.g. `2024-W07`.
+    Week,
+    Month,
+    Year,
+}
+
+impl GroupBy {
+    /// Retrieve the name of the group, for column headers
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Account => "Account",
+            Self::Category => "Category",
+            Self::Payee => "Payee",
+            Self::PayMode => "Pay mode",
+            Self::Week => "Week",
+            Self::Month => "Month",
+            Self::Year => "Year",
+        }
+    }
+
+    /// Name the group that `tr` (or its split in `category`) belongs to
+    fn key_of(&self, tr: &Transaction, category: Option<usize>, db: &HomeBankDb) -> String {
+        match self {
+            Self::Account => tr.account_name(db).unwrap_or_default(),
+            Self::Category => category
+                .and_then(|idx| db.category_full_name(idx))
+                .unwrap_or_default()
+                .to_string(),
+            Self::Payee => tr.payee_name(db).unwrap_or_default(),
+            Self::PayMode => format!("{:?}", tr.pay_mode()),
+            Self::Week => Interval::Week.label(tr.date()),
+            Self::Month => Interval::Month.label(tr.date()),
+            Self::Year => Interval::Year.label(tr.date()),
+        }
+    }
+}
+
+impl FromStr for GroupBy {
+    type Err = TransactionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "account" | "Account" => Ok(Self::Account),
+            "category" | "Category" => Ok(Self::Category),
+            "payee" | "Payee" => Ok(Self::Payee),
+            "pay-mode" | "paymode" | "PayMode" => Ok(Self::PayMode),
+            "week" | "Week" => Ok(Self::Week),
+            "month" | "Month" => Ok(Self::Month),
+            "year" | "Year" => Ok(Self::Year),
+            _ => Err(TransactionError::InvalidGroupBy(s.to_string())),
+        }
+    }
+}
+
+/// The totals of the [`Transaction`s][crate::transaction::transaction_struct::Transaction] sharing the same group keys.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TransactionGroup {
+    /// Name of the group for each [`GroupBy`], in the order they were given.
+    keys: Vec<String>,
+
+    /// Number of transactions (or splits) in the group.
+    count: usize,
+
+    /// Sum of the amounts.
+    sum: f32,
+
+    /// Lowest amount.
+    min: f32,
+
+    /// Highest amount.
+    max: f32,
+}
+
+impl TransactionGroup {
+    /// Create a group with a single amount
+    fn new(keys: Vec<String>, amount: f32) -> Self {
+        Self {
+            keys,
+            count: 1,
+            sum: amount,
+            min: amount,
+            max: amount,
+        }
+    }
+
+    /// Add an amount to the group
+    fn add(&mut self, amount: f32) {
+        self.count += 1;
+        self.sum += amount;
+        self.min = self.min.min(amount);
+        self.max = self.max.max(amount);
+    }
+
+    /// Retrieve the name of the group for each [`GroupBy`]
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// Retrieve the number of transactions (or splits) in the group
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Retrieve the sum of the amounts
+    pub fn sum(&self) -> f32 {
+        self.sum
+    }
+
+    /// Retrieve the average amount
+    pub fn average(&self) -> f32 {
+        self.sum / self.count as f32
+    }
+
+    /// Retrieve the lowest amount
+    pub fn min(&self) -> f32 {
+        self.min
+    }
+
+    /// Retrieve the highest amount
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+}
+
+/// The result of a transaction query, either as individual transactions or collapsed into groups.
+#[derive(Debug, PartialEq, Clone)]
+pub enum GroupedResult {
+    /// Every matching transaction, when there is nothing to group by.
+    Transactions(Vec<Transaction>),
+
+    /// Totals for each group, sorted by their keys.
+    Groups(Vec<TransactionGroup>),
+}
+
+/// Collapse `transactions` into the totals of each combination of the `group_by` keys, sorted by those keys.
+pub fn group_transactions(transactions: &[Transaction], group_by: &[GroupBy], db: &HomeBankDb) -> Vec<TransactionGroup> {
+    let by_category = group_by.contains(&GroupBy::Category);
+    let mut groups: HashMap<Vec<String>, TransactionGroup> = HashMap::new();
+
+    for tr in transactions {
+        // splits only need to be counted separately when they can land in different groups
+        let entries: Vec<(Option<usize>, f32)> = if by_category && tr.is_split() {
+            tr.categories().into_iter().zip(tr.amounts()).map(|(cat, amount)| (*cat, *amount)).collect()
+        } else {
+            vec![(tr.categories().first().and_then(|cat| **cat), *tr.total())]
+        };
+
+        for (category, amount) in entries {
+            let keys: Vec<String> = group_by.iter().map(|g| g.key_of(tr, category, db)).collect();
+
+            groups
+                .entry(keys.clone())
+                .and_modify(|group| group.add(amount))
+                .or_insert_with(|| TransactionGroup::new(keys, amount));
+        }
+    }
+
+    let mut groups: Vec<TransactionGroup> = groups.into_values().collect();
+    groups.sort_by(|a, b| a.keys.cmp(&b.keys));
+
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+    use crate::{Query, QueryTransactions};
+    use clap::Parser;
+
+    #[track_caller]
+    fn check_groups(args: &[&str], expected: Vec<(Vec<&str>, usize, f32)>) {
+        let db = example_db();
+        let query = QueryTransactions::try_parse_from(args).unwrap();
+        let groups = group_transactions(&query.exec(&db), query.group_by(), &db);
+
+        let observed: Vec<(Vec<&str>, usize, f32)> = groups
+            .iter()
+            .map(|g| (g.keys().iter().map(|k| k.as_str()).collect(), g.count(), g.sum()))
+            .collect();
+
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    fn group_by_month() {
+        let expected = vec![
+            (vec!["2021-12"], 1, -1200.0),
+            (vec!["2022-01"], 6, 549.75),
+            (vec!["2022-02"], 4, 694.5),
+        ];
+
+        check_groups(&["transactions", "--group-by", "month"], expected);
+    }
+
+    #[test]
+    fn group_by_month_and_category_counts_splits() {
+        let expected = vec![
+            (vec!["2022-01", "Food:Dining"], 1, -20.0),
+            (vec!["2022-01", "Food:Groceries"], 2, -230.25),
+        ];
+
+        check_groups(
+            &["transactions", "--category", "^Food", "--date-to", "2022-02-01", "--group-by", "month", "--group-by", "category"],
+            expected,
+        );
+    }
+
+    #[test]
+    fn min_max_average() {
+        let db = example_db();
+        let groups = group_transactions(db.transactions(), &[GroupBy::Payee], &db);
+        let landlord = groups.iter().find(|g| g.keys() == ["Landlord"]).unwrap();
+
+        assert_eq!((3, -1200.0, -1200.0, -1200.0), (landlord.count(), landlord.average(), landlord.min(), landlord.max()));
+    }
+
+    #[test]
+    fn parse_invalid_group_by() {
+        assert_eq!(Err(TransactionError::InvalidGroupBy("day".to_string())), GroupBy::from_str("day"));
+    }
+}
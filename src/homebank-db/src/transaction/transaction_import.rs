@@ -0,0 +1,182 @@
+//! Group [`Transaction`s][crate::transaction::transaction_struct::Transaction] into the import batches that added them.
+//!
+//! HomeBank does not record when, or in which import, a transaction was added.
+//! Instead, batches are found heuristically: each transaction is given a marker by an [`ImportMarker`], and a batch is a run of consecutive transactions in the HomeBank file that share the same marker.
+//! The most recent batch is the one containing the latest transaction date, preferring batches later in the file in case of ties.
+
+use super::TransactionError;
+use crate::Transaction;
+use std::str::FromStr;
+
+/// What marks a [`Transaction`][crate::transaction::transaction_struct::Transaction] as part of an import batch.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum ImportMarker {
+    /// Transactions with the `imported` flag set, in the same account.
+    #[default]
+    Flag,
+
+    /// Transactions with the same non-empty info field, such as a statement reference written by the bank.
+    Info,
+}
+
+impl ImportMarker {
+    /// Retrieve the marker for a transaction, if it has one.
+    pub fn marker(&self, tr: &Transaction) -> Option<String> {
+        match self {
+            ImportMarker::Flag => match tr.flags() {
+                Some(flags) if flags.is_imported() => Some(tr.account().to_string()),
+                _ => None,
+            },
+            ImportMarker::Info => match tr.info() {
+                Some(info) if !info.is_empty() => Some(info.clone()),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl FromStr for ImportMarker {
+    type Err = TransactionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flag" | "Flag" => Ok(ImportMarker::Flag),
+            "info" | "Info" => Ok(ImportMarker::Info),
+            _ => Err(TransactionError::InvalidImportMarker(s.to_string())),
+        }
+    }
+}
+
+/// Group transactions into import batches, returning the indices of the transactions in each batch.
+/// Transactions without a marker are not part of any batch.
+pub fn import_batches(transactions: &[Transaction], marker: &ImportMarker) -> Vec<Vec<usize>> {
+    let mut batches: Vec<Vec<usize>> = vec![];
+    let mut prev_marker: Option<String> = None;
+
+    for (i, tr) in transactions.iter().enumerate() {
+        let tr_marker = marker.marker(tr);
+
+        match (&tr_marker, &prev_marker) {
+            // continue the current batch
+            (Some(m), Some(prev)) if m == prev => {
+                if let Some(batch) = batches.last_mut() {
+                    batch.push(i);
+                }
+            }
+            // start a new batch
+            (Some(_), _) => batches.push(vec![i]),
+            // an unmarked transaction ends the current batch
+            (None, _) => {}
+        }
+
+        prev_marker = tr_marker;
+    }
+
+    batches
+}
+
+/// Find the most recent import batch, returning the indices of its transactions.
+pub fn last_import_batch(transactions: &[Transaction], marker: &ImportMarker) -> Vec<usize> {
+    import_batches(transactions, marker)
+        .into_iter()
+        // `max_by_key` keeps the last of equal elements, so later batches win ties
+        .max_by_key(|batch| batch.iter().map(|&i| *transactions[i].date()).max())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PayMode, TransactionFlags, TransactionStatus, TransactionType};
+    use chrono::NaiveDate;
+
+    fn transaction(day: u32, account: usize, flags: u32, info: Option<&str>) -> Transaction {
+        Transaction::new(
+            &NaiveDate::from_ymd_opt(2022, 3, day).unwrap(),
+            -10.0,
+            account,
+            &PayMode::default(),
+            &TransactionStatus::default(),
+            &Some(TransactionFlags::new(flags)),
+            &None,
+            &None,
+            &info.map(|s| s.to_string()),
+            &None,
+            &TransactionType::Expense,
+            &Default::default(),
+        )
+    }
+
+    fn example_transactions() -> Vec<Transaction> {
+        vec![
+            // entered by hand
+            transaction(1, 1, 0, None),
+            // first import into account 1
+            transaction(2, 1, 512, Some("stmt-0302")),
+            transaction(3, 1, 512, Some("stmt-0302")),
+            // entered by hand
+            transaction(4, 1, 0, None),
+            // import into account 2
+            transaction(5, 2, 512, Some("stmt-0310")),
+            transaction(6, 2, 512, Some("stmt-0310")),
+            // second import into account 1, right after the one into account 2
+            transaction(7, 1, 512, Some("stmt-0310")),
+        ]
+    }
+
+    #[track_caller]
+    fn check_import_batches(marker: ImportMarker, expected: Vec<Vec<usize>>) {
+        let observed = import_batches(&example_transactions(), &marker);
+
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    fn batches_by_flag() {
+        check_import_batches(ImportMarker::Flag, vec![vec![1, 2], vec![4, 5], vec![6]]);
+    }
+
+    #[test]
+    fn batches_by_info() {
+        check_import_batches(ImportMarker::Info, vec![vec![1, 2], vec![4, 5, 6]]);
+    }
+
+    #[test]
+    fn no_batches() {
+        let transactions = vec![transaction(1, 1, 0, None), transaction(2, 1, 2, None)];
+
+        assert!(import_batches(&transactions, &ImportMarker::Flag).is_empty());
+        assert!(last_import_batch(&transactions, &ImportMarker::Flag).is_empty());
+    }
+
+    #[test]
+    fn last_batch_by_flag() {
+        let observed = last_import_batch(&example_transactions(), &ImportMarker::Flag);
+
+        assert_eq!(vec![6], observed);
+    }
+
+    #[test]
+    fn last_batch_by_info() {
+        let observed = last_import_batch(&example_transactions(), &ImportMarker::Info);
+
+        assert_eq!(vec![4, 5, 6], observed);
+    }
+
+    #[test]
+    fn last_batch_ties_prefer_later() {
+        let transactions = vec![
+            transaction(9, 1, 512, None),
+            transaction(9, 2, 512, None),
+        ];
+
+        assert_eq!(vec![1], last_import_batch(&transactions, &ImportMarker::Flag));
+    }
+
+    #[test]
+    fn parse_bad_marker() {
+        let expected = Err(TransactionError::InvalidImportMarker("date".to_string()));
+
+        assert_eq!(expected, ImportMarker::from_str("date"));
+    }
+}
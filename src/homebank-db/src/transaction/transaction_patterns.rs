@@ -0,0 +1,339 @@
+//! Text patterns for filtering [`Transaction`s][crate::transaction::transaction_struct::Transaction] by name, memo, info, or tags.
+
+use clap::{error::ErrorKind, ArgMatches, Args, Command, FromArgMatches};
+use regex::{Regex, RegexBuilder};
+
+/// Text patterns for filtering [`Transaction`s][crate::transaction::transaction_struct::Transaction].
+///
+/// Patterns are case-sensitive regular expressions by default.
+/// With `--fixed-strings`, they are case-insensitive substrings instead, so that names like `Amazon.com (Prime)` can be matched as they are written.
+/// It is an option of the commands that take these patterns (`query transactions` and `sum`), not a global one,
+/// because the name patterns of the other queries are parsed as regular expressions by themselves.
+/// With `--ignore-case`, every pattern ignores case; a single pattern can ignore case by starting with `(?i)` instead.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionPatterns {
+    /// Pattern for the category names.
+    category: Option<Regex>,
+
+    /// Pattern for the payee names.
+    payee: Option<Regex>,
+
+    /// Pattern for the category names to leave out.
+    exclude_category: Option<Regex>,
+
+    /// Pattern for the payee names to leave out.
+    exclude_payee: Option<Regex>,
+
+    /// Pattern for the account names.
+    account: Option<Regex>,
+
+    /// Pattern for the memos.
+    memo: Option<Regex>,
+
+    /// Pattern for the info fields.
+    info: Option<Regex>,
+
+    /// Pattern for the tags.
+    tags: Option<Regex>,
+}
+
+impl TransactionPatterns {
+    /// Create a new set of patterns.
+    pub fn new(
+        category: &Option<Regex>,
+        payee: &Option<Regex>,
+        account: &Option<Regex>,
+        memo: &Option<Regex>,
+        info: &Option<Regex>,
+        tags: &Option<Regex>,
+    ) -> Self {
+        Self {
+            category: category.clone(),
+            payee: payee.clone(),
+            exclude_category: None,
+            exclude_payee: None,
+            account: account.clone(),
+            memo: memo.clone(),
+            info: info.clone(),
+            tags: tags.clone(),
+        }
+    }
+
+    /// Retrieve the category pattern
+    pub fn category(&self) -> &Option<Regex> {
+        &self.category
+    }
+
+    /// Retrieve the payee pattern
+    pub fn payee(&self) -> &Option<Regex> {
+        &self.payee
+    }
+
+    /// Retrieve the pattern for the categories to leave out
+    pub fn exclude_category(&self) -> &Option<Regex> {
+        &self.exclude_category
+    }
+
+    /// Retrieve the pattern for the payees to leave out
+    pub fn exclude_payee(&self) -> &Option<Regex> {
+        &self.exclude_payee
+    }
+
+    /// Retrieve the account pattern
+    pub fn account(&self) -> &Option<Regex> {
+        &self.account
+    }
+
+    /// Retrieve the memo pattern
+    pub fn memo(&self) -> &Option<Regex> {
+        &self.memo
+    }
+
+    /// Retrieve the info pattern
+    pub fn info(&self) -> &Option<Regex> {
+        &self.info
+    }
+
+    /// Retrieve the tags pattern
+    pub fn tags(&self) -> &Option<Regex> {
+        &self.tags
+    }
+}
+
+/// Build a regular expression from a pattern given on the command line.
+///
+/// When `fixed_strings` is `true`, the pattern is escaped and matched case-insensitively.
+pub fn pattern_to_regex(pattern: &str, fixed_strings: bool) -> Result<Regex, regex::Error> {
+    build_regex(pattern, fixed_strings, fixed_strings)
+}
+
+/// Build a regular expression from a pattern given on the command line, always matching case-insensitively.
+fn case_insensitive_regex(pattern: &str, fixed_strings: bool) -> Result<Regex, regex::Error> {
+    build_regex(pattern, fixed_strings, true)
+}
+
+/// Build a regular expression from a pattern, escaping it first if it is a fixed string.
+fn build_regex(pattern: &str, fixed_strings: bool, ignore_case: bool) -> Result<Regex, regex::Error> {
+    let pattern = if fixed_strings {
+        regex::escape(pattern)
+    } else {
+        pattern.to_string()
+    };
+
+    RegexBuilder::new(&pattern).case_insensitive(ignore_case).build()
+}
+
+/// Build an optional regular expression from an optional pattern.
+/// Fixed strings always ignore case, while regular expressions only do with `ignore_case`.
+fn optional_regex(pattern: &Option<String>, fixed_strings: bool, ignore_case: bool) -> Result<Option<Regex>, regex::Error> {
+    match pattern {
+        Some(p) => Ok(Some(build_regex(p, fixed_strings, fixed_strings || ignore_case)?)),
+        None => Ok(None),
+    }
+}
+
+// The patterns are collected from the command line as plain strings, and only
+// compiled once it is known whether `--fixed-strings` was given.
+#[derive(Debug, Args)]
+struct RawTransactionPatterns {
+    /// Include transactions with categories that match the regular expression.
+    #[arg(
+        short = 'c',
+        long = "category",
+        value_name = "regex"
+    )]
+    category: Option<String>,
+
+    /// Include transactions involving payees that match the regular expression.
+    #[arg(
+        short = 'p',
+        long = "payee",
+        value_name = "regex"
+    )]
+    payee: Option<String>,
+
+    /// Exclude transactions with categories that match the regular expression, even if they match `--category`. Only the matching splits of a split transaction are left out.
+    #[arg(
+        long = "exclude-category",
+        value_name = "regex"
+    )]
+    exclude_category: Option<String>,
+
+    /// Exclude transactions involving payees that match the regular expression, even if they match `--payee`.
+    #[arg(
+        long = "exclude-payee",
+        value_name = "regex"
+    )]
+    exclude_payee: Option<String>,
+
+    /// Include transactions involving accounts that match the regular expression.
+    #[arg(
+        short = 'a',
+        long = "account",
+        value_name = "regex"
+    )]
+    account: Option<String>,
+
+    /// Include transactions whose memos match this regular expression.
+    #[arg(
+        short = 'm',
+        long = "memo",
+        value_name = "regex"
+    )]
+    memo: Option<String>,
+
+    /// Include transactions whose info fields (e.g. cheque numbers or references) match this regular expression, ignoring case.
+    #[arg(
+        short = 'i',
+        long = "info",
+        value_name = "regex"
+    )]
+    info: Option<String>,
+
+    /// Include transactions whose tags match this regular expression.
+    #[arg(
+        short = 't',
+        long = "tag",
+        value_name = "regex"
+    )]
+    tags: Option<String>,
+
+    /// Match patterns as case-insensitive fixed strings, instead of regular expressions.
+    #[arg(
+        short = 'F',
+        long = "fixed-strings"
+    )]
+    fixed_strings: bool,
+
+    /// Match every pattern regardless of case. A single regular expression can also start with `(?i)` to ignore case.
+    #[arg(
+        short = 'I',
+        long = "ignore-case"
+    )]
+    ignore_case: bool,
+}
+
+impl TryFrom<RawTransactionPatterns> for TransactionPatterns {
+    type Error = regex::Error;
+
+    fn try_from(raw: RawTransactionPatterns) -> Result<Self, Self::Error> {
+        let (fixed, ignore_case) = (raw.fixed_strings, raw.ignore_case);
+
+        Ok(Self {
+            category: optional_regex(&raw.category, fixed, ignore_case)?,
+            payee: optional_regex(&raw.payee, fixed, ignore_case)?,
+            exclude_category: optional_regex(&raw.exclude_category, fixed, ignore_case)?,
+            exclude_payee: optional_regex(&raw.exclude_payee, fixed, ignore_case)?,
+            account: optional_regex(&raw.account, fixed, ignore_case)?,
+            memo: optional_regex(&raw.memo, fixed, ignore_case)?,
+            // info fields hold references like `inv-2022-02` or `INV-2022-02`, so case never matters
+            info: match &raw.info {
+                Some(p) => Some(case_insensitive_regex(p, fixed)?),
+                None => None,
+            },
+            tags: optional_regex(&raw.tags, fixed, ignore_case)?,
+        })
+    }
+}
+
+impl FromArgMatches for TransactionPatterns {
+    fn from_arg_matches(matches: &ArgMatches) -> Result<Self, clap::Error> {
+        let raw = RawTransactionPatterns::from_arg_matches(matches)?;
+
+        match TransactionPatterns::try_from(raw) {
+            Ok(patterns) => Ok(patterns),
+            Err(e) => Err(clap::Error::raw(ErrorKind::ValueValidation, e)),
+        }
+    }
+
+    fn update_from_arg_matches(&mut self, matches: &ArgMatches) -> Result<(), clap::Error> {
+        *self = Self::from_arg_matches(matches)?;
+
+        Ok(())
+    }
+}
+
+impl Args for TransactionPatterns {
+    fn augment_args(cmd: Command) -> Command {
+        RawTransactionPatterns::augment_args(cmd)
+    }
+
+    fn augment_args_for_update(cmd: Command) -> Command {
+        RawTransactionPatterns::augment_args_for_update(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check_pattern_match(pattern: &str, fixed_strings: bool, haystack: &str, expected: bool) {
+        let re = pattern_to_regex(pattern, fixed_strings).unwrap();
+
+        assert_eq!(expected, re.is_match(haystack));
+    }
+
+    #[test]
+    fn regex_pattern() {
+        check_pattern_match("^Amazon", false, "Amazon.com", true);
+    }
+
+    #[test]
+    fn regex_pattern_is_case_sensitive() {
+        check_pattern_match("amazon", false, "Amazon.com", false);
+    }
+
+    #[test]
+    fn fixed_string_metacharacters() {
+        let pattern = "Amazon.com (Prime";
+
+        assert!(pattern_to_regex(pattern, false).is_err());
+        check_pattern_match(pattern, true, "Amazon.com (Prime) Video", true);
+    }
+
+    #[test]
+    fn fixed_string_is_case_insensitive() {
+        check_pattern_match("amazon.COM", true, "Amazon.com (Prime)", true);
+    }
+
+    #[test]
+    fn info_pattern_is_case_insensitive() {
+        let re = case_insensitive_regex("^inv-", false).unwrap();
+
+        assert!(re.is_match("INV-2022-02"));
+    }
+
+    #[test]
+    fn ignore_case_applies_to_every_pattern() {
+        let raw = RawTransactionPatterns {
+            category: Some("^food".to_string()),
+            payee: Some("grocer".to_string()),
+            exclude_category: Some("^salary$".to_string()),
+            exclude_payee: None,
+            account: None,
+            memo: Some("deli".to_string()),
+            info: None,
+            tags: Some("RESTAURANT".to_string()),
+            fixed_strings: false,
+            ignore_case: true,
+        };
+        let patterns = TransactionPatterns::try_from(raw).unwrap();
+
+        assert!(patterns.category().as_ref().unwrap().is_match("Food:Dining"));
+        assert!(patterns.payee().as_ref().unwrap().is_match("Grocer"));
+        assert!(patterns.exclude_category().as_ref().unwrap().is_match("Salary"));
+        assert!(patterns.memo().as_ref().unwrap().is_match("Deli lunch"));
+        assert!(patterns.tags().as_ref().unwrap().is_match("food restaurant"));
+    }
+
+    #[test]
+    fn inline_flag_ignores_case() {
+        check_pattern_match("(?i)amazon", false, "Amazon.com", true);
+    }
+
+    #[test]
+    fn fixed_string_dot_is_literal() {
+        check_pattern_match("Amazon.com", true, "Amazon-com", false);
+    }
+}
@@ -1,40 +1,44 @@
 //! Options for filtering [`Transaction`s][crate::transaction::transaction_struct::Transaction] from the [`HomeBankDb`].
 
-use super::{TransactionStatus, TransactionType};
-use crate::{HomeBankDb, PayMode, Query, Transaction};
+use super::{
+    group_transactions, parse_date_expr, GroupBy, GroupedResult, ImportMarker, TransactionFilter, TransactionFlag, TransactionPatterns, TransactionStatus,
+    TransactionType,
+};
+use crate::{HomeBankDb, PayMode, Query, Transaction, WatchOpts};
 use chrono::NaiveDate;
 use clap::Parser;
 use regex::Regex;
-use std::str::FromStr;
 
 /// Options for filtering [`Transaction`s][crate::transaction::transaction_struct::Transaction] from the [`HomeBankDb`].
 #[derive(Debug, Parser)]
-#[clap(
+#[command(
     name = "transactions",
     visible_alias = "t",
     about = "Query transactions"
 )]
 pub struct QueryTransactions {
-    /// Include transactions starting from (and including) this date.
-    #[clap(
+    /// Include transactions starting from (and including) this date, or the start of a relative date like '-30d', 'ytd', or 'last-month'.
+    #[arg(
         short = 'd',
         long = "date-from",
-        parse(try_from_str = NaiveDate::from_str),
-        value_name = "date"
+        value_parser = parse_date_expr,
+        value_name = "date",
+        allow_hyphen_values = true
     )]
     date_from: Option<NaiveDate>,
 
-    /// Include transactions up to (and excluding) this date.
-    #[clap(
+    /// Include transactions up to (and excluding) this date, or the start of a relative date like 'today' or 'this-month'.
+    #[arg(
         short = 'D',
         long = "date-to",
-        parse(try_from_str = NaiveDate::from_str),
-        value_name = "date"
+        value_parser = parse_date_expr,
+        value_name = "date",
+        allow_hyphen_values = true
     )]
     date_to: Option<NaiveDate>,
 
     /// Include transactions greater than (and including) this amount.
-    #[clap(
+    #[arg(
         short = 'l',
         long = "amount-lower",
         value_name = "amount"
@@ -42,7 +46,7 @@ pub struct QueryTransactions {
     amount_from: Option<f32>,
 
     /// Include transactions less than (and excluding) this amount.
-    #[clap(
+    #[arg(
         short = 'u',
         long = "amount-upper",
         value_name = "amount"
@@ -50,76 +54,101 @@ pub struct QueryTransactions {
     amount_to: Option<f32>,
 
     /// Include transactions with a certain status.
-    #[clap(
+    #[arg(
         short = 's',
         long = "status",
-        value_name = "status"
+        value_name = "status",
+        value_enum
     )]
     status: Option<Vec<TransactionStatus>>,
 
-    /// Include transactions with categories that match the regular expression.
-    #[clap(
-        short = 'c',
-        long = "category",
-        value_name = "regex"
+    /// Include transactions with all of these flags set.
+    #[arg(
+        long = "flag",
+        value_name = "flag"
     )]
-    category: Option<Regex>,
+    flags: Option<Vec<TransactionFlag>>,
 
-    /// Include transactions involving payees that match the regular expression.
-    #[clap(
-        short = 'p',
-        long = "payee",
-        value_name = "regex"
+    /// Include transactions in the account with this key, or transfers into it. Can be given more than once, and combined with '--account'.
+    #[arg(
+        long = "account-id",
+        value_name = "key"
     )]
-    payee: Option<Regex>,
-
-    /// Include transactions involving accounts that match the regular expression.
-    #[clap(
-        short = 'a',
-        long = "account",
-        value_name = "regex"
-    )]
-    account: Option<Regex>,
+    account_ids: Vec<usize>,
 
     /// Include transactions with a certain payment method.
-    #[clap(
+    #[arg(
         short = 'M',
         long = "method",
-        value_name = "method"
+        value_name = "method",
+        value_enum
     )]
     pay_mode: Option<Vec<PayMode>>,
 
-    /// Include transactions whose memos match this regular expression.
-    #[clap(
-        short = 'm',
-        long = "memo",
-        value_name = "regex"
-    )]
-    memo: Option<Regex>,
+    /// Include transactions whose names, memos, info fields, or tags match these patterns.
+    #[command(flatten)]
+    patterns: TransactionPatterns,
 
-    /// Include transactions whose info fields match this regular expression.
-    #[clap(
-        short = 'i',
-        long = "info",
-        value_name = "regex"
-    )]
-    info: Option<Regex>,
+    /// Let `--category` also include the subcategories of any matching category.
+    #[arg(long = "include-subcategories")]
+    include_subcategories: bool,
 
-    /// Include transactions whose tags match this regular expression.
-    #[clap(
-        short = 't',
-        long = "tag",
-        value_name = "regex"
-    )]
-    tags: Option<Regex>,
+    /// Include only transactions with a non-empty memo.
+    #[arg(long = "has-memo", conflicts_with = "no_memo")]
+    has_memo: bool,
+
+    /// Include only transactions without a memo, or with an empty one.
+    #[arg(long = "no-memo")]
+    no_memo: bool,
+
+    /// Exclude voided transactions.
+    /// Unlike `--status`, this can be combined with any other filter.
+    #[arg(long = "exclude-void")]
+    exclude_void: bool,
 
     /// Include `Expense`, `Income`, or `Transfer` transactions.
-    #[clap(
+    /// Can be given multiple times to include more than one type.
+    #[arg(
         short = 'T',
         long = "type",
         value_name = "type"
     )]
     transaction_type: Option<Vec<TransactionType>>,
+
+    /// Exclude transfers between accounts.
+    #[arg(long = "exclude-transfers", conflicts_with = "transfers_only")]
+    exclude_transfers: bool,
+
+    /// Include only transfers between accounts, with both halves of each transfer.
+    #[arg(long = "transfers-only")]
+    transfers_only: bool,
+
+    /// Include only transactions from the most recent import.
+    /// Imports are not recorded by HomeBank, so a batch is a run of consecutive transactions in the file sharing an import marker.
+    #[arg(long = "last-import")]
+    last_import: bool,
+
+    /// What marks transactions as part of the same import: the `flag` set on imported transactions in the same account, or a shared `info` field.
+    #[arg(
+        long = "import-marker",
+        value_name = "marker",
+        default_value = "flag",
+        requires = "last_import"
+    )]
+    import_marker: ImportMarker,
+
+    /// Collapse the transactions into totals for each 'account', 'category', 'payee', 'pay-mode', 'week', 'month', or 'year'. Can be given more than once.
+    #[arg(long = "group-by", value_name = "group")]
+    group_by: Vec<GroupBy>,
+
+    /// Show amounts in the base currency, using the exchange rates in the HomeBank file.
+    /// Amount filters apply to the converted amounts.
+    #[arg(long = "convert-to-base")]
+    convert_to_base: bool,
+
+    /// Re-run the query whenever the HomeBank file changes.
+    #[command(flatten)]
+    watch: WatchOpts,
 }
 
 impl QueryTransactions {
@@ -145,14 +174,22 @@ impl QueryTransactions {
             amount_from: *amount_from,
             amount_to: *amount_to,
             status: status.clone(),
-            category: category.clone(),
-            payee: payee.clone(),
-            account: account.clone(),
+            flags: None,
+            account_ids: vec![],
             pay_mode: pay_mode.clone(),
-            memo: memo.clone(),
-            info: info.clone(),
-            tags: tags.clone(),
+            patterns: TransactionPatterns::new(category, payee, account, memo, info, tags),
+            include_subcategories: false,
+            has_memo: false,
+            no_memo: false,
+            exclude_void: false,
             transaction_type: transaction_type.clone(),
+            exclude_transfers: false,
+            transfers_only: false,
+            last_import: false,
+            import_marker: ImportMarker::default(),
+            group_by: vec![],
+            convert_to_base: false,
+            watch: WatchOpts::default(),
         }
     }
 
@@ -181,19 +218,51 @@ impl QueryTransactions {
         &self.status
     }
 
+    /// Select the flag(s) that must be set for including in the query
+    pub fn flags(&self) -> &Option<Vec<TransactionFlag>> {
+        &self.flags
+    }
+
     /// Select the category regex for including in the query
     pub fn category(&self) -> &Option<Regex> {
-        &self.category
+        self.patterns.category()
     }
 
     /// Select the payee regex for including in the query
     pub fn payee(&self) -> &Option<Regex> {
-        &self.payee
+        self.patterns.payee()
+    }
+
+    /// Select the category regex for excluding from the query, which takes precedence over the category regex
+    pub fn exclude_category(&self) -> &Option<Regex> {
+        self.patterns.exclude_category()
+    }
+
+    /// Select the payee regex for excluding from the query, which takes precedence over the payee regex
+    pub fn exclude_payee(&self) -> &Option<Regex> {
+        self.patterns.exclude_payee()
     }
 
     /// Select the account regex for including in the query
     pub fn account(&self) -> &Option<Regex> {
-        &self.account
+        self.patterns.account()
+    }
+
+    /// Select the keys of the accounts for including in the query, alongside the account regex
+    pub fn account_ids(&self) -> &[usize] {
+        &self.account_ids
+    }
+
+    /// Find the keys of the accounts selected by name or by key, including closed accounts.
+    /// Transactions in any of these accounts, or transfers into them, are included in the query.
+    pub fn selected_accounts(&self, db: &HomeBankDb) -> Vec<usize> {
+        db.accounts()
+            .iter()
+            .filter(|(key, acct)| {
+                self.account_ids.contains(key) || self.account().as_ref().is_some_and(|re| re.is_match(acct.name()))
+            })
+            .map(|(key, _)| *key)
+            .collect()
     }
 
     /// Select the payment method(s) for including in the query
@@ -203,164 +272,163 @@ impl QueryTransactions {
 
     /// Select the memo regex for including in the query
     pub fn memo(&self) -> &Option<Regex> {
-        &self.memo
+        self.patterns.memo()
     }
 
     /// Select the info regex for including in the query
     pub fn info(&self) -> &Option<Regex> {
-        &self.info
+        self.patterns.info()
     }
 
     /// Select the tags regex for including in the query
     pub fn tags(&self) -> &Option<Regex> {
-        &self.tags
+        self.patterns.tags()
     }
 
-    /// Select the transaction type for including in the query
-    pub fn ttype(&self) -> &Option<Vec<TransactionType>> {
-        &self.transaction_type
+    /// Select whether only transactions with a memo are included in the query
+    pub fn has_memo(&self) -> bool {
+        self.has_memo
     }
 
-    /// Filter out dates occurring before the query date
-    pub fn filter_date_from(&self, tr: &Transaction) -> bool {
-        match self.date_from() {
-            Some(d) => tr.date() >= d,
-            None => true,
-        }
+    /// Select whether only transactions without a memo are included in the query
+    pub fn no_memo(&self) -> bool {
+        self.no_memo
     }
 
-    /// Filter out dates occurring after the query date
-    pub fn filter_date_to(&self, tr: &Transaction) -> bool {
-        match self.date_to() {
-            Some(d) => tr.date() < d,
-            None => true,
-        }
+    /// Select whether voided transactions are excluded from the query
+    pub fn exclude_void(&self) -> bool {
+        self.exclude_void
     }
 
-    /// Filter out amounts below the query amount lower bound
-    pub fn filter_amount_from(&self, tr: &Transaction) -> bool {
-        match self.amount_from() {
-            Some(a) => tr.total() >= a,
-            None => true,
-        }
+    /// Select the transaction type for including in the query
+    pub fn ttype(&self) -> &Option<Vec<TransactionType>> {
+        &self.transaction_type
     }
 
-    /// Filter out amounts above the query amount upper
-    pub fn filter_amount_to(&self, tr: &Transaction) -> bool {
-        match self.amount_to() {
-            Some(a) => tr.total() < a,
-            None => true,
-        }
+    /// Select whether only transactions from the most recent import are included in the query
+    pub fn last_import(&self) -> bool {
+        self.last_import
     }
 
-    /// Filter out by status
-    pub fn filter_status(&self, tr: &Transaction) -> bool {
-        match self.status() {
-            Some(v) => v.contains(tr.status()),
-            None => true,
-        }
+    /// Select what marks transactions as part of the same import
+    pub fn import_marker(&self) -> &ImportMarker {
+        &self.import_marker
     }
 
-    /// Filter by payee names
-    pub fn filter_payee(&self, tr: &Transaction, db: &HomeBankDb) -> bool {
-        match (self.payee(), tr.payee_name(db)) {
-            // if there is a regex and there is a category name
-            (Some(re), Some(t_payee_name)) => re.is_match(&t_payee_name),
-            // if there is a regex but no category
-            (Some(_), None) => false,
-            // if there is no regex
-            (None, _) => true,
-        }
+    /// Select whether `--category` also includes subcategories
+    pub fn include_subcategories(&self) -> bool {
+        self.include_subcategories
     }
 
-    /// Filer by account name
-    pub fn filter_account(&self, tr: &Transaction, db: &HomeBankDb) -> bool {
-        match (self.payee(), tr.account_name(db)) {
-            // if there is a regex and there is a category name
-            (Some(re), Some(tr_account_name)) => re.is_match(&tr_account_name),
-            // if there is a regex but no category
-            (Some(_), None) => false,
-            // if there is no regex
-            (None, _) => true,
-        }
+    /// Select whether transfers are excluded
+    pub fn exclude_transfers(&self) -> bool {
+        self.exclude_transfers
     }
 
-    /// Filter by payment method
-    pub fn filter_paymode(&self, tr: &Transaction) -> bool {
-        match self.pay_mode() {
-            Some(v) => v.contains(tr.pay_mode()),
-            None => true,
-        }
+    /// Select whether only transfers are included
+    pub fn transfers_only(&self) -> bool {
+        self.transfers_only
     }
 
-    /// Filter by `TransactionType`
-    pub fn filter_ttype(&self, tr: &Transaction) -> bool {
-        match self.ttype() {
-            Some(v) => v
-                .iter()
-                // check transaction types without explicitly checking the values
-                .any(|queried_type| queried_type.is_similar_to(tr.ttype())),
-            None => true,
-        }
+    /// Select whether amounts are converted into the base currency
+    pub fn convert_to_base(&self) -> bool {
+        self.convert_to_base
     }
 
-    /// Filter by tags
-    pub fn filter_tags(&self, tr: &Transaction) -> bool {
-        match (self.tags(), tr.tags()) {
-            (Some(re), Some(tags)) => {
-                // combine all the tags back into a single string to perform a single regex match
-                // this avoids performing the costly match multiple times
-                let combined_tr_tags = tags.join(",");
-                re.is_match(&combined_tr_tags)
-            }
-            (Some(_), None) => false,
-            (None, _) => true,
-        }
+    /// Retrieve what the transactions are grouped by, if anything
+    pub fn group_by(&self) -> &[GroupBy] {
+        &self.group_by
     }
 
-    /// Filter by memo
-    pub fn filter_memo(&self, tr: &Transaction) -> bool {
-        match (self.memo(), tr.memo()) {
-            (Some(re), Some(memo)) => re.is_match(memo),
-            (Some(_), None) => false,
-            (None, _) => true,
-        }
+    /// Retrieve whether and how the HomeBank file is watched to re-run the query
+    pub fn watch(&self) -> &WatchOpts {
+        &self.watch
     }
 
-    /// Filter by info
-    pub fn filter_info(&self, tr: &Transaction) -> bool {
-        match (self.info(), tr.info()) {
-            (Some(re), Some(info)) => re.is_match(info),
-            (Some(_), None) => false,
-            (None, _) => true,
+    /// Execute the query, collapsing the transactions into totals if there is anything to group them by
+    pub fn grouped(&self, db: &HomeBankDb) -> GroupedResult {
+        let transactions = self.exec(db);
+
+        if self.group_by.is_empty() {
+            GroupedResult::Transactions(transactions)
+        } else {
+            GroupedResult::Groups(group_transactions(&transactions, &self.group_by, db))
         }
     }
 
-    /// Filter map the `Transaction` by the `Category`
-    pub fn filter_category(&self, tr: &Transaction, db: &HomeBankDb) -> Option<Transaction> {
-        match self.category() {
-            Some(re) => {
-                let matching_idx: Vec<usize> = tr
-                    .category_names(db)
-                    .iter()
-                    .enumerate()
-                    .filter_map(|(i, cat)| match cat {
-                        Some(u) => {
-                            if re.is_match(u) {
-                                Some(i)
-                            } else {
-                                None
-                            }
-                        }
-                        None => None,
-                    })
-                    .collect();
-
-                // return the subset of the `Transaction` that matches the category query
-                tr.subset(&matching_idx)
-            }
-            None => Some(tr.clone()),
+    /// Build the [`TransactionFilter`] with the same predicates as this query
+    pub fn filter<'a>(&self, db: &'a HomeBankDb) -> TransactionFilter<'a> {
+        let mut filter = db.transactions_query();
+
+        if let Some(date) = self.date_from() {
+            filter = filter.date_from(*date);
+        }
+        if let Some(date) = self.date_to() {
+            filter = filter.date_to(*date);
+        }
+        if let Some(amount) = self.amount_from() {
+            filter = filter.amount_from(*amount);
+        }
+        if let Some(amount) = self.amount_to() {
+            filter = filter.amount_to(*amount);
+        }
+        if let Some(status) = self.status() {
+            filter = filter.status(status);
+        }
+        if let Some(flags) = self.flags() {
+            filter = filter.flags(flags);
         }
+        if let Some(re) = self.category() {
+            filter = filter.category_matches(re);
+        }
+        if self.include_subcategories() {
+            filter = filter.include_subcategories();
+        }
+        if let Some(re) = self.payee() {
+            filter = filter.payee_matches(re);
+        }
+        if let Some(re) = self.exclude_category() {
+            filter = filter.exclude_category_matches(re);
+        }
+        if let Some(re) = self.exclude_payee() {
+            filter = filter.exclude_payee_matches(re);
+        }
+        if self.account().is_some() || !self.account_ids.is_empty() {
+            // account names are only matched once, rather than for every transaction
+            filter = filter.accounts(&self.selected_accounts(db));
+        }
+        if let Some(pay_mode) = self.pay_mode() {
+            filter = filter.pay_mode(pay_mode);
+        }
+        if let Some(re) = self.memo() {
+            filter = filter.memo_matches(re);
+        }
+        if let Some(re) = self.info() {
+            filter = filter.info_matches(re);
+        }
+        if let Some(re) = self.tags() {
+            filter = filter.tags_match(re);
+        }
+        if self.has_memo() || self.no_memo() {
+            filter = filter.has_memo(self.has_memo());
+        }
+        if self.exclude_void() {
+            filter = filter.exclude_void();
+        }
+        if let Some(ttype) = self.ttype() {
+            filter = filter.transaction_type(ttype);
+        }
+        if self.exclude_transfers() || self.transfers_only() {
+            filter = filter.transfers(self.transfers_only());
+        }
+        if self.last_import() {
+            filter = filter.last_import(*self.import_marker());
+        }
+        if self.convert_to_base() {
+            filter = filter.convert_to_base();
+        }
+
+        filter
     }
 }
 
@@ -368,24 +436,411 @@ impl Query for QueryTransactions {
     type T = Transaction;
 
     fn exec(&self, db: &HomeBankDb) -> Vec<Self::T> {
-        let filt_transactions: Vec<Transaction> = db
-            .transactions()
-            .iter()
-            .filter(|&tr| self.filter_date_from(tr))
-            .filter(|&tr| self.filter_date_to(tr))
-            .filter(|&tr| self.filter_amount_from(tr))
-            .filter(|&tr| self.filter_amount_to(tr))
-            .filter(|&tr| self.filter_status(tr))
-            .filter(|&tr| self.filter_payee(tr, db))
-            .filter(|&tr| self.filter_account(tr, db))
-            .filter(|&tr| self.filter_paymode(tr))
-            .filter(|&tr| self.filter_ttype(tr))
-            .filter(|&tr| self.filter_tags(tr))
-            .filter(|&tr| self.filter_memo(tr))
-            .filter(|&tr| self.filter_info(tr))
-            .filter_map(|tr| self.filter_category(tr, db))
-            .collect();
-
-        filt_transactions
+        #[cfg(feature = "parallel")]
+        if db.transactions().len() >= super::transaction_filter::PARALLEL_THRESHOLD {
+            return self.filter(db).par_splits();
+        }
+
+        self.filter(db).splits().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+    use crate::TransactionFlags;
+
+    #[track_caller]
+    fn check_payee_match(args: &[&str], payee: &str, expected: bool) {
+        let query = QueryTransactions::try_parse_from(args).unwrap();
+        let observed = query.payee().as_ref().unwrap().is_match(payee);
+
+        assert_eq!(expected, observed);
+    }
+
+    fn transaction_with_memo(memo: Option<&str>) -> Transaction {
+        Transaction::new(
+            &NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(),
+            -10.0,
+            1,
+            &PayMode::default(),
+            &TransactionStatus::default(),
+            &None,
+            &None,
+            &memo.map(|m| m.to_string()),
+            &None,
+            &None,
+            &TransactionType::Expense,
+            &Default::default(),
+        )
+    }
+
+    #[track_caller]
+    fn check_memo_presence(args: &[&str], memo: Option<&str>, expected: bool) {
+        let db = example_db();
+        let query = QueryTransactions::try_parse_from(args).unwrap();
+        let observed = query.filter(&db).matches(&transaction_with_memo(memo));
+
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    fn has_memo_present() {
+        check_memo_presence(&["transactions", "--has-memo"], Some("Lunch"), true);
+    }
+
+    #[test]
+    fn has_memo_empty() {
+        check_memo_presence(&["transactions", "--has-memo"], Some(""), false);
+    }
+
+    #[test]
+    fn has_memo_none() {
+        check_memo_presence(&["transactions", "--has-memo"], None, false);
+    }
+
+    #[test]
+    fn no_memo_present() {
+        check_memo_presence(&["transactions", "--no-memo"], Some("Lunch"), false);
+    }
+
+    #[test]
+    fn no_memo_empty() {
+        check_memo_presence(&["transactions", "--no-memo"], Some(""), true);
+    }
+
+    #[test]
+    fn no_memo_none() {
+        check_memo_presence(&["transactions", "--no-memo"], None, true);
+    }
+
+    #[test]
+    fn memo_presence_unset() {
+        check_memo_presence(&["transactions"], None, true);
+        check_memo_presence(&["transactions"], Some("Lunch"), true);
+    }
+
+    #[track_caller]
+    fn check_matches(args: &[&str], expected: Vec<bool>) {
+        let db = example_db();
+        let query = QueryTransactions::try_parse_from(args).unwrap();
+        let observed: Vec<bool> = db.transactions().iter().map(|tr| tr.matches(&query, &db)).collect();
+
+        assert_eq!(expected, observed);
+        // `exec` includes exactly the transactions that match
+        assert_eq!(observed.iter().filter(|&&m| m).count(), query.exec(&db).len());
+    }
+
+    #[test]
+    fn matches_several_filters() {
+        let args = [
+            "transactions",
+            "--payee",
+            "Grocer",
+            "--category",
+            "Groceries",
+            "--status",
+            "cleared",
+            "--date-from",
+            "2022-01-01",
+            "--tag",
+            "food",
+        ];
+        let mut expected = vec![false; 11];
+        expected[3] = true;
+
+        check_matches(&args, expected);
+    }
+
+    #[test]
+    fn matches_split_by_category() {
+        let args = ["transactions", "--category", "Dining", "--date-to", "2022-02-01"];
+        let mut expected = vec![false; 11];
+        expected[4] = true;
+
+        check_matches(&args, expected);
+    }
+
+    #[test]
+    fn matches_info_ignoring_case() {
+        let mut expected = vec![false; 11];
+        expected[10] = true;
+
+        check_matches(&["transactions", "--info", "inv-2022"], expected);
+    }
+
+    #[test]
+    fn matches_transfers_only() {
+        let mut expected = vec![false; 11];
+        expected[5] = true;
+        expected[6] = true;
+
+        check_matches(&["transactions", "--transfers-only"], expected.clone());
+        check_matches(&["transactions", "--exclude-transfers"], expected.iter().map(|m| !m).collect());
+    }
+
+    #[test]
+    fn matches_accounts_by_name_or_key() {
+        let mut expected = vec![false; 11];
+        expected[5] = true;
+        expected[6] = true;
+
+        // the transfer from chequing is included for its destination
+        check_matches(&["transactions", "--account", "^Savings$"], expected.clone());
+        check_matches(&["transactions", "--account-id", "2"], expected);
+        check_matches(&["transactions", "--account", "Chequing|Savings"], vec![true; 11]);
+        check_matches(&["transactions", "--account-id", "3", "--account-id", "2", "--account", "Chequing"], vec![true; 11]);
+        check_matches(&["transactions", "--account-id", "3"], vec![false; 11]);
+    }
+
+    #[test]
+    fn closed_accounts_are_selected() {
+        let xml = r#"<homebank v="1.4" d="050504">
+<account key="1" flags="2" pos="1" type="1" curr="1" name="Old chequing" initial="0"/>
+<ope date="738134" amount="-20" account="1" paymode="1"/>
+</homebank>"#;
+        let db = HomeBankDb::from_reader(xml.as_bytes()).unwrap();
+        let query = QueryTransactions::try_parse_from(["transactions", "--account", "^Old"]).unwrap();
+
+        assert_eq!(vec![1], query.selected_accounts(&db));
+        assert_eq!(1, query.exec(&db).len());
+    }
+
+    #[test]
+    fn exclude_transfers_conflicts_with_transfers_only() {
+        let observed = QueryTransactions::try_parse_from(["transactions", "--exclude-transfers", "--transfers-only"]);
+
+        assert!(observed.is_err());
+    }
+
+    #[test]
+    fn matches_subcategories() {
+        let mut expected = vec![false; 11];
+        expected[0] = true;
+        expected[2] = true;
+        expected[8] = true;
+
+        check_matches(&["transactions", "--category", "^Housing$"], vec![false; 11]);
+        check_matches(&["transactions", "--category", "^Housing$", "--include-subcategories"], expected);
+    }
+
+    #[test]
+    fn exclude_category_without_include() {
+        let mut expected = vec![true; 11];
+        // rent
+        expected[0] = false;
+        expected[2] = false;
+        expected[8] = false;
+
+        check_matches(&["transactions", "--exclude-category", "^Housing"], expected);
+    }
+
+    #[test]
+    fn exclude_category_takes_precedence() {
+        let db = example_db();
+        let args = ["transactions", "--category", "^Food", "--exclude-category", "Dining"];
+        let mut expected = vec![false; 11];
+        // the split grocery trip still has its groceries split
+        expected[3] = true;
+        expected[4] = true;
+
+        check_matches(&args, expected);
+
+        let totals: Vec<f32> = QueryTransactions::try_parse_from(args).unwrap().exec(&db).iter().map(|tr| *tr.total()).collect();
+        assert_eq!(vec![-150.25, -80.0], totals);
+    }
+
+    #[test]
+    fn exclude_payee() {
+        let mut expected = vec![true; 11];
+        expected[0] = false;
+        expected[2] = false;
+        expected[8] = false;
+
+        check_matches(&["transactions", "--exclude-payee", "Landlord"], expected);
+
+        let mut expected = vec![false; 11];
+        expected[3] = true;
+        expected[4] = true;
+        expected[9] = true;
+
+        check_matches(&["transactions", "--payee", "^(Grocer|Landlord)$", "--exclude-payee", "Landlord"], expected);
+    }
+
+    #[test]
+    fn matches_nothing() {
+        let args = ["transactions", "--payee", "Employer", "--type", "expense"];
+
+        check_matches(&args, vec![false; 11]);
+    }
+
+    #[test]
+    fn has_memo_conflicts_with_no_memo() {
+        let observed = QueryTransactions::try_parse_from(["transactions", "--has-memo", "--no-memo"]);
+
+        assert!(observed.is_err());
+    }
+
+    #[track_caller]
+    fn check_flags(args: &[&str], flags: Option<u32>, expected: bool) {
+        let query = QueryTransactions::try_parse_from(args).unwrap();
+        let tr = Transaction::new(
+            &NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(),
+            -10.0,
+            1,
+            &PayMode::default(),
+            &TransactionStatus::default(),
+            &flags.map(TransactionFlags::new),
+            &None,
+            &None,
+            &None,
+            &None,
+            &TransactionType::Expense,
+            &Default::default(),
+        );
+
+        let db = example_db();
+
+        assert_eq!(expected, query.filter(&db).matches(&tr));
+    }
+
+    #[test]
+    fn flag_set() {
+        check_flags(&["transactions", "--flag", "imported"], Some(512 | 2), true);
+    }
+
+    #[test]
+    fn flag_not_set() {
+        check_flags(&["transactions", "--flag", "imported"], Some(2), false);
+    }
+
+    #[test]
+    fn flag_missing_flags() {
+        check_flags(&["transactions", "--flag", "remind"], None, false);
+    }
+
+    #[test]
+    fn flag_all_must_be_set() {
+        let args = ["transactions", "--flag", "remind", "--flag", "scanned"];
+
+        check_flags(&args, Some(32), false);
+        check_flags(&args, Some(33), true);
+    }
+
+    #[test]
+    fn flag_unset() {
+        check_flags(&["transactions"], None, true);
+    }
+
+    #[test]
+    fn parse_bad_flag_name() {
+        let observed = QueryTransactions::try_parse_from(["transactions", "--flag", "transfer"]);
+
+        assert!(observed.is_err());
+    }
+
+    #[test]
+    fn import_marker_requires_last_import() {
+        let observed = QueryTransactions::try_parse_from(["transactions", "--import-marker", "info"]);
+
+        assert!(observed.is_err());
+    }
+
+    #[test]
+    fn parse_last_import() {
+        let query =
+            QueryTransactions::try_parse_from(["transactions", "--last-import", "--import-marker", "info"])
+                .unwrap();
+
+        assert!(query.last_import());
+        assert_eq!(&ImportMarker::Info, query.import_marker());
+    }
+
+    #[track_caller]
+    fn check_exclude_void(args: &[&str], expected_len: usize, expected_sum: f32) {
+        let db = HomeBankDb::try_from(std::path::Path::new("tests/void.xhb")).unwrap();
+        let query = QueryTransactions::try_parse_from(args).unwrap();
+        let observed = query.exec(&db);
+
+        assert_eq!(expected_len, observed.len());
+        assert_eq!(expected_sum, crate::transaction::sum_transactions(&observed, None));
+    }
+
+    #[track_caller]
+    fn check_convert_to_base(args: &[&str], expected_amounts: Vec<f32>) {
+        let db = HomeBankDb::try_from(std::path::Path::new("tests/currencies.xhb")).unwrap();
+        let query = QueryTransactions::try_parse_from(args).unwrap();
+        let observed: Vec<f32> = query.exec(&db).iter().map(|tr| *tr.total()).collect();
+
+        assert_eq!(expected_amounts, observed);
+    }
+
+    #[test]
+    fn convert_to_base() {
+        // the pound has no exchange rate, so it is left as it is
+        check_convert_to_base(&["transactions", "--convert-to-base"], vec![-20.0, -15.0, -5.0]);
+    }
+
+    #[test]
+    fn convert_split_to_base() {
+        let db = HomeBankDb::try_from(std::path::Path::new("tests/currencies.xhb")).unwrap();
+        let query = QueryTransactions::try_parse_from(["transactions", "--convert-to-base", "--category", "Travel"]).unwrap();
+        let observed = query.exec(&db);
+
+        assert_eq!(vec![&-9.0], observed[0].amounts());
+        assert_eq!(&-9.0, observed[0].total());
+    }
+
+    #[test]
+    fn amount_filter_after_conversion() {
+        check_convert_to_base(&["transactions", "--convert-to-base", "--amount-upper=-12"], vec![-20.0, -15.0]);
+        check_convert_to_base(&["transactions", "--amount-upper=-12"], vec![-20.0]);
+    }
+
+    #[test]
+    fn exclude_void() {
+        check_exclude_void(&["transactions", "--exclude-void"], 2, 800.0);
+    }
+
+    #[test]
+    fn include_void_by_default() {
+        check_exclude_void(&["transactions"], 3, 750.0);
+    }
+
+    #[test]
+    fn exclude_void_with_status() {
+        check_exclude_void(&["transactions", "--exclude-void", "--status", "void"], 0, 0.0);
+    }
+
+    #[test]
+    fn parse_invalid_regex_payee() {
+        let args = ["transactions", "-p", "Amazon.com (Prime"];
+        let observed = QueryTransactions::try_parse_from(args);
+
+        assert!(observed.is_err());
+    }
+
+    #[test]
+    fn parse_fixed_string_payee() {
+        let args = ["transactions", "-F", "-p", "amazon.com (prime"];
+
+        check_payee_match(&args, "Amazon.com (Prime) Video", true);
+    }
+
+    #[test]
+    fn parse_fixed_string_payee_after_pattern() {
+        let args = ["transactions", "-p", "Amazon.com (Prime)", "--fixed-strings"];
+
+        check_payee_match(&args, "Amazon.com (Prime)", true);
+        check_payee_match(&args, "Amazon.com Prime", false);
+    }
+
+    #[test]
+    fn parse_regex_payee() {
+        let args = ["transactions", "-p", "Amazon.com (Prime)"];
+
+        // without `-F`, the parentheses are a regex group
+        check_payee_match(&args, "Amazon.com Prime", true);
     }
 }
@@ -0,0 +1,159 @@
+//! Dates relative to today, such as `-30d` or `last-month`, for the date filters of a query.
+
+use super::TransactionError;
+use crate::{category::TODAY, report::Interval};
+use chrono::{Duration, Months, NaiveDate};
+use std::str::FromStr;
+
+/// Find the first and last days (inclusive) of the span of days named by `expr`, relative to `today`.
+///
+/// `expr` can be:
+///
+/// - `today` or `yesterday`
+/// - a single day some days, weeks, months, or years ago, e.g. `-30d`, `-2w`, `-6m`, or `-1y`
+/// - the period including today, e.g. `this-week`, `this-month`, `this-quarter`, or `this-year`
+/// - the period before that, e.g. `last-week`, `last-month`, `last-quarter`, or `last-year`
+/// - `ytd`, from the first day of the year up to today
+pub fn relative_span(expr: &str, today: &NaiveDate) -> Result<(NaiveDate, NaiveDate), TransactionError> {
+    let invalid = || TransactionError::InvalidDateExpression(expr.to_string());
+
+    if let Some(interval) = expr.strip_prefix("this-") {
+        let interval = Interval::from_str(interval).map_err(|_| invalid())?;
+
+        return Ok((interval.start(today), interval.end(today)));
+    }
+
+    if let Some(interval) = expr.strip_prefix("last-") {
+        let interval = Interval::from_str(interval).map_err(|_| invalid())?;
+        let before = interval.start(today).pred_opt().ok_or_else(invalid)?;
+
+        return Ok((interval.start(&before), interval.end(&before)));
+    }
+
+    let day = match expr {
+        "today" => *today,
+        "yesterday" => today.pred_opt().ok_or_else(invalid)?,
+        "ytd" => return Ok((Interval::Year.start(today), *today)),
+        _ => {
+            let ago = expr.strip_prefix('-').ok_or_else(invalid)?;
+            let unit = ago.chars().last().ok_or_else(invalid)?;
+            let n = u32::from_str(&ago[..ago.len() - unit.len_utf8()]).map_err(|_| invalid())?;
+
+            match unit {
+                'd' => today.checked_sub_signed(Duration::days(n.into())),
+                'w' => today.checked_sub_signed(Duration::weeks(n.into())),
+                'm' => today.checked_sub_months(Months::new(n)),
+                'y' => n.checked_mul(12).and_then(|months| today.checked_sub_months(Months::new(months))),
+                _ => None,
+            }
+            .ok_or_else(invalid)?
+        }
+    };
+
+    Ok((day, day))
+}
+
+/// Parse a date filter: either a date like `2024-01-31`, or the first day of a [`relative_span`].
+///
+/// A relative date stands for the first day of its span either way, so that it is included by `--date-from`
+/// and excluded by `--date-to`, the same as a date like `2024-01-31`.
+/// `--date-from last-month --date-to this-month` covers all of last month.
+pub fn date_expr(s: &str, today: &NaiveDate) -> Result<NaiveDate, TransactionError> {
+    match NaiveDate::from_str(s) {
+        Ok(date) => Ok(date),
+        Err(_) => relative_span(s, today).map(|(first, _)| first),
+    }
+}
+
+/// Parse a date filter from the command line, relative to the current local date.
+pub fn parse_date_expr(s: &str) -> Result<NaiveDate, TransactionError> {
+    date_expr(s, &TODAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_db;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[track_caller]
+    fn check_span(expr: &str, today: NaiveDate, expected: (NaiveDate, NaiveDate)) {
+        assert_eq!(Ok(expected), relative_span(expr, &today));
+    }
+
+    #[test]
+    fn last_month() {
+        check_span("last-month", date(2024, 3, 15), (date(2024, 2, 1), date(2024, 2, 29)));
+    }
+
+    #[test]
+    fn last_month_in_january() {
+        check_span("last-month", date(2024, 1, 10), (date(2023, 12, 1), date(2023, 12, 31)));
+    }
+
+    #[test]
+    fn ytd() {
+        check_span("ytd", date(2024, 3, 15), (date(2024, 1, 1), date(2024, 3, 15)));
+    }
+
+    #[test]
+    fn days_ago() {
+        check_span("-30d", date(2024, 3, 15), (date(2024, 2, 14), date(2024, 2, 14)));
+        check_span("-1m", date(2024, 3, 31), (date(2024, 2, 29), date(2024, 2, 29)));
+    }
+
+    #[test]
+    fn this_quarter() {
+        check_span("this-quarter", date(2024, 5, 20), (date(2024, 4, 1), date(2024, 6, 30)));
+    }
+
+    #[test]
+    fn first_day_of_span() {
+        let today = date(2024, 3, 15);
+
+        assert_eq!(Ok(date(2024, 2, 1)), date_expr("last-month", &today));
+        assert_eq!(Ok(date(2024, 3, 1)), date_expr("this-month", &today));
+        assert_eq!(Ok(date(2024, 1, 1)), date_expr("ytd", &today));
+        assert_eq!(Ok(date(2024, 3, 15)), date_expr("today", &today));
+    }
+
+    #[test]
+    fn relative_dates_filter_like_iso_dates() {
+        let db = example_db();
+        let today = date(2022, 2, 15);
+        let totals = |from: &str, to: &str| -> Vec<f32> {
+            db.transactions_query()
+                .date_from(date_expr(from, &today).unwrap())
+                .date_to(date_expr(to, &today).unwrap())
+                .iter()
+                .map(|tr| *tr.total())
+                .collect()
+        };
+
+        // `this-month` is excluded by the end date like `2022-02-01`, so only January is left
+        let expected = totals("2022-01-01", "2022-02-01");
+        assert!(!expected.is_empty());
+        assert_eq!(expected, totals("last-month", "this-month"));
+    }
+
+    #[test]
+    fn iso_dates_are_unchanged() {
+        assert_eq!(Ok(date(2024, 1, 1)), date_expr("2024-01-01", &date(2024, 3, 15)));
+    }
+
+    #[test]
+    fn invalid_expressions() {
+        let today = date(2024, 3, 15);
+
+        for expr in ["tomorrow", "-30", "-d", "30d", "last-decade", "-5x"] {
+            assert_eq!(
+                Err(TransactionError::InvalidDateExpression(expr.to_string())),
+                relative_span(expr, &today),
+                "`{expr}` should not parse"
+            );
+        }
+    }
+}
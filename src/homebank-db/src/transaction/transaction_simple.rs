@@ -2,6 +2,7 @@
 
 /// A simple [`Transaction`][crate::transaction::transaction_struct::Transaction] that only belongs to a single [`Category`][crate::category::category_struct::Category].
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SimpleTransaction {
     /// The [`Category`][crate::category::category_struct::Category] this [`Transaction`][crate::transaction::transaction_struct::Transaction] falls under.
     category: Option<usize>,
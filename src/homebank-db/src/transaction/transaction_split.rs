@@ -5,10 +5,11 @@ use std::str::FromStr;
 use xml::attribute::OwnedAttribute;
 
 /// The string separator used to denote split transactions in the HomeBank XML file.
-const SPLIT_SEPARATOR: &str = "||";
+pub(crate) const SPLIT_SEPARATOR: &str = "||";
 
 /// A [`Transaction`][crate::transaction::transaction_struct::Transaction] that is split across multiple [`Categories`][crate::category::category_struct::Category].
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SplitTransaction {
     /// The number of sub-transactions it is split into.
     /// This must be equal to `categories.len()`, `amounts.len()`, and `memos.len()`.
@@ -1,7 +1,8 @@
 //! Status of a [`Transaction`][crate::transaction::transaction_struct::Transaction].
 
 use super::TransactionError;
-use std::str::FromStr;
+use clap::{builder::PossibleValue, ValueEnum};
+use std::{fmt, str::FromStr};
 
 /// Status of a [`Transaction`][crate::transaction::transaction_struct::Transaction].
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -13,6 +14,61 @@ pub enum TransactionStatus {
     Void,
 }
 
+impl TransactionStatus {
+    /// Retrieve every status, in the order HomeBank numbers them
+    pub fn variants() -> &'static [TransactionStatus] {
+        &[
+            TransactionStatus::None,
+            TransactionStatus::Cleared,
+            TransactionStatus::Reconciled,
+            TransactionStatus::Remind,
+            TransactionStatus::Void,
+        ]
+    }
+
+    /// Retrieve the canonical name of the status, as accepted on the command line
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransactionStatus::None => "none",
+            TransactionStatus::Cleared => "cleared",
+            TransactionStatus::Reconciled => "reconciled",
+            TransactionStatus::Remind => "remind",
+            TransactionStatus::Void => "void",
+        }
+    }
+
+    /// Check if the transaction has not been cleared, reconciled, voided, or marked as a reminder
+    pub fn is_none(&self) -> bool {
+        self == &TransactionStatus::None
+    }
+
+    /// Check if the transaction has cleared the bank, but has not been reconciled with a statement
+    pub fn is_cleared(&self) -> bool {
+        self == &TransactionStatus::Cleared
+    }
+
+    /// Check if the transaction has been reconciled with a statement
+    pub fn is_reconciled(&self) -> bool {
+        self == &TransactionStatus::Reconciled
+    }
+
+    /// Check if the transaction is a reminder
+    pub fn is_reminder(&self) -> bool {
+        self == &TransactionStatus::Remind
+    }
+
+    /// Check if the transaction has been voided
+    pub fn is_void(&self) -> bool {
+        self == &TransactionStatus::Void
+    }
+}
+
+impl fmt::Display for TransactionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 impl Default for TransactionStatus {
     fn default() -> Self {
         Self::None
@@ -48,3 +104,66 @@ impl FromStr for TransactionStatus {
         }
     }
 }
+
+/// Serialized as its name, e.g. `cleared`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for TransactionStatus {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TransactionStatus {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl ValueEnum for TransactionStatus {
+    fn value_variants<'a>() -> &'a [Self] {
+        Self::variants()
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        let value = PossibleValue::new(self.as_str());
+        let value = match self {
+            TransactionStatus::None => value.aliases(["None", "0"]),
+            TransactionStatus::Cleared => value.aliases(["Cleared", "1"]),
+            TransactionStatus::Reconciled => value.aliases(["Reconciled", "2"]),
+            TransactionStatus::Remind => value.aliases(["Remind", "3"]),
+            TransactionStatus::Void => value.aliases(["Void", "4"]),
+        };
+
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn predicates() {
+        let observed: Vec<[bool; 5]> = TransactionStatus::variants()
+            .iter()
+            .map(|st| [st.is_none(), st.is_cleared(), st.is_reconciled(), st.is_reminder(), st.is_void()])
+            .collect();
+
+        // each status has exactly one predicate, in the same order as the variants
+        for (i, predicates) in observed.iter().enumerate() {
+            let expected: Vec<bool> = (0..5).map(|j| i == j).collect();
+            assert_eq!(expected, predicates.to_vec());
+        }
+    }
+
+    #[test]
+    fn variants_in_order() {
+        for (u, status) in TransactionStatus::variants().iter().enumerate() {
+            assert_eq!(Ok(*status), TransactionStatus::try_from(u));
+            assert_eq!(Ok(*status), <TransactionStatus as FromStr>::from_str(&status.to_string()));
+        }
+    }
+}
@@ -1,18 +1,21 @@
 //! Individual transactions applied to one or more [`Account`s][crate::account::account_struct::Account].
 
 use super::{
-    julian_date_from_u32, parse_split_values, split_tags,
+    julian_date_from_u32, parse_split_values, split_tags, tag_words, TAG_SEPARATOR,
     transaction_split::{parse_split_amount_vec, parse_split_cat_vec, parse_split_memo_vec},
-    SimpleTransaction, SplitTransaction, TransactionComplexity, TransactionStatus, TransactionType,
+    SimpleTransaction, SplitTransaction, TransactionComplexity, TransactionFlags, TransactionStatus,
+    TransactionType,
     Transfer,
 };
-use crate::{HomeBankDb, PayMode, TransactionError};
+use crate::{HomeBankDb, PayMode, QueryTransactions, TransactionError};
 use chrono::NaiveDate;
+use regex::Regex;
 use std::str::FromStr;
 use xml::attribute::OwnedAttribute;
 
 /// Individual transactions applied to one or more [`Account`s][crate::account::account_struct::Account].
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Transaction {
     /// Date on which the transaction took place.
     date: NaiveDate,
@@ -30,7 +33,7 @@ pub struct Transaction {
     status: TransactionStatus,
     
     /// Any flags on the transaction.
-    flags: Option<usize>,
+    flags: Option<TransactionFlags>,
     
     /// Which payee was involved with the transaction.
     payee: Option<usize>,
@@ -43,6 +46,10 @@ pub struct Transaction {
     
     /// User-provided tags for the transaction.
     tags: Option<Vec<String>>,
+
+    /// The `tags` attribute as it is written in the HomeBank file, until the tags are changed.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    tags_text: Option<String>,
     
     /// What type of transaction was it?
     /// `Expense`, `Income`, or `Transfer`?
@@ -67,6 +74,7 @@ impl Transaction {
             memo: None,
             info: None,
             tags: None,
+            tags_text: None,
             transaction_type: TransactionType::default(),
             complexity: TransactionComplexity::default(),
         }
@@ -79,7 +87,7 @@ impl Transaction {
         account: usize,
         pay_mode: &PayMode,
         status: &TransactionStatus,
-        flags: &Option<usize>,
+        flags: &Option<TransactionFlags>,
         payee: &Option<usize>,
         memo: &Option<String>,
         info: &Option<String>,
@@ -98,6 +106,7 @@ impl Transaction {
             memo: memo.clone(),
             info: info.clone(),
             tags: tags.clone(),
+            tags_text: None,
             transaction_type: ttype.clone(),
             complexity: complexity.clone(),
         }
@@ -120,7 +129,7 @@ impl Transaction {
 
     /// Retrieve the [`Account`][crate::account::account_struct::Account] name.
     pub fn account_name(&self, db: &HomeBankDb) -> Option<String> {
-        db.accounts().get(&self.account()).map(|acct| acct.name().to_string())
+        db.account(self.account()).map(|acct| acct.name().to_string())
     }
 
     /// Retrieve the status of the [`Transaction`].
@@ -135,12 +144,7 @@ impl Transaction {
 
     /// Retrieve the [`Payee`'s][crate::payee::payee_struct::Payee] name.
     pub fn payee_name(&self, db: &HomeBankDb) -> Option<String> {
-        match self.payee() {
-            Some(idx) => {
-                db.payees().get(idx).map(|payee| payee.name().to_string())
-            }
-            None => None,
-        }
+        self.payee().and_then(|idx| db.payee_name(idx)).map(str::to_string)
     }
 
     /// Retrieve the payment method of the [`Transaction`].
@@ -158,16 +162,53 @@ impl Transaction {
         &self.info
     }
 
+    /// Check if the [`Transaction`]'s info field matches the regular expression.
+    /// Transactions without an info field never match.
+    pub fn info_matches(&self, re: &Regex) -> bool {
+        self.info.as_ref().is_some_and(|info| re.is_match(info))
+    }
+
     /// Retrieve the tags for the [`Transaction`].
     pub fn tags(&self) -> &Option<Vec<String>> {
         &self.tags
     }
 
+    /// Retrieve the `tags` attribute to write to the HomeBank file: as it was read, unless the tags have changed since.
+    pub(crate) fn tags_attribute(&self) -> Option<String> {
+        match (&self.tags_text, &self.tags) {
+            (Some(text), _) => Some(text.clone()),
+            (None, Some(tags)) => Some(tags.join(&TAG_SEPARATOR.to_string())),
+            (None, None) => None,
+        }
+    }
+
+    /// Retrieve the tags for the [`Transaction`] lowercased, sorted, and without duplicates, to compare them regardless of how they were written.
+    ///
+    /// Tags are also split on commas and other whitespace, e.g. `food,Food groceries` is `food` and `groceries`.
+    pub fn normalized_tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self
+            .tags
+            .iter()
+            .flatten()
+            .flat_map(|tag| tag_words(tag))
+            .map(|word| word.to_lowercase())
+            .collect();
+        tags.sort();
+        tags.dedup();
+
+        tags
+    }
+
     /// Retrieve the flags for the [`Transaction`].
-    pub fn flags(&self) -> &Option<usize> {
+    pub fn flags(&self) -> &Option<TransactionFlags> {
         &self.flags
     }
 
+    /// Retrieve the flags for the [`Transaction`], treating missing flags as none being set.
+    pub fn flags_decoded(&self) -> TransactionFlags {
+        self.flags().unwrap_or_default()
+    }
+
     /// Retrieve the type for the [`Transaction`].
     pub fn ttype(&self) -> &TransactionType {
         &self.transaction_type
@@ -178,6 +219,18 @@ impl Transaction {
         self.ttype().is_transfer()
     }
 
+    /// Check if the [`Transaction`] would be included in the results of a [`QueryTransactions`][crate::transaction::transaction_query::QueryTransactions].
+    ///
+    /// `--last-import` depends on the neighbouring transactions in the database, so it is not considered here.
+    pub fn matches(&self, query: &QueryTransactions, db: &HomeBankDb) -> bool {
+        query.filter(db).matches(self)
+    }
+
+    /// Check if the [`Transaction`] has been voided.
+    pub fn is_void(&self) -> bool {
+        self.status().is_void()
+    }
+
     /// Retrieve the transfer key for the [`Transaction`].
     pub fn transfer_key(&self) -> Option<&usize> {
         if let TransactionType::Transfer(xfer) = self.ttype() {
@@ -215,12 +268,7 @@ impl Transaction {
     pub fn category_names(&self, db: &HomeBankDb) -> Vec<Option<String>> {
         self.categories()
             .iter()
-            .map(|&cat_idx| match cat_idx {
-                Some(idx) => {
-                    db.categories().get(idx).map(|category| category.full_name(db))
-                }
-                None => None,
-            })
+            .map(|cat_idx| cat_idx.and_then(|idx| db.category_full_name(idx)).map(str::to_string))
             .collect()
     }
 
@@ -253,6 +301,86 @@ impl Transaction {
                 complexity,
             ))
     }
+
+    /// Convert the [`Transaction`] into the base currency of the database, using the exchange rate of its account's currency.
+    /// Transactions in currencies without an exchange rate are left unchanged.
+    pub fn to_base_currency(&self, db: &HomeBankDb) -> Self {
+        let rate = db
+            .account(self.account)
+            .and_then(|acct| db.convert_to_base(1.0, acct.currency()));
+
+        let mut converted = self.clone();
+        if let Some(rate) = rate {
+            converted.amount *= rate;
+            converted.complexity.scale(rate);
+        }
+
+        converted
+    }
+
+    /// Set the [`Category`][crate::category::category_struct::Category] of a [`SimpleTransaction`][crate::transaction::transaction_simple::SimpleTransaction].
+    /// Split transactions are left unchanged, since each split has its own category.
+    pub(crate) fn set_category(&mut self, category: usize) {
+        if let TransactionComplexity::Simple(simple) = &mut self.complexity {
+            *simple.mut_category() = Some(category);
+        }
+    }
+
+    /// Set the [`Payee`][crate::payee::payee_struct::Payee] of the [`Transaction`].
+    pub(crate) fn set_payee(&mut self, payee: usize) {
+        self.payee = Some(payee);
+    }
+
+    /// Set the payment method of the [`Transaction`].
+    pub(crate) fn set_pay_mode(&mut self, pay_mode: &PayMode) {
+        self.pay_mode = *pay_mode;
+    }
+
+    /// Add tags to the [`Transaction`], skipping any that it already has.
+    pub(crate) fn add_tags(&mut self, tags: &[String]) {
+        self.tags_text = None;
+        let existing = self.tags.get_or_insert_with(Vec::new);
+        for tag in tags {
+            if !existing.contains(tag) {
+                existing.push(tag.clone());
+            }
+        }
+    }
+}
+
+impl PartialEq for Transaction {
+    /// Compare every field except how the tags are written in the HomeBank file.
+    fn eq(&self, other: &Self) -> bool {
+        // destructured so that a new field can't be forgotten here
+        let Self {
+            date,
+            amount,
+            account,
+            pay_mode,
+            status,
+            flags,
+            payee,
+            memo,
+            info,
+            tags,
+            tags_text: _,
+            transaction_type,
+            complexity,
+        } = self;
+
+        *date == other.date
+            && *amount == other.amount
+            && *account == other.account
+            && *pay_mode == other.pay_mode
+            && *status == other.status
+            && *flags == other.flags
+            && *payee == other.payee
+            && *memo == other.memo
+            && *info == other.info
+            && *tags == other.tags
+            && *transaction_type == other.transaction_type
+            && *complexity == other.complexity
+    }
 }
 
 impl Default for Transaction {
@@ -343,9 +471,9 @@ impl TryFrom<Vec<OwnedAttribute>> for Transaction {
                     }
                 }
                 "flags" => {
-                    tr.flags = match usize::from_str(&i.value) {
+                    tr.flags = match TransactionFlags::from_str(&i.value) {
                         Ok(f) => Some(f),
-                        Err(_) => return Err(TransactionError::InvalidFlags),
+                        Err(e) => return Err(e),
                     }
                 }
                 "payee" => {
@@ -380,14 +508,18 @@ impl TryFrom<Vec<OwnedAttribute>> for Transaction {
                         }
                     }
                 }
+                "info" => {
+                    tr.info = Some(i.value.clone()).filter(|s| !s.is_empty());
+                }
                 "tags" => {
-                    // split the tags string by commas
+                    // split the tags string by spaces and commas
                     let tags = split_tags(&i.value);
                     if tags.is_empty() {
                         tr.tags = None;
                     } else {
                         tr.tags = Some(tags);
                     }
+                    tr.tags_text = Some(i.value.clone());
                 }
                 // handle split categories
                 "scat" => {
@@ -541,14 +673,32 @@ impl TryFrom<Vec<OwnedAttribute>> for Transaction {
     }
 }
 
+/// [`Transaction`]s are ordered by date.
+/// Different transactions on the same date have no order, so that the ordering agrees with equality.
+impl PartialOrd for Transaction {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match self.date.cmp(&other.date) {
+            std::cmp::Ordering::Equal if self != other => None,
+            ordering => Some(ordering),
+        }
+    }
+}
+
 /// Sum the total amount from all the [`Transaction`]s.
-pub fn sum_transactions(v: &[Transaction]) -> f32 {
-    v.iter().fold(0.0, |sum, tr| sum + tr.total())
+/// If a [`TransactionType`] is given, only [`Transaction`]s of that type are included (e.g. to sum income and expenses separately).
+pub fn sum_transactions(v: &[Transaction], ttype: Option<&TransactionType>) -> f32 {
+    v.iter()
+        .filter(|tr| match ttype {
+            Some(t) => t.is_similar_to(tr.ttype()),
+            None => true,
+        })
+        .fold(0.0, |sum, tr| sum + tr.total())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tests::example_db;
     use xml::{name::OwnedName, reader::XmlEvent, EventReader};
 
     #[test]
@@ -634,6 +784,7 @@ mod tests {
             info: None,
             memo: None,
             tags: None,
+            tags_text: None,
             pay_mode: PayMode::None,
             payee: Some(1),
             status: TransactionStatus::None,
@@ -854,6 +1005,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_newer_paymode() {
+        let input = r#"<ope paymode="11">"#;
+        let expected = Ok(Transaction {
+            pay_mode: PayMode::Other(11),
+            ..Default::default()
+        });
+
+        check_try_from_single_str(input, expected);
+    }
+
     #[test]
     fn parse_bad_paymode() {
         // use a string that should work in the `from_str` method to make sure that there
@@ -902,13 +1064,31 @@ mod tests {
     fn parse_good_flag() {
         let input = r#"<ope flags="1">"#;
         let expected = Ok(Transaction {
-            flags: Some(1),
+            flags: Some(TransactionFlags::new(1)),
             ..Default::default()
         });
 
         check_try_from_single_str(input, expected);
     }
 
+    #[test]
+    fn flags_decoded_missing() {
+        let tr = Transaction::default();
+
+        assert_eq!(TransactionFlags::default(), tr.flags_decoded());
+    }
+
+    #[test]
+    fn flags_decoded_split() {
+        let tr = Transaction {
+            flags: Some(TransactionFlags::new(256)),
+            ..Default::default()
+        };
+
+        assert!(tr.flags_decoded().is_split());
+        assert!(!tr.flags_decoded().is_income());
+    }
+
     #[test]
     fn parse_bad_flags() {
         let input = r#"<ope flags="somethingelse">"#;
@@ -971,6 +1151,28 @@ mod tests {
         check_try_from_single_str(input, expected);
     }
 
+    #[test]
+    fn parse_info() {
+        let input = r#"<ope info="INV-2022-02">"#;
+        let expected = Ok(Transaction {
+            info: Some(String::from("INV-2022-02")),
+            ..Default::default()
+        });
+
+        check_try_from_single_str(input, expected);
+    }
+
+    #[test]
+    fn parse_empty_info() {
+        let input = r#"<ope info="">"#;
+        let expected = Ok(Transaction {
+            info: None,
+            ..Default::default()
+        });
+
+        check_try_from_single_str(input, expected);
+    }
+
     #[test]
     fn parse_empty_tags() {
         let input = r#"<ope tags="">"#;
@@ -1015,6 +1217,82 @@ mod tests {
         check_try_from_single_str(input, expected);
     }
 
+    fn tagged(tags: &str) -> Transaction {
+        let attribute = OwnedAttribute::new(OwnedName::local("tags"), tags);
+
+        Transaction::try_from(vec![attribute]).unwrap()
+    }
+
+    #[track_caller]
+    fn check_tags(input: &str, expected: &[&str], normalized: &[&str]) {
+        let tr = tagged(input);
+        let observed: Vec<&str> = tr.tags().iter().flatten().map(|tag| tag.as_str()).collect();
+
+        assert_eq!(expected, observed);
+        assert_eq!(normalized, tr.normalized_tags());
+        // the attribute is written back as it was read
+        assert_eq!(Some(input.to_string()), tr.tags_attribute());
+    }
+
+    #[test]
+    fn parse_padded_tags() {
+        let input = r#"<ope tags="  food,  groceries ">"#;
+        let expected = Ok(Transaction {
+            tags: Some(vec![String::from("food"), String::from("groceries")]),
+            ..Default::default()
+        });
+
+        check_try_from_single_str(input, expected);
+        check_tags("  food,  groceries ", &["food", "groceries"], &["food", "groceries"]);
+    }
+
+    #[test]
+    fn parse_duplicate_tags() {
+        let input = r#"<ope tags="food,Food,FOOD weekly">"#;
+        let expected = Ok(Transaction {
+            tags: Some(vec![String::from("food"), String::from("weekly")]),
+            ..Default::default()
+        });
+
+        check_try_from_single_str(input, expected);
+        check_tags("food,Food,FOOD", &["food"], &["food"]);
+        check_tags("food Food", &["food"], &["food"]);
+    }
+
+    #[test]
+    fn parse_only_separator_tags() {
+        let input = r#"<ope tags=" , ,">"#;
+        let expected = Ok(Transaction {
+            tags: None,
+            ..Default::default()
+        });
+
+        check_try_from_single_str(input, expected);
+        check_tags("  ", &[], &[]);
+        check_tags(" , ,", &[], &[]);
+    }
+
+    #[test]
+    fn tags_attribute_after_adding_tags() {
+        let mut tr = tagged("food,groceries");
+        assert_eq!(Some("food,groceries".to_string()), tr.tags_attribute());
+
+        tr.add_tags(&[String::from("weekly")]);
+        assert_eq!(Some("food groceries weekly".to_string()), tr.tags_attribute());
+        assert_eq!(None, Transaction::default().tags_attribute());
+    }
+
+    #[test]
+    fn normalized_tags() {
+        let tr = Transaction {
+            tags: Some(vec![String::from("Weekly"), String::from("food"), String::from("weekly")]),
+            ..Default::default()
+        };
+
+        assert_eq!(vec!["food", "weekly"], tr.normalized_tags());
+        assert!(Transaction::default().normalized_tags().is_empty());
+    }
+
     #[test]
     fn parse_simple_split() {
         let input = r#"<ope date="736696" amount="-1088.72" account="5" paymode="8" st="2" flags="256" payee="13" scat="83||100" samt="-1119.8||31.079999999999998" smem="January||Internet payment (Dec 1 - Dec 30)"/>"#;
@@ -1024,7 +1302,7 @@ mod tests {
             account: 5,
             pay_mode: PayMode::Deposit,
             status: TransactionStatus::Reconciled,
-            flags: Some(256),
+            flags: Some(TransactionFlags::new(256)),
             payee: Some(13),
             complexity: TransactionComplexity::Split(SplitTransaction::new(
                 2,
@@ -1051,7 +1329,7 @@ mod tests {
             account: 5,
             pay_mode: PayMode::Deposit,
             status: TransactionStatus::Reconciled,
-            flags: Some(256),
+            flags: Some(TransactionFlags::new(256)),
             payee: Some(13),
             complexity: TransactionComplexity::Split(SplitTransaction::new(
                 2,
@@ -1123,6 +1401,34 @@ mod tests {
         check_try_from_single_str(input, expected);
     }
 
+    #[track_caller]
+    fn check_sum(ttype: Option<TransactionType>, expected: f32) {
+        let db = example_db();
+        let observed = sum_transactions(db.transactions(), ttype.as_ref());
+
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    fn sum_all() {
+        check_sum(None, 44.25);
+    }
+
+    #[test]
+    fn sum_income() {
+        check_sum(Some(TransactionType::Income), 4000.0);
+    }
+
+    #[test]
+    fn sum_expense() {
+        check_sum(Some(TransactionType::Expense), -3955.75);
+    }
+
+    #[test]
+    fn sum_transfer() {
+        check_sum(Some(TransactionType::Transfer(Transfer::default())), 0.0);
+    }
+
     #[track_caller]
     fn check_subset(input: (Transaction, Vec<usize>), expected: Option<Transaction>) {
         let tr = input.0;
@@ -1167,7 +1473,7 @@ mod tests {
             account: 5,
             pay_mode: PayMode::Deposit,
             status: TransactionStatus::Reconciled,
-            flags: Some(256),
+            flags: Some(TransactionFlags::new(256)),
             payee: Some(13),
             complexity: TransactionComplexity::Split(SplitTransaction::new(
                 2,
@@ -1187,7 +1493,7 @@ mod tests {
             account: 5,
             pay_mode: PayMode::Deposit,
             status: TransactionStatus::Reconciled,
-            flags: Some(256),
+            flags: Some(TransactionFlags::new(256)),
             payee: Some(13),
             complexity: TransactionComplexity::Split(SplitTransaction::new(
                 1,
@@ -1209,7 +1515,7 @@ mod tests {
             account: 5,
             pay_mode: PayMode::Deposit,
             status: TransactionStatus::Reconciled,
-            flags: Some(256),
+            flags: Some(TransactionFlags::new(256)),
             payee: Some(13),
             complexity: TransactionComplexity::Split(SplitTransaction::new(
                 2,
@@ -1227,4 +1533,43 @@ mod tests {
 
         check_subset((tr, idx), expected);
     }
+
+    #[test]
+    fn info_matches() {
+        let db = example_db();
+        let re = Regex::new("^INV-2022").unwrap();
+        let observed: Vec<bool> = db.transactions().iter().map(|tr| tr.info_matches(&re)).collect();
+        let mut expected = vec![false; 11];
+        expected[10] = true;
+
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    fn order_by_date() {
+        let db = example_db();
+        let trs = db.transactions();
+
+        assert!(trs[0] < trs[1]);
+        assert!(trs[10] > trs[9]);
+        // both halves of the transfer are on the same day
+        assert_eq!(None, trs[5].partial_cmp(&trs[6]));
+        assert_eq!(Some(std::cmp::Ordering::Equal), trs[5].partial_cmp(&trs[5].clone()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let xml = r#"<homebank v="1.4" d="050504">
+<ope date="738160" amount="-100" account="1" paymode="11" st="1" flags="256" payee="3" info="1234" tags="food weekly" scat="4||5" samt="-80||-20" smem="Groceries||Deli lunch"/>
+</homebank>"#;
+        let tr = HomeBankDb::from_reader(xml.as_bytes()).unwrap().transactions()[0].clone();
+        let json = serde_json::to_string(&tr).unwrap();
+
+        // enums are written as their names, dates as ISO dates, and references as raw indices
+        assert!(json.contains(r#""date":"2022-01-05""#), "{json}");
+        assert!(json.contains(r#""pay_mode":"11","status":"cleared""#), "{json}");
+        assert!(json.contains(r#""payee":3"#), "{json}");
+        assert_eq!(tr, serde_json::from_str::<Transaction>(&json).unwrap());
+    }
 }
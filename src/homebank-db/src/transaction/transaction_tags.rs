@@ -1,13 +1,33 @@
 //! Helper functions to handle the processing of [`Transaction`][crate::transaction::transaction_struct::Transaction] tags.
 
 /// The character separating each tag in the HomeBank XML file.
-const TAG_SEPARATOR: char = ' ';
+pub(crate) const TAG_SEPARATOR: char = ' ';
 
 /// Get the list of tags for a [`Transaction`][crate::transaction::transaction_struct::Transaction] and parse them.
+///
+/// Tags are split into their [words][tag_words], and a tag that differs from an earlier one only by case is dropped.
+/// The [`Transaction`][crate::transaction::transaction_struct::Transaction] keeps the attribute as it is written,
+/// so that it is written back to the HomeBank file unchanged.
 pub(crate) fn split_tags(s: &str) -> Vec<String> {
-    s.split(TAG_SEPARATOR)
-        .map(|s| s.to_string())
-        // remove any empty strings as these are not valid tags
-        .filter(|s| !s.is_empty())
-        .collect()
+    let mut tags: Vec<String> = vec![];
+
+    for tag in tag_words(s) {
+        if !tags.iter().any(|t| t.to_lowercase() == tag.to_lowercase()) {
+            tags.push(tag.to_string());
+        }
+    }
+
+    tags
+}
+
+/// Split a tag, as it is written in the HomeBank file, into the words it is compared by.
+///
+/// Commas and other whitespace are accepted as separators too, e.g. `food,groceries` is `food` and `groceries`.
+pub(crate) fn tag_words(tag: &str) -> impl Iterator<Item = &str> {
+    tag.split(is_tag_separator).filter(|word| !word.is_empty())
+}
+
+/// Check whether `c` separates the words of a tag list, see [`tag_words`].
+pub(crate) fn is_tag_separator(c: char) -> bool {
+    c == TAG_SEPARATOR || c == ',' || c.is_whitespace()
 }
@@ -2,6 +2,7 @@
 
 /// [`Transaction`s][crate::transaction::transaction_struct::Transaction] that transfer amounts between [`Account`s][crate::account::account_struct::Account].
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Transfer {
     /// Unique identifier for the transfer.
     transfer_key: usize,
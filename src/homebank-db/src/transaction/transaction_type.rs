@@ -6,6 +6,7 @@ use std::str::FromStr;
 
 /// The type of a [`Transaction`][crate::transaction::transaction_struct::Transaction].
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TransactionType {
     /// An amount that is withdrawn from an [`Account`][crate::account::account_struct::Account].
     /// Also known as a "credit" in a [double-entry bookkeeping system](https://en.wikipedia.org/wiki/Double-entry_bookkeeping).
@@ -20,6 +21,16 @@ pub enum TransactionType {
 }
 
 impl TransactionType {
+    /// Determine if the [`Transaction`][crate::transaction::transaction_struct::Transaction] is an `Income`.
+    pub fn is_income(&self) -> bool {
+        matches!(self, TransactionType::Income)
+    }
+
+    /// Determine if the [`Transaction`][crate::transaction::transaction_struct::Transaction] is an `Expense`.
+    pub fn is_expense(&self) -> bool {
+        matches!(self, TransactionType::Expense)
+    }
+
     /// Determine if the [`Transaction`][crate::transaction::transaction_struct::Transaction] is a [`Transfer`][crate::transaction::transaction_transfer::Transfer].
     pub fn is_transfer(&self) -> bool {
         matches!(self, TransactionType::Transfer(_))
@@ -29,7 +40,9 @@ impl TransactionType {
     /// Useful for comparing if two [`TransactionType`]s are both [`Transfer`s][crate::transaction::transaction_transfer::Transfer] or
     /// not, without checking the values within the transfer.
     pub fn is_similar_to(&self, other: &Self) -> bool {
-        matches!((self, other), (TransactionType::Expense, TransactionType::Expense) | (TransactionType::Income, TransactionType::Income) | (TransactionType::Transfer(_), TransactionType::Transfer(_)))
+        (self.is_expense() && other.is_expense())
+            || (self.is_income() && other.is_income())
+            || (self.is_transfer() && other.is_transfer())
     }
 }
 
@@ -53,3 +66,39 @@ impl FromStr for TransactionType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check_predicates(ttype: TransactionType, income: bool, expense: bool, transfer: bool) {
+        assert_eq!(income, ttype.is_income());
+        assert_eq!(expense, ttype.is_expense());
+        assert_eq!(transfer, ttype.is_transfer());
+    }
+
+    #[test]
+    fn income_predicates() {
+        check_predicates(TransactionType::Income, true, false, false);
+    }
+
+    #[test]
+    fn expense_predicates() {
+        check_predicates(TransactionType::Expense, false, true, false);
+    }
+
+    #[test]
+    fn transfer_predicates() {
+        check_predicates(TransactionType::Transfer(Transfer::default()), false, false, true);
+    }
+
+    #[test]
+    fn similar_transfers() {
+        let a = TransactionType::Transfer(Transfer::default());
+        let b = TransactionType::from_str("transfer").unwrap();
+
+        assert!(a.is_similar_to(&b));
+        assert!(!a.is_similar_to(&TransactionType::Income));
+    }
+}